@@ -0,0 +1,50 @@
+//! Embeds build information shown by `tmx version --verbose`.
+//!
+//! Packagers building from a source tarball (Homebrew, Nix) can set
+//! `TMX_GIT_COMMIT`, and `SOURCE_DATE_EPOCH` for a reproducible build date.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=TMX_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    let commit = std::env::var("TMX_GIT_COMMIT")
+        .ok()
+        .filter(|c| !c.is_empty())
+        .or_else(git_commit)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let build_epoch = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    println!("cargo:rustc-env=TMX_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=TMX_BUILD_EPOCH={}", build_epoch);
+    println!(
+        "cargo:rustc-env=TMX_BUILD_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+}
+
+/// Short hash of the checked-out commit, marked `-dirty` with uncommitted changes
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--always", "--dirty", "--abbrev=12", "--exclude", "*"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
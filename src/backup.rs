@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::log;
+
+/// Get the backup directory ($XDG_STATE_HOME/tmx/backups, default ~/.local/state/tmx/backups)
+pub fn backup_dir() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("backups"))
+}
+
+/// Filename prefix for backups of a config file (`tmx.toml` -> `tmx-`)
+fn backup_prefix(config_path: &Path) -> String {
+    let stem = config_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config".to_string());
+    format!("{}-", stem)
+}
+
+/// Save a timestamped copy of the config file before it is modified.
+///
+/// Backups are named `<stem>-YYYYMMDD-HHMMSS.toml` (UTC) so they sort
+/// chronologically. Does nothing if the config file doesn't exist yet.
+///
+/// # Returns
+/// The path of the backup, or None if there was nothing to back up.
+pub fn create(config_path: &Path) -> Result<Option<PathBuf>> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let dir = backup_dir()?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create backup directory: {}", dir.display()))?;
+
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day, hours, minutes, seconds) = log::utc_datetime(secs);
+    let stamp = format!(
+        "{}{:04}{:02}{:02}-{:02}{:02}{:02}",
+        backup_prefix(config_path),
+        year,
+        month,
+        day,
+        hours,
+        minutes,
+        seconds
+    );
+
+    // Several writes within one second get a numeric suffix
+    let mut path = dir.join(format!("{}.toml", stamp));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}.{}.toml", stamp, n));
+        n += 1;
+    }
+
+    fs::copy(config_path, &path)
+        .with_context(|| format!("Failed to back up config to {}", path.display()))?;
+    log::info(&format!("backed up {} to {}", config_path.display(), path.display()));
+
+    Ok(Some(path))
+}
+
+/// List backups of a config file, newest first
+pub fn list(config_path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = backup_prefix(config_path);
+    let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read backup directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            // The timestamp must follow the prefix, so `tmx-work.toml` backups aren't listed for `tmx.toml`
+            path.file_name()
+                .and_then(|n| n.to_string_lossy().strip_prefix(&prefix).map(str::to_string))
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .collect();
+
+    backups.sort_by(|a, b| {
+        let a = a.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let b = b.file_name().unwrap_or_default().to_string_lossy().to_string();
+        natural_key(&b).cmp(&natural_key(&a))
+    });
+    Ok(backups)
+}
+
+/// Remove backups of any config file last modified before a point in time.
+///
+/// # Returns
+/// The number of backups removed.
+pub fn prune(older_than: SystemTime) -> Result<usize> {
+    let dir = backup_dir()?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read backup directory: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let modified = fs::metadata(&path).and_then(|m| m.modified());
+        if path.extension().is_some_and(|e| e == "toml")
+            && modified.is_ok_and(|modified| modified < older_than)
+        {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove backup: {}", path.display()))?;
+            log::info(&format!("gc: removed backup {}", path.display()));
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Sort key for backup names that orders `x.toml` before `x.1.toml` before `x.10.toml`
+fn natural_key(name: &str) -> (String, usize) {
+    let base = name.trim_end_matches(".toml");
+    match base.rsplit_once('.') {
+        Some((stamp, n)) if n.chars().all(|c| c.is_ascii_digit()) => {
+            (stamp.to_string(), n.parse().unwrap_or(0))
+        }
+        _ => (base.to_string(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_prefix() {
+        assert_eq!(backup_prefix(Path::new("/home/me/.config/tmx/tmx.toml")), "tmx-");
+        assert_eq!(backup_prefix(Path::new("work.toml")), "work-");
+    }
+
+    #[test]
+    fn test_natural_key_order() {
+        let mut names = vec![
+            "tmx-20260101-000000.10.toml",
+            "tmx-20260101-000000.toml",
+            "tmx-20260101-000000.2.toml",
+            "tmx-20251231-235959.toml",
+        ];
+        names.sort_by_key(|n| std::cmp::Reverse(natural_key(n)));
+        assert_eq!(
+            names,
+            vec![
+                "tmx-20260101-000000.10.toml",
+                "tmx-20260101-000000.2.toml",
+                "tmx-20260101-000000.toml",
+                "tmx-20251231-235959.toml",
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "tmx")]
@@ -12,6 +12,25 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Write logs to this file instead of ~/.cache/tmx/tmx.log ('-' for stderr)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<String>,
+
+    /// Override a config value for this invocation, e.g.
+    /// --set sessions.dev.windows.0.panes.0.command="nvim +42 src/main.rs"
+    #[arg(long = "set", global = true, value_name = "PATH=VALUE")]
+    pub overrides: Vec<String>,
+
+    /// Without a command: print the session cycling would go to instead of
+    /// going there (starting one detached if none is running)
+    #[arg(long)]
+    pub print_target: bool,
+
+    /// Without a command: how to hand over to the session cycling goes to
+    /// (default: refresh the current session inside tmux, attach outside)
+    #[arg(long, value_enum, conflicts_with = "print_target")]
+    pub attach_mode: Option<AttachMode>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -21,45 +40,523 @@ pub enum Commands {
     /// Open or attach to a session
     #[command(alias = "o")]
     Open {
-        /// Session name or ID from config
+        /// Session name or ID from config, or an https:// URL of a config to import
         session: String,
+
+        /// Expected SHA-256 checksum of the config at the URL (skips confirmation)
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+
+        /// Kill a running session with this name and recreate it from config
+        #[arg(short, long)]
+        force: bool,
+
+        /// Create the windows and panes without running any configured commands
+        #[arg(long)]
+        no_commands: bool,
+
+        /// How to hand over to the session (default: switch inside tmux, attach outside)
+        #[arg(long, value_enum)]
+        attach_mode: Option<AttachMode>,
+
+        /// Open in a new terminal window (same as --attach-mode new-terminal)
+        #[arg(long, visible_alias = "terminal", conflicts_with = "attach_mode")]
+        new_terminal: bool,
+
+        /// Only print the session name on stdout, creating the session detached
+        /// if needed; progress goes to stderr (for terminals that attach themselves)
+        #[arg(long, conflicts_with_all = ["attach_mode", "new_terminal"])]
+        print_target: bool,
     },
 
     /// Close a running session
     #[command(alias = "c")]
     Close {
-        /// Session name to stop
-        session: String,
+        /// Session name to stop (default: the current session, inside tmux)
+        session: Option<String>,
+
+        /// Don't ask for confirmation before closing the current session
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Attach or switch to a running session (never creates one)
+    #[command(alias = "a")]
+    Attach {
+        /// Session name or ID from config (default: choose from running sessions)
+        session: Option<String>,
+
+        /// How to hand over to the session (default: switch inside tmux, attach outside)
+        #[arg(long, value_enum)]
+        attach_mode: Option<AttachMode>,
+
+        /// Open in a new terminal window (same as --attach-mode new-terminal)
+        #[arg(long, visible_alias = "terminal", conflicts_with = "attach_mode")]
+        new_terminal: bool,
+    },
+
+    /// Pick a running or configured session and switch to it
+    Picker {
+        /// Run in a tmux popup: fit the list to the popup and switch the
+        /// client that opened it (bind with display-popup -E)
+        #[arg(long)]
+        tmux_popup: bool,
     },
 
     /// Refresh the layout of a running session
     #[command(alias = "r")]
     Refresh {
-        /// Session name to refresh
+        /// Session name to refresh (default: the current session, inside tmux)
+        session: Option<String>,
+
+        /// Only refresh this window (by name), leaving the others untouched
+        #[arg(short, long)]
+        window: Option<String>,
+    },
+
+    /// Recreate a running session from config, keeping the focused window/pane
+    Reload {
+        /// Session name to reload
+        session: String,
+    },
+
+    /// Interactively reconcile a running session with its config, in either direction
+    Sync {
+        /// Session name to sync
+        session: String,
+    },
+
+    /// Run a command in a pane of a running session and exit with its status
+    Run {
+        /// Pane as <session>[:<window>[.<pane>]] (default: the active window and pane);
+        /// inside tmux, :<window>[.<pane>] or :.<pane> addresses the current session and window
+        target: String,
+
+        /// Give up waiting after this many seconds (default: wait until it finishes)
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// The command to run, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Copy the last lines of a pane into a tmux paste buffer and the clipboard
+    Copy {
+        /// Pane as <session>[:<window>[.<pane>]], as for `tmx run`
+        target: String,
+
+        /// Number of lines to copy, from the bottom of the pane
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: usize,
+
+        /// Only fill the tmux paste buffer, not the system clipboard
+        #[arg(long)]
+        no_clipboard: bool,
+    },
+
+    /// Paste the most recent tmux paste buffer into a pane
+    Paste {
+        /// Pane as <session>[:<window>[.<pane>]], as for `tmx run`
+        target: String,
+    },
+
+    /// Measure session creation on a separate tmux server
+    Bench {
+        /// Session name or ID from config
+        session: String,
+
+        /// Number of timed create/destroy runs
+        #[arg(short = 'n', long, default_value_t = 10)]
+        iterations: usize,
+    },
+
+    /// Clone or update a shared git repository of session configs
+    #[command(name = "sync-config")]
+    SyncConfig {
+        /// Git URL to clone or update (default: update all shared repositories)
+        url: Option<String>,
+    },
+
+    /// Import session configs shared elsewhere
+    Import {
+        #[command(subcommand)]
+        action: ImportCommands,
+    },
+
+    /// Open the session for a ticket, created from the [ticket] template
+    Ticket {
+        /// Ticket ID, e.g. JIRA-1234
+        ticket: String,
+
+        /// How to hand over to the session (default: switch inside tmux, attach outside)
+        #[arg(long, value_enum)]
+        attach_mode: Option<AttachMode>,
+
+        /// Open in a new terminal window (same as --attach-mode new-terminal)
+        #[arg(long, visible_alias = "terminal", conflicts_with = "attach_mode")]
+        new_terminal: bool,
+    },
+
+    /// Open or close a workspace (a set of sessions started together)
+    #[command(alias = "ws")]
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceCommands,
+    },
+
+    /// Save window layouts under [layouts] and apply them to other windows
+    Layout {
+        #[command(subcommand)]
+        action: LayoutCommands,
+    },
+
+    /// Create a project with a session config for its stack
+    Project {
+        #[command(subcommand)]
+        action: ProjectCommands,
+    },
+
+    /// Start, stop or inspect the tmux server
+    Server {
+        #[command(subcommand)]
+        action: ServerCommands,
+    },
+
+    /// Print a diagram of a session's windows and panes
+    Graph {
+        /// Session name or ID from config
         session: String,
+
+        /// Diagram format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Mermaid)]
+        format: GraphFormat,
+
+        /// Draw the running session instead of its configuration
+        #[arg(long)]
+        live: bool,
+    },
+
+    /// Show the windows and panes of running sessions and what runs in them
+    Status {
+        /// Session name or ID from config (default: every running session)
+        session: Option<String>,
+
+        /// Run the panes' health checks and show pass/fail badges (exits 1 if any fail)
+        #[arg(long)]
+        health: bool,
+
+        /// Print one line for the session, e.g. for tmux's status-right:
+        /// `dev ok windows=4 panes=11 drift=0 clients=1`
+        #[arg(long, requires = "session", conflicts_with = "health")]
+        summary: bool,
+    },
+
+    /// Print a running session as config, e.g. to keep a session built by hand
+    Snapshot {
+        /// Name of the running session
+        session: String,
+
+        /// Config key for the session (default: the session's name)
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Add the session to the config file instead of printing it
+        #[arg(long)]
+        append: bool,
+    },
+
+    /// Print the current session's name and drift status for a shell prompt
+    PromptSegment {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
 
     /// List configured and running sessions
     #[command(alias = "ls")]
-    List,
+    List {
+        /// Redraw the table every SECONDS (default: 2) until interrupted
+        #[arg(short, long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2")]
+        watch: Option<u64>,
+    },
+
+    /// Show the tmx log
+    Logs {
+        /// Only show the trace of executed tmux commands (recorded with -v)
+        #[arg(long)]
+        commands: bool,
+
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+    },
+
+    /// Drop stale session state, old config backups and old log records
+    Gc,
+
+    /// Show recently opened, closed and refreshed sessions and the outcomes
+    History {
+        /// Only show operations on this session
+        session: Option<String>,
+
+        /// Number of operations to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Manage the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
 
     /// Initialize configuration file
     Init,
 
     /// Validate configuration syntax
-    Validate,
+    Validate {
+        /// Output format (json: a list of errors and warnings for tools)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Fail on roots and env files using undefined variables or unknown users
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Warn about valid settings that are likely mistakes, with fix hints
+    Lint,
+
+    /// Upgrade the config file to the current format version (keeps a backup)
+    Migrate {
+        /// Show the changes and the migrated config without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show version information (with -v/--verbose: build, tmux and path details for bug reports)
+    Version,
 
     /// Generate shell completions
     Completions {
         /// Shell type (fish, bash, zsh)
         shell: String,
+
+        /// Write the script to the shell's completions directory instead of stdout
+        #[arg(long)]
+        install: bool,
     },
 
     /// List configured sessions (hidden, for completions)
     #[command(name = "__list-configured", hide = true)]
-    ListConfigured,
+    ListConfigured {
+        /// Follow each session ID with a tab and its source
+        #[arg(long)]
+        sources: bool,
+    },
+
+    /// List workspaces (hidden, for completions)
+    #[command(name = "__list-workspaces", hide = true)]
+    ListWorkspaces,
 
     /// List running sessions (hidden, for completions)
     #[command(name = "__list-running", hide = true)]
     ListRunning,
+
+    /// List a session's windows (hidden, for completing `session:window` targets)
+    #[command(name = "__list-windows", hide = true)]
+    ListWindows { session: String },
+
+    /// Show a notification for a pane whose command exited (hidden, run by tmux hooks)
+    #[command(name = "__notify-exit", hide = true)]
+    NotifyExit {
+        session: String,
+        window: String,
+        pane: String,
+        #[arg(default_value = "")]
+        status: String,
+    },
+
+    /// Run a session's on_died command (hidden, run by tmux hooks)
+    #[command(name = "__session-died", hide = true)]
+    SessionDied { session: String },
+
+    /// External plugin subcommand (runs `tmx-<name>` from PATH)
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+/// How tmx hands over to a session once it is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AttachMode {
+    /// Attach the terminal to the session (`tmux attach-session`)
+    Attach,
+    /// Switch the current tmux client to the session (`tmux switch-client`)
+    Switch,
+    /// Print the session name and leave attaching to the caller
+    Print,
+    /// Attach from a new terminal window (`terminal_command` in the config)
+    NewTerminal,
+}
+
+impl AttachMode {
+    /// Combine `--attach-mode` with the `--new-terminal` shorthand
+    pub fn from_args(attach_mode: Option<Self>, new_terminal: bool) -> Option<Self> {
+        if new_terminal {
+            Some(AttachMode::NewTerminal)
+        } else {
+            attach_mode
+        }
+    }
+}
+
+/// Output format of commands with machine-readable output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// JSON
+    Json,
+}
+
+/// Diagram format of `tmx graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Mermaid flowchart (renders in GitHub and GitLab markdown)
+    Mermaid,
+    /// Graphviz digraph
+    Dot,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Restore the config file from a backup taken before tmx modified it
+    Restore {
+        /// Backup file name (default: newest backup)
+        backup: Option<String>,
+
+        /// List available backups instead of restoring
+        #[arg(long)]
+        list: bool,
+    },
+    /// Print the message catalog as a translation file
+    Messages,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommands {
+    /// Fetch a config over HTTPS and add its sessions to the configuration
+    Url {
+        /// https:// URL of a tmx config
+        url: String,
+
+        /// Expected SHA-256 checksum of the config (skips confirmation)
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+
+        /// Import without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LayoutCommands {
+    /// Save the layout of a running window under [layouts] in the config
+    Save {
+        /// Window to save, as <session>[:<window>]
+        target: String,
+
+        /// Layout name, used as layout = "@<name>"
+        name: String,
+    },
+    /// Arrange a running window with a layout from [layouts]
+    Apply {
+        /// Window to arrange, as <session>[:<window>]
+        target: String,
+
+        /// Layout name from [layouts]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProjectCommands {
+    /// Create a project directory with a .tmx.toml and open its session
+    New {
+        /// Project (directory and session) name
+        name: String,
+
+        /// Stack the files and windows are set up for
+        #[arg(long, value_enum)]
+        template: ProjectTemplate,
+
+        /// How to hand over to the session (default: switch inside tmux, attach outside)
+        #[arg(long, value_enum)]
+        attach_mode: Option<AttachMode>,
+
+        /// Open in a new terminal window (same as --attach-mode new-terminal)
+        #[arg(long, visible_alias = "terminal", conflicts_with = "attach_mode")]
+        new_terminal: bool,
+    },
+    /// Show the commands a project config runs and allow tmx to use it
+    Trust {
+        /// Project config to trust (default: the .tmx.toml found from the current directory)
+        path: Option<String>,
+    },
+}
+
+/// Stacks `tmx project new` can set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProjectTemplate {
+    /// Cargo binary crate
+    Rust,
+    /// npm package using node's test runner
+    Node,
+    /// Python module with unittest tests
+    Python,
+}
+
+#[derive(Subcommand)]
+pub enum ServerCommands {
+    /// Start the tmux server, kept running even without sessions
+    Start,
+    /// Kill the tmux server and all its sessions
+    Kill {
+        /// Don't ask for confirmation when sessions are running
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show the server's socket, pid, version and session count
+    Info,
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceCommands {
+    /// Start all sessions of a workspace and attach to its primary session
+    #[command(alias = "o")]
+    Open {
+        /// Workspace name from config
+        name: String,
+
+        /// How to hand over to the primary session (default: switch inside tmux, attach outside)
+        #[arg(long, value_enum)]
+        attach_mode: Option<AttachMode>,
+
+        /// Open the primary session in a new terminal window
+        #[arg(long, visible_alias = "terminal", conflicts_with = "attach_mode")]
+        new_terminal: bool,
+    },
+
+    /// Close all running sessions of a workspace
+    #[command(alias = "c")]
+    Close {
+        /// Workspace name from config
+        name: String,
+    },
 }
@@ -0,0 +1,234 @@
+//! Closing sessions according to their `close_strategy`.
+//!
+//! A graceful close interrupts every busy pane, optionally runs the
+//! session's `shutdown_command` (with `sh -c` in the session root, like
+//! hooks), and waits for the processes to exit before killing the session,
+//! so services get a chance to shut down cleanly.
+
+use crate::config::{CloseStrategy, Session};
+use crate::hooks::{self, Hook, HookSession};
+use crate::log;
+use crate::messages;
+use crate::shell::ShellKind;
+use crate::state;
+use crate::tmux;
+use crate::tmux::query::{self, LivePane, LiveSession};
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Seconds to wait for processes to exit when `close_timeout` isn't set
+const DEFAULT_CLOSE_TIMEOUT: u64 = 10;
+
+/// How often the panes are checked while waiting for them to exit
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Close a running session.
+///
+/// # Arguments
+/// * `session_name` - The running session's name
+/// * `session` - Its config ID and configuration, if any (unconfigured sessions are killed)
+pub fn close_session(session_name: &str, session: Option<(&str, &Session)>) -> Result<()> {
+    run_pre_close(session_name, session);
+    stop_processes(session_name, session)?;
+    // Forgotten first, so the session isn't reported as died (`on_died`)
+    state::forget_session(session_name)?;
+    tmux::kill_session(session_name)
+}
+
+/// Run a session's `pre_close` commands in its root; a failure is reported
+/// but doesn't stop the session from closing.
+///
+/// # Arguments
+/// * `session_name` - The running session's name
+/// * `session` - Its config ID and configuration, if any (nothing is run without one)
+pub fn run_pre_close(session_name: &str, session: Option<(&str, &Session)>) {
+    if let Some((id, session)) = session {
+        let root = session.root_expanded();
+        hooks::run_or_warn(
+            Hook::PreClose,
+            &session.pre_close,
+            &HookSession::new(session_name, Some(id), session),
+            Path::new(&root),
+        );
+    }
+}
+
+/// Stop the processes of a session before it is killed, if it closes gracefully.
+///
+/// Busy panes are interrupted, then the panes are polled until they are back
+/// at their shell or `close_timeout` passes.
+///
+/// # Arguments
+/// * `session_name` - The running session's name
+/// * `session` - Its config ID and configuration, if any (nothing is done without one)
+pub fn stop_processes(session_name: &str, session: Option<(&str, &Session)>) -> Result<()> {
+    let Some((id, session)) = session.filter(|(_, s)| s.close_strategy == CloseStrategy::Graceful)
+    else {
+        return Ok(());
+    };
+    let Some(live) = query::live_session(session_name)? else {
+        return Ok(());
+    };
+    let timeout = Duration::from_secs(session.close_timeout.unwrap_or(DEFAULT_CLOSE_TIMEOUT));
+    let started = Instant::now();
+    // The pane tmx runs in (when closing the current session) is left alone
+    let own_pane = std::env::var("TMUX_PANE").ok();
+    let is_own = |pane: &LivePane| own_pane.as_deref() == Some(pane.id.as_str());
+
+    messages::say(
+        messages::SESSION_STOPPING_PROCESSES,
+        &[("session", &session_name)],
+    );
+    log::info(&format!("stopping processes in session '{}'", session_name));
+    for pane in panes(&live).filter(|pane| is_busy(pane) && !is_own(pane)) {
+        tmux::send_interrupt(&pane.id)?;
+    }
+
+    if let Some(ref command) = session.shutdown_command {
+        let hook_session = HookSession::new(session_name, Some(id), session);
+        run_shutdown_command(command, &hook_session, session, timeout)?;
+    }
+
+    loop {
+        // Programs exit on their own, so ask tmux again each time
+        tmux::clear_query_cache();
+        let busy: Vec<String> = match query::live_session(session_name)? {
+            Some(live) => panes(&live)
+                .filter(|pane| is_busy(pane) && !is_own(pane))
+                .map(|pane| pane.current_command.clone())
+                .collect(),
+            None => return Ok(()),
+        };
+        if busy.is_empty() {
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            log::info(&format!(
+                "processes still running in session '{}' after {}s: {}",
+                session_name,
+                timeout.as_secs(),
+                busy.join(", ")
+            ));
+            messages::say(
+                messages::SESSION_KILLING_BUSY,
+                &[
+                    ("seconds", &timeout.as_secs()),
+                    ("processes", &busy.join(", ")),
+                ],
+            );
+            return Ok(());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn panes(live: &LiveSession) -> impl Iterator<Item = &LivePane> {
+    live.windows.iter().flat_map(|window| window.panes.iter())
+}
+
+/// Whether a pane is running something other than its shell
+fn is_busy(pane: &LivePane) -> bool {
+    !pane.dead && ShellKind::from_name(&pane.current_command).is_none()
+}
+
+/// Run a session's `shutdown_command` in its root, waiting at most `timeout`
+/// for it; a command still running then is killed and reported.
+///
+/// It runs outside the session rather than in one of its panes, which may
+/// hold an editor or another program that would take it as keystrokes.
+fn run_shutdown_command(
+    command: &str,
+    hook_session: &HookSession,
+    session: &Session,
+    timeout: Duration,
+) -> Result<()> {
+    let session_name = hook_session.name;
+    let root = session.root_expanded();
+    messages::say(messages::SESSION_SHUTDOWN_RUNNING, &[("command", &command)]);
+    log::info(&format!(
+        "shutdown command of session '{}' in {}: {}",
+        session_name, root, command
+    ));
+    let mut child = hooks::shell_command(command, hook_session, Path::new(&root))
+        .spawn()
+        .with_context(|| format!("shutdown_command '{}' couldn't run", command))?;
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Failed to wait for shutdown_command")?
+        {
+            if !status.success() {
+                log::error(&format!(
+                    "shutdown command of session '{}' failed ({})",
+                    session_name, status
+                ));
+                messages::say(
+                    messages::SESSION_SHUTDOWN_FAILED,
+                    &[("command", &command), ("status", &status)],
+                );
+            }
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            log::error(&format!(
+                "shutdown command of session '{}' killed after {}s",
+                session_name,
+                timeout.as_secs()
+            ));
+            messages::say(
+                messages::SESSION_SHUTDOWN_TIMEOUT,
+                &[("command", &command), ("seconds", &timeout.as_secs())],
+            );
+            return Ok(());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_run_shutdown_command() {
+        let dir = std::env::temp_dir().join(format!("tmx-close-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let session: Session = toml::from_str(&format!(
+            "name = \"api\"\nroot = \"{}\"\nwindows = []",
+            dir.display()
+        ))
+        .unwrap();
+
+        let hook_session = HookSession::new("api", Some("api"), &session);
+
+        // Runs in the session root, with the session name
+        run_shutdown_command(
+            "echo \"$TMX_SESSION\" > stopped",
+            &hook_session,
+            &session,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(dir.join("stopped")).unwrap(), "api\n");
+
+        // A command that doesn't finish in time is stopped
+        let started = Instant::now();
+        run_shutdown_command(
+            "sleep 10",
+            &hook_session,
+            &session,
+            Duration::from_millis(300),
+        )
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cli::AttachMode;
+use crate::commands::{default, start};
+use crate::context::Context;
+use crate::log;
+use crate::prompt;
+use crate::tmux;
+use anyhow::Result;
+
+/// Attach or switch to a running session, without ever creating one.
+///
+/// Without a session argument, a numbered chooser of running sessions is
+/// shown (or the only running session is attached directly).
+///
+/// # Arguments
+/// * `session_id` - Session name or config ID, or None to choose interactively
+/// * `attach_mode` - How to hand over to the session (None: decide from context)
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: Option<&str>, attach_mode: Option<AttachMode>, ctx: &Context) -> Result<()> {
+    log::info(&format!("attach command: session_id={:?}", session_id));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let running = tmux::list_sessions()?;
+    if running.is_empty() {
+        anyhow::bail!("No tmux sessions are running\n  Hint: Start one with 'tmx open <session>'");
+    }
+
+    let target = match session_id {
+        Some(id) => resolve_running(id, &running, ctx)?,
+        None => match choose_session(&running, ctx)? {
+            Some(name) => name,
+            None => return Ok(()),
+        },
+    };
+
+    log::info(&format!("attaching to '{}'", target));
+    start::attach_or_switch(&target, attach_mode, ctx)
+}
+
+/// Map a session name or config ID to the name of a running session
+pub fn resolve_running(session_id: &str, running: &[String], ctx: &Context) -> Result<String> {
+    let mut candidates = vec![tmux::sanitize_session_name(session_id)];
+    if let Some(session) = ctx.config().ok().and_then(|c| c.get_session(session_id)) {
+        candidates.push(tmux::sanitize_session_name(&session.name));
+    }
+
+    candidates
+        .into_iter()
+        .find(|name| running.contains(name))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Session '{}' is not running\n  \
+                 Running sessions: {}\n  \
+                 Hint: Use 'tmx open {}' to create it",
+                session_id,
+                running.join(", "),
+                session_id
+            )
+        })
+}
+
+/// Let the user pick a running session
+///
+/// # Returns
+/// The chosen session name, or None if the user cancelled.
+fn choose_session(running: &[String], ctx: &Context) -> Result<Option<String>> {
+    let ordered = default::order_sessions(running, ctx.config().ok());
+    if let [only] = ordered.as_slice() {
+        return Ok(Some(only.clone()));
+    }
+
+    let current = if ctx.is_inside_tmux {
+        tmux::get_current_session().ok()
+    } else {
+        None
+    };
+    let labels: Vec<String> = ordered
+        .iter()
+        .map(|name| match &current {
+            Some(current) if current == name => format!("{} (current)", name),
+            _ => name.clone(),
+        })
+        .collect();
+
+    let choice = prompt::pick("Running sessions:", &labels)?;
+    Ok(choice.map(|i| ordered[i].clone()))
+}
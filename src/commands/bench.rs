@@ -0,0 +1,134 @@
+use crate::commands::start;
+use crate::context::Context;
+use crate::log;
+use crate::session;
+use crate::tmux;
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+/// Kills the isolated benchmark server when the benchmark ends, even on error
+struct IsolatedServer;
+
+impl IsolatedServer {
+    fn start() -> Self {
+        tmux::set_socket_name(Some(&format!("tmx-bench-{}", std::process::id())));
+        Self
+    }
+}
+
+impl Drop for IsolatedServer {
+    fn drop(&mut self) {
+        if let Err(e) = tmux::kill_server() {
+            log::error(&format!("failed to kill benchmark server: {}", e));
+        }
+        tmux::set_socket_name(None);
+    }
+}
+
+/// Repeatedly create and destroy a copy of a session and report phase timings.
+///
+/// Runs on a separate tmux server (`tmux -L tmx-bench-<pid>`), so running
+/// sessions are never touched. One untimed warm-up run starts the server
+/// first, so its startup cost doesn't skew the results.
+///
+/// # Arguments
+/// * `session_id` - The session name or ID from config
+/// * `iterations` - Number of timed runs
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: &str, iterations: usize, ctx: &Context) -> Result<()> {
+    log::info(&format!("bench command: session_id={} iterations={}", session_id, iterations));
+
+    if !tmux::is_installed() {
+        log::error("tmux is not installed");
+        anyhow::bail!("tmux is not installed");
+    }
+
+    if iterations == 0 {
+        anyhow::bail!("Iterations must be at least 1");
+    }
+
+    let config = ctx.config()?;
+    let (mut session, _) = start::resolve_session(session_id, config)?;
+    session.name = format!("tmx-bench-{}", tmux::sanitize_session_name(&session.name));
+
+    let _server = IsolatedServer::start();
+
+    println!(
+        "Benchmarking '{}' ({} window(s)) over {} iteration(s)...",
+        session_id,
+        session.windows.len(),
+        iterations
+    );
+
+    // Warm-up: starts the server and fills caches (e.g. prompted env values)
+    session::create_session_measured(&session, ctx)?;
+    tmux::kill_session(&session.name)?;
+
+    let mut samples: Vec<(&'static str, Vec<Duration>)> = Vec::new();
+    let mut record = |phase: &'static str, duration: Duration| {
+        match samples.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, durations)) => durations.push(duration),
+            None => samples.push((phase, vec![duration])),
+        }
+    };
+
+    for _ in 0..iterations {
+        let started = Instant::now();
+        let timings = session::create_session_measured(&session, ctx)?;
+        let created = started.elapsed();
+
+        let kill_started = Instant::now();
+        tmux::kill_session(&session.name)?;
+        let killed = kill_started.elapsed();
+
+        for (phase, duration) in timings.phases() {
+            record(phase, *duration);
+        }
+        record("kill-session", killed);
+        record("total", created + killed);
+    }
+
+    println!();
+    println!(
+        "{:<14} {:>10} {:>10} {:>10} {:>10}",
+        "phase", "min", "p50", "p90", "max"
+    );
+    for (phase, durations) in &mut samples {
+        durations.sort();
+        println!(
+            "{:<14} {:>10} {:>10} {:>10} {:>10}",
+            phase,
+            format_ms(durations[0]),
+            format_ms(percentile(durations, 50)),
+            format_ms(percentile(durations, 90)),
+            format_ms(durations[durations.len() - 1]),
+        );
+    }
+
+    Ok(())
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Format a duration in milliseconds with one decimal
+fn format_ms(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(5));
+        assert_eq!(percentile(&samples, 90), Duration::from_millis(9));
+        assert_eq!(percentile(&samples, 100), Duration::from_millis(10));
+        assert_eq!(percentile(&samples[..1], 90), Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::commands::run::{self, TargetPane};
+use crate::context::Context;
+use crate::log;
+use crate::tmux;
+use anyhow::Result;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// Copy the last lines of a pane into a tmux paste buffer and the clipboard.
+///
+/// Inside tmux, tmux itself forwards the buffer to the terminal's clipboard
+/// (`set-buffer -w`); outside, tmx writes an OSC 52 sequence to the terminal,
+/// which also reaches the local clipboard over SSH.
+///
+/// # Arguments
+/// * `target` - `<session>[:<window>[.<pane>]]`, as for `tmx run`
+/// * `lines` - How many lines to copy, counted from the bottom of the pane
+/// * `clipboard` - Also copy to the system clipboard
+/// * `ctx` - Shared context containing configuration and state
+pub fn copy(target: &str, lines: usize, clipboard: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "copy command: target={} lines={} clipboard={}",
+        target, lines, clipboard
+    ));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let TargetPane {
+        session,
+        window,
+        pane,
+    } = run::resolve_target(target, ctx)?;
+    let address = format!("{}:{}.{}", session, window.index, pane.index);
+
+    let captured = tmux::capture_pane(&pane.id, lines)?;
+    let text = tail(&captured, lines);
+    if text.is_empty() {
+        println!("Pane {} is empty, nothing copied", address);
+        return Ok(());
+    }
+
+    if clipboard && ctx.is_inside_tmux {
+        if let Err(e) = tmux::set_buffer(&text, true) {
+            log::error(&format!("copy: set-buffer -w failed: {}", e));
+            println!("Warning: Couldn't copy to the clipboard (needs tmux 3.2+)");
+            tmux::set_buffer(&text, false)?;
+        }
+    } else {
+        tmux::set_buffer(&text, false)?;
+        if clipboard && let Err(e) = write_osc52(&text) {
+            log::info(&format!("copy: no terminal for OSC 52: {}", e));
+        }
+    }
+
+    println!("✓ Copied {} line(s) from {}", text.lines().count(), address);
+    Ok(())
+}
+
+/// Paste the most recent tmux paste buffer into a pane.
+///
+/// # Arguments
+/// * `target` - `<session>[:<window>[.<pane>]]`, as for `tmx run`
+/// * `ctx` - Shared context containing configuration and state
+pub fn paste(target: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!("paste command: target={}", target));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let TargetPane {
+        session,
+        window,
+        pane,
+    } = run::resolve_target(target, ctx)?;
+    let address = format!("{}:{}.{}", session, window.index, pane.index);
+
+    // tmux quietly does nothing when there is no buffer to paste
+    if !tmux::has_buffers()? {
+        anyhow::bail!(
+            "No paste buffer to paste into {}\n  \
+             Hint: Copy something first, e.g. 'tmx copy <session>:<window>.<pane>'",
+            address
+        );
+    }
+    tmux::paste_buffer(&pane.id)?;
+
+    println!("✓ Pasted into {}", address);
+    Ok(())
+}
+
+/// The last `lines` lines of captured pane output, without the blank lines
+/// below the cursor
+fn tail(captured: &str, lines: usize) -> String {
+    let all: Vec<&str> = captured.trim_end().lines().collect();
+    all[all.len().saturating_sub(lines)..]
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Send text to the terminal's clipboard with an OSC 52 escape sequence
+fn write_osc52(text: &str) -> std::io::Result<()> {
+    let mut tty = OpenOptions::new().write(true).open("/dev/tty")?;
+    write!(tty, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    tty.flush()
+}
+
+/// Standard base64 with padding, as OSC 52 expects
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail() {
+        let captured = "$ make\ncc -o app main.c  \nwarning: unused\n$ \n\n\n";
+        assert_eq!(tail(captured, 2), "warning: unused\n$");
+        assert_eq!(
+            tail(captured, 100),
+            "$ make\ncc -o app main.c\nwarning: unused\n$"
+        );
+        assert_eq!(tail("\n\n", 10), "");
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("tmx ✓".as_bytes()), "dG14IOKckw==");
+    }
+}
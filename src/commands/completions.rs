@@ -1,5 +1,7 @@
 use crate::shells;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Shell {
@@ -21,17 +23,78 @@ impl std::str::FromStr for Shell {
     }
 }
 
-pub fn run_completions(shell: Shell) -> Result<()> {
-    match shell {
-        Shell::Fish => {
-            println!("{}", shells::fish::generate_completions());
-        }
-        Shell::Bash => {
-            println!("{}", shells::bash::generate_completions());
+impl Shell {
+    /// Generate the completion script for this shell
+    fn generate(self) -> String {
+        match self {
+            Shell::Fish => shells::fish::generate_completions(),
+            Shell::Bash => shells::bash::generate_completions(),
+            Shell::Zsh => shells::zsh::generate_completions(),
         }
+    }
+
+    /// Conventional per-user location for this shell's completion scripts
+    ///
+    /// - fish: `$XDG_CONFIG_HOME/fish/completions/tmx.fish`
+    /// - bash: `$XDG_DATA_HOME/bash-completion/completions/tmx` (loaded by bash-completion)
+    /// - zsh: `${ZDOTDIR:-~}/.zfunc/_tmx` (must be on `fpath`)
+    fn install_path(self) -> Result<PathBuf> {
+        let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+
+        let path = match self {
+            Shell::Fish => xdg_dir("XDG_CONFIG_HOME", home_dir.join(".config"))
+                .join("fish")
+                .join("completions")
+                .join("tmx.fish"),
+            Shell::Bash => xdg_dir("XDG_DATA_HOME", home_dir.join(".local").join("share"))
+                .join("bash-completion")
+                .join("completions")
+                .join("tmx"),
+            Shell::Zsh => std::env::var("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or(home_dir)
+                .join(".zfunc")
+                .join("_tmx"),
+        };
+
+        Ok(path)
+    }
+}
+
+/// Read an XDG base directory from the environment, falling back to its default
+fn xdg_dir(var: &str, default: PathBuf) -> PathBuf {
+    std::env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or(default)
+}
+
+pub fn run_completions(shell: Shell, install: bool) -> Result<()> {
+    if !install {
+        println!("{}", shell.generate());
+        return Ok(());
+    }
+
+    let path = shell.install_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+
+    fs::write(&path, format!("{}\n", shell.generate()))
+        .with_context(|| format!("Failed to write completions: {}", path.display()))?;
+
+    println!("✓ Completions installed to {}", path.display());
+    match shell {
+        Shell::Fish => println!("Open a new fish shell to use them"),
+        Shell::Bash => println!("Requires the bash-completion package; open a new shell to use them"),
         Shell::Zsh => {
-            println!("{}", shells::zsh::generate_completions());
+            println!("Make sure the directory is on your fpath, e.g. in ~/.zshrc:");
+            println!("  fpath=({} $fpath)", path.parent().unwrap_or(&path).display());
+            println!("  autoload -Uz compinit && compinit");
         }
     }
+
     Ok(())
 }
@@ -0,0 +1,77 @@
+use crate::backup;
+use crate::context::Context;
+use crate::log;
+use crate::messages;
+use anyhow::{Context as _, Result};
+use std::fs;
+
+/// Restore the config file from a backup.
+///
+/// The current config is itself backed up first, so a restore can be undone.
+///
+/// # Arguments
+/// * `backup_name` - Backup file name (from `--list`); defaults to the newest backup
+/// * `list` - List available backups instead of restoring
+/// * `ctx` - Shared context containing configuration and state
+pub fn restore(backup_name: Option<&str>, list: bool, ctx: &Context) -> Result<()> {
+    let config_path = ctx.config_path();
+    let backups = backup::list(config_path)?;
+
+    if list {
+        if backups.is_empty() {
+            println!("No backups of {}", config_path.display());
+        } else {
+            println!("Backups of {} (newest first):", config_path.display());
+            for path in &backups {
+                if let Some(name) = path.file_name() {
+                    println!("  {}", name.to_string_lossy());
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let source = match backup_name {
+        Some(name) => backups
+            .iter()
+            .find(|p| p.file_name().is_some_and(|n| n == name))
+            .with_context(|| {
+                format!("Backup '{}' not found\nHint: Run 'tmx config restore --list'", name)
+            })?,
+        None => backups
+            .first()
+            .with_context(|| format!("No backups of {}", config_path.display()))?,
+    };
+
+    // Keep the config being replaced so the restore itself can be undone
+    if let Some(saved) = backup::create(config_path)? {
+        println!("Saved current config to {}", saved.display());
+    }
+
+    fs::copy(source, config_path)
+        .with_context(|| format!("Failed to restore {}", config_path.display()))?;
+    log::info(&format!("restored {} from {}", config_path.display(), source.display()));
+
+    println!(
+        "✓ Restored {} from {}",
+        config_path.display(),
+        source.display()
+    );
+    Ok(())
+}
+
+/// Print the English message catalog as a translation file.
+///
+/// Saved as `~/.config/tmx/messages/<lang>.toml` (e.g. `de.toml`) and
+/// translated, it replaces tmx's output in that language.
+pub fn messages() -> Result<()> {
+    if let Some(dir) = messages::translations_dir() {
+        println!(
+            "# Save as {}/<lang>.toml and translate the values",
+            dir.display()
+        );
+        println!("# Keep the {{placeholders}}; messages with unknown ones are shown in English");
+    }
+    print!("{}", messages::catalog_toml());
+    Ok(())
+}
@@ -1,17 +1,25 @@
+use crate::cli::AttachMode;
+use crate::commands::{refresh, start};
+use crate::config::Config;
 use crate::context::Context as AppContext;
+use crate::messages;
 use crate::tmux;
-use crate::{commands::refresh, config::Config};
 use anyhow::{Context, Result};
 
 /// Cycle through running tmux sessions, or start the first configured session if none are running.
 ///
 /// Behavior:
-/// - If no sessions are running: start the first configured session
+/// - If no sessions are running: start the `default` session for the current
+///   directory, or the first configured session
 /// - If inside tmux: get current session and switch to next
 /// - If outside tmux: attach to first running session
 ///
 /// Cycling order: configured sessions (alphabetically), then unconfigured sessions (alphabetically)
-pub fn run(ctx: &AppContext) -> Result<()> {
+///
+/// With an `attach_mode`, tmx hands over to the session that way instead: the
+/// current session inside tmux, the first running session outside, or the
+/// started one (e.g. `print` only prints its name).
+pub fn run(attach_mode: Option<AttachMode>, ctx: &AppContext) -> Result<()> {
     // Check if tmux is installed
     if !tmux::is_installed() {
         anyhow::bail!("tmux is not installed");
@@ -24,8 +32,8 @@ pub fn run(ctx: &AppContext) -> Result<()> {
         // No sessions running, start default or first from config
         let config = ctx.config().context("Failed to load configuration")?;
 
-        // Use default session if specified, otherwise use first session
-        let session_id = if let Some(ref default) = config.default {
+        // Use the default session for this directory if specified, otherwise use first session
+        let session_id = if let Some(default) = config.default_session_id() {
             // Validate that the default session exists
             if !config.sessions.contains_key(default) {
                 anyhow::bail!(
@@ -34,7 +42,7 @@ pub fn run(ctx: &AppContext) -> Result<()> {
                     config.session_ids().join(", ")
                 );
             }
-            default.clone()
+            default.to_string()
         } else {
             // No default specified, use first session
             let session_ids = config.session_ids();
@@ -44,8 +52,11 @@ pub fn run(ctx: &AppContext) -> Result<()> {
             session_ids[0].clone()
         };
 
-        println!("No sessions running. Starting '{}'...", session_id);
-        return crate::commands::start::run(&session_id, ctx);
+        messages::say(
+            messages::SESSION_STARTING_DEFAULT,
+            &[("session", &session_id)],
+        );
+        return start::run(&session_id, false, false, attach_mode, ctx);
     }
 
     // Get config from context to determine session ordering (only load once!)
@@ -55,17 +66,20 @@ pub fn run(ctx: &AppContext) -> Result<()> {
     // If inside tmux, get current session and switch to next
     if ctx.is_inside_tmux {
         let current = tmux::get_current_session()?;
-        return refresh::run(&current, ctx);
+        if attach_mode.is_some() {
+            return start::attach_or_switch(&current, attach_mode, ctx);
+        }
+        return refresh::run(Some(&current), None, ctx);
     }
 
     // Not in tmux, attach to first session
     let first = &ordered_sessions[0];
-    println!("Attaching to session '{}'...", first);
-    tmux::attach_session(first)
+    messages::say(messages::SESSION_CYCLE_ATTACHING, &[("session", first)]);
+    start::attach_or_switch(first, attach_mode, ctx)
 }
 
 /// Order sessions: configured sessions first (alphabetically), then unconfigured sessions (alphabetically)
-fn order_sessions(running: &[String], config: Option<&Config>) -> Vec<String> {
+pub fn order_sessions(running: &[String], config: Option<&Config>) -> Vec<String> {
     let mut result = Vec::new();
 
     if let Some(cfg) = config {
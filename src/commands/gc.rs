@@ -0,0 +1,46 @@
+use crate::context::Context;
+use crate::gc;
+use crate::log;
+use anyhow::Result;
+
+/// Clean up stale state and old files now.
+///
+/// Drops state records of sessions that no longer exist, removes config
+/// backups older than the retention period and trims the log file to its
+/// size cap. Without a loadable config, the default settings are used.
+///
+/// # Arguments
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(ctx: &Context) -> Result<()> {
+    log::info("gc command");
+
+    let config = ctx.config().ok();
+    let gc_config = config.map(|c| c.gc.clone()).unwrap_or_default();
+    let log_config = config.map(|c| c.log.clone()).unwrap_or_default();
+
+    let report = gc::run(&gc_config, &log_config)?;
+    if report.is_empty() {
+        println!("✓ Nothing to clean up");
+        return Ok(());
+    }
+    if !report.sessions.is_empty() {
+        println!(
+            "✓ Dropped {} stale session record(s): {}",
+            report.sessions.len(),
+            report.sessions.join(", ")
+        );
+    }
+    if report.backups > 0 {
+        println!(
+            "✓ Removed {} config backup(s) older than {} days",
+            report.backups, gc_config.backup_retention_days
+        );
+    }
+    if report.log_bytes > 0 {
+        println!(
+            "✓ Trimmed {} KB of old records from the log file",
+            report.log_bytes.div_ceil(1024)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::cli::GraphFormat;
+use crate::commands::{attach, start};
+use crate::context::Context;
+use crate::graph::Diagram;
+use crate::log;
+use crate::tmux;
+use crate::tmux::query;
+use anyhow::{Context as _, Result};
+
+/// Print a diagram of a session's windows and panes.
+///
+/// # Arguments
+/// * `session_id` - Session name or ID from config
+/// * `format` - `mermaid` or `dot` (Graphviz)
+/// * `live` - Draw the running session instead of its configuration
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: &str, format: GraphFormat, live: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "graph command: session_id={} format={:?} live={}",
+        session_id, format, live
+    ));
+
+    let diagram = if live {
+        if !tmux::is_installed() {
+            anyhow::bail!("tmux is not installed");
+        }
+        let running = tmux::list_sessions()?;
+        let name = attach::resolve_running(session_id, &running, ctx)?;
+        let session = query::live_session(&name)?
+            .with_context(|| format!("Session '{}' is not running", name))?;
+        Diagram::from_live(&session)
+    } else {
+        let (session, _) = start::resolve_session(session_id, ctx.config()?)?;
+        Diagram::from_config(&session)
+    };
+
+    match format {
+        GraphFormat::Mermaid => print!("{}", diagram.to_mermaid()),
+        GraphFormat::Dot => print!("{}", diagram.to_dot()),
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::cli::OutputFormat;
+use crate::log;
+use crate::messages;
+use crate::state::{HistoryEntry, Outcome, State};
+use crate::table::{Cell, Style, Table};
+use anyhow::Result;
+
+/// Print the recent opens, closes and refreshes with their outcomes.
+///
+/// # Arguments
+/// * `session` - Only show operations on this session
+/// * `lines` - Number of operations to show, most recent last
+/// * `format` - `text` for a table, `json` for scripts
+pub fn run(session: Option<&str>, lines: usize, format: OutputFormat) -> Result<()> {
+    let state = State::load();
+    let entries: Vec<&HistoryEntry> = state
+        .history
+        .iter()
+        .filter(|entry| session.is_none_or(|s| entry.session == s))
+        .collect();
+    let entries = &entries[entries.len().saturating_sub(lines)..];
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        match session {
+            Some(session) => {
+                messages::say(messages::HISTORY_EMPTY_SESSION, &[("session", &session)])
+            }
+            None => messages::say(messages::HISTORY_EMPTY, &[]),
+        }
+        return Ok(());
+    }
+
+    print!("{}", history_table(entries).render());
+    Ok(())
+}
+
+fn history_table(entries: &[&HistoryEntry]) -> Table {
+    let mut table = Table::new(&["TIME (UTC)", "OPERATION", "SESSION", "OUTCOME", "ERROR"]);
+    for entry in entries {
+        let outcome = match entry.outcome {
+            Outcome::Ok => Cell::new("ok", Style::Green),
+            Outcome::Failed => Cell::new("failed", Style::Red),
+        };
+        table.row(vec![
+            format_time(entry.time).into(),
+            entry.operation.to_string().into(),
+            entry.session.as_str().into(),
+            outcome,
+            entry.error.clone().unwrap_or_default().into(),
+        ]);
+    }
+    table
+}
+
+/// `YYYY-MM-DD HH:MM:SS`, as in the log
+fn format_time(secs: u64) -> String {
+    let (year, month, day, hours, minutes, seconds) = log::utc_datetime(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hours, minutes, seconds
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Operation;
+
+    #[test]
+    fn test_history_table() {
+        let opened = HistoryEntry {
+            time: 1_700_000_000,
+            operation: Operation::Open,
+            session: "dev".to_string(),
+            outcome: Outcome::Ok,
+            error: None,
+        };
+        let failed = HistoryEntry {
+            operation: Operation::Close,
+            session: "api".to_string(),
+            outcome: Outcome::Failed,
+            error: Some("Session 'api' does not exist".to_string()),
+            ..opened.clone()
+        };
+        assert_eq!(
+            history_table(&[&opened, &failed]).render_with(false),
+            "TIME (UTC)           OPERATION  SESSION  OUTCOME  ERROR\n\
+             2023-11-14 22:13:20  open       dev      ok\n\
+             2023-11-14 22:13:20  close      api      failed   Session 'api' does not exist\n"
+        );
+    }
+}
@@ -0,0 +1,532 @@
+use crate::checksum;
+use crate::cli::AttachMode;
+use crate::commands::start;
+use crate::config::{Config, OptionValue, Session};
+use crate::context::Context;
+use crate::log;
+use crate::messages;
+use crate::prompt;
+use crate::remote::{self, REMOTE_SOURCE};
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// First line of a cached config, followed by its URL
+const SOURCE_PREFIX: &str = "# Imported from ";
+/// Second line of a cached config, followed by the checksum of what was fetched
+const SHA256_PREFIX: &str = "# sha256: ";
+
+/// A config fetched from a URL; as with shared config repositories, only
+/// its sessions (and which one to open) are used
+#[derive(Debug, Deserialize)]
+struct Fetched {
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    sessions: BTreeMap<String, Session>,
+}
+
+/// A config cached from a URL
+struct Imported {
+    /// The cached file in the remote config directory
+    path: PathBuf,
+    /// IDs of its sessions, as defined in the file
+    ids: Vec<String>,
+    /// The session the file names as its `default`
+    default: Option<String>,
+}
+
+/// Fetch a config from an HTTPS URL and add its sessions to the configuration.
+///
+/// The file is cached in the remote config directory, where its sessions are
+/// loaded like those of shared config repositories. Unless `sha256` is given
+/// and matches, the fetched sessions and the commands they run are shown and
+/// importing them has to be confirmed; fetching the same content again
+/// needs no confirmation.
+///
+/// # Arguments
+/// * `url` - HTTPS URL of a tmx config
+/// * `sha256` - Expected SHA-256 checksum of the file
+/// * `yes` - Import without asking for confirmation
+/// * `ctx` - Shared context containing configuration and state
+pub fn url(url: &str, sha256: Option<&str>, yes: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "import url command: url={} sha256={:?} yes={}",
+        url, sha256, yes
+    ));
+
+    let Some(imported) = import(url, sha256, yes)? else {
+        return Ok(());
+    };
+
+    let ctx = ctx.reloaded();
+    let config = ctx.config()?;
+    for id in &imported.ids {
+        let id = configured_id(id, config).unwrap_or_else(|| id.clone());
+        messages::say(messages::IMPORT_OPEN_HINT, &[("session", &id)]);
+    }
+    Ok(())
+}
+
+/// Fetch a config from an HTTPS URL, import it (see `url`) and open one of
+/// its sessions: its `default`, or its only session.
+///
+/// # Arguments
+/// * `url` - HTTPS URL of a tmx config
+/// * `sha256` - Expected SHA-256 checksum of the file
+/// * `force` - Kill any running session with the same name and recreate it
+/// * `no_commands` - Create windows and panes without running any configured commands
+/// * `attach_mode` - How to hand over to the session (None: decide from context)
+/// * `ctx` - Shared context containing configuration and state
+pub fn open(
+    url: &str,
+    sha256: Option<&str>,
+    force: bool,
+    no_commands: bool,
+    attach_mode: Option<AttachMode>,
+    ctx: &Context,
+) -> Result<()> {
+    log::info(&format!("open command: url={} sha256={:?}", url, sha256));
+
+    let Some(imported) = import(url, sha256, false)? else {
+        return Ok(());
+    };
+    let id = match (&imported.default, imported.ids.as_slice()) {
+        (Some(id), _) => id.clone(),
+        (None, [id]) => id.clone(),
+        (None, ids) => anyhow::bail!(
+            "{} defines {} sessions and no default\n  Hint: Open one with 'tmx open <session>' ({})",
+            url,
+            ids.len(),
+            ids.join(", ")
+        ),
+    };
+
+    let ctx = ctx.reloaded();
+    let id = configured_id(&id, ctx.config()?).with_context(|| {
+        format!(
+            "Session '{}' from {} was not loaded\n  Hint: Check {}",
+            id,
+            url,
+            imported.path.display()
+        )
+    })?;
+    start::run(&id, force, no_commands, attach_mode, &ctx)
+}
+
+/// Fetch, check and cache a config.
+///
+/// # Returns
+/// The cached config, or None if importing it was cancelled.
+fn import(url: &str, sha256: Option<&str>, yes: bool) -> Result<Option<Imported>> {
+    let content = remote::fetch(url)?;
+    let sum = checksum::sha256_hex(&content);
+    if let Some(expected) = sha256
+        && !expected.trim().eq_ignore_ascii_case(&sum)
+    {
+        anyhow::bail!(
+            "Checksum mismatch for {}\n  Expected: {}\n  Fetched:  {}\n  \
+             Hint: The file changed since the checksum was published; check it before importing",
+            url,
+            expected.trim(),
+            sum
+        );
+    }
+    let content =
+        String::from_utf8(content).with_context(|| format!("{} is not a text file", url))?;
+    let fetched = parse(&content, url)?;
+
+    let path = remote::import_path(url)?;
+    let cached = fs::read_to_string(&path).ok();
+    let header = cached.as_deref().and_then(cached_header);
+    if let Some((source, _)) = header
+        && source != url
+    {
+        anyhow::bail!(
+            "{} already holds a config imported from {}\n  \
+             Hint: Remove it to import {} instead",
+            path.display(),
+            source,
+            url
+        );
+    }
+    if cached.is_some() && header.is_none() {
+        anyhow::bail!(
+            "{} already exists and wasn't imported from a URL\n  Hint: Move it away to import {}",
+            path.display(),
+            url
+        );
+    }
+
+    let unchanged = header.is_some_and(|(_, cached_sum)| cached_sum == sum);
+    let ids: Vec<String> = fetched.sessions.keys().cloned().collect();
+    let count = ids.len().to_string();
+
+    if !unchanged && sha256.is_none() && !yes {
+        if messages::on_stderr() {
+            anyhow::bail!(
+                "Importing {} needs confirmation\n  \
+                 Hint: Pass its checksum with --sha256 {}, or import it first with 'tmx import url {}'",
+                url,
+                sum,
+                url
+            );
+        }
+        messages::say(
+            messages::IMPORT_FETCHED,
+            &[("url", &url), ("bytes", &content.len()), ("sha256", &sum)],
+        );
+        for (id, session) in &fetched.sessions {
+            show_commands(id, session);
+        }
+        let question = format!(
+            "Import {} session(s) into {}? The commands above run when they are opened",
+            count,
+            path.display()
+        );
+        if prompt::choose(&question, &[('y', "import"), ('n', "cancel")])? != Some('y') {
+            messages::say(messages::CANCELLED, &[]);
+            return Ok(None);
+        }
+    }
+
+    if unchanged {
+        messages::say(
+            messages::IMPORT_UNCHANGED,
+            &[("url", &url), ("path", &path.display())],
+        );
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(
+            &path,
+            format!(
+                "{}{}\n{}{}\n\n{}",
+                SOURCE_PREFIX, url, SHA256_PREFIX, sum, content
+            ),
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+        log::info(&format!(
+            "import: cached {} ({}) as {}",
+            url,
+            sum,
+            path.display()
+        ));
+        messages::say(
+            messages::IMPORT_DONE,
+            &[("count", &count), ("url", &url), ("path", &path.display())],
+        );
+    }
+
+    Ok(Some(Imported {
+        path,
+        ids,
+        default: fetched.default,
+    }))
+}
+
+/// Parse and validate a fetched config
+fn parse(content: &str, url: &str) -> Result<Fetched> {
+    let fetched: Fetched =
+        toml::from_str(content).with_context(|| format!("{} is not a valid tmx config", url))?;
+    if fetched.sessions.is_empty() {
+        anyhow::bail!(
+            "{} defines no sessions\n  Hint: Link to a tmx.toml with [sessions.<id>] tables",
+            url
+        );
+    }
+    for (id, session) in &fetched.sessions {
+        session
+            .validate()
+            .with_context(|| format!("Session '{}' from {} is invalid", id, url))?;
+    }
+    if let Some(default) = &fetched.default
+        && !fetched.sessions.contains_key(default)
+    {
+        anyhow::bail!(
+            "{} names '{}' as its default, but defines no such session",
+            url,
+            default
+        );
+    }
+    Ok(fetched)
+}
+
+/// The URL and checksum recorded at the top of a cached config
+fn cached_header(content: &str) -> Option<(&str, &str)> {
+    let mut lines = content.lines();
+    let url = lines.next()?.strip_prefix(SOURCE_PREFIX)?;
+    let sum = lines.next()?.strip_prefix(SHA256_PREFIX)?;
+    Some((url, sum))
+}
+
+/// Print a session's size and every command it runs, so they can be
+/// reviewed before the config is used
+pub fn show_commands(id: &str, session: &Session) {
+    messages::say(
+        messages::IMPORT_SESSION,
+        &[("session", &id), ("windows", &session.windows.len())],
+    );
+    for (kind, command) in commands(session) {
+        messages::say(
+            messages::IMPORT_COMMAND,
+            &[("kind", &kind), ("command", &command)],
+        );
+    }
+}
+
+/// The shell commands a session runs, labelled with where they come from.
+///
+/// tmux options are included too, as their values can run commands
+/// (`#(command)` in status formats, `default-shell`).
+pub fn commands(session: &Session) -> Vec<(String, String)> {
+    fn options(
+        commands: &mut Vec<(String, String)>,
+        place: &str,
+        options: &BTreeMap<String, OptionValue>,
+    ) {
+        commands.extend(
+            options
+                .iter()
+                .map(|(option, value)| (format!("{}option {}", place, option), value.to_string())),
+        );
+    }
+
+    let mut commands = Vec::new();
+    let hooks = [
+        ("on_create", &session.on_create),
+        ("pre_window", &session.pre_window),
+        ("post_create", &session.post_create),
+        ("pre_close", &session.pre_close),
+    ];
+    for (hook, hook_commands) in hooks {
+        commands.extend(hook_commands.iter().map(|c| (hook.to_string(), c.clone())));
+    }
+    let session_commands = [
+        ("default_command", session.default_command.as_ref()),
+        (
+            "on_attach_send",
+            session.on_attach_send.as_ref().map(|send| &send.command),
+        ),
+        ("shutdown_command", session.shutdown_command.as_ref()),
+        ("on_died", session.on_died.as_ref()),
+    ];
+    for (field, command) in session_commands {
+        commands.extend(command.map(|c| (field.to_string(), c.clone())));
+    }
+    options(&mut commands, "", &session.options);
+
+    for window in &session.windows {
+        commands.extend(
+            window
+                .pre_window
+                .iter()
+                .map(|c| (format!("{} pre_window", window.name), c.clone())),
+        );
+        options(&mut commands, &format!("{} ", window.name), &window.options);
+        for pane in &window.panes {
+            commands.extend(
+                pane.setup
+                    .iter()
+                    .map(|c| (format!("{} setup", window.name), c.clone())),
+            );
+            if !pane.command.trim().is_empty() {
+                commands.push((window.name.clone(), pane.command.clone()));
+            }
+            commands.extend(
+                pane.healthcheck
+                    .iter()
+                    .map(|c| (format!("{} healthcheck", window.name), c.clone())),
+            );
+            options(
+                &mut commands,
+                &format!("{} pane ", window.name),
+                &pane.options,
+            );
+        }
+    }
+    commands
+}
+
+/// ID of an imported session in the configuration: its own ID, or
+/// `remote:<id>` if a local session with that ID shadows it
+fn configured_id(id: &str, config: &Config) -> Option<String> {
+    [id.to_string(), format!("{}:{}", REMOTE_SOURCE, id)]
+        .into_iter()
+        .find(|id| config.sessions.contains_key(id) && config.session_source(id) == REMOTE_SOURCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default = "api"
+
+[sessions.api]
+name = "api"
+on_create = ["docker compose up -d"]
+
+[[sessions.api.windows]]
+name = "server"
+panes = [{ command = "cargo run", setup = ["nvm use"] }, { command = "" }]
+
+[sessions.docs]
+name = "docs"
+
+[[sessions.docs.windows]]
+name = "edit"
+panes = [{ command = "vi" }]
+"#;
+
+    #[test]
+    fn test_parse() {
+        let url = "https://example.com/api.toml";
+        let fetched = parse(CONFIG, url).unwrap();
+        assert_eq!(fetched.default.as_deref(), Some("api"));
+        assert_eq!(
+            commands(&fetched.sessions["api"]),
+            [
+                ("on_create".to_string(), "docker compose up -d".to_string()),
+                ("server setup".to_string(), "nvm use".to_string()),
+                ("server".to_string(), "cargo run".to_string()),
+            ]
+        );
+
+        assert!(parse("version = 2\n", url).is_err());
+        assert!(parse(&CONFIG.replace("default = \"api\"", "default = \"x\""), url).is_err());
+    }
+
+    #[test]
+    fn test_commands_lists_every_command_field() {
+        let session: Session = toml::from_str(
+            r##"
+name = "api"
+on_create = ["on-create"]
+pre_window = ["pre-window"]
+post_create = ["post-create"]
+pre_close = ["pre-close"]
+default_command = "default-command"
+on_attach_send = { command = "attach-send" }
+close_strategy = "graceful"
+shutdown_command = "shutdown"
+on_died = "died"
+options = { status-right = "#(status)" }
+
+[[windows]]
+name = "w"
+pre_window = ["window-pre-window"]
+options = { "@watch" = "window-option" }
+panes = [{ command = "pane-command", setup = ["setup"], healthcheck = "health", options = { "@role" = "pane-option" } }]
+"##,
+        )
+        .unwrap();
+        let commands = commands(&session);
+        let listed: Vec<&str> = commands.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(
+            listed,
+            [
+                "on-create",
+                "pre-window",
+                "post-create",
+                "pre-close",
+                "default-command",
+                "attach-send",
+                "shutdown",
+                "died",
+                "#(status)",
+                "window-pre-window",
+                "window-option",
+                "setup",
+                "pane-command",
+                "health",
+                "pane-option",
+            ]
+        );
+
+        // Every field must be classified: a new field holding a command has to
+        // be listed by `commands` before it can be added here
+        const COMMAND_FIELDS: &[&str] = &[
+            "on_create",
+            "pre_window",
+            "post_create",
+            "pre_close",
+            "default_command",
+            "on_attach_send",
+            "shutdown_command",
+            "on_died",
+            "options",
+            "command",
+            "setup",
+            "healthcheck",
+        ];
+        const OTHER_FIELDS: &[&str] = &[
+            "name",
+            "root",
+            "windows",
+            "startup_window",
+            "startup_pane",
+            "on_conflict",
+            "missing_root",
+            "env",
+            "env_file",
+            "env_file_missing",
+            "login_shell",
+            "isolate_history",
+            "tags",
+            "window_size",
+            "aggressive_resize",
+            "destroy_unattached",
+            "detach_on_destroy",
+            "close_strategy",
+            "close_timeout",
+            "pass_env",
+            "setup_concurrency",
+            "params",
+            "group_with",
+            "panes",
+            "layout",
+            "close_on_exit",
+            "send_enter",
+            "literal",
+            "notify_on_exit",
+            "exec",
+            "setup_timeout",
+            "split",
+            "size",
+            "style",
+            "title",
+            "after",
+        ];
+        let json = serde_json::to_value(&session).unwrap();
+        let window = &json["windows"][0];
+        let pane = &window["panes"][0];
+        for object in [&json, window, pane] {
+            for field in object.as_object().unwrap().keys() {
+                assert!(
+                    COMMAND_FIELDS.contains(&field.as_str())
+                        || OTHER_FIELDS.contains(&field.as_str()),
+                    "field '{}' isn't classified; if it holds a command, list it in commands()",
+                    field
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cached_header() {
+        let content = format!(
+            "{}https://example.com/api.toml\n{}abc123\n\n{}",
+            SOURCE_PREFIX, SHA256_PREFIX, CONFIG
+        );
+        assert_eq!(
+            cached_header(&content),
+            Some(("https://example.com/api.toml", "abc123"))
+        );
+        assert_eq!(cached_header(CONFIG), None);
+    }
+}
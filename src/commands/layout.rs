@@ -0,0 +1,138 @@
+use crate::commands::run::{self, TargetPane};
+use crate::config_edit::ConfigEditor;
+use crate::context::Context;
+use crate::layout;
+use crate::log;
+use crate::tmux;
+use anyhow::Result;
+
+/// Save the layout of a running window under `[layouts]` in the config file.
+///
+/// Windows refer to it as `layout = "@<name>"`, and `tmx layout apply`
+/// arranges other windows with it.
+///
+/// # Arguments
+/// * `target` - `<session>[:<window>]`, as for `tmx run`
+/// * `name` - Name of the layout in `[layouts]`
+/// * `ctx` - Shared context containing configuration and state
+pub fn save(target: &str, name: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "layout save command: target={} name={}",
+        target, name
+    ));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+    let name = layout_name(name)?;
+
+    let TargetPane {
+        session, window, ..
+    } = run::resolve_target(target, ctx)?;
+    let address = format!("{}:{}", session, window.index);
+
+    let mut editor = ConfigEditor::open(ctx.config_path())?;
+    let replaced = editor.set_layout(name, &window.layout)?;
+    editor.save()?;
+
+    log::info(&format!(
+        "layout: saved {} ({}) as '@{}'",
+        address, window.layout, name
+    ));
+    println!(
+        "✓ {} layout '@{}' from {} ({} pane(s))",
+        if replaced { "Updated" } else { "Saved" },
+        name,
+        address,
+        window.panes.len()
+    );
+    println!(
+        "  Use it with layout = \"@{}\" or 'tmx layout apply <target> {}'",
+        name, name
+    );
+    Ok(())
+}
+
+/// Arrange a running window with a layout from `[layouts]`.
+///
+/// # Arguments
+/// * `target` - `<session>[:<window>]`, as for `tmx run`
+/// * `name` - Name of the layout in `[layouts]` (with or without `@`)
+/// * `ctx` - Shared context containing configuration and state
+pub fn apply(target: &str, name: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "layout apply command: target={} name={}",
+        target, name
+    ));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+    let name = layout_name(name)?;
+    let config = ctx.config()?;
+    let layout = config.layouts.get(name).ok_or_else(|| {
+        let mut names: Vec<_> = config.layouts.keys().map(|n| format!("@{}", n)).collect();
+        names.sort_unstable();
+        anyhow::anyhow!(
+            "Layout '@{}' is not defined\n  Hint: Save one with 'tmx layout save <session>:<window> {}' (defined: {})",
+            name,
+            name,
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        )
+    })?;
+
+    let TargetPane {
+        session, window, ..
+    } = run::resolve_target(target, ctx)?;
+    let address = format!("{}:{}", session, window.index);
+
+    // tmux refuses custom layouts for a different number of panes
+    if let Some(count) = layout::pane_count(layout)
+        && count != window.panes.len()
+    {
+        anyhow::bail!(
+            "Layout '@{}' has {} pane(s), but window {} has {}\n  \
+             Hint: Apply it to a window with {} pane(s)",
+            name,
+            count,
+            address,
+            window.panes.len(),
+            count
+        );
+    }
+
+    tmux::select_layout(&session, window.index, layout, ctx.is_verbose())?;
+
+    log::info(&format!("layout: applied '@{}' to {}", name, address));
+    println!("✓ Applied layout '@{}' to {}", name, address);
+    Ok(())
+}
+
+/// A layout name without its `@`, checked to be usable as `layout = "@<name>"`
+fn layout_name(name: &str) -> Result<&str> {
+    let name = name.strip_prefix('@').unwrap_or(name);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        anyhow::bail!(
+            "Invalid layout name '{}'\n  Hint: Use letters, digits, '-' and '_', e.g. editor-wide",
+            name
+        );
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_name() {
+        assert_eq!(layout_name("wide").unwrap(), "wide");
+        assert_eq!(layout_name("@editor-wide").unwrap(), "editor-wide");
+        assert!(layout_name("@").is_err());
+        assert!(layout_name("two words").is_err());
+    }
+}
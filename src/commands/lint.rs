@@ -0,0 +1,37 @@
+use crate::context::Context;
+use crate::lint;
+use anyhow::Result;
+
+/// Check the config for settings that are valid but likely mistakes.
+///
+/// Sessions are validated first, since lints assume a valid config. Each
+/// warning is printed with its code and a hint on how to fix it.
+pub fn run(ctx: &Context) -> Result<()> {
+    // Get config from context (lazy-loaded)
+    let config = ctx.config()?;
+
+    let mut lints = Vec::new();
+    for id in config.session_ids() {
+        let session = &config.sessions[&id];
+        if let Err(e) = session.validate() {
+            eprintln!("✗ Validation failed for session '{}':\n", id);
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        lints.extend(lint::lint_session(&id, session));
+    }
+
+    if lints.is_empty() {
+        println!("✓ No lint warnings in {} session(s)", config.sessions.len());
+        return Ok(());
+    }
+
+    for lint in &lints {
+        println!("⚠ {}: {} [{}]", lint.location(), lint.message, lint.code);
+        println!("  Hint: {}", lint.hint);
+    }
+    println!();
+    println!("Found {} warning(s)", lints.len());
+
+    Ok(())
+}
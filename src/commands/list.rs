@@ -1,68 +1,181 @@
+use crate::config::{Config, Session, contract_home};
 use crate::context::Context;
+use crate::log;
+use crate::state;
+use crate::table::{Cell, Style, Table};
 use crate::tmux;
+use crate::tmux::query::{self, SessionSummary};
 use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn run(ctx: &Context) -> Result<()> {
+/// Clear the terminal and move the cursor to the top left
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+/// List configured and running sessions as a table.
+///
+/// Configured sessions come first (by ID), followed by running sessions that
+/// aren't in the config. Sessions created by tmx are marked in the TMX column.
+/// When sessions also come from shared config repositories, a SOURCE column
+/// shows where each one is defined.
+///
+/// # Arguments
+/// * `watch` - Redraw the table every this many seconds until interrupted
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(watch: Option<u64>, ctx: &Context) -> Result<()> {
     // Get config from context (lazy-loaded)
     let config = ctx.config()?;
 
-    // Get running sessions
-    let running_sessions = tmux::list_sessions().unwrap_or_default();
+    let Some(interval) = watch else {
+        print!("{}", render(config));
+        return Ok(());
+    };
 
-    // Collect configured session names to filter from running list
-    let configured_session_names: std::collections::HashSet<_> = config
-        .sessions
-        .values()
-        .map(|s| s.name.clone())
-        .collect();
-
-    // Filter out configured sessions from running sessions
-    let other_running: Vec<_> = running_sessions
-        .iter()
-        .filter(|s| !configured_session_names.contains(*s))
-        .collect();
-
-    // Only show configured sessions if no sessions are running
-    if running_sessions.is_empty() {
-        println!("Configured sessions:");
-        let session_ids = config.session_ids();
-        if session_ids.is_empty() {
-            println!("  (none)");
+    let interval = Duration::from_secs(interval.max(1));
+    let redraw = std::io::stdout().is_terminal();
+    loop {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let (_, _, _, hours, minutes, seconds) = log::utc_datetime(now);
+        let mut frame = String::new();
+        if redraw {
+            frame.push_str(CLEAR_SCREEN);
         } else {
-            for id in session_ids {
-                println!("  {}", id);
-            }
+            frame.push('\n');
         }
-        println!();
+        frame.push_str(&format!(
+            "Every {}s: tmx list    {:02}:{:02}:{:02} UTC\n\n",
+            interval.as_secs(),
+            hours,
+            minutes,
+            seconds
+        ));
+        frame.push_str(&render(config));
+
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        drop(stdout);
+        thread::sleep(interval);
+        tmux::clear_query_cache();
     }
+}
 
-    println!("Running tmux sessions:");
-    if running_sessions.is_empty() {
-        println!("  (none)");
-    } else {
-        // Show configured sessions that are running
-        let session_ids = config.session_ids();
-        for id in &session_ids {
-            if let Some(session) = config.sessions.get(id) {
-                if running_sessions.contains(&session.name) {
-                    println!("  {} (c)", id);
-                }
-            }
+/// Render the session table (or a hint when there are no sessions)
+fn render(config: &Config) -> String {
+    // Get running sessions (None: no tmux server)
+    let summaries = query::session_summaries().unwrap_or(Some(Vec::new()));
+    let running: &[SessionSummary] = summaries.as_deref().unwrap_or_default();
+
+    let with_sources = !config.session_sources.is_empty();
+    let mut headers = vec!["NAME", "STATE", "WINDOWS", "ROOT", "TAGS", "TMX"];
+    if with_sources {
+        headers.insert(1, "SOURCE");
+    }
+    let mut table = Table::new(&headers);
+    for id in config.session_ids() {
+        let session = &config.sessions[&id];
+        let live = running
+            .iter()
+            .find(|s| s.name == tmux::sanitize_session_name(&session.name));
+        let mut row = configured_row(&id, session, live);
+        if with_sources {
+            row.insert(1, Cell::new(config.session_source(&id), Style::Dim));
         }
-        // Show other running sessions (not configured)
-        for session in other_running {
-            println!("  {}", session);
+        table.row(row);
+    }
+    for live in running.iter().filter(|live| {
+        !config
+            .sessions
+            .values()
+            .any(|s| tmux::sanitize_session_name(&s.name) == live.name)
+    }) {
+        let mut row = vec![
+            live.name.as_str().into(),
+            state_cell(Some(live)),
+            live.windows.to_string().into(),
+            contract_home(&live.path).into(),
+            Cell::default(),
+            managed_cell(&live.name),
+        ];
+        if with_sources {
+            row.insert(1, Cell::default());
         }
+        table.row(row);
     }
 
-    Ok(())
+    let mut out = if table.is_empty() {
+        "No sessions configured or running\n  \
+         Hint: Run 'tmx init' to create a config with an example session\n"
+            .to_string()
+    } else {
+        table.render()
+    };
+    if summaries.is_none() {
+        out.push_str("(no tmux server running)\n");
+    }
+    out
+}
+
+/// Row for a configured session (`live`: its running tmux session, if any)
+fn configured_row(id: &str, session: &Session, live: Option<&SessionSummary>) -> Vec<Cell> {
+    let windows = live.map_or(session.windows.len(), |l| l.windows);
+    vec![
+        id.into(),
+        state_cell(live),
+        windows.to_string().into(),
+        session.root.as_str().into(),
+        Cell::new(session.tags.join(","), Style::Yellow),
+        live.map(|l| managed_cell(&l.name)).unwrap_or_default(),
+    ]
+}
+
+/// Whether a session is running, and attached
+fn state_cell(live: Option<&SessionSummary>) -> Cell {
+    match live {
+        Some(l) if l.attached > 1 => Cell::new(format!("attached ({})", l.attached), Style::Green),
+        Some(l) if l.attached > 0 => Cell::new("attached", Style::Green),
+        Some(_) => Cell::new("running", Style::Green),
+        None => Cell::new("stopped", Style::Dim),
+    }
+}
+
+/// Marker for running sessions that tmx created
+fn managed_cell(name: &str) -> Cell {
+    if state::is_managed(name) {
+        Cell::new("✓", Style::Green)
+    } else {
+        Cell::default()
+    }
 }
 
 /// List only configured session names (for completions)
-pub fn list_configured(ctx: &Context) -> Result<()> {
+///
+/// # Arguments
+/// * `sources` - Follow each ID with a tab and the source it comes from
+/// * `ctx` - Shared context containing configuration and state
+pub fn list_configured(sources: bool, ctx: &Context) -> Result<()> {
     let config = ctx.config()?;
     for id in config.session_ids() {
-        println!("{}", id);
+        if sources {
+            println!("{}\t{}", id, config.session_source(&id));
+        } else {
+            println!("{}", id);
+        }
+    }
+    Ok(())
+}
+
+/// List only workspace names (for completions)
+pub fn list_workspaces(ctx: &Context) -> Result<()> {
+    let config = ctx.config()?;
+    let mut names: Vec<_> = config.workspaces.keys().collect();
+    names.sort_unstable();
+    for name in names {
+        println!("{}", name);
     }
     Ok(())
 }
@@ -75,3 +188,28 @@ pub fn list_running() -> Result<()> {
     }
     Ok(())
 }
+
+/// List the window names of a session (for completing `session:window`).
+///
+/// A running session's live windows are listed; otherwise the windows
+/// configured for the session ID.
+pub fn list_windows(session_id: &str, ctx: &Context) -> Result<()> {
+    let configured = ctx
+        .config()
+        .ok()
+        .and_then(|config| config.get_session(session_id).cloned());
+    let name = configured
+        .as_ref()
+        .map_or(session_id.to_string(), |s| tmux::sanitize_session_name(&s.name));
+
+    let windows: Vec<String> = match query::live_session(&name).ok().flatten() {
+        Some(live) => live.windows.into_iter().map(|w| w.name).collect(),
+        None => configured
+            .map(|s| s.windows.into_iter().map(|w| w.name).collect())
+            .unwrap_or_default(),
+    };
+    for window in windows {
+        println!("{}", window);
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::log;
+use anyhow::{Context, Result};
+use std::fs;
+
+/// Print the tail of the tmx log file.
+///
+/// # Arguments
+/// * `commands` - Only show the tmux command trace (recorded with -v)
+/// * `lines` - Number of records to show from the end of the log
+pub fn run(commands: bool, lines: usize) -> Result<()> {
+    let Some(path) = log::path() else {
+        anyhow::bail!("Not logging to a file (--log-file - or TMX_LOG_FILE=- sends logs to stderr)");
+    };
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read log file: {}", path.display()))?;
+
+    let records: Vec<&str> = content
+        .lines()
+        .filter(|line| !commands || line.contains(log::COMMAND_TRACE_TAG))
+        .collect();
+
+    if records.is_empty() {
+        if commands {
+            println!("No tmux commands recorded in {}", path.display());
+            println!("Hint: Commands are only traced when tmx runs with -v");
+        } else {
+            println!("Log file {} is empty", path.display());
+        }
+        return Ok(());
+    }
+
+    for record in &records[records.len().saturating_sub(lines)..] {
+        println!("{}", record);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::config::{CONFIG_VERSION, Config};
+use crate::config_edit::ConfigEditor;
+use crate::context::Context;
+use crate::log;
+use crate::migrate;
+use anyhow::{Context as _, Result};
+
+/// Upgrade the config file to the current format version, in place.
+///
+/// The previous file is backed up first (see `tmx config restore`).
+///
+/// # Arguments
+/// * `dry_run` - Print the changes and the migrated config without writing it
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(dry_run: bool, ctx: &Context) -> Result<()> {
+    let config_path = ctx.config_path();
+    let mut editor = ConfigEditor::open(config_path)?;
+
+    let from = migrate::document_version(editor.document())?;
+    if from == CONFIG_VERSION {
+        println!("✓ Config is already at format version {}", CONFIG_VERSION);
+        return Ok(());
+    }
+
+    let changes = migrate::migrate(editor.document_mut())?;
+    toml::from_str::<Config>(&editor.to_string()).context(
+        "The migrated config is invalid\n  Hint: Please report this as a tmx bug; your config was not changed",
+    )?;
+
+    if dry_run {
+        println!(
+            "Would migrate {} from format version {} to {}:",
+            config_path.display(),
+            from,
+            CONFIG_VERSION
+        );
+        print_changes(&changes);
+        println!();
+        print!("{}", editor);
+        return Ok(());
+    }
+
+    editor.save()?;
+    log::info(&format!(
+        "migrated config {} from version {} to {}",
+        config_path.display(),
+        from,
+        CONFIG_VERSION
+    ));
+    println!(
+        "✓ Migrated {} from format version {} to {}",
+        config_path.display(),
+        from,
+        CONFIG_VERSION
+    );
+    print_changes(&changes);
+    Ok(())
+}
+
+fn print_changes(changes: &[String]) {
+    for change in changes {
+        println!("  - {}", change);
+    }
+}
@@ -1,8 +1,35 @@
+pub mod attach;
+pub mod bench;
+pub mod buffer;
 pub mod completions;
+pub mod config;
 pub mod default;
+pub mod gc;
+pub mod graph;
+pub mod history;
+pub mod import;
 pub mod init;
+pub mod layout;
+pub mod lint;
 pub mod list;
+pub mod logs;
+pub mod migrate;
+pub mod notify;
+pub mod picker;
+pub mod plugin;
+pub mod project;
+pub mod prompt_segment;
 pub mod refresh;
+pub mod reload;
+pub mod run;
+pub mod server;
+pub mod snapshot;
 pub mod start;
+pub mod status;
 pub mod stop;
+pub mod sync;
+pub mod sync_config;
+pub mod ticket;
 pub mod validate;
+pub mod version;
+pub mod workspace;
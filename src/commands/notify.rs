@@ -0,0 +1,80 @@
+use crate::context::Context;
+use crate::log;
+use crate::notify;
+use crate::placeholders;
+use crate::resolved::{ResolvedSession, SESSION_JSON_ENV};
+use crate::state::State;
+use crate::tmux;
+use anyhow::{Context as _, Result};
+use std::process::Command;
+
+/// Announce that a pane's command exited (run by the pane-died hook tmx installs
+/// for panes with `notify_on_exit`).
+///
+/// # Arguments
+/// * `session` - Session name
+/// * `window` - Window name
+/// * `pane` - Pane index
+/// * `status` - Exit status as reported by tmux (empty if killed by a signal)
+pub fn exit(session: &str, window: &str, pane: &str, status: &str) -> Result<()> {
+    log::info(&format!(
+        "pane {}:{}.{} exited with status '{}'",
+        session, window, pane, status
+    ));
+
+    let body = match status {
+        "" => format!("{}.{} was terminated", window, pane),
+        "0" => format!("{}.{} finished successfully", window, pane),
+        code => format!("{}.{} failed with status {}", window, pane, code),
+    };
+    notify::send(&format!("tmx: {}", session), &body)
+}
+
+/// Run a session's `on_died` command if it closed unexpectedly (run by the
+/// session-closed hook tmx installs for sessions with `on_died`).
+///
+/// Sessions tmx closes itself are removed from the state file first, so only
+/// sessions still recorded as created by tmx count as unexpected.
+///
+/// # Arguments
+/// * `session` - Name of the session that closed
+/// * `ctx` - Shared context containing configuration and state
+pub fn session_died(session: &str, ctx: &Context) -> Result<()> {
+    if !State::load().sessions.contains_key(session) {
+        return Ok(());
+    }
+    // Recreated under the same name (e.g. `tmx open --force`) rather than gone
+    if tmux::has_session(session)? {
+        return Ok(());
+    }
+    // Another process may have closed it through tmx meanwhile
+    let recorded = State::update(|state| state.sessions.remove(session).is_some())?;
+    if !recorded {
+        return Ok(());
+    }
+
+    let config = ctx.config()?;
+    let Some((id, configured, command)) = config
+        .sessions
+        .iter()
+        .find(|(_, s)| tmux::sanitize_session_name(&s.name) == session)
+        .and_then(|(id, s)| Some((id, s, s.on_died.as_deref()?)))
+    else {
+        return Ok(());
+    };
+
+    let command = placeholders::expand_on_died(command, session)?;
+    log::info(&format!("session '{}' died, running: {}", session, command));
+    let json = ResolvedSession::new(Some(id), configured).to_json()?;
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .env("TMX_SESSION", session)
+        .env(SESSION_JSON_ENV, json)
+        .status()
+        .with_context(|| format!("Failed to run on_died command: {}", command))?;
+    if !status.success() {
+        log::error(&format!("on_died command for '{}' failed: {}", session, status));
+    }
+    Ok(())
+}
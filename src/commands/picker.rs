@@ -0,0 +1,124 @@
+use crate::commands::{default, start};
+use crate::context::Context;
+use crate::log;
+use crate::prompt;
+use crate::tmux;
+use anyhow::Result;
+
+/// Size assumed when the terminal's size can't be read
+const FALLBACK_SIZE: (usize, usize) = (24, 80);
+
+/// A session the picker offers
+#[derive(Debug, PartialEq)]
+enum Choice {
+    /// A running session, by name
+    Running(String),
+    /// A configured session that isn't running, by ID
+    Configured(String),
+}
+
+/// Pick a running or configured session and switch to it.
+///
+/// Running sessions are listed first (in the order `tmx` cycles through
+/// them), then configured sessions that aren't running, which are created
+/// when picked.
+///
+/// With `tmux_popup`, the picker runs in a tmux popup (`display-popup -E`):
+/// the list is laid out to fit the popup, and the client that opened the
+/// popup is switched to the chosen session before the popup closes.
+///
+/// # Arguments
+/// * `tmux_popup` - Run as a popup of the client to switch
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(tmux_popup: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!("picker command: tmux_popup={}", tmux_popup));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+    if tmux_popup && !in_popup(ctx) {
+        anyhow::bail!(
+            "Not running in a tmux popup\n  \
+             Hint: Bind it in tmux.conf, e.g. bind-key s display-popup -E \"tmx picker --tmux-popup\""
+        );
+    }
+
+    let current = if ctx.is_inside_tmux {
+        tmux::get_current_session().ok()
+    } else {
+        None
+    };
+    let choices = choices(&tmux::list_sessions()?, ctx);
+    if choices.is_empty() {
+        anyhow::bail!("No sessions to pick from\n  Hint: Configure one with 'tmx init'");
+    }
+    let labels: Vec<String> = choices
+        .iter()
+        .map(|choice| match choice {
+            Choice::Running(name) if current.as_ref() == Some(name) => {
+                format!("{} (current)", name)
+            }
+            Choice::Running(name) => name.clone(),
+            Choice::Configured(id) => format!("{} (not running)", id),
+        })
+        .collect();
+
+    let picked = if tmux_popup {
+        let size = prompt::terminal_size().unwrap_or(FALLBACK_SIZE);
+        prompt::pick_sized("Sessions:", &labels, size)?
+    } else {
+        prompt::pick("Sessions:", &labels)?
+    };
+    let Some(index) = picked else {
+        return Ok(());
+    };
+
+    let target = match &choices[index] {
+        Choice::Running(name) => name.clone(),
+        Choice::Configured(id) => start::open_session(id, false, false, ctx)?,
+    };
+    log::info(&format!("picker: picked '{}'", target));
+
+    if !tmux_popup {
+        return start::attach_or_switch(&target, None, ctx);
+    }
+    // The popup belongs to a client of the session it was opened from
+    let parent = current
+        .as_deref()
+        .map(tmux::last_active_client)
+        .transpose()?
+        .flatten();
+    match parent {
+        Some(client) => tmux::switch_client_of(&client, &target),
+        None => tmux::switch_client(&target),
+    }
+}
+
+/// Whether tmx runs in a tmux popup: popups get the session's TMUX, but
+/// aren't panes, so they have no TMUX_PANE
+fn in_popup(ctx: &Context) -> bool {
+    ctx.is_inside_tmux && std::env::var("TMUX_PANE").map_or(true, |pane| pane.is_empty())
+}
+
+/// Running sessions in cycling order, then configured sessions that aren't running
+fn choices(running: &[String], ctx: &Context) -> Vec<Choice> {
+    let config = ctx.config().ok();
+    let mut choices: Vec<Choice> = default::order_sessions(running, config)
+        .into_iter()
+        .map(Choice::Running)
+        .collect();
+    if let Some(config) = config {
+        let mut ids = config.session_ids();
+        ids.sort_unstable();
+        choices.extend(
+            ids.into_iter()
+                .filter(|id| {
+                    config.get_session(id).is_some_and(|session| {
+                        !running.contains(&tmux::sanitize_session_name(&session.name))
+                    })
+                })
+                .map(|id| Choice::Configured(id.to_string())),
+        );
+    }
+    choices
+}
@@ -0,0 +1,113 @@
+use crate::context::Context;
+use crate::log;
+use crate::resolved::{ResolvedSession, SESSION_JSON_ENV};
+use crate::tmux;
+use anyhow::Result;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Prefix for external plugin executables (`tmx foo` runs `tmx-foo`)
+const PLUGIN_PREFIX: &str = "tmx-";
+
+/// Run an external plugin subcommand, git-style.
+///
+/// Looks up `tmx-<name>` on PATH and replaces the current process with it,
+/// passing the remaining arguments through. The plugin receives context via
+/// environment variables:
+/// - TMX_CONFIG_PATH: Resolved config file path
+/// - TMX_SESSION: Current tmux session name (only when run inside tmux)
+/// - TMX_SESSION_JSON: Resolved session JSON for TMX_SESSION (only when it is configured)
+///
+/// # Arguments
+/// * `args` - The unknown subcommand followed by its arguments
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(args: &[String], ctx: &Context) -> Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        anyhow::bail!("No subcommand given");
+    };
+
+    let executable = format!("{}{}", PLUGIN_PREFIX, name);
+    let Some(path) = find_in_path(&executable) else {
+        anyhow::bail!(
+            "Unknown command '{}'\n  \
+             No '{}' executable found on PATH\n  \
+             Hint: Run 'tmx --help' to see built-in commands",
+            name,
+            executable
+        );
+    };
+
+    log::info(&format!("plugin command: {} {}", path.display(), rest.join(" ")));
+
+    let mut command = Command::new(&path);
+    command.args(rest).env("TMX_CONFIG_PATH", ctx.config_path());
+
+    if ctx.is_inside_tmux
+        && let Ok(session) = tmux::get_current_session()
+    {
+        if let Ok(config) = ctx.config()
+            && let Some(id) = config.session_id_for(&session)
+            && let Some(configured) = config.sessions.get(id)
+        {
+            let json = ResolvedSession::new(Some(id), configured).to_json()?;
+            command.env(SESSION_JSON_ENV, json);
+        }
+        command.env("TMX_SESSION", session);
+    }
+
+    exec(command, &path)
+}
+
+/// Replace the current process with the plugin (exec only returns on failure)
+#[cfg(unix)]
+fn exec(mut command: Command, path: &std::path::Path) -> Result<()> {
+    let err = command.exec();
+    anyhow::bail!("Failed to execute {}: {}", path.display(), err)
+}
+
+/// Run the plugin and exit with its status (no exec outside Unix)
+#[cfg(not(unix))]
+fn exec(mut command: Command, path: &std::path::Path) -> Result<()> {
+    let status = command
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to execute {}: {}", path.display(), e))?;
+    std::process::exit(status.code().unwrap_or(1))
+}
+
+/// Find an executable file by name in the directories listed in PATH
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| executable_candidates(&dir, name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn executable_candidates(dir: &std::path::Path, name: &str) -> Vec<PathBuf> {
+    vec![dir.join(name)]
+}
+
+/// On Windows, plugins are found with an `.exe`, `.cmd` or `.bat` extension
+#[cfg(not(unix))]
+fn executable_candidates(dir: &std::path::Path, name: &str) -> Vec<PathBuf> {
+    ["exe", "cmd", "bat"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
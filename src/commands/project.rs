@@ -0,0 +1,314 @@
+use crate::cli::{AttachMode, ProjectTemplate};
+use crate::commands::start;
+use crate::config::{CONFIG_VERSION, PROJECT_CONFIG_FILE};
+use crate::context::Context;
+use crate::log;
+use crate::messages;
+use crate::trust;
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files, test watcher and REPL of a stack; `{name}` in file contents is the
+/// project name
+struct Stack {
+    files: &'static [(&'static str, &'static str)],
+    test_command: &'static str,
+    repl_command: &'static str,
+}
+
+const RUST: Stack = Stack {
+    files: &[
+        (
+            "Cargo.toml",
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+        ),
+        (
+            "src/main.rs",
+            r#"fn greeting() -> &'static str {
+    "Hello, world!"
+}
+
+fn main() {
+    println!("{}", greeting());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greeting() {
+        assert_eq!(greeting(), "Hello, world!");
+    }
+}
+"#,
+        ),
+        (".gitignore", "/target\n"),
+    ],
+    test_command: "cargo watch -x test",
+    repl_command: "evcxr",
+};
+
+const NODE: Stack = Stack {
+    files: &[
+        (
+            "package.json",
+            r#"{
+  "name": "{name}",
+  "version": "0.1.0",
+  "private": true,
+  "scripts": {
+    "start": "node index.js",
+    "test": "node --test"
+  }
+}
+"#,
+        ),
+        (
+            "index.js",
+            r#"function greeting() {
+  return "Hello, world!";
+}
+
+if (require.main === module) {
+  console.log(greeting());
+}
+
+module.exports = { greeting };
+"#,
+        ),
+        (
+            "test/index.test.js",
+            r#"const test = require("node:test");
+const assert = require("node:assert");
+const { greeting } = require("../index.js");
+
+test("greeting", () => {
+  assert.strictEqual(greeting(), "Hello, world!");
+});
+"#,
+        ),
+        (".gitignore", "node_modules/\n"),
+    ],
+    test_command: "node --test --watch",
+    repl_command: "node",
+};
+
+const PYTHON: Stack = Stack {
+    files: &[
+        (
+            "main.py",
+            r#"def greeting():
+    return "Hello, world!"
+
+
+if __name__ == "__main__":
+    print(greeting())
+"#,
+        ),
+        (
+            "test_main.py",
+            r#"import unittest
+
+from main import greeting
+
+
+class GreetingTest(unittest.TestCase):
+    def test_greeting(self):
+        self.assertEqual(greeting(), "Hello, world!")
+
+
+if __name__ == "__main__":
+    unittest.main()
+"#,
+        ),
+        (".gitignore", "__pycache__/\n.venv/\n"),
+    ],
+    test_command: "watchexec -e py -- python3 -m unittest",
+    repl_command: "python3",
+};
+
+/// Create a project directory for a stack and open its session.
+///
+/// The directory gets a minimal project for the stack and a `.tmx.toml`
+/// with an editor, a test watcher and a REPL window, which also makes the
+/// project's session the default in that directory.
+///
+/// # Arguments
+/// * `name` - Project name: the directory (in the current directory) and the session
+/// * `template` - The stack to set up
+/// * `attach_mode` - How to hand over to the session (None: decide from context)
+/// * `ctx` - Shared context containing configuration and state
+pub fn new(
+    name: &str,
+    template: ProjectTemplate,
+    attach_mode: Option<AttachMode>,
+    ctx: &Context,
+) -> Result<()> {
+    log::info(&format!(
+        "project new command: name={} template={:?}",
+        name, template
+    ));
+    validate_name(name)?;
+
+    let dir = std::env::current_dir()
+        .context("Could not determine the current directory")?
+        .join(name);
+    if dir.exists() {
+        anyhow::bail!(
+            "{} already exists\n  \
+             Hint: Choose another name, or add a {} to it to give it a session",
+            dir.display(),
+            PROJECT_CONFIG_FILE
+        );
+    }
+
+    let stack = stack(template);
+    for (path, content) in stack.files {
+        write_file(&dir.join(path), &content.replace("{name}", name))?;
+    }
+    let config_path = dir.join(PROJECT_CONFIG_FILE);
+    write_file(&config_path, &project_config(name, stack))?;
+    trust::trust_written(&config_path)?;
+
+    log::info(&format!("project: created {}", dir.display()));
+    messages::say(
+        messages::PROJECT_CREATED,
+        &[("project", &name), ("dir", &dir.display())],
+    );
+
+    start::run(
+        name,
+        false,
+        false,
+        attach_mode,
+        &ctx.for_project(config_path),
+    )
+}
+
+/// Trust a project config, so tmx uses it without asking (`tmx project trust`).
+///
+/// # Arguments
+/// * `path` - The `.tmx.toml`, or a directory containing one; None for the
+///   one found from the current directory
+pub fn trust(path: Option<&str>, ctx: &Context) -> Result<()> {
+    let path = match path {
+        Some(path) => {
+            let path = PathBuf::from(shellexpand::tilde(path).to_string());
+            if path.is_dir() {
+                path.join(PROJECT_CONFIG_FILE)
+            } else {
+                path
+            }
+        }
+        None => ctx.project_config_path().cloned().with_context(|| {
+            format!(
+                "No {} found in the current directory or its parents\n  \
+                 Hint: Pass the project config's path",
+                PROJECT_CONFIG_FILE
+            )
+        })?,
+    };
+    trust::trust(&path)
+}
+
+fn stack(template: ProjectTemplate) -> &'static Stack {
+    match template {
+        ProjectTemplate::Rust => &RUST,
+        ProjectTemplate::Node => &NODE,
+        ProjectTemplate::Python => &PYTHON,
+    }
+}
+
+/// Check that a project name works as a directory, session ID and package name
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        anyhow::bail!(
+            "Invalid project name '{}'\n  Hint: Use letters, digits, '-' and '_', e.g. my-app",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// The `.tmx.toml` for a project: its session, rooted at the project and
+/// opened by default there
+fn project_config(name: &str, stack: &Stack) -> String {
+    let editor = std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    let mut config = format!(
+        "# Session for {name}, opened with `tmx` or `tmx open {name}` in this directory\n\
+         version = {CONFIG_VERSION}\n\
+         default = \"{name}\"\n\
+         \n\
+         [sessions.{name}]\n\
+         name = \"{name}\"\n\
+         root = \".\"\n"
+    );
+    for (window, command) in [
+        ("editor", editor.as_str()),
+        ("test", stack.test_command),
+        ("repl", stack.repl_command),
+    ] {
+        config.push_str(&format!(
+            "\n[[sessions.{name}.windows]]\n\
+             name = \"{window}\"\n\
+             \n\
+             [[sessions.{name}.windows.panes]]\n\
+             command = {}\n",
+            toml_edit::value(command)
+        ));
+    }
+    config
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_project_config() {
+        for template in [
+            ProjectTemplate::Rust,
+            ProjectTemplate::Node,
+            ProjectTemplate::Python,
+        ] {
+            let config: Config =
+                toml::from_str(&project_config("my-app", stack(template))).unwrap();
+            let session = &config.sessions["my-app"];
+            session.validate().unwrap();
+            assert_eq!(session.root, ".");
+            let windows: Vec<_> = session.windows.iter().map(|w| w.name.as_str()).collect();
+            assert_eq!(windows, ["editor", "test", "repl"]);
+            assert_eq!(config.default_session_id(), Some("my-app"));
+        }
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("my-app").is_ok());
+        assert!(validate_name("app_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("-app").is_err());
+        assert!(validate_name("../app").is_err());
+        assert!(validate_name("my app").is_err());
+    }
+}
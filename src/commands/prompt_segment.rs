@@ -0,0 +1,163 @@
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::context::Context;
+use crate::diff;
+use crate::log;
+use crate::tmux;
+use crate::tmux::query;
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a computed segment is reused before tmux is queried again
+const CACHE_TTL_SECS: u64 = 10;
+
+/// Status of the current session, as printed by `tmx prompt-segment`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Segment {
+    session: String,
+    /// Whether the session is in the config
+    configured: bool,
+    /// Number of structural differences between the session and its config
+    differences: usize,
+    /// When the segment was computed (seconds since the Unix epoch)
+    computed: u64,
+}
+
+impl Segment {
+    /// `dev ✓` in sync, `dev ⚠ drifted` with differences, `dev` unconfigured
+    fn text(&self) -> String {
+        match (self.configured, self.differences) {
+            (false, _) => self.session.clone(),
+            (true, 0) => format!("{} ✓", self.session),
+            (true, _) => format!("{} ⚠ drifted", self.session),
+        }
+    }
+}
+
+/// Print the current session's status for a shell prompt segment.
+///
+/// Prints nothing outside tmux. Results are cached per session for a few
+/// seconds so prompts redrawn on every command stay fast.
+///
+/// # Arguments
+/// * `format` - `text` for a prompt (e.g. `dev ⚠ drifted`), `json` for scripts
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(format: OutputFormat, ctx: &Context) -> Result<()> {
+    // $TMUX is "<socket>,<server pid>,<session id>", so it identifies the session
+    let Some(key) = std::env::var("TMUX").ok().filter(|v| !v.is_empty()) else {
+        return Ok(());
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut cache = load_cache();
+    let segment = match cache.get(&key) {
+        Some(segment) if now.saturating_sub(segment.computed) < CACHE_TTL_SECS => segment.clone(),
+        _ => {
+            let segment = compute(ctx.config().ok(), now)?;
+            cache.retain(|_, s| now.saturating_sub(s.computed) < CACHE_TTL_SECS);
+            cache.insert(key, segment.clone());
+            if let Err(e) = save_cache(&cache) {
+                log::error(&format!("failed to save the prompt cache: {}", e));
+            }
+            segment
+        }
+    };
+
+    match format {
+        OutputFormat::Text => println!("{}", segment.text()),
+        OutputFormat::Json => println!("{}", serde_json::to_string(&segment)?),
+    }
+    Ok(())
+}
+
+/// Compare the current session with its configuration
+fn compute(config: Option<&Config>, now: u64) -> Result<Segment> {
+    let name = tmux::get_current_session()?;
+    let session = config.and_then(|c| {
+        c.session_id_for(&name)
+            .map(|id| &c.sessions[id])
+            // Session names are sanitized when the session is created
+            .or_else(|| {
+                c.sessions
+                    .values()
+                    .find(|s| tmux::sanitize_session_name(&s.name) == name)
+            })
+    });
+
+    let differences = match (session, query::live_session(&name)?) {
+        (Some(session), Some(live)) => {
+            let base_index = tmux::session_base_index(&name)?;
+            diff::diff_session(session, &live, base_index).len()
+        }
+        _ => 0,
+    };
+
+    Ok(Segment {
+        session: name,
+        configured: session.is_some(),
+        differences,
+        computed: now,
+    })
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("prompt-cache.json"))
+}
+
+/// Load cached segments by $TMUX value (empty if missing or unreadable)
+fn load_cache() -> BTreeMap<String, Segment> {
+    cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &BTreeMap<String, Segment>) -> Result<()> {
+    let path = cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create state directory: {}", dir.display()))?;
+    }
+    fs::write(&path, serde_json::to_string(cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_text() {
+        let segment = Segment {
+            session: "dev".to_string(),
+            configured: true,
+            differences: 0,
+            computed: 0,
+        };
+        assert_eq!(segment.text(), "dev ✓");
+        assert_eq!(
+            Segment {
+                differences: 2,
+                ..segment.clone()
+            }
+            .text(),
+            "dev ⚠ drifted"
+        );
+        assert_eq!(
+            Segment {
+                configured: false,
+                ..segment
+            }
+            .text(),
+            "dev"
+        );
+    }
+}
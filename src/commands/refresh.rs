@@ -1,8 +1,15 @@
+use crate::commands::start;
+use crate::config::{Session, Window};
 use crate::context::Context as AppContext;
+use crate::lock::SessionLock;
 use crate::log;
+use crate::messages;
+use crate::roots::SessionRoots;
 use crate::session;
+use crate::state::{self, Operation};
 use crate::tmux;
-use anyhow::{Context, Result};
+use crate::tmux::query::{LiveSession, LiveWindow};
+use anyhow::Result;
 
 /// Refresh the layout of a running session according to its configuration.
 ///
@@ -12,84 +19,99 @@ use anyhow::{Context, Result};
 /// - Keeps extra panes if current session has more panes than config
 /// - Reapplies layout from configuration
 ///
+/// Windows are located by name, so windows moved or inserted by hand since
+/// the session was created are still matched to the right configuration.
+///
 /// # Arguments
-/// * `session_id` - The session name or ID from config
+/// * `session_id` - The session name or ID from config (None: the current session)
+/// * `window_name` - Only refresh the window with this name
 /// * `ctx` - Shared context containing configuration and state
-pub fn run(session_id: &str, ctx: &AppContext) -> Result<()> {
-    log::info(&format!("refresh command: session_id={}", session_id));
+pub fn run(session_id: Option<&str>, window_name: Option<&str>, ctx: &AppContext) -> Result<()> {
+    log::info(&format!(
+        "refresh command: session_id={:?} window={:?}",
+        session_id, window_name
+    ));
+
+    let current;
+    let session_id = match session_id {
+        Some(id) => id,
+        None if ctx.is_inside_tmux => {
+            current = tmux::get_current_session()?;
+            &current
+        }
+        None => anyhow::bail!(
+            "No session given\n  Hint: Use 'tmx refresh <session>', or run it inside tmux to refresh the current session"
+        ),
+    };
+
+    let result = refresh_session(session_id, window_name, ctx);
+    state::record_operation(Operation::Refresh, session_id, &result);
+    result
+}
 
+/// Refresh a session, or one of its windows
+fn refresh_session(session_id: &str, window_name: Option<&str>, ctx: &AppContext) -> Result<()> {
     // Get config from context (lazy-loaded)
     let config = ctx.config()?;
 
     // Find session in config, or use default session's settings for unconfigured sessions
-    let session = if let Some(s) = config.get_session(session_id) {
-        log::info(&format!("found session '{}' in config", session_id));
-        s.clone()
-    } else {
-        // Session not in config - use default session's settings with the requested name
-        log::info(&format!("session '{}' not in config, using default layout", session_id));
-        let default_id = config.default.as_ref().ok_or_else(|| {
-            log::error(&format!("no default session configured for '{}'", session_id));
-            anyhow::anyhow!(
-                "Session '{}' not found and no default session configured",
-                session_id
-            )
-        })?;
-
-        let default_session = config.get_session(default_id).ok_or_else(|| {
-            log::error(&format!("default session '{}' not found", default_id));
-            anyhow::anyhow!(
-                "Default session '{}' not found in configuration",
-                default_id
-            )
-        })?;
-
-        // Clone the default session and change the name
-        let mut dynamic_session = default_session.clone();
-        dynamic_session.name = session_id.to_string();
-        // Use current working directory instead of the default session's root
-        let cwd = std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "~".to_string());
-        dynamic_session.root = cwd.clone();
-        log::info(&format!("using default session '{}' as template with root '{}'", default_id, cwd));
-        dynamic_session
-    };
+    let (session, _) = start::resolve_running_session(session_id, config)?;
 
     let session_name = &session.name;
+    let windows = windows_to_refresh(&session, window_name)?;
+
+    // Hold the session lock so a concurrent open/refresh doesn't interleave splits
+    let _lock = SessionLock::acquire(session_name)?;
 
-    // Verify session exists in tmux
-    if !tmux::has_session(session_name)? {
+    // Verify session exists in tmux and snapshot its current windows/panes
+    let Some(live) = tmux::query::live_session(session_name)? else {
         anyhow::bail!("Session '{}' is not running", session_name);
-    }
+    };
 
-    println!("Refreshing layout for session '{}'...", session_name);
+    match window_name {
+        Some(name) => messages::say(
+            messages::SESSION_REFRESHING_WINDOW,
+            &[("window", &name), ("session", &session_name)],
+        ),
+        None => messages::say(messages::SESSION_REFRESHING, &[("session", &session_name)]),
+    }
 
-    // Get tmux base-index from context (cached)
-    let base_index = ctx.base_index()?;
     let verbose = ctx.is_verbose();
-    let session_root = session.root_expanded();
-
-    // Process each window
-    for (window_offset, window) in session.windows.iter().enumerate() {
-        let window_index = base_index + window_offset;
-        let window_root = window.root_expanded(&session_root);
+    let roots = SessionRoots::resolve(&session)?;
 
-        // Get current pane count
-        let current_pane_count = tmux::count_panes(session_name, window_index)
-            .context(format!("Failed to count panes in window {}", window_index))?;
+    // Process each window (or only the requested one)
+    for (window_offset, window) in windows {
+        let Some(live_window) = locate_window(&live, &session.windows, window_offset) else {
+            if window_name.is_some() {
+                anyhow::bail!(
+                    "Window '{}' is not running in session '{}'\n  \
+                     Hint: It may have been renamed or closed; reopen the session to recreate it",
+                    window.name,
+                    session_name
+                );
+            }
+            log::info(&format!("refresh: window '{}' not found, skipping", window.name));
+            messages::say(messages::REFRESH_WINDOW_MISSING, &[("window", &window.name)]);
+            continue;
+        };
+        let window_index = live_window.index;
+        let current_pane_count = live_window.panes.len();
 
         let expected_pane_count = window.panes.len();
 
-        println!(
-            "  Window '{}': current={} panes, config={} panes",
-            window.name, current_pane_count, expected_pane_count
+        messages::say(
+            messages::REFRESH_WINDOW_PANES,
+            &[
+                ("window", &window.name),
+                ("current", &current_pane_count),
+                ("expected", &expected_pane_count),
+            ],
         );
 
         // Add new panes if config has more panes than current
         if current_pane_count < expected_pane_count {
             let panes_to_add = expected_pane_count - current_pane_count;
-            println!("    Adding {} pane(s)...", panes_to_add);
+            messages::say(messages::REFRESH_ADDING_PANES, &[("count", &panes_to_add)]);
 
             // Create additional panes using shared logic
             // Don't apply sizes here - let apply_window_layout handle it
@@ -97,25 +119,160 @@ pub fn run(session_id: &str, ctx: &AppContext) -> Result<()> {
                 session_name,
                 window_index,
                 window,
-                &window_root,
+                &roots.windows[window_offset],
                 current_pane_count,
                 false, // Don't apply sizes here - let apply_window_layout handle it
+                None,
+                &session,
                 verbose,
             )?;
         } else if current_pane_count > expected_pane_count {
-            println!(
-                "    Keeping {} extra pane(s) (not removing)",
-                current_pane_count - expected_pane_count
+            messages::say(
+                messages::REFRESH_KEEPING_PANES,
+                &[("count", &(current_pane_count - expected_pane_count))],
             );
         }
 
         // Always apply layout and custom sizes during refresh
         if expected_pane_count > 1 {
-            println!("    Applying layout and sizes...");
+            messages::say(messages::REFRESH_APPLYING_LAYOUT, &[]);
             session::apply_window_layout(session_name, window_index, window, verbose)?;
         }
+
+        session::apply_window_options(&session, window_index, window)?;
+        session::apply_pane_options(session_name, window_index, window)?;
     }
 
-    println!("✓ Session '{}' layout refreshed", session_name);
+    messages::say(messages::SESSION_REFRESHED, &[("session", &session_name)]);
     Ok(())
 }
+
+/// The configured windows to refresh, with their positions in the session
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `window_name` - Only the windows with this name (None: all of them)
+///
+/// # Returns
+/// An error listing the available windows if no window has the given name.
+fn windows_to_refresh<'a>(
+    session: &'a Session,
+    window_name: Option<&str>,
+) -> Result<Vec<(usize, &'a Window)>> {
+    let windows: Vec<_> = session
+        .windows
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| window_name.is_none_or(|name| w.name == name))
+        .collect();
+    if let Some(name) = window_name
+        && windows.is_empty()
+    {
+        let available: Vec<_> = session.windows.iter().map(|w| w.name.as_str()).collect();
+        anyhow::bail!(
+            "Window '{}' not found in session '{}'\n  \
+             Available windows:\n    \
+             - {}",
+            name,
+            session.name,
+            available.join("\n    - ")
+        );
+    }
+    Ok(windows)
+}
+
+/// Find the running window for a configured window by name.
+///
+/// Configured windows may share a name: the n-th configured window with a
+/// name matches the n-th running window (by index) with that name.
+///
+/// # Arguments
+/// * `live` - The running session
+/// * `windows` - The configured windows
+/// * `offset` - Position of the window to find in `windows`
+pub fn locate_window<'a>(
+    live: &'a LiveSession,
+    windows: &[Window],
+    offset: usize,
+) -> Option<&'a LiveWindow> {
+    let name = &windows[offset].name;
+    let occurrence = windows[..offset].iter().filter(|w| &w.name == name).count();
+    live.windows.iter().filter(|w| &w.name == name).nth(occurrence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live_window(index: usize, name: &str) -> LiveWindow {
+        LiveWindow {
+            id: format!("@{}", index),
+            index,
+            name: name.to_string(),
+            layout: String::new(),
+            active: false,
+            panes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_locate_window_by_name() {
+        let windows: Vec<Window> = ["editor", "logs", "logs"]
+            .iter()
+            .map(|name| toml::from_str(&format!("name = \"{}\"\npanes = []", name)).unwrap())
+            .collect();
+        // "logs" was moved before "editor" and a scratch window was inserted
+        let live = LiveSession {
+            id: "$1".to_string(),
+            name: "dev".to_string(),
+            windows: vec![
+                live_window(1, "logs"),
+                live_window(2, "scratch"),
+                live_window(3, "editor"),
+                live_window(5, "logs"),
+            ],
+        };
+
+        let index = |offset| locate_window(&live, &windows, offset).map(|w| w.index);
+        assert_eq!(index(0), Some(3));
+        assert_eq!(index(1), Some(1));
+        assert_eq!(index(2), Some(5));
+
+        let closed = LiveSession {
+            windows: vec![live_window(0, "editor")],
+            ..live
+        };
+        assert!(locate_window(&closed, &windows, 1).is_none());
+    }
+
+    #[test]
+    fn test_windows_to_refresh() {
+        let session: Session = toml::from_str(
+            r#"
+name = "dev"
+windows = [
+    { name = "editor", panes = [] },
+    { name = "logs", panes = [] },
+    { name = "logs", panes = [] },
+]
+"#,
+        )
+        .unwrap();
+        let offsets = |window_name| {
+            windows_to_refresh(&session, window_name)
+                .unwrap()
+                .iter()
+                .map(|(offset, _)| *offset)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(offsets(None), [0, 1, 2]);
+        assert_eq!(offsets(Some("logs")), [1, 2]);
+
+        let err = windows_to_refresh(&session, Some("notes")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'notes' not found in session 'dev'")
+        );
+        assert!(err.to_string().contains("- editor\n    - logs"));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::commands::start;
+use crate::context::Context;
+use crate::lock::SessionLock;
+use crate::log;
+use crate::messages;
+use crate::session;
+use crate::state;
+use crate::tmux;
+use anyhow::Result;
+
+/// Recreate a running session from config, restoring the focused window and pane.
+///
+/// Unlike refresh, this kills all running processes in the session. The new
+/// session is built under a temporary name first so the clients attached to
+/// the old session can be switched over before it is killed; they stay on it
+/// when it takes the old name.
+///
+/// # Arguments
+/// * `session_id` - The session name or ID from config
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!("reload command: session_id={}", session_id));
+
+    let config = ctx.config()?;
+    let (session, _) = start::resolve_running_session(session_id, config)?;
+    let session_name = tmux::sanitize_session_name(&session.name);
+
+    let _lock = SessionLock::acquire(&session_name)?;
+
+    if !tmux::has_session(&session_name)? {
+        anyhow::bail!("Session '{}' is not running", session_name);
+    }
+
+    // Snapshot focus so it can be restored by window name and pane position
+    let (focused_window, focused_pane) = tmux::get_active_pane(&session_name)?;
+    log::info(&format!(
+        "reload: focus was window '{}' pane offset {}",
+        focused_window, focused_pane
+    ));
+
+    messages::say(messages::SESSION_RELOADING, &[("session", &session_name)]);
+
+    // Build the replacement under a temporary name
+    let mut replacement = session.clone();
+    replacement.name = format!("{}-tmx-reload", session_name);
+    session::create_session(&replacement, ctx)?;
+
+    // Move every client over before killing the old session so none is detached
+    for client in tmux::session_clients(&session_name)? {
+        tmux::switch_client_of(&client, &replacement.name)?;
+    }
+
+    state::forget_session(&session_name)?;
+    tmux::kill_session(&session_name)?;
+    tmux::rename_session(&replacement.name, &session_name)?;
+
+    let id = config.session_id_for(&session.name);
+    if let Err(e) = state::forget_session(&replacement.name)
+        .and_then(|_| state::record_session(&session_name, id, &session.param_values))
+    {
+        log::error(&format!("failed to record session '{}': {}", session_name, e));
+    }
+
+    // Restore focus to the equivalent window/pane, if it still exists in config
+    if let Some(window_offset) = session.windows.iter().position(|w| w.name == focused_window) {
+        let window_index = tmux::session_base_index(&session_name)? + window_offset;
+        tmux::select_window(&session_name, window_index)?;
+        if focused_pane < session.windows[window_offset].panes.len() {
+            let pane_index = tmux::pane_base_index(&session_name, window_index)? + focused_pane;
+            tmux::select_pane(&session_name, window_index, pane_index)?;
+        }
+    }
+
+    messages::say(messages::SESSION_RELOADED, &[("session", &session_name)]);
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use crate::commands::attach;
+use crate::context::Context;
+use crate::log;
+use crate::shell::{shell_escape, ShellKind};
+use crate::tmux;
+use crate::tmux::query::{self, LivePane, LiveSession, LiveWindow};
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often to check that the pane is still alive while waiting
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A pane addressed as `[<session>][:<window>[.<pane>]]`
+#[derive(Debug, PartialEq, Eq)]
+struct PaneSpec<'a> {
+    /// Session name or config ID (default: the session tmx runs in)
+    session: Option<&'a str>,
+    /// Window index or name (default: the active window)
+    window: Option<&'a str>,
+    /// Pane index (default: the active pane)
+    pane: Option<usize>,
+}
+
+/// The pane a target addresses, in its running session
+#[derive(Debug)]
+pub struct TargetPane {
+    /// tmux name of the session
+    pub session: String,
+    pub window: LiveWindow,
+    pub pane: LivePane,
+}
+
+/// Run a command in a pane of a running session and wait for it to finish.
+///
+/// The command is typed into the pane's shell followed by a sentinel that
+/// saves its exit status and signals a tmux wait-for channel. tmx exits with
+/// the command's exit status, so scripts can run one-off tasks inside an
+/// existing environment.
+///
+/// # Arguments
+/// * `target` - `<session>[:<window>[.<pane>]]`, window by index or name. Inside
+///   tmux, the session may be left out (`:build.1`) to address the current
+///   session, and then the window defaults to the current window
+/// * `command` - The command and its arguments, joined with spaces
+/// * `timeout` - Give up waiting after this many seconds (None: wait indefinitely)
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(target: &str, command: &[String], timeout: Option<u64>, ctx: &Context) -> Result<()> {
+    log::info(&format!("run command: target={} command={:?}", target, command));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+    if command.is_empty() {
+        anyhow::bail!("No command given\n  Hint: Use 'tmx run {} -- <command>'", target);
+    }
+
+    let TargetPane {
+        session: session_name,
+        window,
+        pane,
+    } = resolve_target(target, ctx)?;
+
+    if std::env::var("TMUX_PANE").is_ok_and(|id| id == pane.id) {
+        anyhow::bail!(
+            "Pane {}:{}.{} is the pane tmx is running in\n  \
+             Hint: Pick another pane, e.g. 'tmx run :.{} -- ...'",
+            session_name,
+            window.index,
+            pane.index,
+            if pane.index == 0 { 1 } else { 0 }
+        );
+    }
+
+    let shell = ShellKind::from_name(&pane.current_command).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Pane {}:{}.{} is running '{}', not a shell\n  \
+             Hint: tmx run types the command into a shell prompt, so pick an idle pane",
+            session_name,
+            window.index,
+            pane.index,
+            pane.current_command
+        )
+    })?;
+
+    let channel = run_channel();
+    let status_file = std::env::temp_dir().join(format!("{}.status", channel));
+    let status_path = status_file.to_string_lossy();
+    let line = format!(
+        "{}; {}; tmux wait-for -S {}",
+        command.join(" "),
+        shell.status_command(&shell_escape(&status_path)),
+        channel
+    );
+
+    tmux::send_text(&session_name, window.index, pane.index, &line, true, true)?;
+    wait_for_command(&channel, &pane.id, timeout).map_err(|e| {
+        anyhow::anyhow!("Pane {}:{}.{}: {}", session_name, window.index, pane.index, e)
+    })?;
+
+    let status = fs::read_to_string(&status_file)
+        .with_context(|| format!("Failed to read the exit status from {}", status_file.display()));
+    let _ = fs::remove_file(&status_file);
+    let code: i32 = status?
+        .trim()
+        .parse()
+        .context("Failed to parse the command's exit status")?;
+
+    log::info(&format!("run command finished with status {}", code));
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Find the running pane a `[<session>][:<window>[.<pane>]]` target addresses.
+///
+/// The session is matched by tmux name or config ID, the window by index or
+/// name. Inside tmux, the session may be left out (`:build.1`) to address
+/// the current session, and then the window defaults to the current window;
+/// otherwise the window and pane default to the active ones.
+pub fn resolve_target(target: &str, ctx: &Context) -> Result<TargetPane> {
+    let current_window;
+    let mut spec = parse_target(target)?;
+    let session_name = match spec.session {
+        Some(session) => attach::resolve_running(session, &tmux::list_sessions()?, ctx)?,
+        None => {
+            if !ctx.is_inside_tmux {
+                anyhow::bail!(
+                    "Invalid target '{}': missing session\n  \
+                     Hint: Use <session>:<window>.<pane>, e.g. dev:1.0 (the session can only be left out inside tmux)",
+                    target
+                );
+            }
+            let current = tmux::get_current_pane()?;
+            if spec.window.is_none() {
+                current_window = current.window_index.to_string();
+                spec.window = Some(&current_window);
+            }
+            current.session
+        }
+    };
+    let live = query::live_session(&session_name)?
+        .with_context(|| format!("Session '{}' is not running", session_name))?;
+    let (window, pane) = find_pane(&live, &spec)?;
+    Ok(TargetPane {
+        window: window.clone(),
+        pane: pane.clone(),
+        session: session_name,
+    })
+}
+
+/// Wait for the sentinel, checking regularly that the pane is still there
+/// (e.g. the command may have exited its shell)
+fn wait_for_command(channel: &str, pane_id: &str, timeout: Option<u64>) -> Result<()> {
+    let started = Instant::now();
+    loop {
+        // A signal sent between two waits is remembered by tmux, so none is missed
+        if tmux::wait_for(channel, POLL_INTERVAL)? {
+            return Ok(());
+        }
+        if !tmux::pane_alive(pane_id)? {
+            anyhow::bail!("the pane closed before the command finished");
+        }
+        if let Some(secs) = timeout
+            && started.elapsed() >= Duration::from_secs(secs)
+        {
+            anyhow::bail!(
+                "the command didn't finish within {}s\n  Hint: It keeps running in the pane",
+                secs
+            );
+        }
+    }
+}
+
+/// Parse `[<session>][:<window>[.<pane>]]`
+fn parse_target(target: &str) -> Result<PaneSpec<'_>> {
+    let (session, rest) = match target.split_once(':') {
+        Some((session, rest)) => (session, Some(rest)),
+        None => (target, None),
+    };
+    let session = (!session.is_empty()).then_some(session);
+    if session.is_none() && rest.is_none_or(str::is_empty) {
+        anyhow::bail!(
+            "Invalid target '{}': missing session and window\n  Hint: Use <session>:<window>.<pane>, e.g. dev:1.0, or :<window>.<pane> for the current session",
+            target
+        );
+    }
+
+    let Some(rest) = rest.filter(|r| !r.is_empty()) else {
+        return Ok(PaneSpec {
+            session,
+            window: None,
+            pane: None,
+        });
+    };
+
+    // Window names may contain dots, so only a numeric suffix is a pane index
+    let (window, pane) = match rest.rsplit_once('.') {
+        Some((window, pane)) if pane.parse::<usize>().is_ok() => (window, pane.parse().ok()),
+        _ => (rest, None),
+    };
+    Ok(PaneSpec {
+        session,
+        window: (!window.is_empty()).then_some(window),
+        pane,
+    })
+}
+
+/// Find the addressed window and pane in a running session
+fn find_pane<'a>(live: &'a LiveSession, spec: &PaneSpec) -> Result<(&'a LiveWindow, &'a LivePane)> {
+    let window = match spec.window {
+        Some(w) => live
+            .windows
+            .iter()
+            .find(|win| win.name == w || w.parse() == Ok(win.index))
+            .ok_or_else(|| {
+                let available: Vec<String> = live
+                    .windows
+                    .iter()
+                    .map(|win| format!("{} ({})", win.index, win.name))
+                    .collect();
+                anyhow::anyhow!(
+                    "Window '{}' not found in session '{}'\n  Available windows: {}",
+                    w,
+                    live.name,
+                    available.join(", ")
+                )
+            })?,
+        None => live
+            .windows
+            .iter()
+            .find(|win| win.active)
+            .or(live.windows.first())
+            .context("Session has no windows")?,
+    };
+
+    let pane = match spec.pane {
+        Some(p) => window.panes.iter().find(|pane| pane.index == p).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Pane {} not found in window '{}' (panes: {})",
+                p,
+                window.name,
+                window
+                    .panes
+                    .iter()
+                    .map(|pane| pane.index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?,
+        None => window
+            .panes
+            .iter()
+            .find(|pane| pane.active)
+            .or(window.panes.first())
+            .context("Window has no panes")?,
+    };
+
+    Ok((window, pane))
+}
+
+/// Unique wait-for channel for one `tmx run`
+fn run_channel() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    format!("tmx-run-{}-{}", std::process::id(), nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("dev").unwrap(),
+            PaneSpec {
+                session: Some("dev"),
+                window: None,
+                pane: None
+            }
+        );
+        assert_eq!(
+            parse_target("dev:1.2").unwrap(),
+            PaneSpec {
+                session: Some("dev"),
+                window: Some("1"),
+                pane: Some(2)
+            }
+        );
+        assert_eq!(
+            parse_target("dev:build.v2").unwrap(),
+            PaneSpec {
+                session: Some("dev"),
+                window: Some("build.v2"),
+                pane: None
+            }
+        );
+        assert_eq!(
+            parse_target("dev:.1").unwrap(),
+            PaneSpec {
+                session: Some("dev"),
+                window: None,
+                pane: Some(1)
+            }
+        );
+        assert_eq!(
+            parse_target(":.1").unwrap(),
+            PaneSpec {
+                session: None,
+                window: None,
+                pane: Some(1)
+            }
+        );
+        assert!(parse_target(":").is_err());
+    }
+}
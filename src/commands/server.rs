@@ -0,0 +1,109 @@
+use crate::context::Context;
+use crate::log;
+use crate::messages;
+use crate::prompt;
+use crate::tmux;
+use crate::tmux::query::{self, ServerInfo};
+use anyhow::Result;
+
+/// Start the tmux server (kept running without sessions) if it isn't running
+pub fn start(_ctx: &Context) -> Result<()> {
+    ensure_installed()?;
+
+    if let Some(info) = query::server_info()? {
+        println!("tmux server is already running (pid {})", info.pid);
+        return Ok(());
+    }
+
+    tmux::start_server()?;
+    let info = query::server_info()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "tmux server exited right after starting\n  \
+             Hint: Check your ~/.tmux.conf, e.g. with 'tmux start-server'"
+        )
+    })?;
+    log::info(&format!("started tmux server pid {} on {}", info.pid, info.socket_path));
+    println!("✓ Started tmux server on {} (pid {})", info.socket_path, info.pid);
+    Ok(())
+}
+
+/// Kill the tmux server and all its sessions.
+///
+/// # Arguments
+/// * `force` - Don't ask for confirmation when sessions are running
+/// * `ctx` - Shared context containing configuration and state
+pub fn kill(force: bool, _ctx: &Context) -> Result<()> {
+    ensure_installed()?;
+
+    let Some(sessions) = tmux::server_sessions()? else {
+        println!("No tmux server is running");
+        return Ok(());
+    };
+
+    if !sessions.is_empty() && !force {
+        let question = format!(
+            "Kill the tmux server and its {} session(s): {}?",
+            sessions.len(),
+            sessions.join(", ")
+        );
+        if prompt::choose(&question, &[('y', "kill"), ('n', "cancel")])? != Some('y') {
+            messages::say(messages::CANCELLED, &[]);
+            return Ok(());
+        }
+    }
+
+    tmux::kill_server()?;
+    log::info(&format!("killed tmux server with {} session(s)", sessions.len()));
+    println!("✓ Killed tmux server ({} session(s) closed)", sessions.len());
+    Ok(())
+}
+
+/// Print the state of the tmux server
+pub fn info(_ctx: &Context) -> Result<()> {
+    ensure_installed()?;
+
+    let Some(info) = query::server_info()? else {
+        println!("tmux server: not running");
+        println!("  Hint: Start it with 'tmx server start' or 'tmx open <session>'");
+        return Ok(());
+    };
+    let sessions = tmux::list_sessions()?;
+
+    println!("tmux server: running");
+    println!("  socket:   {}", info.socket_path);
+    println!("  pid:      {}", info.pid);
+    println!("  version:  tmux {}", info.version);
+    println!("  started:  {}", started(&info));
+    println!("  sessions: {}", sessions.len());
+    Ok(())
+}
+
+/// One-line summary of the server for status output
+pub fn summary() -> String {
+    match query::server_info() {
+        Ok(Some(info)) => format!(
+            "tmux {} on {} (pid {})",
+            info.version, info.socket_path, info.pid
+        ),
+        Ok(None) => "not running".to_string(),
+        Err(e) => format!("unknown ({})", e),
+    }
+}
+
+/// Format the server start time as `YYYY-MM-DD HH:MM:SS UTC`
+fn started(info: &ServerInfo) -> String {
+    let (year, month, day, hours, minutes, seconds) = log::utc_datetime(info.started);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, hours, minutes, seconds
+    )
+}
+
+/// Fail with a clear error if tmux can't be run
+fn ensure_installed() -> Result<()> {
+    if !tmux::is_installed() {
+        log::error("tmux is not installed");
+        anyhow::bail!("tmux is not installed");
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::commands::attach;
+use crate::config::Config;
+use crate::config_edit::ConfigEditor;
+use crate::context::Context;
+use crate::log;
+use crate::snapshot;
+use crate::tmux;
+use crate::tmux::query;
+use anyhow::{Context as _, Result};
+
+/// Dump a running session as a `[sessions.<id>]` config block.
+///
+/// The block is printed, or with `append` added to the end of the config
+/// file so the session can be recreated with `tmx open`.
+///
+/// # Arguments
+/// * `session_id` - Name of the running session (or its ID in config)
+/// * `id` - Config key for the new session (default: the session's name)
+/// * `append` - Add the session to the config file instead of printing it
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: &str, id: Option<&str>, append: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "snapshot command: session_id={} id={:?} append={}",
+        session_id, id, append
+    ));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let running = tmux::list_sessions()?;
+    let name = attach::resolve_running(session_id, &running, ctx)?;
+    let live = query::live_session(&name)?
+        .with_context(|| format!("Session '{}' is not running", name))?;
+    let root = query::session_summaries()?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|summary| summary.name == name)
+        .map(|summary| summary.path)
+        .with_context(|| format!("Session '{}' is not running", name))?;
+
+    let id = id.unwrap_or(&name);
+    let doc = snapshot::session_document(id, &live, &root);
+
+    // Make sure the snapshot loads back as a valid session
+    let config: Config = toml::from_str(&doc.to_string())
+        .with_context(|| format!("Snapshot of session '{}' is not valid config", name))?;
+    config.sessions[id].validate()?;
+
+    if !append {
+        print!("{}", doc);
+        return Ok(());
+    }
+
+    let session = doc["sessions"][id]
+        .as_table()
+        .cloned()
+        .context("Snapshot has no session table")?;
+    let mut editor = ConfigEditor::open(ctx.config_path())?;
+    editor.append_session(id, session)?;
+    editor.save()?;
+
+    log::info(&format!("snapshot: added session '{}' to config", id));
+    println!(
+        "✓ Added session '{}' to {}",
+        id,
+        ctx.config_path().display()
+    );
+    Ok(())
+}
@@ -1,18 +1,356 @@
+use crate::cli::AttachMode;
+use crate::config::{Config, OnConflict, Session};
 use crate::context::Context;
+use crate::lock::SessionLock;
 use crate::log;
+use crate::messages;
+use crate::placeholders;
 use crate::session;
+use crate::state::{self, Operation};
 use crate::tmux;
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
 
 /// Attach to or switch to a tmux session depending on context.
 ///
 /// If already inside tmux, switches the client to the target session.
-/// Otherwise, attaches to the session from outside tmux.
-fn attach_or_switch(session_name: &str, ctx: &Context) -> Result<()> {
-    if ctx.is_inside_tmux {
-        tmux::switch_client(session_name)
+/// Otherwise, attaches to the session from outside tmux. When the session
+/// runs on a different tmux server than the one we're inside, tmux can't
+/// switch to it, so this fails with guidance instead.
+///
+/// # Arguments
+/// * `session_name` - The session to hand over to
+/// * `mode` - Explicit attach mode from `--attach-mode`, or None to decide from context
+/// * `ctx` - Shared context containing configuration and state
+pub fn attach_or_switch(session_name: &str, mode: Option<AttachMode>, ctx: &Context) -> Result<()> {
+    let mode = mode.unwrap_or(if ctx.is_inside_tmux {
+        AttachMode::Switch
+    } else {
+        AttachMode::Attach
+    });
+    log::info(&format!("handing over to '{}' with attach mode {:?}", session_name, mode));
+
+    match mode {
+        AttachMode::Attach => tmux::attach_session(session_name),
+        AttachMode::Switch => {
+            if let Some(server) = other_server_socket(session_name, ctx) {
+                return Err(nested_server_error(session_name, &server, ctx));
+            }
+            tmux::switch_client(session_name)
+        }
+        AttachMode::Print => {
+            println!("{}", tmux::sanitize_session_name(session_name));
+            Ok(())
+        }
+        AttachMode::NewTerminal => open_in_terminal(session_name, ctx),
+    }
+}
+
+/// Socket path of the session's tmux server, if it isn't the server we're inside
+fn other_server_socket(session_name: &str, ctx: &Context) -> Option<String> {
+    let current = ctx.tmux_socket.as_ref()?;
+    let target = tmux::server_socket_path(session_name).ok()?;
+    let same = match (fs::canonicalize(current), fs::canonicalize(&target)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => *current == Path::new(&target),
+    };
+    (!same).then_some(target)
+}
+
+/// Explain why a session on another tmux server can't be switched to
+fn nested_server_error(session_name: &str, server: &str, ctx: &Context) -> anyhow::Error {
+    let current = ctx
+        .tmux_socket
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    log::error(&format!(
+        "session '{}' is on server {}, not the current server {}",
+        session_name, server, current
+    ));
+    let name = tmux::sanitize_session_name(session_name);
+    anyhow::anyhow!(
+        "Session '{}' runs on a different tmux server ({}) than the one you're in ({}), \
+         so tmux can't switch to it\n  \
+         Hint: Open it in a new terminal window with 'tmx open {} --new-terminal' \
+         (needs terminal_command in the config), or detach and run 'tmux -S {} attach -t {}'",
+        name,
+        server,
+        current,
+        name,
+        server,
+        name
+    )
+}
+
+/// Launch the configured terminal emulator running `tmux attach` for the session
+fn open_in_terminal(session_name: &str, ctx: &Context) -> Result<()> {
+    let terminal_command = ctx
+        .config()?
+        .terminal_command
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No terminal_command configured to open a new terminal window\n  \
+                 Hint: Add e.g. terminal_command = \"wezterm start --\" at the top of the config"
+            )
+        })?;
+
+    let name = tmux::sanitize_session_name(session_name);
+    let words = terminal_command
+        .split_whitespace()
+        .map(|word| placeholders::expand_terminal_command(word, &name))
+        .collect::<Result<Vec<_>>>()
+        .context("Invalid terminal_command in the config")?;
+    let (program, args) = words.split_first().expect("terminal_command is not blank");
+    let attach = tmux::attach_argv(session_name)?;
+    log::info(&format!(
+        "opening '{}' in a new terminal: {} {}",
+        session_name,
+        words.join(" "),
+        attach.join(" ")
+    ));
+
+    Command::new(program)
+        .args(args)
+        .args(&attach)
+        // The new window is outside tmux, even though we may be inside it
+        .env_remove("TMUX")
+        .env_remove("TMUX_PANE")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to start terminal '{}'\n  Hint: Check terminal_command in the config",
+                program
+            )
+        })?;
+
+    messages::say(messages::SESSION_OPENED_IN_TERMINAL, &[("session", &name)]);
+    Ok(())
+}
+
+/// Resolve a session by name or ID from config.
+///
+/// Unconfigured sessions use the default session's layout with the requested
+/// name, rooted at the current working directory.
+///
+/// # Returns
+/// The resolved session and whether it was built from the default session.
+pub fn resolve_session(session_id: &str, config: &Config) -> Result<(Session, bool)> {
+    resolve_session_with(session_id, config, &config.params)
+}
+
+/// Resolve a running session to change it in place, like `resolve_session`.
+///
+/// Its `params` are filled in with the values given with `--set`, or if
+/// there are none, with the values it was opened with.
+///
+/// # Returns
+/// The resolved session and whether it was built from the default session.
+pub fn resolve_running_session(session_id: &str, config: &Config) -> Result<(Session, bool)> {
+    if config.params.is_empty() {
+        let name = config
+            .get_session(session_id)
+            .map_or(session_id, |s| s.name.as_str());
+        if let Some(params) = state::recorded_params(name) {
+            let params: HashMap<String, String> = params.into_iter().collect();
+            return resolve_session_with(session_id, config, &params);
+        }
+    }
+    resolve_session(session_id, config)
+}
+
+/// Resolve a session, filling in its `params` with the given values
+fn resolve_session_with(
+    session_id: &str,
+    config: &Config,
+    params: &HashMap<String, String>,
+) -> Result<(Session, bool)> {
+    if let Some(s) = config.get_session(session_id) {
+        log::info(&format!("found session '{}' in config", session_id));
+        return Ok((s.with_params(params)?, false));
+    }
+
+    // Session not in config - use default session's layout with the requested name
+    log::info(&format!("session '{}' not in config, using default layout", session_id));
+    let default_id = config.default_session_id().ok_or_else(|| {
+        log::error(&format!("no default session configured for '{}'", session_id));
+        anyhow::anyhow!(
+            "Session '{}' not found and no default session configured\nAvailable sessions: {}",
+            session_id,
+            config.session_ids().join(", ")
+        )
+    })?;
+
+    let default_session = config.get_session(default_id).ok_or_else(|| {
+        log::error(&format!("default session '{}' not found", default_id));
+        anyhow::anyhow!(
+            "Default session '{}' not found in configuration",
+            default_id
+        )
+    })?;
+
+    // Clone the default session and change the name
+    let mut dynamic_session = default_session.with_params(params)?;
+    dynamic_session.name = session_id.to_string();
+    // Use current working directory instead of the default session's root
+    let cwd = std::env::current_dir()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "~".to_string());
+    dynamic_session.root = cwd.clone();
+    dynamic_session.expand_window_names()?;
+    log::info(&format!("using default session '{}' as template with root '{}'", default_id, cwd));
+    Ok((dynamic_session, true))
+}
+
+/// Open a configured session whose name is already taken by a running session.
+///
+/// Sessions created by tmx are attached to directly. Otherwise the session's
+/// `on_conflict` policy decides whether to attach, fail, recreate it from
+/// config, or create it under a new name (reusing the one made the last time).
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `id` - Its config key
+/// * `ctx` - Shared context containing configuration and state
+///
+/// # Returns
+/// The name of the session to attach to.
+fn open_existing(session: &Session, id: &str, ctx: &Context) -> Result<String> {
+    let session_name = &session.name;
+    let sanitized_name = tmux::sanitize_session_name(session_name);
+
+    if state::is_managed(session_name) {
+        log::info(&format!("attaching to existing session '{}'", sanitized_name));
+        messages::say(messages::SESSION_ATTACHING, &[("session", &sanitized_name)]);
+        session::update_passed_environment(session)?;
+        return Ok(session_name.clone());
+    }
+
+    log::info(&format!(
+        "session '{}' was not created by tmx, on_conflict={:?}",
+        sanitized_name, session.on_conflict
+    ));
+
+    match session.on_conflict {
+        OnConflict::Attach => {
+            messages::say(messages::SESSION_ATTACHING, &[("session", &sanitized_name)]);
+            session::update_passed_environment(session)?;
+            Ok(session_name.clone())
+        }
+        OnConflict::Error => {
+            anyhow::bail!(
+                "Session '{}' is already running but was not created by tmx\n  \
+                 Hint: Close it with 'tmx close {}' or set on_conflict to \
+                 \"attach\", \"recreate\" or \"rename\"",
+                sanitized_name,
+                sanitized_name
+            )
+        }
+        OnConflict::Recreate => {
+            messages::say(
+                messages::SESSION_RECREATING,
+                &[("session", &sanitized_name)],
+            );
+            state::forget_session(session_name)?;
+            tmux::kill_session(session_name)?;
+            session::create_session(session, ctx)?;
+            Ok(session_name.clone())
+        }
+        OnConflict::Rename => {
+            let mut renamed = session.clone();
+            if let Some(name) = existing_rename(&sanitized_name, id) {
+                messages::say(messages::SESSION_ATTACHING, &[("session", &name)]);
+                renamed.name = name;
+                session::update_passed_environment(&renamed)?;
+                return Ok(renamed.name);
+            }
+
+            renamed.name = next_free_name(&sanitized_name)?;
+            messages::say(
+                messages::SESSION_RENAMED,
+                &[("session", &sanitized_name), ("name", &renamed.name)],
+            );
+            session::create_session(&renamed, ctx)?;
+            // The new name isn't in the config, so record which session it came from
+            if let Err(e) = state::record_session(&renamed.name, Some(id), &renamed.param_values) {
+                log::error(&format!(
+                    "failed to record session '{}': {}",
+                    renamed.name, e
+                ));
+            }
+            Ok(renamed.name)
+        }
+    }
+}
+
+/// Kill a running session (if any) and rebuild it from config.
+///
+/// # Returns
+/// The name of the session to attach to.
+fn force_recreate(session_id: &str, no_commands: bool, ctx: &Context) -> Result<String> {
+    let config = ctx.config()?;
+    let (session, _) = resolve_session(session_id, config)?;
+    let session = commands_unless(&session, no_commands);
+    let session_name = &session.name;
+
+    if tmux::has_session(session_name)? {
+        log::info(&format!("force: killing existing session '{}'", session_name));
+        messages::say(
+            messages::SESSION_KILLING,
+            &[("session", &tmux::sanitize_session_name(session_name))],
+        );
+        state::forget_session(session_name)?;
+        tmux::kill_session(session_name)?;
+    }
+
+    session::create_session(&session, ctx)?;
+    Ok(session.name)
+}
+
+/// The running session an earlier `on_conflict = "rename"` open created for a
+/// config ID, if any: one named `<name>-N` that tmx created from that ID
+fn existing_rename(name: &str, id: &str) -> Option<String> {
+    state::State::load()
+        .sessions
+        .into_iter()
+        .filter(|(running, record)| record.id.as_deref() == Some(id) && is_rename_of(running, name))
+        .map(|(running, _)| running)
+        .find(|running| state::is_managed(running))
+}
+
+/// Whether a session name has the form `<name>-N` (N >= 2) `next_free_name` gives
+fn is_rename_of(candidate: &str, name: &str) -> bool {
+    candidate
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .and_then(|n| n.parse::<u32>().ok())
+        .is_some_and(|n| n >= 2)
+}
+
+/// Find the first session name of the form `<name>-N` (N >= 2) not running in tmux
+fn next_free_name(name: &str) -> Result<String> {
+    let running = tmux::list_sessions()?;
+    let free = (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !running.contains(candidate))
+        .expect("unbounded range always yields a free name");
+    Ok(free)
+}
+
+/// The session to create, without its commands with `--no-commands`
+fn commands_unless(session: &Session, no_commands: bool) -> Session {
+    if no_commands {
+        session.without_commands()
     } else {
-        tmux::attach_session(session_name)
+        session.clone()
     }
 }
 
@@ -21,11 +359,27 @@ fn attach_or_switch(session_name: &str, ctx: &Context) -> Result<()> {
 /// If the session already exists in tmux, we'll attach to it directly.
 /// If not, we'll look it up in the configuration and create it.
 ///
+/// A per-session lock is held while the session is checked and built, so a
+/// concurrent `tmx open` of the same session waits and then attaches instead
+/// of interleaving its own splits.
+///
 /// # Arguments
 /// * `session_id` - The session ID/name to attach to or create
+/// * `force` - Kill any running session with the same name and recreate it
+/// * `no_commands` - Create windows and panes without running any configured commands
+/// * `attach_mode` - How to hand over to the session (None: decide from context)
 /// * `ctx` - Shared context containing configuration and state
-pub fn run(session_id: &str, ctx: &Context) -> Result<()> {
-    log::info(&format!("open command: session_id={}", session_id));
+pub fn run(
+    session_id: &str,
+    force: bool,
+    no_commands: bool,
+    attach_mode: Option<AttachMode>,
+    ctx: &Context,
+) -> Result<()> {
+    log::info(&format!(
+        "open command: session_id={} force={} no_commands={}",
+        session_id, force, no_commands
+    ));
 
     // Check if tmux is installed
     if !tmux::is_installed() {
@@ -33,77 +387,178 @@ pub fn run(session_id: &str, ctx: &Context) -> Result<()> {
         anyhow::bail!("tmux is not installed");
     }
 
+    // The lock is released before attaching, which blocks until the client detaches
+    let target = open_session(session_id, force, no_commands, ctx)?;
+    attach_or_switch(&target, attach_mode, ctx)
+}
+
+/// Create a session if it isn't running yet, without attaching to it.
+///
+/// # Arguments
+/// * `session_id` - The session ID/name to open
+/// * `force` - Kill any running session with the same name and recreate it
+/// * `no_commands` - Create windows and panes without running any configured commands
+/// * `ctx` - Shared context containing configuration and state
+///
+/// # Returns
+/// The name of the running session.
+pub fn open_session(
+    session_id: &str,
+    force: bool,
+    no_commands: bool,
+    ctx: &Context,
+) -> Result<String> {
+    let _lock = lock_session(session_id, ctx)?;
+    open_group(session_id, force, no_commands, ctx)?;
+    let target = if force {
+        force_recreate(session_id, no_commands, ctx)
+    } else {
+        prepare_session(session_id, no_commands, ctx)
+    };
+    state::record_operation(
+        Operation::Open,
+        target.as_deref().unwrap_or(session_id),
+        &target,
+    );
+    target
+}
+
+/// Take the lock of the session opened for a session ID.
+///
+/// The lock is taken on the session's name, as refresh, reload and sync take
+/// it, so they exclude each other even when the ID and name differ. A
+/// session that can't be resolved is locked by the name given.
+pub fn lock_session(session_id: &str, ctx: &Context) -> Result<SessionLock> {
+    let name = ctx
+        .config()
+        .ok()
+        .and_then(|config| resolve_session(session_id, config).ok())
+        .map_or_else(|| session_id.to_string(), |(session, _)| session.name);
+    SessionLock::acquire(&name)
+}
+
+/// Open the configured session a grouped session shares its windows with
+/// (`group_with`), if it isn't running and the grouped session is about to
+/// be created.
+fn open_group(session_id: &str, force: bool, no_commands: bool, ctx: &Context) -> Result<()> {
+    let Some(config) = ctx.config().ok() else {
+        return Ok(());
+    };
+    let Some(session) = config.get_session(session_id) else {
+        return Ok(());
+    };
+    let Some(ref group) = session.group_with else {
+        return Ok(());
+    };
+    if !force && tmux::has_session(&session.name)? {
+        return Ok(());
+    }
+    let Some(group_id) = config.session_id_for(group) else {
+        // A running session named in group_with is grouped with as it is
+        return Ok(());
+    };
+    let target = &config.sessions[group_id];
+    if let Some(ref chained) = target.group_with {
+        anyhow::bail!(
+            "Session '{}' is grouped with '{}', which is itself grouped with '{}'\n  \
+             Hint: Set group_with = \"{}\" to join the same group",
+            session.name,
+            group,
+            chained,
+            chained
+        );
+    }
+    if !tmux::has_session(&target.name)? {
+        log::info(&format!(
+            "opening '{}' for session '{}' to group with",
+            group_id, session.name
+        ));
+        open_session(group_id, false, no_commands, ctx)?;
+    }
+    Ok(())
+}
+
+/// Find or create the session to open.
+///
+/// # Arguments
+/// * `session_id` - The session ID/name to attach to or create
+/// * `no_commands` - Create windows and panes without running any configured commands
+/// * `ctx` - Shared context containing configuration and state
+///
+/// # Returns
+/// The name of the session to attach to.
+pub fn prepare_session(session_id: &str, no_commands: bool, ctx: &Context) -> Result<String> {
     // First, check if a session with this name already exists in tmux
     // This allows attaching to any existing session, even if not in config
     if tmux::has_session(session_id)? {
+        // A configured session with this name is subject to its on_conflict policy
+        let configured = ctx
+            .config()
+            .ok()
+            .and_then(|c| c.session_id_for(session_id).map(|id| (id, &c.sessions[id])))
+            .filter(|(_, s)| {
+                tmux::sanitize_session_name(&s.name) == tmux::sanitize_session_name(session_id)
+            });
+        if let Some((id, session)) = configured {
+            return open_existing(&commands_unless(session, no_commands), id, ctx);
+        }
+
         log::info(&format!("attaching to existing session '{}'", session_id));
-        println!("Attaching to existing session '{}'...", session_id);
-        return attach_or_switch(session_id, ctx);
+        messages::say(messages::SESSION_ATTACHING, &[("session", &session_id)]);
+        return Ok(session_id.to_string());
     }
 
     // Session doesn't exist, so we need to create it from configuration
     let config = ctx.config()?;
 
     // Find the session in config, or use default session's layout for unconfigured sessions
-    let (session, is_dynamic) = if let Some(s) = config.get_session(session_id) {
-        log::info(&format!("found session '{}' in config", session_id));
-        (s.clone(), false)
-    } else {
-        // Session not in config - use default session's layout with the requested name
-        log::info(&format!("session '{}' not in config, using default layout", session_id));
-        let default_id = config.default.as_ref().ok_or_else(|| {
-            log::error(&format!("no default session configured for '{}'", session_id));
-            anyhow::anyhow!(
-                "Session '{}' not found and no default session configured\nAvailable sessions: {}",
-                session_id,
-                config.session_ids().join(", ")
-            )
-        })?;
-
-        let default_session = config.get_session(default_id).ok_or_else(|| {
-            log::error(&format!("default session '{}' not found", default_id));
-            anyhow::anyhow!(
-                "Default session '{}' not found in configuration",
-                default_id
-            )
-        })?;
-
-        // Clone the default session and change the name
-        let mut dynamic_session = default_session.clone();
-        dynamic_session.name = session_id.to_string();
-        // Use current working directory instead of the default session's root
-        let cwd = std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "~".to_string());
-        dynamic_session.root = cwd.clone();
-        log::info(&format!("using default session '{}' as template with root '{}'", default_id, cwd));
-        (dynamic_session, true)
-    };
+    let (session, is_dynamic) = resolve_session(session_id, config)?;
+    let session = commands_unless(&session, no_commands);
 
     let session_name = &session.name;
     let sanitized_name = tmux::sanitize_session_name(session_name);
 
     // Warn user if session name contains special characters
     if sanitized_name != *session_name {
-        println!(
-            "Note: Session name '{}' contains special characters and will be created as '{}'",
-            session_name, sanitized_name
+        messages::say(
+            messages::SESSION_NAME_SANITIZED,
+            &[("name", session_name), ("session", &sanitized_name)],
         );
     }
 
     // Double-check if session exists with the configured name (may differ from session_id)
     if tmux::has_session(session_name)? {
-        println!("Attaching to existing session '{}'...", sanitized_name);
-        attach_or_switch(session_name, ctx)?;
-    } else {
-        // Create the session
-        if is_dynamic {
-            println!("Creating session '{}' using default layout...", sanitized_name);
+        match config.session_id_for(session_id) {
+            Some(id) if !is_dynamic => return open_existing(&session, id, ctx),
+            _ => {
+                messages::say(messages::SESSION_ATTACHING, &[("session", &sanitized_name)]);
+                return Ok(session_name.clone());
+            }
         }
-        session::create_session(&session, ctx)?;
-        // Attach to the newly created session
-        attach_or_switch(session_name, ctx)?;
     }
 
-    Ok(())
+    // Create the session
+    if is_dynamic {
+        messages::say(
+            messages::SESSION_CREATING_DEFAULT,
+            &[("session", &sanitized_name)],
+        );
+    }
+    session::create_session(&session, ctx)?;
+    Ok(session.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rename_of() {
+        assert!(is_rename_of("dev-2", "dev"));
+        assert!(is_rename_of("dev-12", "dev"));
+        assert!(!is_rename_of("dev", "dev"));
+        assert!(!is_rename_of("dev-1", "dev"));
+        assert!(!is_rename_of("dev-api", "dev"));
+        assert!(!is_rename_of("devops-2", "dev"));
+    }
 }
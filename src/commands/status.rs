@@ -0,0 +1,402 @@
+use crate::commands::{attach, refresh, stop};
+use crate::config::{Config, Session};
+use crate::context::Context;
+use crate::diff;
+use crate::health::{self, Check, Health};
+use crate::log;
+use crate::messages;
+use crate::table::{Cell, Style, Table};
+use crate::tmux;
+use crate::tmux::query::{self, LiveSession};
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a summary line is reused before tmux is queried again
+const SUMMARY_TTL_SECS: u64 = 5;
+
+/// A pane as shown by `tmx status`
+struct PaneStatus {
+    session: String,
+    window: String,
+    index: usize,
+    command: String,
+    dead: bool,
+    /// The configured health check, if the pane has one
+    check: Option<Check>,
+}
+
+/// Show the windows and panes of running sessions and what runs in them.
+///
+/// With `health`, the configured `healthcheck` of each pane is run and its
+/// result shown as a badge; tmx exits with status 1 if any check fails, so
+/// scripts can wait for an environment to be healthy.
+///
+/// # Arguments
+/// * `session_id` - Only show this session (None: every running session)
+/// * `health` - Run the panes' health checks
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: Option<&str>, health: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!(
+        "status command: session_id={:?} health={}",
+        session_id, health
+    ));
+
+    if !tmux::is_installed() {
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let running = tmux::list_sessions()?;
+    let names = match session_id {
+        Some(id) => vec![attach::resolve_running(id, &running, ctx)?],
+        None => running,
+    };
+    if names.is_empty() {
+        messages::say(messages::STATUS_NO_SESSIONS, &[]);
+        return Ok(());
+    }
+
+    let mut panes = Vec::new();
+    for name in &names {
+        if let Some(live) = query::live_session(name)? {
+            panes.extend(pane_statuses(
+                &live,
+                stop::configured_session(name, ctx).map(|(_, s)| s),
+            ));
+        }
+    }
+
+    let results: Vec<Option<Health>> = if health {
+        let checks: Vec<Check> = panes.iter().filter_map(|p| p.check.clone()).collect();
+        let mut results = health::run_checks(&checks, health::CHECK_TIMEOUT).into_iter();
+        panes
+            .iter()
+            .map(|p| p.check.as_ref().and_then(|_| results.next()))
+            .collect()
+    } else {
+        vec![None; panes.len()]
+    };
+
+    print!("{}", status_table(&panes, &results, health).render());
+
+    if health {
+        let checked = results.iter().flatten().count();
+        let failed = results
+            .iter()
+            .flatten()
+            .filter(|r| matches!(r, Health::Fail(_)))
+            .count();
+        if checked == 0 {
+            println!(
+                "No health checks configured\n  Hint: Add healthcheck = \"<command>\" to a pane"
+            );
+        } else if failed > 0 {
+            log::info(&format!("{} of {} health checks failed", failed, checked));
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+/// State of a session in its summary line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum SummaryState {
+    /// Running and matching its config
+    Ok,
+    /// Running, with windows or panes that differ from its config
+    Drifted,
+    /// Running but not in the config
+    Unconfigured,
+    /// Configured but not running
+    Stopped,
+}
+
+impl SummaryState {
+    fn as_str(self) -> &'static str {
+        match self {
+            SummaryState::Ok => "ok",
+            SummaryState::Drifted => "drifted",
+            SummaryState::Unconfigured => "unconfigured",
+            SummaryState::Stopped => "stopped",
+        }
+    }
+}
+
+/// A session's state and counts, as printed by `tmx status --summary`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Summary {
+    session: String,
+    state: SummaryState,
+    windows: usize,
+    panes: usize,
+    /// Number of structural differences between the session and its config
+    drift: usize,
+    /// Number of attached clients
+    clients: usize,
+    /// When the summary was computed (seconds since the Unix epoch)
+    computed: u64,
+}
+
+impl Summary {
+    /// `dev ok windows=4 panes=11 drift=0 clients=1`
+    fn line(&self) -> String {
+        format!(
+            "{} {} windows={} panes={} drift={} clients={}",
+            self.session,
+            self.state.as_str(),
+            self.windows,
+            self.panes,
+            self.drift,
+            self.clients
+        )
+    }
+}
+
+/// Print a session's state as one line of `key=value` fields, for tmux's
+/// `status-right` and other scripts.
+///
+/// Results are cached per session for a few seconds, so a status line
+/// redrawn every few seconds doesn't query tmux each time.
+///
+/// # Arguments
+/// * `session_id` - Session name or ID from config
+/// * `ctx` - Shared context containing configuration and state
+pub fn summary(session_id: &str, ctx: &Context) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut cache = load_summaries();
+    let summary = match cache.get(session_id) {
+        Some(summary) if now.saturating_sub(summary.computed) < SUMMARY_TTL_SECS => summary.clone(),
+        _ => {
+            log::info(&format!("status summary: session_id={}", session_id));
+            let summary = compute_summary(session_id, ctx, now)?;
+            cache.retain(|_, s| now.saturating_sub(s.computed) < SUMMARY_TTL_SECS);
+            cache.insert(session_id.to_string(), summary.clone());
+            if let Err(e) = save_summaries(&cache) {
+                log::error(&format!("failed to save the status cache: {}", e));
+            }
+            summary
+        }
+    };
+
+    println!("{}", summary.line());
+    Ok(())
+}
+
+/// Query tmux for a session and compare it with its configuration
+fn compute_summary(session_id: &str, ctx: &Context, now: u64) -> Result<Summary> {
+    let summaries = query::session_summaries()?.unwrap_or_default();
+    let running: Vec<String> = summaries.iter().map(|s| s.name.clone()).collect();
+    let configured = ctx.config().ok().and_then(|c| c.get_session(session_id));
+
+    let Ok(name) = attach::resolve_running(session_id, &running, ctx) else {
+        let session = configured.with_context(|| {
+            format!(
+                "Session '{}' is not configured or running\n  \
+                 Hint: Run 'tmx list' to see the sessions",
+                session_id
+            )
+        })?;
+        return Ok(Summary {
+            session: tmux::sanitize_session_name(&session.name),
+            state: SummaryState::Stopped,
+            windows: 0,
+            panes: 0,
+            drift: 0,
+            clients: 0,
+            computed: now,
+        });
+    };
+
+    let live = query::live_session(&name)?
+        .with_context(|| format!("Session '{}' is not running", name))?;
+    let session = stop::configured_session(&name, ctx).map(|(_, s)| s);
+    let drift = match session {
+        Some(session) => {
+            let base_index = tmux::session_base_index(&name)?;
+            diff::diff_session(session, &live, base_index).len()
+        }
+        None => 0,
+    };
+    let state = match (session, drift) {
+        (None, _) => SummaryState::Unconfigured,
+        (Some(_), 0) => SummaryState::Ok,
+        (Some(_), _) => SummaryState::Drifted,
+    };
+
+    Ok(Summary {
+        state,
+        windows: live.windows.len(),
+        panes: live.windows.iter().map(|w| w.panes.len()).sum(),
+        drift,
+        clients: summaries
+            .iter()
+            .find(|s| s.name == name)
+            .map_or(0, |s| s.attached),
+        session: name,
+        computed: now,
+    })
+}
+
+fn summary_cache_path() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("status-cache.json"))
+}
+
+/// Load cached summaries by session argument (empty if missing or unreadable)
+fn load_summaries() -> BTreeMap<String, Summary> {
+    summary_cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_summaries(cache: &BTreeMap<String, Summary>) -> Result<()> {
+    let path = summary_cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create state directory: {}", dir.display()))?;
+    }
+    fs::write(&path, serde_json::to_string(cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The panes of a running session, with the health checks of the configured
+/// panes they correspond to
+fn pane_statuses(live: &LiveSession, session: Option<&Session>) -> Vec<PaneStatus> {
+    let mut statuses = Vec::new();
+    for live_window in &live.windows {
+        // The configured window this one was created from, matched like `tmx refresh` does
+        let configured = session.and_then(|session| {
+            (0..session.windows.len())
+                .find(|&offset| {
+                    refresh::locate_window(live, &session.windows, offset)
+                        .is_some_and(|w| w.id == live_window.id)
+                })
+                .map(|offset| (session, &session.windows[offset]))
+        });
+
+        for (position, pane) in live_window.panes.iter().enumerate() {
+            let check = configured.and_then(|(session, window)| {
+                let configured_pane = window.panes.get(position)?;
+                let window_root = window.root_expanded(&session.root_expanded());
+                Some(Check {
+                    command: configured_pane.healthcheck.clone()?,
+                    dir: PathBuf::from(configured_pane.root_expanded(&window_root)),
+                })
+            });
+            statuses.push(PaneStatus {
+                session: live.name.clone(),
+                window: format!("{}:{}", live_window.index, live_window.name),
+                index: pane.index,
+                command: pane.current_command.clone(),
+                dead: pane.dead,
+                check,
+            });
+        }
+    }
+    statuses
+}
+
+/// Table of panes; the session and window are only named on their first row
+fn status_table(panes: &[PaneStatus], results: &[Option<Health>], health: bool) -> Table {
+    let mut headers = vec!["SESSION", "WINDOW", "PANE", "COMMAND"];
+    if health {
+        headers.push("HEALTH");
+    }
+    let mut table = Table::new(&headers);
+
+    let mut previous: Option<&PaneStatus> = None;
+    for (pane, result) in panes.iter().zip(results) {
+        let new_session = previous.is_none_or(|p| p.session != pane.session);
+        let new_window = new_session || previous.is_some_and(|p| p.window != pane.window);
+        let session = if new_session { pane.session.as_str() } else { "" };
+        let window = if new_window { pane.window.as_str() } else { "" };
+        let command = if pane.dead {
+            Cell::new(format!("{} (exited)", pane.command), Style::Dim)
+        } else {
+            pane.command.as_str().into()
+        };
+        let badge = match result {
+            Some(Health::Pass) => Cell::new(Health::Pass.badge(), Style::Green),
+            Some(fail) => Cell::new(fail.badge(), Style::Red),
+            None => Cell::default(),
+        };
+        table.row(vec![
+            Cell::new(session, Style::Bold),
+            window.into(),
+            pane.index.to_string().into(),
+            command,
+            badge,
+        ]);
+        previous = Some(pane);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_line() {
+        let summary = Summary {
+            session: "dev".to_string(),
+            state: SummaryState::Ok,
+            windows: 4,
+            panes: 11,
+            drift: 0,
+            clients: 1,
+            computed: 0,
+        };
+        assert_eq!(
+            summary.line(),
+            "dev ok windows=4 panes=11 drift=0 clients=1"
+        );
+        assert_eq!(
+            Summary {
+                state: SummaryState::Drifted,
+                drift: 2,
+                ..summary
+            }
+            .line(),
+            "dev drifted windows=4 panes=11 drift=2 clients=1"
+        );
+    }
+
+    #[test]
+    fn test_status_table() {
+        let pane = |window: &str, index, command: &str| PaneStatus {
+            session: "dev".to_string(),
+            window: window.to_string(),
+            index,
+            command: command.to_string(),
+            dead: false,
+            check: None,
+        };
+        let panes = [
+            pane("0:editor", 0, "nvim"),
+            pane("1:server", 0, "node"),
+            pane("1:server", 1, "zsh"),
+        ];
+        let results = [
+            None,
+            Some(Health::Fail("exit 7".to_string())),
+            Some(Health::Pass),
+        ];
+
+        assert_eq!(
+            status_table(&panes, &results, true).render_with(false),
+            "SESSION  WINDOW    PANE  COMMAND  HEALTH\n\
+             dev      0:editor  0     nvim\n\
+             \x20        1:server  0     node     ✗ fail (exit 7)\n\
+             \x20                  1     zsh      ✓ pass\n"
+        );
+    }
+}
@@ -1,10 +1,28 @@
+use crate::close;
+use crate::commands::default;
+use crate::config::{DetachOnDestroy, Session};
 use crate::context::Context;
 use crate::log;
+use crate::messages;
+use crate::prompt;
+use crate::state::{self, Operation};
 use crate::tmux;
 use anyhow::Result;
 
-pub fn run(session_name: &str, _ctx: &Context) -> Result<()> {
-    log::info(&format!("close command: session_name={}", session_name));
+/// Close a running session.
+///
+/// Without a session name, the current session is closed after asking for
+/// confirmation. Its clients are first switched to another session, so their
+/// terminals aren't left without tmux: the next or previous session in cycling
+/// order with `detach_on_destroy = "next"` or `"previous"`, otherwise the most
+/// recently used one. With `detach_on_destroy = "on"` they detach instead.
+///
+/// # Arguments
+/// * `session_name` - The session to close (None: the current session, inside tmux)
+/// * `force` - Don't ask for confirmation before closing the current session
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_name: Option<&str>, force: bool, ctx: &Context) -> Result<()> {
+    log::info(&format!("close command: session_name={:?}", session_name));
 
     // Check if tmux is installed
     if !tmux::is_installed() {
@@ -12,6 +30,17 @@ pub fn run(session_name: &str, _ctx: &Context) -> Result<()> {
         anyhow::bail!("tmux is not installed");
     }
 
+    let Some(session_name) = session_name else {
+        return close_current(force, ctx);
+    };
+
+    let result = close_named(session_name, ctx);
+    state::record_operation(Operation::Close, session_name, &result);
+    result
+}
+
+/// Close a session by name
+fn close_named(session_name: &str, ctx: &Context) -> Result<()> {
     // Check if session exists
     if !tmux::has_session(session_name)? {
         log::error(&format!("session '{}' does not exist", session_name));
@@ -21,11 +50,182 @@ pub fn run(session_name: &str, _ctx: &Context) -> Result<()> {
         );
     }
 
-    // Kill the session
-    tmux::kill_session(session_name)?;
+    // Only the cycling order is up to tmx; tmux handles the other policies itself
+    let session = configured_session(session_name, ctx);
+    let handover = Handover::from_policy(session.and_then(|(_, s)| s.detach_on_destroy));
+    if matches!(handover, Handover::Cycle(_))
+        && let Some(other) = destination(session_name, &handover, ctx)?
+    {
+        switch_clients(session_name, &other)?;
+    }
+
+    close::close_session(session_name, session)?;
     log::info(&format!("session '{}' stopped", session_name));
 
-    println!("✓ Session '{}' stopped", session_name);
+    messages::say(messages::SESSION_STOPPED, &[("session", &session_name)]);
 
     Ok(())
 }
+
+/// Close the session tmx is running in, moving its clients elsewhere first
+fn close_current(force: bool, ctx: &Context) -> Result<()> {
+    if !ctx.is_inside_tmux {
+        anyhow::bail!(
+            "No session given\n  Hint: Use 'tmx close <session>', or run it inside tmux to close the current session"
+        );
+    }
+    let session_name = tmux::get_current_session()?;
+
+    if !force {
+        let question = format!("Close the current session '{}'?", session_name);
+        if prompt::choose(&question, &[('y', "close"), ('n', "cancel")])? != Some('y') {
+            messages::say(messages::CANCELLED, &[]);
+            return Ok(());
+        }
+    }
+
+    let configured = configured_session(&session_name, ctx);
+    close::run_pre_close(&session_name, configured);
+    close::stop_processes(&session_name, configured)?;
+
+    let handover = Handover::from_policy(configured.and_then(|(_, s)| s.detach_on_destroy));
+    match destination(&session_name, &handover, ctx)? {
+        Some(other) => {
+            switch_clients(&session_name, &other)?;
+            messages::say(messages::SESSION_SWITCHED, &[("session", &other)]);
+        }
+        None if handover == Handover::Detach => {
+            messages::say(messages::SESSION_CLIENT_DETACHES, &[])
+        }
+        None => messages::say(messages::SESSION_CLIENT_EXITS, &[]),
+    }
+
+    // This pane is killed with the session, so report before killing it
+    log::info(&format!("session '{}' stopped", session_name));
+    messages::say(messages::SESSION_STOPPED, &[("session", &session_name)]);
+    state::record_operation(Operation::Close, &session_name, &Ok(()));
+    state::forget_session(&session_name)?;
+    tmux::kill_session(&session_name)
+}
+
+/// Where the clients of a closing session go
+#[derive(Debug, PartialEq)]
+enum Handover {
+    /// They detach
+    Detach,
+    /// They switch to the session this many places along the cycling order
+    Cycle(isize),
+    /// They switch to the most recently used other session
+    LastUsed,
+}
+
+impl Handover {
+    /// The handover a session's `detach_on_destroy` asks for
+    fn from_policy(policy: Option<DetachOnDestroy>) -> Self {
+        match policy {
+            Some(DetachOnDestroy::On) => Self::Detach,
+            Some(DetachOnDestroy::Next) => Self::Cycle(1),
+            Some(DetachOnDestroy::Previous) => Self::Cycle(-1),
+            _ => Self::LastUsed,
+        }
+    }
+}
+
+/// The session to move the clients of a closing session to
+///
+/// # Returns
+/// The other session, or None if the clients detach or no other session is running.
+fn destination(session_name: &str, handover: &Handover, ctx: &Context) -> Result<Option<String>> {
+    match *handover {
+        Handover::Detach => Ok(None),
+        Handover::Cycle(step) => {
+            let ordered = default::order_sessions(&tmux::list_sessions()?, ctx.config().ok());
+            Ok(cycle_neighbor(&ordered, session_name, step))
+        }
+        Handover::LastUsed => tmux::last_attached_session(session_name),
+    }
+}
+
+/// Switch every client of a session to another one
+fn switch_clients(session_name: &str, other: &str) -> Result<()> {
+    for client in tmux::session_clients(session_name)? {
+        tmux::switch_client_of(&client, other)?;
+    }
+    Ok(())
+}
+
+/// The session `step` places after `current` in cycling order, wrapping around
+fn cycle_neighbor(ordered: &[String], current: &str, step: isize) -> Option<String> {
+    let position = ordered.iter().position(|name| name == current)?;
+    if ordered.len() < 2 {
+        return None;
+    }
+    let next = (position as isize + step).rem_euclid(ordered.len() as isize);
+    Some(ordered[next as usize].clone())
+}
+
+/// The config ID and configuration of a running session, if it has one
+pub fn configured_session<'a>(
+    session_name: &str,
+    ctx: &'a Context,
+) -> Option<(&'a str, &'a Session)> {
+    let config = ctx.config().ok()?;
+    if let Some(id) = config.session_id_for(session_name) {
+        return Some((id, &config.sessions[id]));
+    }
+    // Session names are sanitized when the session is created
+    config
+        .sessions
+        .iter()
+        .find(|(_, s)| tmux::sanitize_session_name(&s.name) == session_name)
+        .map(|(id, s)| (id.as_str(), s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_neighbor() {
+        let ordered: Vec<String> = ["api", "dev", "notes"].map(String::from).to_vec();
+        assert_eq!(cycle_neighbor(&ordered, "dev", 1).as_deref(), Some("notes"));
+        assert_eq!(cycle_neighbor(&ordered, "notes", 1).as_deref(), Some("api"));
+        assert_eq!(
+            cycle_neighbor(&ordered, "api", -1).as_deref(),
+            Some("notes")
+        );
+        assert_eq!(cycle_neighbor(&ordered[..1], "api", 1), None);
+        assert_eq!(cycle_neighbor(&ordered, "other", 1), None);
+    }
+
+    #[test]
+    fn test_handover_from_policy() {
+        assert_eq!(Handover::from_policy(None), Handover::LastUsed);
+        assert_eq!(
+            Handover::from_policy(Some(DetachOnDestroy::On)),
+            Handover::Detach
+        );
+        assert_eq!(
+            Handover::from_policy(Some(DetachOnDestroy::Off)),
+            Handover::LastUsed
+        );
+        assert_eq!(
+            Handover::from_policy(Some(DetachOnDestroy::NoDetached)),
+            Handover::LastUsed
+        );
+        assert_eq!(
+            Handover::from_policy(Some(DetachOnDestroy::Next)),
+            Handover::Cycle(1)
+        );
+        assert_eq!(
+            Handover::from_policy(Some(DetachOnDestroy::Previous)),
+            Handover::Cycle(-1)
+        );
+    }
+
+    #[test]
+    fn test_detaching_clients_have_no_destination() {
+        let ctx = Context::new(Some("/nonexistent/tmx.toml".to_string()), false).unwrap();
+        assert_eq!(destination("dev", &Handover::Detach, &ctx).unwrap(), None);
+    }
+}
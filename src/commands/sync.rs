@@ -0,0 +1,209 @@
+use crate::commands::start;
+use crate::config_edit::ConfigEditor;
+use crate::context::Context;
+use crate::diff::{self, Difference};
+use crate::lock::SessionLock;
+use crate::log;
+use crate::messages;
+use crate::prompt;
+use crate::roots::SessionRoots;
+use crate::session;
+use crate::tmux;
+use crate::trust;
+use anyhow::{Context as _, Result};
+
+/// A change to the config file chosen during sync
+enum ConfigChange {
+    RenameWindow { offset: usize, name: String },
+    RemoveWindow { offset: usize },
+    AppendWindow { name: String, pane_count: usize },
+    SetPaneCount { offset: usize, count: usize },
+}
+
+/// Interactively reconcile a running session with its configuration.
+///
+/// Each difference between config and tmux is shown with a choice to update
+/// tmux to match the config, update the config to match tmux, or skip it.
+/// tmux changes are applied immediately; config changes are written together
+/// at the end so comments and formatting are preserved.
+///
+/// # Arguments
+/// * `session_id` - The session name or ID from config
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(session_id: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!("sync command: session_id={}", session_id));
+
+    let config = ctx.config()?;
+    let id = config.session_id_for(session_id).with_context(|| {
+        format!(
+            "Session '{}' is not configured\nAvailable sessions: {}",
+            session_id,
+            config.session_ids().join(", ")
+        )
+    })?;
+    let (session, _) = start::resolve_running_session(id, config)?;
+    let session = &session;
+    let session_name = &session.name;
+
+    let _lock = SessionLock::acquire(session_name)?;
+
+    let Some(live) = tmux::query::live_session(session_name)? else {
+        anyhow::bail!("Session '{}' is not running", session_name);
+    };
+
+    let base_index = tmux::session_base_index(session_name)?;
+    let verbose = ctx.is_verbose();
+    let diffs = diff::diff_session(session, &live, base_index);
+
+    if diffs.is_empty() {
+        messages::say(messages::SYNC_MATCHES, &[("session", &session_name)]);
+        return Ok(());
+    }
+
+    messages::say(
+        messages::SYNC_DIFFERS,
+        &[("session", &session_name), ("count", &diffs.len())],
+    );
+    println!();
+    print!("{}", diff::differences_table(&diffs, base_index).render());
+
+    let roots = SessionRoots::resolve(session)?;
+    let mut changes = Vec::new();
+    let mut windows_to_kill = Vec::new();
+
+    for (i, difference) in diffs.iter().enumerate() {
+        println!();
+        let question = format!("[{}/{}] {}", i + 1, diffs.len(), difference);
+        let choice = prompt::choose(
+            &question,
+            &[('t', "update tmux"), ('c', "update config"), ('s', "skip")],
+        )?;
+
+        match (choice, difference) {
+            (Some('t'), Difference::MissingWindow { offset, .. }) => {
+                session::add_window(session, *offset, base_index + offset, ctx)?;
+            }
+            (Some('t'), Difference::ExtraWindow { index, .. }) => {
+                // Killed last: with renumber-windows on, a kill shifts the windows after it
+                windows_to_kill.push(*index);
+            }
+            (Some('t'), Difference::WindowName { index, config_name, .. }) => {
+                tmux::rename_window(session_name, *index, config_name)?;
+            }
+            (
+                Some('t'),
+                Difference::PaneCount {
+                    offset,
+                    index,
+                    config_count,
+                    live_count,
+                    ..
+                },
+            ) => {
+                let window = &session.windows[*offset];
+                if config_count > live_count {
+                    session::create_window_panes(
+                        session_name,
+                        *index,
+                        window,
+                        &roots.windows[*offset],
+                        *live_count,
+                        false,
+                        None,
+                        session,
+                        verbose,
+                    )?;
+                } else if let Some(live_window) = live.window(*index) {
+                    // Kill from the end so earlier panes keep their indices
+                    for pane in live_window.panes[*config_count..].iter().rev() {
+                        tmux::kill_pane(&pane.id)?;
+                    }
+                }
+                session::apply_window_layout(session_name, *index, window, verbose)?;
+            }
+            (Some('c'), Difference::MissingWindow { offset, .. }) => {
+                changes.push(ConfigChange::RemoveWindow { offset: *offset });
+            }
+            (Some('c'), Difference::ExtraWindow { name, pane_count, .. }) => {
+                changes.push(ConfigChange::AppendWindow {
+                    name: name.clone(),
+                    pane_count: *pane_count,
+                });
+            }
+            (Some('c'), Difference::WindowName { offset, live_name, .. }) => {
+                changes.push(ConfigChange::RenameWindow {
+                    offset: *offset,
+                    name: live_name.clone(),
+                });
+            }
+            (Some('c'), Difference::PaneCount { offset, live_count, .. }) => {
+                changes.push(ConfigChange::SetPaneCount {
+                    offset: *offset,
+                    count: *live_count,
+                });
+            }
+            _ => messages::say(messages::SYNC_SKIPPED, &[]),
+        }
+    }
+
+    // Highest index first, so earlier windows keep their indices
+    windows_to_kill.sort_unstable_by(|a, b| b.cmp(a));
+    for index in windows_to_kill {
+        tmux::kill_window(session_name, index)?;
+    }
+
+    if !changes.is_empty() {
+        write_config_changes(ctx, id, changes)?;
+    }
+
+    println!();
+    messages::say(messages::SESSION_SYNCED, &[("session", &session_name)]);
+    Ok(())
+}
+
+/// Apply config changes in an order that keeps window offsets valid:
+/// in-place edits first, then removals from the end, then appends.
+fn write_config_changes(ctx: &Context, id: &str, mut changes: Vec<ConfigChange>) -> Result<()> {
+    let (path, id) = ctx.session_file(id).with_context(|| {
+        format!(
+            "Session '{}' comes from a shared config repository, which tmx doesn't edit\n  \
+             Hint: Change it in the repository, or only update tmux",
+            id
+        )
+    })?;
+    let mut editor = ConfigEditor::open(path)?;
+
+    changes.sort_by_key(|change| match change {
+        ConfigChange::RenameWindow { .. } | ConfigChange::SetPaneCount { .. } => (0, 0),
+        ConfigChange::RemoveWindow { offset } => (1, usize::MAX - offset),
+        ConfigChange::AppendWindow { .. } => (2, 0),
+    });
+
+    for change in &changes {
+        match change {
+            ConfigChange::RenameWindow { offset, name } => editor.rename_window(&id, *offset, name)?,
+            ConfigChange::RemoveWindow { offset } => editor.remove_window(&id, *offset)?,
+            ConfigChange::AppendWindow { name, pane_count } => {
+                editor.append_window(&id, name, *pane_count)?
+            }
+            ConfigChange::SetPaneCount { offset, count } => {
+                editor.set_pane_count(&id, *offset, *count)?
+            }
+        }
+    }
+
+    editor.save()?;
+    // The file was trusted to be loaded; only window names and pane counts changed
+    if ctx
+        .project_config_path()
+        .is_some_and(|project| project == path)
+    {
+        trust::trust_written(path)?;
+    }
+    log::info(&format!("sync: wrote {} change(s) to config", changes.len()));
+    messages::say(
+        messages::SYNC_WROTE,
+        &[("count", &changes.len()), ("path", &path.display())],
+    );
+    Ok(())
+}
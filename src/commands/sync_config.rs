@@ -0,0 +1,45 @@
+use crate::log;
+use crate::remote;
+use anyhow::Result;
+
+/// Clone or update shared config repositories.
+///
+/// Sessions defined in `.toml` files of the checkouts under
+/// ~/.config/tmx/remote are included in the configuration; sessions in the
+/// local config file take precedence over shared ones with the same ID.
+///
+/// # Arguments
+/// * `url` - Git URL to clone (or pull, if already cloned); None updates all checkouts
+pub fn run(url: Option<&str>) -> Result<()> {
+    log::info(&format!("sync-config command: url={:?}", url));
+
+    let checkouts = match url {
+        Some(url) => vec![remote::clone_or_pull(url)?],
+        None => {
+            let checkouts = remote::checkouts()?;
+            if checkouts.is_empty() {
+                anyhow::bail!(
+                    "No shared config repositories\n  Hint: Add one with 'tmx sync-config <git-url>'"
+                );
+            }
+            for dir in &checkouts {
+                remote::pull(dir)?;
+            }
+            checkouts
+        }
+    };
+
+    for dir in checkouts {
+        let sessions = remote::load_sessions(&dir)?;
+        let ids: Vec<_> = sessions.iter().map(|(id, _)| id.as_str()).collect();
+        println!(
+            "✓ {}: {} session(s){}{}",
+            dir.display(),
+            ids.len(),
+            if ids.is_empty() { "" } else { ": " },
+            ids.join(", ")
+        );
+    }
+
+    Ok(())
+}
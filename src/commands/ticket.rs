@@ -0,0 +1,76 @@
+use crate::cli::AttachMode;
+use crate::commands::start;
+use crate::config;
+use crate::context::Context;
+use crate::git;
+use crate::lock::SessionLock;
+use crate::log;
+use crate::messages;
+use crate::session;
+use crate::tmux;
+use anyhow::{Context as _, Result};
+use std::path::Path;
+
+/// Open the session for a ticket, creating it from the `[ticket]` template.
+///
+/// The session is named and rooted after the ticket. With `worktree` set, a
+/// missing root is created with `git worktree add` on the ticket's branch.
+/// A running session for the ticket is attached to as it is.
+///
+/// # Arguments
+/// * `ticket` - The ticket ID, e.g. `JIRA-1234`
+/// * `attach_mode` - How to hand over to the session (None: decide from context)
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(ticket: &str, attach_mode: Option<AttachMode>, ctx: &Context) -> Result<()> {
+    log::info(&format!("ticket command: ticket={}", ticket));
+
+    if !tmux::is_installed() {
+        log::error("tmux is not installed");
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let config = ctx.config()?;
+    let settings = config.ticket.as_ref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No [ticket] section in the config\n  \
+             Hint: Add e.g.\n\n    \
+             [ticket]\n    \
+             template = \"dev\"\n    \
+             root = \"~/src/app-tickets/{{ticket}}\"\n    \
+             worktree = \"~/src/app\""
+        )
+    })?;
+    let session = settings.session(ticket, config)?;
+    let session_name = session.name.clone();
+
+    let lock = SessionLock::acquire(&session_name)?;
+    if tmux::has_session(&session_name)? {
+        messages::say(
+            messages::SESSION_ATTACHING,
+            &[("session", &tmux::sanitize_session_name(&session_name))],
+        );
+    } else {
+        if let Some(ref repo) = settings.worktree {
+            let root = config::expand_path(&session.root);
+            if !Path::new(&root).exists() {
+                let branch = settings.branch_name(ticket)?;
+                messages::say(messages::TICKET_WORKTREE, &[("root", &root), ("branch", &branch)]);
+                git::add_worktree(Path::new(&config::expand_path(repo)), Path::new(&root), &branch)
+                    .with_context(|| format!("Failed to create the worktree for ticket '{}'", ticket))?;
+            }
+        }
+
+        messages::say(
+            messages::TICKET_CREATING,
+            &[
+                ("session", &tmux::sanitize_session_name(&session_name)),
+                ("template", &settings.template),
+            ],
+        );
+        session::create_session(&session, ctx)?;
+    }
+
+    // Release the lock before attaching, which blocks until the client detaches
+    drop(lock);
+    start::attach_or_switch(&session_name, attach_mode, ctx)
+}
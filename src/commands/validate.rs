@@ -1,11 +1,91 @@
+use crate::cli::OutputFormat;
+use crate::config::{CONFIG_VERSION, Config};
 use crate::context::Context;
+use crate::lint::{self, Lint};
 use anyhow::Result;
+use serde::Serialize;
 
-pub fn run(ctx: &Context) -> Result<()> {
+/// Severity of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+/// A validation error or warning, as emitted by `--format json`
+#[derive(Debug, Serialize)]
+struct Diagnostic {
+    severity: Severity,
+    /// Stable machine-readable identifier
+    code: &'static str,
+    session: Option<String>,
+    workspace: Option<String>,
+    window: Option<String>,
+    /// 0-based pane index within the window
+    pane: Option<usize>,
+    field: Option<&'static str>,
+    message: String,
+    hint: Option<String>,
+}
+
+impl Diagnostic {
+    /// An error from an error message, with its `Hint:` line split off
+    fn error(code: &'static str, message: String) -> Self {
+        let (message, hint) = match message.split_once("\n  Hint: ") {
+            Some((message, hint)) => (message.to_string(), Some(hint.to_string())),
+            None => (message, None),
+        };
+        Self {
+            severity: Severity::Error,
+            code,
+            session: None,
+            workspace: None,
+            window: None,
+            pane: None,
+            field: None,
+            message,
+            hint,
+        }
+    }
+}
+
+impl From<Lint> for Diagnostic {
+    fn from(lint: Lint) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code: lint.code,
+            session: Some(lint.session),
+            workspace: None,
+            window: lint.window,
+            pane: lint.pane,
+            field: Some(lint.field),
+            message: lint.message,
+            hint: Some(lint.hint),
+        }
+    }
+}
+
+/// Validate the config.
+///
+/// # Arguments
+/// * `format` - `text` for people, `json` for a list of errors and warnings
+///   that tools such as pre-commit hooks and editors can consume
+/// * `strict` - Treat paths with undefined environment variables or unknown
+///   users as errors instead of warnings
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(format: OutputFormat, strict: bool, ctx: &Context) -> Result<()> {
+    match format {
+        OutputFormat::Text => run_text(strict, ctx),
+        OutputFormat::Json => run_json(strict, ctx),
+    }
+}
+
+fn run_text(strict: bool, ctx: &Context) -> Result<()> {
     // Get config from context (lazy-loaded)
     let config = ctx.config()?;
 
-    let mut has_warnings = false;
+    let mut warnings = Vec::new();
 
     // Validate each session
     for (id, session) in &config.sessions {
@@ -16,22 +96,51 @@ pub fn run(ctx: &Context) -> Result<()> {
         }
 
         // Check for warnings: layout specified with custom pane sizes
-        for window in &session.windows {
-            if window.layout.is_some() && window.panes.iter().any(|p| p.size.is_some()) {
-                if !has_warnings {
-                    println!();
-                    println!("⚠ Warnings:");
-                    has_warnings = true;
-                }
-                println!(
-                    "  Session '{}', window '{}': both layout and pane sizes specified - sizes will override layout",
-                    id, window.name
-                );
-            }
+        warnings.extend(lint::validate_warnings(id, session));
+
+        let path_warnings = lint::path_warnings(id, session);
+        if strict && let Some(first) = path_warnings.first() {
+            eprintln!("✗ Validation failed for session '{}':\n", id);
+            eprintln!("{}: {}\n  Hint: {}", first.location(), first.message, first.hint);
+            std::process::exit(1);
+        }
+        warnings.extend(path_warnings);
+    }
+
+    for (name, workspace) in &config.workspaces {
+        if let Err(e) = workspace.validate(name, config) {
+            eprintln!("✗ Validation failed for workspace '{}':\n", name);
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = config.validate_default() {
+        eprintln!("✗ Validation failed for default:\n");
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(ref ticket) = config.ticket
+        && let Err(e) = ticket.validate(config)
+    {
+        eprintln!("✗ Validation failed for [ticket]:\n");
+        eprintln!("{:#}", e);
+        std::process::exit(1);
+    }
+
+    if !warnings.is_empty() {
+        println!();
+        println!("⚠ Warnings:");
+        for warning in &warnings {
+            println!("  {}: {}", warning.location(), warning.message);
         }
+        println!();
     }
 
-    if has_warnings {
+    if let Some(outdated) = outdated_version(config) {
+        println!("⚠ {}", outdated.message);
+        println!("  Hint: {}", outdated.hint.unwrap_or_default());
         println!();
     }
 
@@ -40,3 +149,174 @@ pub fn run(ctx: &Context) -> Result<()> {
 
     Ok(())
 }
+
+/// Print all errors and warnings as a JSON array, exiting with status 1 if
+/// there are errors
+fn run_json(strict: bool, ctx: &Context) -> Result<()> {
+    let diagnostics = match ctx.config() {
+        Ok(config) => diagnose(config, strict),
+        Err(e) => vec![Diagnostic::error("invalid-config", format!("{:#}", e))],
+    };
+
+    println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Collect every validation error and warning in the config.
+///
+/// Errors are reported per window where possible, so one broken window
+/// doesn't hide problems elsewhere. With `strict`, unexpandable paths are
+/// errors.
+fn diagnose(config: &Config, strict: bool) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = outdated_version(config).into_iter().collect();
+
+    for id in config.session_ids() {
+        let session = &config.sessions[&id];
+        let mut window_errors = false;
+        for window in &session.windows {
+            if let Err(e) = window.validate() {
+                window_errors = true;
+                diagnostics.push(Diagnostic {
+                    session: Some(id.clone()),
+                    window: Some(window.name.clone()),
+                    ..Diagnostic::error("invalid-window", e.to_string())
+                });
+            }
+        }
+
+        // Window errors are already reported; otherwise this finds session-level errors
+        if !window_errors && let Err(e) = session.validate() {
+            diagnostics.push(Diagnostic {
+                session: Some(id.clone()),
+                ..Diagnostic::error("invalid-session", e.to_string())
+            });
+        }
+
+        diagnostics.extend(
+            lint::validate_warnings(&id, session)
+                .into_iter()
+                .map(Diagnostic::from),
+        );
+
+        let path_severity = if strict {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        diagnostics.extend(lint::path_warnings(&id, session).into_iter().map(|lint| {
+            Diagnostic {
+                severity: path_severity,
+                ..Diagnostic::from(lint)
+            }
+        }));
+    }
+
+    let mut workspaces: Vec<_> = config.workspaces.iter().collect();
+    workspaces.sort_unstable_by_key(|(name, _)| *name);
+    for (name, workspace) in workspaces {
+        if let Err(e) = workspace.validate(name, config) {
+            diagnostics.push(Diagnostic {
+                workspace: Some(name.clone()),
+                field: Some("sessions"),
+                ..Diagnostic::error("invalid-workspace", e.to_string())
+            });
+        }
+    }
+
+    if let Err(e) = config.validate_default() {
+        diagnostics.push(Diagnostic {
+            field: Some("default"),
+            ..Diagnostic::error("invalid-default", e.to_string())
+        });
+    }
+
+    if let Some(ref ticket) = config.ticket
+        && let Err(e) = ticket.validate(config)
+    {
+        diagnostics.push(Diagnostic {
+            field: Some("ticket"),
+            ..Diagnostic::error("invalid-ticket", format!("{:#}", e))
+        });
+    }
+
+    diagnostics
+}
+
+/// Warning for a config file in an older format version
+fn outdated_version(config: &Config) -> Option<Diagnostic> {
+    let version = config.format_version();
+    (version < CONFIG_VERSION).then(|| Diagnostic {
+        severity: Severity::Warning,
+        field: Some("version"),
+        hint: Some("Run 'tmx migrate' to upgrade it (the current file is backed up)".to_string()),
+        ..Diagnostic::error(
+            "outdated-version",
+            format!(
+                "Config format version {} is older than the current version {}",
+                version, CONFIG_VERSION
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        let config: Config = toml::from_str(
+            r#"
+version = 2
+default = { "*" = "dev", "~/work" = "work" }
+
+[sessions.dev]
+name = "dev"
+
+[[sessions.dev.windows]]
+name = "editor"
+layout = "sideways"
+panes = [{ command = "" }]
+
+[[sessions.dev.windows]]
+name = "logs"
+layout = "tiled"
+panes = [{ command = "" }, { command = "", size = "30%" }]
+
+[workspaces.all]
+sessions = ["dev", "api"]
+"#,
+        )
+        .unwrap();
+
+        let diagnostics = diagnose(&config, false);
+        let summary: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code, d.window.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, "invalid-window", Some("editor")),
+                (Severity::Warning, "layout-with-sizes", Some("logs")),
+                (Severity::Error, "invalid-workspace", None),
+                (Severity::Error, "invalid-default", None),
+            ]
+        );
+
+        let json = serde_json::to_value(&diagnostics[0]).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["session"], "dev");
+        assert_eq!(json["pane"], serde_json::Value::Null);
+        assert!(
+            json["hint"]
+                .as_str()
+                .unwrap()
+                .starts_with("Use 'even-horizontal'")
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::commands::server;
+use crate::context::Context;
+use crate::log;
+use crate::tmux;
+use anyhow::Result;
+
+/// Git commit tmx was built from (set by build.rs)
+const GIT_COMMIT: &str = env!("TMX_GIT_COMMIT");
+
+/// Build time in seconds since the Unix epoch (set by build.rs)
+const BUILD_EPOCH: &str = env!("TMX_BUILD_EPOCH");
+
+/// Target triple tmx was built for (set by build.rs)
+const BUILD_TARGET: &str = env!("TMX_BUILD_TARGET");
+
+/// Print version information.
+///
+/// The verbose form lists everything useful in a bug report: build details,
+/// the tmux found at runtime, and the config and log files in use.
+///
+/// # Arguments
+/// * `verbose` - Include build, tmux and path details
+/// * `ctx` - Shared context containing configuration and state
+pub fn run(verbose: bool, ctx: &Context) -> Result<()> {
+    let version = env!("CARGO_PKG_VERSION");
+
+    if !verbose {
+        println!("tmx {} ({})", version, GIT_COMMIT);
+        return Ok(());
+    }
+
+    let config_path = ctx.config_path();
+    let config_status = if config_path.exists() { "" } else { " (not found)" };
+    let log_path = log::path().map_or_else(
+        || "none (logging to stderr)".to_string(),
+        |p| p.display().to_string(),
+    );
+
+    println!("tmx:     {}", version);
+    println!("commit:  {}", GIT_COMMIT);
+    println!("built:   {}", build_date());
+    println!("target:  {}", BUILD_TARGET);
+    println!("tmux:    {}", tmux::version().unwrap_or_else(|| "not found".to_string()));
+    println!("server:  {}", server::summary());
+    println!("config:  {}{}", config_path.display(), config_status);
+    if let Some(project) = ctx.project_config_path() {
+        println!("project: {}", project.display());
+    }
+    println!("log:     {}", log_path);
+
+    Ok(())
+}
+
+/// Format the build time as `YYYY-MM-DD HH:MM:SS UTC`
+fn build_date() -> String {
+    let Ok(secs) = BUILD_EPOCH.parse::<u64>() else {
+        return "unknown".to_string();
+    };
+    let (year, month, day, hours, minutes, seconds) = log::utc_datetime(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, hours, minutes, seconds
+    )
+}
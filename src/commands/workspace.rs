@@ -0,0 +1,103 @@
+use crate::cli::AttachMode;
+use crate::close;
+use crate::commands::start;
+use crate::context::Context;
+use crate::log;
+use crate::messages;
+use crate::state::{self, Operation};
+use crate::tmux;
+use anyhow::Result;
+
+/// Start every session of a workspace and attach to its primary session.
+///
+/// Sessions are created detached in the order listed; sessions that are
+/// already running are kept as they are (subject to their `on_conflict`
+/// policy).
+///
+/// # Arguments
+/// * `name` - Workspace name from the `[workspaces]` table
+/// * `attach_mode` - How to hand over to the primary session (None: decide from context)
+/// * `ctx` - Shared context containing configuration and state
+pub fn open(name: &str, attach_mode: Option<AttachMode>, ctx: &Context) -> Result<()> {
+    log::info(&format!("workspace open command: name={}", name));
+
+    if !tmux::is_installed() {
+        log::error("tmux is not installed");
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let config = ctx.config()?;
+    let workspace = config.get_workspace(name)?;
+    workspace.validate(name, config)?;
+
+    let mut attach_target = None;
+    for session_id in &workspace.sessions {
+        let lock = start::lock_session(session_id, ctx)?;
+        let target = start::prepare_session(session_id, false, ctx);
+        drop(lock);
+        state::record_operation(
+            Operation::Open,
+            target.as_deref().unwrap_or(session_id),
+            &target,
+        );
+        let target = target?;
+
+        if session_id == workspace.attach_session() {
+            attach_target = Some(target);
+        }
+    }
+
+    messages::say(
+        messages::WORKSPACE_RUNNING,
+        &[
+            ("workspace", &name),
+            ("sessions", &workspace.sessions.len()),
+        ],
+    );
+
+    match attach_target {
+        Some(target) => start::attach_or_switch(&target, attach_mode, ctx),
+        None => Ok(()),
+    }
+}
+
+/// Kill every running session of a workspace.
+///
+/// # Arguments
+/// * `name` - Workspace name from the `[workspaces]` table
+/// * `ctx` - Shared context containing configuration and state
+pub fn close(name: &str, ctx: &Context) -> Result<()> {
+    log::info(&format!("workspace close command: name={}", name));
+
+    if !tmux::is_installed() {
+        log::error("tmux is not installed");
+        anyhow::bail!("tmux is not installed");
+    }
+
+    let config = ctx.config()?;
+    let workspace = config.get_workspace(name)?;
+
+    let mut closed = 0;
+    for session_id in &workspace.sessions {
+        let session = config.get_session(session_id);
+        let session_name = session.map_or(session_id.as_str(), |s| s.name.as_str());
+
+        if !tmux::has_session(session_name)? {
+            log::info(&format!("workspace session '{}' is not running", session_name));
+            continue;
+        }
+
+        let result = close::close_session(session_name, session.map(|s| (session_id.as_str(), s)));
+        state::record_operation(Operation::Close, session_name, &result);
+        result?;
+        log::info(&format!("session '{}' stopped", session_name));
+        messages::say(messages::SESSION_STOPPED, &[("session", &session_name)]);
+        closed += 1;
+    }
+
+    if closed == 0 {
+        messages::say(messages::WORKSPACE_NOT_RUNNING, &[("workspace", &name)]);
+    }
+
+    Ok(())
+}
@@ -1,15 +1,343 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+use crate::git;
+use crate::layout;
+use crate::overrides::Override;
+use crate::placeholders;
+use crate::remote;
+
+/// Current version of the config file format (`version = N`)
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Source name of the sessions defined in the config file itself
+pub const CONFIG_SOURCE: &str = "global";
+
+/// File name of a project's own config, found in the current directory or
+/// one of its parents
+pub const PROJECT_CONFIG_FILE: &str = ".tmx.toml";
+
+/// Source name of the sessions defined in a project's `.tmx.toml`
+pub const PROJECT_SOURCE: &str = "project";
 
 /// Main configuration structure
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Config file format version (files without one are version 1)
+    #[serde(default)]
+    pub version: Option<u32>,
+    #[serde(default)]
     pub sessions: HashMap<String, Session>,
     #[serde(default)]
-    pub default: Option<String>,
+    pub default: Option<DefaultSession>,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub tmux: TmuxConfig,
+    #[serde(default)]
+    pub gc: GcConfig,
+    #[serde(default)]
+    pub workspaces: HashMap<String, Workspace>,
+    /// Terminal emulator command that runs a program in a new window
+    /// (e.g. `wezterm start --`); the attach command line is appended
+    #[serde(default)]
+    pub terminal_command: Option<String>,
+    /// Named layouts that windows refer to as `layout = "@name"`: a tmux
+    /// preset or a custom layout string
+    #[serde(default)]
+    pub layouts: HashMap<String, String>,
+    /// How `tmx ticket` builds a session for a ticket
+    #[serde(default)]
+    pub ticket: Option<TicketConfig>,
+    /// Where sessions that aren't from the config file come from, by ID
+    /// (see `include_sessions`)
+    #[serde(skip)]
+    pub session_sources: HashMap<String, String>,
+    /// Values for session `params`, given as `--set <param>=<value>`
+    #[serde(skip)]
+    pub params: HashMap<String, String>,
+}
+
+/// Keys of the top level of the config file, which `--set <key>=<value>`
+/// sets rather than naming a session parameter
+pub const TOP_LEVEL_KEYS: &[&str] = &[
+    "version",
+    "sessions",
+    "default",
+    "log",
+    "tmux",
+    "gc",
+    "workspaces",
+    "terminal_command",
+    "layouts",
+    "ticket",
+];
+
+/// Session-per-ticket settings (`[ticket]`).
+///
+/// `name`, `root` and `branch` accept the `{ticket}`, `{ticket_lower}` and
+/// `{hostname}` placeholders.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TicketConfig {
+    /// Session whose windows and panes are used for ticket sessions
+    pub template: String,
+    /// Session name
+    #[serde(default = "default_ticket_placeholder")]
+    pub name: String,
+    /// Root directory of the ticket's session, e.g. `~/src/app-tickets/{ticket}`
+    pub root: String,
+    /// Git repository to create `root` from with `git worktree add`
+    #[serde(default)]
+    pub worktree: Option<String>,
+    /// Branch checked out in the worktree (created if it doesn't exist)
+    #[serde(default = "default_ticket_placeholder")]
+    pub branch: String,
+}
+
+fn default_ticket_placeholder() -> String {
+    "{ticket}".to_string()
+}
+
+impl TicketConfig {
+    /// Build the session for a ticket from the template session.
+    ///
+    /// # Arguments
+    /// * `ticket` - The ticket ID, e.g. `JIRA-1234`
+    /// * `config` - The configuration containing the template session
+    pub fn session(&self, ticket: &str, config: &Config) -> Result<Session> {
+        validate_ticket_id(ticket)?;
+        let template = self.template_session(config)?;
+
+        let mut session = template.clone();
+        session.name = placeholders::expand_ticket(&self.name, ticket).context("Invalid [ticket] name")?;
+        session.root = placeholders::expand_ticket(&self.root, ticket).context("Invalid [ticket] root")?;
+        session.expand_window_names()?;
+        Ok(session)
+    }
+
+    /// The branch checked out in a ticket's worktree
+    pub fn branch_name(&self, ticket: &str) -> Result<String> {
+        placeholders::expand_ticket(&self.branch, ticket).context("Invalid [ticket] branch")
+    }
+
+    /// Validate the ticket settings against the configured sessions
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        self.template_session(config)?;
+        for (field, template) in [("name", &self.name), ("root", &self.root), ("branch", &self.branch)] {
+            placeholders::expand_ticket(template, "TICKET-1")
+                .with_context(|| format!("Invalid [ticket] {}", field))?;
+        }
+        Ok(())
+    }
+
+    fn template_session<'a>(&self, config: &'a Config) -> Result<&'a Session> {
+        config.get_session(&self.template).ok_or_else(|| {
+            anyhow::anyhow!(
+                "[ticket] template '{}' is not a configured session\n  Available sessions: {}",
+                self.template,
+                config.session_ids().join(", ")
+            )
+        })
+    }
+}
+
+/// Check that a ticket ID is safe to use in session names, paths and branches
+fn validate_ticket_id(ticket: &str) -> Result<()> {
+    let valid = !ticket.is_empty()
+        && !ticket.starts_with(['.', '-'])
+        && ticket
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        anyhow::bail!(
+            "Invalid ticket ID '{}'\n  \
+             Hint: Use letters, digits, '-', '_' and '.', e.g. JIRA-1234",
+            ticket
+        );
+    }
+    Ok(())
+}
+
+/// Session started when tmx runs without a session: one session ID, or a map
+/// from directory prefixes to session IDs (`"*"` matches any directory)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum DefaultSession {
+    One(String),
+    ByDirectory(HashMap<String, String>),
+}
+
+/// Key of a `default` map entry that matches any directory
+pub const ANY_DIRECTORY: &str = "*";
+
+impl DefaultSession {
+    /// Get the default session ID for a directory.
+    ///
+    /// With a map, the entry with the longest directory prefix containing
+    /// `dir` wins (prefixes match whole path components, `~` is expanded),
+    /// falling back to the `"*"` entry.
+    pub fn for_directory(&self, dir: &Path) -> Option<&str> {
+        let map = match self {
+            DefaultSession::One(id) => return Some(id),
+            DefaultSession::ByDirectory(map) => map,
+        };
+
+        map.iter()
+            .filter(|(prefix, _)| prefix.as_str() != ANY_DIRECTORY)
+            .map(|(prefix, id)| (PathBuf::from(expand_path(prefix)), id))
+            .filter(|(prefix, _)| dir.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.components().count())
+            .map(|(_, id)| id.as_str())
+            .or_else(|| map.get(ANY_DIRECTORY).map(String::as_str))
+    }
+
+    /// All session IDs this default can resolve to
+    pub fn session_ids(&self) -> Vec<&str> {
+        match self {
+            DefaultSession::One(id) => vec![id.as_str()],
+            DefaultSession::ByDirectory(map) => map.values().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// A set of sessions opened and closed together (`tmx workspace`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Workspace {
+    /// Session names or IDs, started in this order
+    pub sessions: Vec<String>,
+    /// Session to attach to once all are running (default: the first)
+    #[serde(default)]
+    pub attach: Option<String>,
+}
+
+impl Workspace {
+    /// Get the session to attach to after opening the workspace
+    pub fn attach_session(&self) -> &str {
+        self.attach
+            .as_deref()
+            .or(self.sessions.first().map(String::as_str))
+            .unwrap_or_default()
+    }
+
+    /// Validate the workspace against the configured sessions
+    pub fn validate(&self, name: &str, config: &Config) -> Result<()> {
+        if self.sessions.is_empty() {
+            anyhow::bail!("Workspace '{}' must list at least one session", name);
+        }
+
+        for session in &self.sessions {
+            if config.get_session(session).is_none() {
+                anyhow::bail!(
+                    "Workspace '{}' refers to unknown session '{}'\n  Available sessions: {}",
+                    name,
+                    session,
+                    config.session_ids().join(", ")
+                );
+            }
+        }
+
+        if let Some(ref attach) = self.attach
+            && !self.sessions.contains(attach)
+        {
+            anyhow::bail!(
+                "Workspace '{}' attaches to '{}', which is not one of its sessions\n  \
+                 Hint: Add it to sessions or choose one of: {}",
+                name,
+                attach,
+                self.sessions.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Settings for how tmx talks to tmux
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TmuxConfig {
+    /// Retries for transient tmux failures (server starting up, lost server)
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for each retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// On Windows, run tmux inside WSL through `wsl.exe` when there is no native tmux
+    #[serde(default)]
+    pub wsl: bool,
+    /// Run sessions on a dedicated tmux server (`tmux -L <name>`)
+    #[serde(default)]
+    pub socket_name: Option<String>,
+    /// Send the commands that build a session through one control-mode
+    /// client (`tmux -C`) instead of starting tmux for each
+    #[serde(default = "default_true")]
+    pub control_mode: bool,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            retries: default_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            wsl: false,
+            socket_name: None,
+            control_mode: true,
+        }
+    }
+}
+
+/// Logging configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LogConfig {
+    #[serde(default = "default_log_targets")]
+    pub targets: Vec<LogTarget>,
+    /// Size in KB the log file is kept under by `tmx gc` (0: no limit)
+    #[serde(default = "default_log_max_size_kb")]
+    pub max_size_kb: u64,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            targets: default_log_targets(),
+            max_size_kb: default_log_max_size_kb(),
+        }
+    }
+}
+
+/// Cleanup of stale state and old files (`[gc]`, see `tmx gc`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GcConfig {
+    /// Clean up at most once a day when tmx starts
+    #[serde(default = "default_true")]
+    pub auto: bool,
+    /// Days config backups are kept (0: forever)
+    #[serde(default = "default_backup_retention_days")]
+    pub backup_retention_days: u64,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            backup_retention_days: default_backup_retention_days(),
+        }
+    }
+}
+
+/// Destination for log records
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogTarget {
+    /// Append to ~/.cache/tmx/tmx.log
+    File,
+    /// Send to the local syslog daemon
+    Syslog,
+    /// Send to the systemd journal
+    Journald,
 }
 
 /// Startup window specification (by name or index)
@@ -26,11 +354,261 @@ pub struct Session {
     pub name: String,
     #[serde(default = "default_root")]
     pub root: String,
+    #[serde(default)]
     pub windows: Vec<Window>,
     #[serde(default)]
     pub startup_window: Option<StartupWindow>,
     #[serde(default)]
     pub startup_pane: Option<usize>,
+    #[serde(default)]
+    pub on_conflict: OnConflict,
+    /// What to do when a session, window or pane root doesn't exist
+    #[serde(default)]
+    pub missing_root: MissingRoot,
+    /// Variables set in the session environment, so every pane (including
+    /// panes opened later) starts with them; a pane's `env` and env files
+    /// take precedence
+    #[serde(default)]
+    pub env: HashMap<String, EnvValue>,
+    /// Dotenv file(s) applied to every pane, relative to the session root
+    #[serde(default)]
+    pub env_file: Option<EnvFile>,
+    /// What to do when an env file doesn't exist
+    #[serde(default)]
+    pub env_file_missing: EnvFileMissing,
+    /// Command the panes start with instead of a login shell (tmux
+    /// `default-command`), e.g. `exec zsh`
+    #[serde(default)]
+    pub default_command: Option<String>,
+    /// Start the panes' shells as login shells (default: true); false skips
+    /// the login profile, e.g. `~/.zprofile`
+    #[serde(default)]
+    pub login_shell: Option<bool>,
+    /// Give the session its own shell history file (HISTFILE)
+    #[serde(default)]
+    pub isolate_history: bool,
+    /// Free-form labels shown by `tmx list`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How windows are sized when several clients are attached (tmux `window-size`)
+    #[serde(default)]
+    pub window_size: Option<WindowSize>,
+    /// Size windows for the clients looking at them rather than all attached
+    /// clients (tmux `aggressive-resize`)
+    #[serde(default)]
+    pub aggressive_resize: Option<bool>,
+    /// Kill the session when its last client detaches (tmux `destroy-unattached`)
+    #[serde(default)]
+    pub destroy_unattached: Option<bool>,
+    /// Where the session's clients go when it is closed (tmux `detach-on-destroy`)
+    #[serde(default)]
+    pub detach_on_destroy: Option<DetachOnDestroy>,
+    /// Other tmux session options set when the session is created
+    /// (`set-option`), e.g. `status-left` or `@user` options
+    #[serde(default)]
+    pub options: BTreeMap<String, OptionValue>,
+    /// Command typed into a window each time a client attaches
+    #[serde(default)]
+    pub on_attach_send: Option<AttachSend>,
+    /// How the session's processes are stopped when it is closed
+    #[serde(default)]
+    pub close_strategy: CloseStrategy,
+    /// Shell command run in the session root when closing gracefully, after
+    /// interrupting the panes (e.g. `docker compose down`)
+    #[serde(default)]
+    pub shutdown_command: Option<String>,
+    /// Seconds to wait for processes to exit when closing gracefully
+    /// (default: 10)
+    #[serde(default)]
+    pub close_timeout: Option<u64>,
+    /// Shell command run when the session closes other than through tmx
+    /// (e.g. `tmux kill-session`); `{name}` is the session name
+    #[serde(default)]
+    pub on_died: Option<String>,
+    /// Variables copied from tmx's environment into the session environment
+    /// when it is opened; `*` matches any characters (e.g. `AWS_*`)
+    #[serde(default)]
+    pub pass_env: Vec<String>,
+    /// Most panes of the session running their `setup` at once; the others
+    /// are queued until a batch finishes (default: no limit). Only setup is
+    /// batched, as main commands such as servers may never finish
+    #[serde(default)]
+    pub setup_concurrency: Option<usize>,
+    /// Parameters that must be given with `--set <param>=<value>` to open
+    /// the session; `{param}` in roots and commands is replaced by the value
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// Values `params` were filled in with (set by `with_params`)
+    #[serde(skip)]
+    pub param_values: BTreeMap<String, String>,
+    /// Shell commands run in the session root before the session is created;
+    /// if one fails, the session isn't created
+    #[serde(default)]
+    pub on_create: Vec<String>,
+    /// Shell commands run in each window's root before the window is created
+    #[serde(default)]
+    pub pre_window: Vec<String>,
+    /// Shell commands run in the session root once the session is created
+    #[serde(default)]
+    pub post_create: Vec<String>,
+    /// Shell commands run in the session root before `tmx close` stops the session
+    #[serde(default)]
+    pub pre_close: Vec<String>,
+    /// Session whose windows this session shares (tmux session groups,
+    /// `new-session -t`): a configured session ID or a running session's
+    /// name. The session has no windows of its own, but its own current window
+    #[serde(default)]
+    pub group_with: Option<String>,
+}
+
+/// A command sent to a window whenever a client attaches to the session:
+/// `on_attach_send = { window = "editor", command = "git fetch" }`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct AttachSend {
+    /// Window name (default: the session's first window)
+    #[serde(default)]
+    pub window: Option<String>,
+    pub command: String,
+}
+
+/// Value of tmux's `window-size` option
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowSize {
+    /// The size of the largest attached client
+    Largest,
+    /// The size of the smallest attached client
+    Smallest,
+    /// The size of the client that was most recently active
+    Latest,
+    /// Keep the size set with `resize-window`
+    Manual,
+}
+
+impl WindowSize {
+    /// The option value passed to tmux
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WindowSize::Largest => "largest",
+            WindowSize::Smallest => "smallest",
+            WindowSize::Latest => "latest",
+            WindowSize::Manual => "manual",
+        }
+    }
+}
+
+/// Value of tmux's `detach-on-destroy` option
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DetachOnDestroy {
+    /// Detach the clients
+    On,
+    /// Switch the clients to the most recently active session
+    Off,
+    /// Switch the clients to the most recently active session that has no
+    /// clients of its own
+    NoDetached,
+    /// Switch the clients to the previous session (in `tmx` cycling order
+    /// when closed with `tmx close`)
+    Previous,
+    /// Switch the clients to the next session (in `tmx` cycling order when
+    /// closed with `tmx close`)
+    Next,
+}
+
+impl DetachOnDestroy {
+    /// The option value passed to tmux
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DetachOnDestroy::On => "on",
+            DetachOnDestroy::Off => "off",
+            DetachOnDestroy::NoDetached => "no-detached",
+            DetachOnDestroy::Previous => "previous",
+            DetachOnDestroy::Next => "next",
+        }
+    }
+}
+
+/// One or more dotenv files: `env_file = ".env"` or `env_file = [".env", ".env.local"]`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EnvFile {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl EnvFile {
+    /// The configured file paths, in precedence order (later files win)
+    pub fn paths(&self) -> &[String] {
+        match self {
+            EnvFile::One(path) => std::slice::from_ref(path),
+            EnvFile::Many(paths) => paths,
+        }
+    }
+}
+
+/// How a session's processes are stopped when it is closed
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseStrategy {
+    /// Kill the session right away
+    #[default]
+    Kill,
+    /// Send C-c to every pane (and run `shutdown_command`), wait for the
+    /// processes to exit, then kill the session
+    Graceful,
+}
+
+/// How to handle a root directory that doesn't exist
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingRoot {
+    /// Refuse to create the session
+    #[default]
+    Error,
+    /// Create the directory (and its parents)
+    Create,
+    /// Print a warning and start in the home directory instead
+    Home,
+}
+
+/// How to handle an `env_file` that doesn't exist
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvFileMissing {
+    /// Refuse to create the session
+    #[default]
+    Error,
+    /// Print a warning and skip the file
+    Warn,
+    /// Skip the file silently
+    Ignore,
+}
+
+/// What to do when opening a session whose name is taken by a running
+/// session that tmx didn't create
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Attach to the running session
+    #[default]
+    Attach,
+    /// Refuse to open the session
+    Error,
+    /// Kill the running session and create it from config
+    Recreate,
+    /// Create the session under the next free name (`dev-2`, `dev-3`, ...)
+    Rename,
+}
+
+/// What to close when a window's first pane command exits
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseOnExit {
+    /// Close the window
+    Window,
+    /// Kill the whole session
+    Session,
 }
 
 /// Window configuration
@@ -42,6 +620,26 @@ pub struct Window {
     pub layout: Option<String>,
     #[serde(default)]
     pub root: Option<String>,
+    /// Environment of the window's panes; a pane's `env` takes precedence
+    #[serde(default)]
+    pub env: HashMap<String, EnvValue>,
+    /// Dotenv file(s) applied to the window's panes, relative to the window root
+    #[serde(default)]
+    pub env_file: Option<EnvFile>,
+    /// Close the window or the session when the first pane's command exits
+    #[serde(default)]
+    pub close_on_exit: Option<CloseOnExit>,
+    /// tmux window options set on the window (`set-option -w`), e.g.
+    /// `automatic-rename` or `monitor-activity`
+    #[serde(default)]
+    pub options: BTreeMap<String, OptionValue>,
+    /// Shell commands run in the window's root before it is created, after
+    /// the session's `pre_window`
+    #[serde(default)]
+    pub pre_window: Vec<String>,
+    /// `name` as written in the config, before placeholders were expanded
+    #[serde(skip)]
+    pub name_template: Option<String>,
 }
 
 /// Pane configuration
@@ -50,19 +648,198 @@ pub struct Pane {
     #[serde(default)]
     pub command: String,
     #[serde(default)]
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, EnvValue>,
+    /// Dotenv file(s) for this pane, relative to the pane root; `env` takes precedence
+    #[serde(default)]
+    pub env_file: Option<EnvFile>,
+    /// Start the pane's shell as a login shell (overrides the session's `login_shell`)
+    #[serde(default)]
+    pub login_shell: Option<bool>,
+    /// Press Enter after the command (false leaves it typed but not submitted)
+    #[serde(default = "default_true")]
+    pub send_enter: bool,
+    /// Type the command literally (send-keys -l) instead of interpreting key names
+    #[serde(default)]
+    pub literal: bool,
+    /// Show a desktop notification when `command` exits (the pane's shell exits with it)
+    #[serde(default)]
+    pub notify_on_exit: bool,
+    /// Run `command` as the pane's process instead of typing it into a shell;
+    /// the pane closes when the command exits
+    #[serde(default)]
+    pub exec: bool,
+    /// Commands run (and waited for) before `command`, e.g. `nvm use` or `make deps`
+    #[serde(default)]
+    pub setup: Vec<String>,
+    /// Seconds to wait for `setup` before moving on
+    #[serde(default = "default_setup_timeout")]
+    pub setup_timeout: u64,
     #[serde(default)]
     pub root: Option<String>,
     #[serde(default)]
     pub split: Option<String>,
     #[serde(default)]
     pub size: Option<String>,
+    /// tmux options set on the pane (`set-option -p`, tmux 3.0+), e.g.
+    /// `remain-on-exit` or `@user` options read by status-line scripts
+    #[serde(default)]
+    pub options: BTreeMap<String, OptionValue>,
+    /// Pane style (as set by `select-pane -P`), e.g. `bg=colour52` to mark panes
+    /// pointing at production
+    #[serde(default)]
+    pub style: Option<String>,
+    /// Name of the pane, shown in the pane border (`select-pane -T`) and used
+    /// by other panes in the window to refer to it with `after`
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Title of a pane in the same window whose setup and command are sent
+    /// before this pane's; panes are still created in order, so they keep
+    /// their place in the layout
+    #[serde(default)]
+    pub after: Option<String>,
+    /// Shell command that exits with status 0 while the pane's service is
+    /// healthy, run by `tmx status --health` (e.g. `curl -sf localhost:8080/health`)
+    #[serde(default)]
+    pub healthcheck: Option<String>,
+}
+
+/// Value of a tmux option; booleans are set as `on`/`off`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum OptionValue {
+    Flag(bool),
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionValue::Flag(true) => write!(f, "on"),
+            OptionValue::Flag(false) => write!(f, "off"),
+            OptionValue::Number(n) => write!(f, "{}", n),
+            OptionValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Prefix of env values looked up in the OS keyring
+pub const KEYRING_SCHEME: &str = "keyring:";
+
+/// Value of an environment variable set in a pane.
+///
+/// Encrypted values are decrypted when the session is created, so secrets
+/// never have to be stored in plaintext in the config file.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    /// Plaintext value, or a `keyring:<service>/<account>` reference to the OS keyring
+    Plain(String),
+    /// Key in a sops-encrypted file: `{ sops = "secrets.yaml#api_key" }`
+    Sops { sops: String },
+    /// age-encrypted value: an ASCII-armored ciphertext or the path of an encrypted file
+    Age {
+        age: String,
+        /// Identity file used to decrypt (default: $TMX_AGE_IDENTITY or ~/.config/sops/age/keys.txt)
+        #[serde(default)]
+        identity: Option<String>,
+    },
+    /// Asked for interactively when the session is created: `{ prompt = true, hidden = true }`
+    Prompt {
+        prompt: bool,
+        /// Don't echo the value while it is typed
+        #[serde(default)]
+        hidden: bool,
+    },
+}
+
+impl EnvValue {
+    /// Validate the value specification (without decrypting anything)
+    fn validate(&self, key: &str) -> Result<()> {
+        match self {
+            EnvValue::Plain(value) => {
+                if let Some(entry) = value.strip_prefix(KEYRING_SCHEME) {
+                    let valid = entry
+                        .split_once('/')
+                        .is_some_and(|(service, account)| !service.is_empty() && !account.is_empty());
+                    if !valid {
+                        anyhow::bail!(
+                            "Invalid keyring reference for env variable '{}'\n  \
+                             Found: '{}'\n  \
+                             Expected: 'keyring:<service>/<account>', e.g. \"keyring:github/me\"",
+                            key,
+                            value
+                        );
+                    }
+                }
+                Ok(())
+            }
+            EnvValue::Sops { sops } => match sops.split_once('#') {
+                Some((file, path)) if !file.is_empty() && !path.is_empty() => Ok(()),
+                _ => anyhow::bail!(
+                    "Invalid sops reference for env variable '{}'\n  \
+                     Found: '{}'\n  \
+                     Expected: '<file>#<key>', e.g. \"secrets.yaml#api_key\" or \"secrets.yaml#db.password\"",
+                    key,
+                    sops
+                ),
+            },
+            EnvValue::Age { age, .. } => {
+                if age.trim().is_empty() {
+                    anyhow::bail!(
+                        "Empty age value for env variable '{}'\n  \
+                         Hint: Use an ASCII-armored ciphertext or the path of an encrypted file",
+                        key
+                    );
+                }
+                Ok(())
+            }
+            EnvValue::Prompt { prompt, .. } => {
+                if !prompt {
+                    anyhow::bail!(
+                        "Env variable '{}' has prompt = false\n  \
+                         Hint: Set prompt = true, or use a plain string value",
+                        key
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn default_setup_timeout() -> u64 {
+    300
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn default_root() -> String {
     "~".to_string()
 }
 
+fn default_log_targets() -> Vec<LogTarget> {
+    vec![LogTarget::File]
+}
+
+fn default_log_max_size_kb() -> u64 {
+    1024
+}
+
+fn default_backup_retention_days() -> u64 {
+    30
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    50
+}
+
 /// Helper for creating startup window index validation errors
 fn startup_window_index_error(
     session_name: &str,
@@ -102,7 +879,9 @@ fn invalid_layout_error(window_name: &str, found: &str, valid_layouts: &[&str])
         "Invalid layout value in window '{}'\n  \
          Found: '{}'\n  \
          Valid layouts are:\n    \
-         - {}\n  \
+         - {}\n    \
+         - a custom layout string (tmux display -p '#{{window_layout}}')\n    \
+         - @name of a layout defined under [layouts]\n  \
          Hint: Use 'even-horizontal' for side-by-side panes or 'tiled' for grid layout",
         window_name,
         found,
@@ -147,11 +926,104 @@ impl Config {
     /// Returns an error if the file cannot be read, is invalid TOML,
     /// or contains no sessions.
     pub fn load_from(path: &PathBuf) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::load_with_overrides(path, &[])
+    }
+
+    /// Load configuration from a specific path, with `--set` overrides applied
+    /// to the file's contents first.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the TOML configuration file
+    /// * `overrides` - Values to replace, as if they were written in the file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, is invalid TOML, an
+    /// override doesn't fit the file's structure, or it contains no sessions.
+    pub fn load_with_overrides(path: &PathBuf, overrides: &[Override]) -> Result<Self> {
+        Self::load_with_project(path, None, overrides)
+    }
+
+    /// Load configuration from a specific path merged with a project's
+    /// `.tmx.toml` (see `include_project`), with `--set` overrides applied to
+    /// the file's contents first.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the TOML configuration file; it may be missing if
+    ///   there is a project config
+    /// * `project` - Path to the project's `.tmx.toml`, if one was found
+    /// * `overrides` - Values to replace, as if they were written in the file
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be read, is invalid TOML, an
+    /// override doesn't fit the file's structure, or there are no sessions.
+    pub fn load_with_project(
+        path: &PathBuf,
+        project: Option<&Path>,
+        overrides: &[Override],
+    ) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            // A project config works without a global one
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && project.is_some() => {
+                String::new()
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read config file: {}", path.display()));
+            }
+        };
+
+        let mut config: Config = if overrides.is_empty() {
+            toml::from_str(&content)
+                .with_context(|| format!("Failed to parse config file: {}", path.display()))?
+        } else {
+            let mut table: toml::Table = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+            for value in overrides.iter().filter(|value| value.param().is_none()) {
+                value.apply(&mut table)?;
+            }
+            toml::Value::Table(table)
+                .try_into()
+                .context("Invalid config after applying --set overrides")?
+        };
+
+        config.params = overrides.iter().filter_map(Override::param).collect();
+
+        if config.format_version() > CONFIG_VERSION {
+            anyhow::bail!(
+                "Config file {} has format version {}, but this tmx supports up to version {}\n  \
+                 Hint: Upgrade tmx",
+                path.display(),
+                config.format_version(),
+                CONFIG_VERSION
+            );
+        }
+
+        if let Some(project) = project {
+            config.include_project(project)?;
+        }
+
+        // Include sessions from shared config repositories (see `tmx sync-config`)
+        if let Ok(remote_dir) = remote::remote_dir() {
+            for (source, sessions) in remote::load_sources(&remote_dir)? {
+                config.include_sessions(&source, sessions);
+            }
+        }
+
+        // Resolve placeholders such as `{hostname}` in session and window names
+        for (id, session) in config.sessions.iter_mut() {
+            session.name = if session.name == BRANCH_NAME {
+                branch_session_name(id, &session.root_expanded())
+            } else {
+                placeholders::expand_session_name(&session.name)
+                    .map_err(|e| anyhow::anyhow!("Session '{}':\n{}", id, e))?
+            };
+            session
+                .expand_window_names()
+                .map_err(|e| anyhow::anyhow!("Session '{}':\n{}", id, e))?;
+        }
 
-        let config: Config = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        config.resolve_layouts()?;
 
         // Validate that there's at least one session
         if config.sessions.is_empty() {
@@ -161,6 +1033,37 @@ impl Config {
         Ok(config)
     }
 
+    /// Replace `layout = "@name"` references in windows with the named layout
+    /// from `[layouts]`
+    pub fn resolve_layouts(&mut self) -> Result<()> {
+        for (id, session) in self.sessions.iter_mut() {
+            for window in session.windows.iter_mut() {
+                let Some(name) = window.layout.as_deref().and_then(|l| l.strip_prefix('@')) else {
+                    continue;
+                };
+                let layout = self.layouts.get(name).ok_or_else(|| {
+                    let mut names: Vec<_> = self.layouts.keys().map(|n| format!("@{}", n)).collect();
+                    names.sort_unstable();
+                    anyhow::anyhow!(
+                        "Window '{}' in session '{}' uses layout '@{}', which is not defined\n  \
+                         Hint: Define it under [layouts] (defined: {})",
+                        window.name,
+                        id,
+                        name,
+                        if names.is_empty() { "none".to_string() } else { names.join(", ") }
+                    )
+                })?;
+                window.layout = Some(layout.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the config file format version (1 for files without `version`)
+    pub fn format_version(&self) -> u32 {
+        self.version.unwrap_or(1)
+    }
+
     /// Get the default config file path (~/.config/tmx/tmx.toml)
     ///
     /// Note: This returns the default path only. For env var handling,
@@ -177,29 +1080,611 @@ impl Config {
         Ok(home_dir.join(".config").join("tmx"))
     }
 
-    /// Get a session by name or ID
-    pub fn get_session(&self, name: &str) -> Option<&Session> {
-        // Try direct lookup first
-        if let Some(session) = self.sessions.get(name) {
-            return Some(session);
-        }
-
-        // Try finding by session name field
-        self.sessions.values().find(|s| s.name == name)
+    /// Get the state directory ($XDG_STATE_HOME/tmx, default ~/.local/state/tmx)
+    pub fn state_dir() -> Result<PathBuf> {
+        let state_dir = match std::env::var("XDG_STATE_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => {
+                let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+                home_dir.join(".local").join("state")
+            }
+        };
+        Ok(state_dir.join("tmx"))
     }
 
-    /// List all session names (from TOML keys)
-    pub fn session_ids(&self) -> Vec<String> {
-        let mut ids: Vec<String> = self.sessions.keys().cloned().collect();
-        ids.sort_unstable();
+    /// Add sessions from another source, such as a shared config repository.
+    ///
+    /// A session whose ID is already taken stays reachable under
+    /// `<source>:<id>`; if its tmux name is taken as well, it is prefixed with
+    /// the source so both sessions can run side by side.
+    ///
+    /// # Arguments
+    /// * `source` - Name of the source, shown by `tmx list`
+    /// * `sessions` - The source's sessions, by ID
+    pub fn include_sessions(&mut self, source: &str, sessions: Vec<(String, Session)>) {
+        for (id, mut session) in sessions {
+            let id = if self.sessions.contains_key(&id) {
+                let qualified = format!("{}:{}", source, id);
+                crate::log::info(&format!(
+                    "session '{}' from {} is shadowed, available as '{}'",
+                    id, source, qualified
+                ));
+                if session.name != BRANCH_NAME
+                    && self.sessions.values().any(|s| s.name == session.name)
+                {
+                    session.name = format!("{}-{}", source, session.name);
+                }
+                qualified
+            } else {
+                id
+            };
+            self.session_sources.insert(id.clone(), source.to_string());
+            self.sessions.insert(id, session);
+        }
+    }
+
+    /// Merge a project's `.tmx.toml` into the config.
+    ///
+    /// The project's sessions take precedence: a session with the same ID
+    /// stays available as `<source>:<id>` (e.g. `global:dev`). Its layouts and
+    /// workspaces are added, and its `default` replaces the global one.
+    /// Relative session roots are relative to the project file's directory.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the project's `.tmx.toml`
+    pub fn include_project(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read project config: {}", path.display()))?;
+        let project: Config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse project config: {}", path.display()))?;
+        if project.format_version() > CONFIG_VERSION {
+            anyhow::bail!(
+                "Project config {} has format version {}, but this tmx supports up to version {}\n  \
+                 Hint: Upgrade tmx",
+                path.display(),
+                project.format_version(),
+                CONFIG_VERSION
+            );
+        }
+        crate::log::info(&format!("including project config {}", path.display()));
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for (id, mut session) in project.sessions {
+            if is_relative_root(&session.root) {
+                session.root = expand_nested_root(&session.root, &dir.to_string_lossy());
+            }
+            let source = self.session_source(&id).to_string();
+            let shadowed = self.sessions.insert(id.clone(), session);
+            self.session_sources
+                .insert(id.clone(), PROJECT_SOURCE.to_string());
+
+            if let Some(mut shadowed) = shadowed {
+                let qualified = format!("{}:{}", source, id);
+                crate::log::info(&format!(
+                    "session '{}' from {} is shadowed by the project, available as '{}'",
+                    id, source, qualified
+                ));
+                if shadowed.name != BRANCH_NAME
+                    && self.sessions.values().any(|s| s.name == shadowed.name)
+                {
+                    shadowed.name = format!("{}-{}", source, shadowed.name);
+                }
+                self.session_sources.insert(qualified.clone(), source);
+                self.sessions.insert(qualified, shadowed);
+            }
+        }
+
+        self.layouts.extend(project.layouts);
+        self.workspaces.extend(project.workspaces);
+        if project.default.is_some() {
+            self.default = project.default;
+        }
+        Ok(())
+    }
+
+    /// Find the project config for a directory: `.tmx.toml` in the directory
+    /// or the nearest parent, looking no further than the enclosing git
+    /// repository's root or the home directory.
+    pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+        let home = dirs::home_dir();
+        for dir in dir.ancestors() {
+            let path = dir.join(PROJECT_CONFIG_FILE);
+            if path.is_file() {
+                return Some(path);
+            }
+            if dir.join(".git").exists() || home.as_deref() == Some(dir) {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Get the source a session comes from (`global` for the config file)
+    pub fn session_source(&self, id: &str) -> &str {
+        self.session_sources
+            .get(id)
+            .map_or(CONFIG_SOURCE, String::as_str)
+    }
+
+    /// Get a session by name, ID or `<source>:<id>`
+    pub fn get_session(&self, name: &str) -> Option<&Session> {
+        self.session_id_for(name).map(|id| &self.sessions[id])
+    }
+
+    /// Get the default session ID for the current working directory
+    pub fn default_session_id(&self) -> Option<&str> {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        self.default.as_ref()?.for_directory(&cwd)
+    }
+
+    /// Check that `default` only refers to configured sessions
+    pub fn validate_default(&self) -> Result<()> {
+        let Some(ref default) = self.default else {
+            return Ok(());
+        };
+        for id in default.session_ids() {
+            if !self.sessions.contains_key(id) {
+                anyhow::bail!(
+                    "Default session '{}' not found in configuration\n  Available sessions: {}",
+                    id,
+                    self.session_ids().join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the config key (ID) for a session given its name, ID or
+    /// `<source>:<id>`
+    pub fn session_id_for(&self, name: &str) -> Option<&str> {
+        if let Some((id, _)) = self.sessions.get_key_value(name) {
+            return Some(id);
+        }
+
+        // `global:dev` or `team:dev` picks the session with that ID from the source
+        if let Some((source, id)) = name.split_once(':')
+            && let Some((id, _)) = self.sessions.get_key_value(id)
+            && self.session_source(id) == source
+        {
+            return Some(id);
+        }
+
+        self.sessions
+            .iter()
+            .find(|(_, s)| s.name == name)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Get a workspace by name, with a helpful error if it doesn't exist
+    pub fn get_workspace(&self, name: &str) -> Result<&Workspace> {
+        self.workspaces.get(name).ok_or_else(|| {
+            let mut names: Vec<_> = self.workspaces.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            if names.is_empty() {
+                anyhow::anyhow!(
+                    "Workspace '{}' not found\n  Hint: Define it under [workspaces.{}] in the config",
+                    name,
+                    name
+                )
+            } else {
+                anyhow::anyhow!(
+                    "Workspace '{}' not found\nAvailable workspaces: {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        })
+    }
+
+    /// List all session names (from TOML keys)
+    pub fn session_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.sessions.keys().cloned().collect();
+        ids.sort_unstable();
         ids
     }
 }
 
+/// Make a session name safe to use as a file name
+fn history_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Root value that resolves to the git repository enclosing the current directory
+pub const GIT_ROOT: &str = "@git";
+
+/// Session name that resolves to the git branch checked out in the session root
+pub const BRANCH_NAME: &str = "@branch";
+
+/// Name of a `name = "@branch"` session: the branch checked out in its root,
+/// sanitized for tmux (`feature/login.v2` becomes `feature/login_v2`).
+///
+/// # Arguments
+/// * `id` - The session's config key, used outside a git repository
+/// * `root` - The session's expanded root
+fn branch_session_name(id: &str, root: &str) -> String {
+    match git::current_branch(Path::new(root)) {
+        Some(branch) => crate::tmux::sanitize_session_name(&branch),
+        None => {
+            crate::log::info(&format!(
+                "session '{}': {} is not in a git repository, naming it after its ID",
+                id, root
+            ));
+            id.to_string()
+        }
+    }
+}
+
+/// Expand `~`, `~user` and environment variables (`$VAR`, `${VAR}`) in a path.
+///
+/// Undefined variables and unknown users are left as they are;
+/// `path_problems` reports them.
+pub fn expand_path(path: &str) -> String {
+    let path = expand_user_home(path);
+    shellexpand::full_with_context_no_errors(
+        &path,
+        || dirs::home_dir().map(|p| p.to_string_lossy().to_string()),
+        |var| std::env::var(var).ok(),
+    )
+    .to_string()
+}
+
+/// Shorten a path in the home directory to `~/...`
+pub fn contract_home(path: &str) -> String {
+    let Some(home) = dirs::home_dir() else {
+        return path.to_string();
+    };
+    let home = home.to_string_lossy();
+    match path.strip_prefix(home.as_ref()) {
+        Some("") => "~".to_string(),
+        Some(rest) if rest.starts_with('/') => format!("~{}", rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Find what `expand_path` can't expand in a path: undefined environment
+/// variables (`$NAME`) and unknown users (`~name`)
+pub fn path_problems(path: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(user) = tilde_user(path)
+        && user_home(user).is_none()
+    {
+        problems.push(format!("~{}", user));
+    }
+    let _ = shellexpand::env_with_context_no_errors(path, |var| {
+        if std::env::var(var).is_err() {
+            problems.push(format!("${}", var));
+        }
+        None::<String>
+    });
+    problems
+}
+
+/// The user named by a leading `~user`, if any
+fn tilde_user(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix('~')?;
+    let user = rest.split('/').next().unwrap_or_default();
+    (!user.is_empty()).then_some(user)
+}
+
+/// Replace a leading `~user` with that user's home directory
+fn expand_user_home(path: &str) -> String {
+    let Some(user) = tilde_user(path) else {
+        return path.to_string();
+    };
+    match user_home(user) {
+        Some(home) => format!("{}{}", home.display(), &path[1 + user.len()..]),
+        None => path.to_string(),
+    }
+}
+
+/// Look up a user's home directory in /etc/passwd, falling back to a sibling
+/// of the current user's home (e.g. /home/alice or /Users/alice)
+fn user_home(user: &str) -> Option<PathBuf> {
+    let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+    let from_passwd = passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 6 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    });
+
+    from_passwd.or_else(|| {
+        let sibling = dirs::home_dir()?.parent()?.join(user);
+        sibling.is_dir().then_some(sibling)
+    })
+}
+
+/// Expand a configured root directory.
+///
+/// `~`, `~user` and environment variables are expanded (see `expand_path`),
+/// and `@git` resolves to the root of the git repository containing the
+/// current working directory (falling back to the current directory outside
+/// a repository).
+pub fn expand_root(root: &str) -> String {
+    if root != GIT_ROOT {
+        return expand_path(root);
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    match find_git_root(&cwd) {
+        Some(git_root) => git_root.to_string_lossy().to_string(),
+        None => {
+            crate::log::info(&format!(
+                "{} is not inside a git repository, using it as root",
+                cwd.display()
+            ));
+            cwd.to_string_lossy().to_string()
+        }
+    }
+}
+
+/// Whether a root is relative to its parent's root (not absolute, `~`, `$VAR` or `@git`)
+fn is_relative_root(root: &str) -> bool {
+    root != GIT_ROOT
+        && !root.starts_with('~')
+        && !root.starts_with('$')
+        && Path::new(root).is_relative()
+}
+
+/// Expand a window or pane root, resolving relative paths against the parent root
+fn expand_nested_root(root: &str, parent_root: &str) -> String {
+    if !is_relative_root(root) {
+        return expand_root(root);
+    }
+
+    let mut path = PathBuf::from(expand_path(parent_root));
+    for component in Path::new(root).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                path.pop();
+            }
+            other => path.push(other),
+        }
+    }
+    path.to_string_lossy().to_string()
+}
+
+/// Check that a relative root stays inside its parent's root (no escaping via `..`)
+fn validate_relative_root(root: &str, owner: &str) -> Result<()> {
+    if !is_relative_root(root) {
+        return Ok(());
+    }
+
+    let mut depth: usize = 0;
+    for component in Path::new(root).components() {
+        match component {
+            Component::ParentDir => {
+                depth = depth.checked_sub(1).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Relative root of {} escapes its parent directory\n  \
+                         Found: '{}'\n  \
+                         Hint: Relative roots must stay inside the session/window root; \
+                         use an absolute or ~ path instead",
+                        owner,
+                        root
+                    )
+                })?;
+            }
+            Component::Normal(_) => depth += 1,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Find the nearest directory at or above `dir` containing `.git`
+/// (a directory, or a file for worktrees and submodules)
+fn find_git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
 impl Session {
     /// Get the expanded root directory
     pub fn root_expanded(&self) -> String {
-        shellexpand::tilde(&self.root).to_string()
+        expand_root(&self.root)
+    }
+
+    /// A copy of the session that creates its windows and panes but runs none
+    /// of the configured commands (`tmx open --no-commands`)
+    pub fn without_commands(&self) -> Session {
+        let mut session = self.clone();
+        session.on_attach_send = None;
+        session.on_create.clear();
+        session.pre_window.clear();
+        session.post_create.clear();
+        for window in &mut session.windows {
+            // Closing on exit needs the first pane's command
+            window.close_on_exit = None;
+            window.pre_window.clear();
+            for pane in &mut window.panes {
+                pane.command.clear();
+                pane.setup.clear();
+                pane.exec = false;
+                pane.notify_on_exit = false;
+            }
+        }
+        session
+    }
+
+    /// A copy of the session with its `params` filled in: `{param}` in the
+    /// session, window and pane roots, pane commands, setup commands and
+    /// hooks is replaced by its value.
+    ///
+    /// # Arguments
+    /// * `values` - Values given with `--set <param>=<value>`
+    ///
+    /// # Errors
+    /// Returns an error if a parameter has no value, or a value is given for
+    /// a parameter the session doesn't declare.
+    pub fn with_params(&self, values: &HashMap<String, String>) -> Result<Session> {
+        let mut unknown: Vec<_> = values
+            .keys()
+            .filter(|key| !self.params.contains(key))
+            .collect();
+        unknown.sort_unstable();
+        if let Some(param) = unknown.first() {
+            if self.params.is_empty() {
+                anyhow::bail!(
+                    "Session '{}' has no parameters, so '--set {}=...' doesn't apply to it\n  \
+                     Hint: Declare it with params = [\"{}\"], or give a config path, e.g. --set sessions.<id>.root=...",
+                    self.name,
+                    param,
+                    param
+                );
+            }
+            anyhow::bail!(
+                "Session '{}' has no parameter '{}'\n  Hint: Its parameters are: {}",
+                self.name,
+                param,
+                self.params.join(", ")
+            );
+        }
+
+        let missing: Vec<_> = self
+            .params
+            .iter()
+            .filter(|p| !values.contains_key(*p))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Session '{}' needs a value for parameter(s): {}\n  Hint: Open it with {}",
+                self.name,
+                missing
+                    .iter()
+                    .map(|p| p.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                missing
+                    .iter()
+                    .map(|p| format!("--set {}=<value>", p))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+        }
+
+        let fill = |text: &str| {
+            self.params.iter().fold(text.to_string(), |text, param| {
+                text.replace(&format!("{{{}}}", param), &values[param])
+            })
+        };
+        let fill_all =
+            |commands: &[String]| -> Vec<String> { commands.iter().map(|c| fill(c)).collect() };
+        let mut session = self.clone();
+        session.param_values = values.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        session.root = fill(&self.root);
+        session.on_create = fill_all(&self.on_create);
+        session.pre_window = fill_all(&self.pre_window);
+        session.post_create = fill_all(&self.post_create);
+        session.pre_close = fill_all(&self.pre_close);
+        session.group_with = self.group_with.as_deref().map(fill);
+        for window in &mut session.windows {
+            window.root = window.root.as_deref().map(fill);
+            window.pre_window = fill_all(&window.pre_window);
+            for pane in &mut window.panes {
+                pane.command = fill(&pane.command);
+                pane.root = pane.root.as_deref().map(fill);
+                pane.setup = pane.setup.iter().map(|command| fill(command)).collect();
+            }
+        }
+        Ok(session)
+    }
+
+    /// Expand `{placeholder}`s in window names, e.g. `name = "{root_basename}"`.
+    ///
+    /// The names as written are kept, so sessions derived from this one (for
+    /// tickets or unconfigured names) can be expanded again with their own
+    /// name and root.
+    pub fn expand_window_names(&mut self) -> Result<()> {
+        let session_root = self.root_expanded();
+        for (index, window) in self.windows.iter_mut().enumerate() {
+            let root = window.root_expanded(&session_root);
+            let template = window.name_template.get_or_insert_with(|| window.name.clone());
+            window.name = placeholders::expand_window_name(template, &self.name, &root, index)
+                .map_err(|e| anyhow::anyhow!("Window {} ('{}'):\n{}", index, template, e))?;
+        }
+        Ok(())
+    }
+
+    /// The variables in `vars` that `pass_env` selects, sorted by name.
+    ///
+    /// # Arguments
+    /// * `vars` - The environment to pick from, usually `std::env::vars()`
+    pub fn passed_environment(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<(String, String)> {
+        let mut passed: Vec<_> = vars
+            .into_iter()
+            .filter(|(key, _)| self.pass_env.iter().any(|pattern| env_pattern_matches(pattern, key)))
+            .collect();
+        passed.sort();
+        passed
+    }
+
+    /// The window `on_attach_send` types into
+    pub fn attach_send_window(&self, send: &AttachSend) -> String {
+        send.window
+            .clone()
+            .unwrap_or_else(|| self.windows[0].name.clone())
+    }
+
+    fn validate_attach_send(&self, send: &AttachSend) -> Result<()> {
+        if send.command.trim().is_empty() {
+            anyhow::bail!(
+                "Session '{}' has on_attach_send without a command\n  \
+                 Hint: e.g. on_attach_send = {{ window = \"editor\", command = \"git fetch\" }}",
+                self.name
+            );
+        }
+        if let Some(ref window) = send.window
+            && !self.windows.iter().any(|w| &w.name == window)
+        {
+            let available: Vec<_> = self.windows.iter().map(|w| w.name.as_str()).collect();
+            anyhow::bail!(
+                "on_attach_send in session '{}' refers to unknown window '{}'\n  Available windows: {}",
+                self.name,
+                window,
+                available.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// The tmux window options set on every window of the session
+    pub fn window_options(&self) -> Vec<(&'static str, &'static str)> {
+        let mut options = Vec::new();
+        if let Some(size) = self.window_size {
+            options.push(("window-size", size.as_str()));
+        }
+        if let Some(aggressive) = self.aggressive_resize {
+            options.push(("aggressive-resize", if aggressive { "on" } else { "off" }));
+        }
+        options
+    }
+
+    /// The tmux session options set when the session is created (turning
+    /// `destroy_unattached` on waits for a client, see `session.rs`)
+    pub fn session_options(&self) -> Vec<(&'static str, &str)> {
+        let mut options = Vec::new();
+        if let Some(ref command) = self.default_command {
+            options.push(("default-command", command.as_str()));
+        }
+        if self.destroy_unattached == Some(false) {
+            options.push(("destroy-unattached", "off"));
+        }
+        if let Some(detach) = self.detach_on_destroy {
+            options.push(("detach-on-destroy", detach.as_str()));
+        }
+        options
     }
 
     /// Resolve startup window to index
@@ -221,35 +1706,157 @@ impl Session {
         self.startup_pane.unwrap_or(0)
     }
 
+    /// Get the session's shell history file, used with `isolate_history`
+    /// (<state dir>/history/<session name>)
+    pub fn history_file(&self) -> Result<PathBuf> {
+        Ok(Config::state_dir()?
+            .join("history")
+            .join(history_file_name(&self.name)))
+    }
+
+    /// Check a session that shares the windows of another (`group_with`)
+    fn validate_group(&self, group: &str) -> Result<()> {
+        if group.trim().is_empty() {
+            anyhow::bail!(
+                "Session '{}' has an empty group_with\n  \
+                 Hint: Name the session to share windows with, e.g. group_with = \"dev\"",
+                self.name
+            );
+        }
+        if group == self.name {
+            anyhow::bail!("Session '{}' can't be grouped with itself", self.name);
+        }
+        if !self.windows.is_empty() {
+            anyhow::bail!(
+                "Session '{}' shares the windows of '{}', so it can't have windows of its own\n  \
+                 Hint: Remove its windows, or add them to '{}'",
+                self.name,
+                group,
+                group
+            );
+        }
+        if self
+            .on_attach_send
+            .as_ref()
+            .is_some_and(|send| send.window.is_none())
+        {
+            anyhow::bail!(
+                "Session '{}' shares the windows of '{}', so on_attach_send needs a window\n  \
+                 Hint: e.g. on_attach_send = {{ window = \"editor\", command = \"git fetch\" }}",
+                self.name,
+                group
+            );
+        }
+        Ok(())
+    }
+
     /// Validate the session configuration
     pub fn validate(&self) -> Result<()> {
         if self.name.is_empty() {
             anyhow::bail!("Session name cannot be empty");
         }
 
-        if self.windows.is_empty() {
+        if let Some(ref group) = self.group_with {
+            self.validate_group(group)?;
+        } else if self.windows.is_empty() {
             anyhow::bail!("Session '{}' must have at least one window", self.name);
         }
 
-        // Validate startup_window if specified
-        if let Some(StartupWindow::Index(i)) = &self.startup_window {
-            if *i >= self.windows.len() {
-                return Err(startup_window_index_error(
-                    &self.name,
-                    *i,
-                    self.windows.len() - 1,
-                    self.windows.len(),
-                ));
-            }
+        // Validate startup_window if specified (grouped sessions pick theirs
+        // from the group's windows when they are created)
+        if self.group_with.is_none()
+            && let Some(StartupWindow::Index(i)) = &self.startup_window
+            && *i >= self.windows.len()
+        {
+            return Err(startup_window_index_error(
+                &self.name,
+                *i,
+                self.windows.len() - 1,
+                self.windows.len(),
+            ));
+        }
+
+        if self.group_with.is_none()
+            && let Some(StartupWindow::Name(name)) = &self.startup_window
+            && !self.windows.iter().any(|w| &w.name == name)
+        {
+            let available: Vec<_> = self.windows.iter().map(|w| w.name.as_str()).collect();
+            return Err(startup_window_name_error(&self.name, name, &available));
+        }
+
+        if let Some(ref send) = self.on_attach_send {
+            self.validate_attach_send(send)?;
+        }
+
+        if self
+            .default_command
+            .as_ref()
+            .is_some_and(|command| command.trim().is_empty())
+        {
+            anyhow::bail!(
+                "Session '{}' has an empty default_command\n  \
+                 Hint: Remove it to start login shells, or use login_shell = false for non-login shells",
+                self.name
+            );
+        }
+
+        if self.setup_concurrency == Some(0) {
+            anyhow::bail!(
+                "Session '{}' has setup_concurrency = 0, so no setup could run\n  \
+                 Hint: Use 1 or more, or remove it to run all setups at once",
+                self.name
+            );
         }
 
-        if let Some(StartupWindow::Name(name)) = &self.startup_window {
-            if !self.windows.iter().any(|w| &w.name == name) {
-                let available: Vec<_> = self.windows.iter().map(|w| w.name.as_str()).collect();
-                return Err(startup_window_name_error(&self.name, name, &available));
+        for param in &self.params {
+            if param.is_empty() || !param.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                anyhow::bail!(
+                    "Invalid parameter '{}' in session '{}'\n  Hint: Use letters, digits and '_'",
+                    param,
+                    self.name
+                );
+            }
+            if TOP_LEVEL_KEYS.contains(&param.as_str()) {
+                anyhow::bail!(
+                    "Parameter '{}' in session '{}' has the name of a config key, so --set {}=... \
+                     would change the config instead\n  Hint: Choose another name",
+                    param,
+                    self.name,
+                    param
+                );
             }
         }
 
+        for (key, value) in &self.env {
+            value
+                .validate(key)
+                .map_err(|e| anyhow::anyhow!("env in session '{}': {}", self.name, e))?;
+        }
+
+        for option in self.options.keys() {
+            validate_option_name(option, &format!("session '{}'", self.name))?;
+        }
+
+        for pattern in &self.pass_env {
+            validate_env_pattern(pattern)
+                .map_err(|e| anyhow::anyhow!("pass_env in session '{}': {}", self.name, e))?;
+        }
+
+        if let Some(ref command) = self.on_died {
+            placeholders::expand_on_died(command, &self.name)
+                .map_err(|e| anyhow::anyhow!("on_died in session '{}': {}", self.name, e))?;
+        }
+
+        if self.close_strategy == CloseStrategy::Kill
+            && (self.shutdown_command.is_some() || self.close_timeout.is_some())
+        {
+            anyhow::bail!(
+                "Session '{}' sets shutdown_command or close_timeout, which only apply to graceful closing\n  \
+                 Hint: Add close_strategy = \"graceful\"",
+                self.name
+            );
+        }
+
         for (i, window) in self.windows.iter().enumerate() {
             window.validate().map_err(|e| {
                 anyhow::anyhow!(
@@ -286,40 +1893,220 @@ impl Window {
             anyhow::bail!("Window '{}' must have at least one pane", self.name);
         }
 
-        // Validate layout if specified
-        if let Some(ref layout) = self.layout {
-            if !Self::VALID_LAYOUTS.contains(&layout.as_str()) {
-                return Err(invalid_layout_error(
-                    &self.name,
-                    layout,
-                    Self::VALID_LAYOUTS,
-                ));
+        for (key, value) in &self.env {
+            value.validate(key)?;
+        }
+
+        for option in self.options.keys() {
+            validate_option_name(option, &format!("window '{}'", self.name))?;
+        }
+
+        // Validate layout if specified: a preset, or a custom layout string
+        // with one cell per pane
+        if let Some(ref layout) = self.layout
+            && !Self::VALID_LAYOUTS.contains(&layout.as_str())
+        {
+            match layout::pane_count(layout) {
+                Some(count) if count != self.panes.len() => anyhow::bail!(
+                    "Custom layout of window '{}' has {} pane(s), but the window has {}\n  \
+                     Hint: Save the layout from a window with the same number of panes \
+                     (tmux display -p '#{{window_layout}}')",
+                    self.name,
+                    count,
+                    self.panes.len()
+                ),
+                Some(_) => {}
+                None => {
+                    return Err(invalid_layout_error(
+                        &self.name,
+                        layout,
+                        Self::VALID_LAYOUTS,
+                    ));
+                }
             }
         }
 
+        if let Some(ref root) = self.root {
+            validate_relative_root(root, &format!("window '{}'", self.name))?;
+        }
+
+        if self.close_on_exit.is_some() && self.panes[0].command.trim().is_empty() {
+            anyhow::bail!(
+                "Window '{}' has close_on_exit but its first pane has no command\n  \
+                 Hint: close_on_exit watches the first pane's command, e.g. command = \"nvim\"",
+                self.name
+            );
+        }
+
         // Validate pane split directions
         for (i, pane) in self.panes.iter().enumerate() {
-            if let Some(ref split) = pane.split {
-                if split != "horizontal" && split != "vertical" {
-                    return Err(invalid_split_error(i, &self.name, split));
-                }
+            if let Some(ref root) = pane.root {
+                validate_relative_root(root, &format!("pane {} of window '{}'", i, self.name))?;
+            }
+
+            if let Some(ref split) = pane.split
+                && split != "horizontal"
+                && split != "vertical"
+            {
+                return Err(invalid_split_error(i, &self.name, split));
             }
 
             // Validate pane size format if specified
             if let Some(ref size) = pane.size {
                 validate_size_format(size, i, &self.name)?;
             }
+
+            for (key, value) in &pane.env {
+                value.validate(key)?;
+            }
+
+            if pane.exec {
+                validate_exec(pane, i, &self.name)?;
+            } else if !pane.send_enter {
+                // Both watch for the command's exit, so it is submitted followed by `exit`
+                let option = if pane.notify_on_exit {
+                    Some("notify_on_exit")
+                } else if i == 0 && self.close_on_exit.is_some() {
+                    Some("close_on_exit")
+                } else {
+                    None
+                };
+                if let Some(option) = option {
+                    anyhow::bail!(
+                        "Pane {} in window '{}' has send_enter = false with {}\n  \
+                         Hint: {} runs the command right away to watch it exit; \
+                         remove send_enter = false or {}",
+                        i,
+                        self.name,
+                        option,
+                        option,
+                        option
+                    );
+                }
+            }
+
+            for option in pane.options.keys() {
+                validate_option_name(option, &format!("pane {} in window '{}'", i, self.name))?;
+            }
+
+            if let Some(ref style) = pane.style
+                && style.trim().is_empty()
+            {
+                anyhow::bail!(
+                    "Pane {} in window '{}' has an empty style\n  \
+                     Hint: Use a tmux style such as \"bg=colour52\", or remove style",
+                    i,
+                    self.name
+                );
+            }
+
+            if let Some(ref title) = pane.title {
+                if title.trim().is_empty() {
+                    anyhow::bail!("Pane {} in window '{}' has an empty title", i, self.name);
+                }
+                if self.panes[..i].iter().any(|p| p.title.as_ref() == Some(title)) {
+                    anyhow::bail!(
+                        "Window '{}' has more than one pane titled '{}'\n  \
+                         Hint: Pane titles identify panes for after, so they must be unique",
+                        self.name,
+                        title
+                    );
+                }
+            }
+
+            if let Some(ref check) = pane.healthcheck
+                && check.trim().is_empty()
+            {
+                anyhow::bail!(
+                    "Pane {} in window '{}' has an empty healthcheck\n  \
+                     Hint: e.g. healthcheck = \"curl -sf localhost:8080/health\"",
+                    i,
+                    self.name
+                );
+            }
+
+            if pane.exec && pane.after.is_some() {
+                anyhow::bail!(
+                    "Pane {} in window '{}' has both exec = true and after\n  \
+                     Hint: Exec panes start their command when they are created; \
+                     remove exec to type the command after the other pane's",
+                    i,
+                    self.name
+                );
+            }
         }
 
+        self.command_order()?;
+
         Ok(())
     }
 
+    /// The order the panes' setup and commands are sent in: by position,
+    /// except that a pane with `after` comes after the pane it names.
+    ///
+    /// # Returns
+    /// Pane indices in sending order.
+    ///
+    /// # Errors
+    /// Returns an error if `after` names an unknown pane, or the dependencies
+    /// form a cycle.
+    pub fn command_order(&self) -> Result<Vec<usize>> {
+        let dependencies = self
+            .panes
+            .iter()
+            .enumerate()
+            .map(|(i, pane)| {
+                let Some(ref after) = pane.after else {
+                    return Ok(None);
+                };
+                self.panes
+                    .iter()
+                    .position(|p| p.title.as_ref() == Some(after))
+                    .map(Some)
+                    .ok_or_else(|| {
+                        let titles: Vec<_> =
+                            self.panes.iter().filter_map(|p| p.title.as_deref()).collect();
+                        anyhow::anyhow!(
+                            "Pane {} in window '{}' comes after unknown pane '{}'\n  \
+                             Hint: Give that pane title = \"{}\" (titled panes: {})",
+                            i,
+                            self.name,
+                            after,
+                            after,
+                            if titles.is_empty() { "none".to_string() } else { titles.join(", ") }
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut order = Vec::with_capacity(self.panes.len());
+        let mut placed = vec![false; self.panes.len()];
+        while order.len() < self.panes.len() {
+            let next = (0..self.panes.len())
+                .find(|&i| !placed[i] && dependencies[i].is_none_or(|dependency| placed[dependency]));
+            let Some(next) = next else {
+                let waiting: Vec<_> = (0..self.panes.len())
+                    .filter(|&i| !placed[i])
+                    .map(|i| i.to_string())
+                    .collect();
+                anyhow::bail!(
+                    "Panes {} in window '{}' wait for each other through after",
+                    waiting.join(", "),
+                    self.name
+                );
+            };
+            placed[next] = true;
+            order.push(next);
+        }
+        Ok(order)
+    }
+
     /// Get the expanded root directory for this window
     pub fn root_expanded(&self, session_root: &str) -> String {
         if let Some(ref root) = self.root {
-            shellexpand::tilde(root).to_string()
+            expand_nested_root(root, session_root)
         } else {
-            shellexpand::tilde(session_root).to_string()
+            expand_path(session_root)
         }
     }
 }
@@ -328,13 +2115,96 @@ impl Pane {
     /// Get the expanded root directory for this pane
     pub fn root_expanded(&self, window_root: &str) -> String {
         if let Some(ref root) = self.root {
-            shellexpand::tilde(root).to_string()
+            expand_nested_root(root, window_root)
         } else {
             window_root.to_string()
         }
     }
 }
 
+/// Validate a pane with `exec = true`, which has no shell to type into
+fn validate_exec(pane: &Pane, pane_idx: usize, window_name: &str) -> Result<()> {
+    if pane.command.trim().is_empty() {
+        anyhow::bail!(
+            "Pane {} in window '{}' has exec = true but no command\n  \
+             Hint: Set command, or remove exec to start a shell",
+            pane_idx,
+            window_name
+        );
+    }
+
+    if pane.login_shell.is_some() {
+        anyhow::bail!(
+            "Pane {} in window '{}' has both exec = true and login_shell\n  \
+             Hint: exec panes run their command instead of a shell; remove login_shell",
+            pane_idx,
+            window_name
+        );
+    }
+
+    if !pane.setup.is_empty() {
+        anyhow::bail!(
+            "Pane {} in window '{}' has both exec = true and setup\n  \
+             Hint: Setup steps are typed into a shell; fold them into the command instead, \
+             e.g. command = \"make deps && {}\"",
+            pane_idx,
+            window_name,
+            pane.command
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate the name of a tmux option set on `place` (e.g. `window 'logs'`)
+fn validate_option_name(option: &str, place: &str) -> Result<()> {
+    let name = option.strip_prefix('@').unwrap_or(option);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!(
+            "Invalid tmux option '{}' on {}\n  \
+             Hint: Use a tmux option name such as remain-on-exit, or @name for a user option",
+            option,
+            place
+        );
+    }
+    Ok(())
+}
+
+/// Validate a `pass_env` entry: a variable name, optionally with `*` wildcards
+fn validate_env_pattern(pattern: &str) -> Result<()> {
+    let valid = !pattern.is_empty()
+        && pattern
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '*');
+    if !valid {
+        anyhow::bail!(
+            "Invalid variable name '{}'\n  \
+             Hint: Use names like \"SSH_AUTH_SOCK\", or \"*\" as a wildcard (e.g. \"AWS_*\")",
+            pattern
+        );
+    }
+    Ok(())
+}
+
+/// Whether a variable name matches a `pass_env` entry, where `*` matches
+/// any run of characters
+fn env_pattern_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(remaining) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            // Try every position for the wildcard, shortest match first
+            remaining
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(remaining.len()))
+                .any(|i| env_pattern_matches(rest, &remaining[i..]))
+        }
+    }
+}
+
 /// Validate pane size format
 fn validate_size_format(size: &str, pane_index: usize, window_name: &str) -> Result<()> {
     let is_valid = if let Some(percent_str) = size.strip_suffix('%') {
@@ -369,6 +2239,9 @@ fn validate_size_format(size: &str, pane_index: usize, window_name: &str) -> Res
 pub const DEFAULT_CONFIG: &str = r#"# TMX Configuration
 # Define your tmux sessions here
 
+# Config file format version (upgrade older files with `tmx migrate`)
+version = 2
+
 # Default session to start when no sessions are running (optional)
 default = "dev"
 
@@ -440,6 +2313,7 @@ mod tests {
         assert_eq!(config.sessions.len(), 2);
         assert!(config.sessions.contains_key("dev"));
         assert!(config.sessions.contains_key("work"));
+        assert_eq!(config.format_version(), CONFIG_VERSION);
     }
 
     #[test]
@@ -458,86 +2332,428 @@ mod tests {
             windows: vec![],
             startup_window: None,
             startup_pane: None,
+            on_conflict: OnConflict::default(),
+            missing_root: MissingRoot::default(),
+            env: HashMap::new(),
+            env_file: None,
+            env_file_missing: EnvFileMissing::default(),
+            default_command: None,
+            login_shell: None,
+            isolate_history: false,
+            tags: vec![],
+            window_size: None,
+            aggressive_resize: None,
+            destroy_unattached: None,
+            detach_on_destroy: None,
+            options: BTreeMap::new(),
+            on_attach_send: None,
+            close_strategy: CloseStrategy::default(),
+            shutdown_command: None,
+            close_timeout: None,
+            on_died: None,
+            pass_env: vec![],
+            setup_concurrency: None,
+            params: vec![],
+            param_values: BTreeMap::new(),
+            on_create: vec![],
+            pre_window: vec![],
+            post_create: vec![],
+            pre_close: vec![],
+            group_with: None,
         };
         let expanded = session.root_expanded();
         assert!(!expanded.contains('~'));
     }
 
+    #[test]
+    fn test_expand_path() {
+        // SAFETY: the variable name is unique to this test
+        unsafe { std::env::set_var("TMX_TEST_PROJECTS", "/srv/projects") };
+        assert_eq!(expand_path("$TMX_TEST_PROJECTS/api"), "/srv/projects/api");
+        assert_eq!(expand_path("${TMX_TEST_PROJECTS}/api"), "/srv/projects/api");
+        assert!(path_problems("$TMX_TEST_PROJECTS/api").is_empty());
+
+        // Undefined variables and unknown users are kept and reported
+        assert_eq!(expand_path("$TMX_TEST_UNDEFINED/api"), "$TMX_TEST_UNDEFINED/api");
+        assert_eq!(
+            path_problems("~tmx-no-such-user/$TMX_TEST_UNDEFINED"),
+            vec!["~tmx-no-such-user", "$TMX_TEST_UNDEFINED"]
+        );
+
+        if user_home("root").is_some() {
+            assert!(!expand_path("~root/src").starts_with('~'));
+        }
+    }
+
+    #[test]
+    fn test_default_for_directory() {
+        let config: Config = toml::from_str(
+            r#"
+default = { "/home/me/work" = "work", "/home/me/work/api" = "api", "*" = "dev" }
+"#,
+        )
+        .unwrap();
+        let default = config.default.unwrap();
+        let id = |dir: &str| default.for_directory(Path::new(dir));
+        assert_eq!(id("/home/me/work"), Some("work"));
+        assert_eq!(id("/home/me/work/web/src"), Some("work"));
+        assert_eq!(id("/home/me/work/api/src"), Some("api"));
+        // Prefixes match whole path components
+        assert_eq!(id("/home/me/workshop"), Some("dev"));
+        assert_eq!(id("/tmp"), Some("dev"));
+
+        let config: Config = toml::from_str(r#"default = "dev""#).unwrap();
+        assert_eq!(
+            config.default.unwrap().for_directory(Path::new("/tmp")),
+            Some("dev")
+        );
+    }
+
+    #[test]
+    fn test_history_file_name() {
+        assert_eq!(history_file_name("api"), "api");
+        assert_eq!(history_file_name("web/app:1"), "web_app_1");
+        assert_eq!(history_file_name(".."), "__");
+    }
+
+    #[test]
+    fn test_find_git_root() {
+        let dir = std::env::temp_dir().join(format!("tmx-git-root-{}", std::process::id()));
+        let nested = dir.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+
+        assert_eq!(find_git_root(&nested), Some(dir.clone()));
+        assert_eq!(find_git_root(&dir), Some(dir.clone()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_branch_session_name() {
+        let dir = std::env::temp_dir().join(format!("tmx-branch-name-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let root = dir.to_string_lossy();
+        assert_eq!(branch_session_name("app", &root), "app");
+
+        let init = std::process::Command::new("git")
+            .args(["init", "-q", "-b", "feature/login.v2"])
+            .arg(&dir)
+            .status()
+            .unwrap();
+        assert!(init.success());
+        assert_eq!(branch_session_name("app", &root), "feature/login_v2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_startup_window_by_index() {
         let config: Config = toml::from_str(
             r#"
 [sessions.test]
 name = "test"
-root = "~"
-startup_window = 1
+root = "~"
+startup_window = 1
+
+[[sessions.test.windows]]
+name = "first"
+[[sessions.test.windows.panes]]
+command = ""
+
+[[sessions.test.windows]]
+name = "second"
+[[sessions.test.windows.panes]]
+command = ""
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert_eq!(session.resolve_startup_window(), 1);
+    }
+
+    #[test]
+    fn test_startup_window_by_name() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+root = "~"
+startup_window = "second"
+
+[[sessions.test.windows]]
+name = "first"
+[[sessions.test.windows.panes]]
+command = ""
+
+[[sessions.test.windows]]
+name = "second"
+[[sessions.test.windows.panes]]
+command = ""
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert_eq!(session.resolve_startup_window(), 1);
+    }
+
+    #[test]
+    fn test_window_layout_validation() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "win"
+layout = "main-vertical"
+
+[[sessions.test.windows.panes]]
+command = ""
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert!(session.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "win"
+layout = "invalid-layout"
+
+[[sessions.test.windows.panes]]
+command = ""
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert!(session.validate().is_err());
+    }
+
+    #[test]
+    fn test_resolve_layouts() {
+        let mut config: Config = toml::from_str(
+            r#"
+[layouts]
+ide = "95e4,120x40,0,0{60x40,0,0,0,59x40,61,0[59x20,61,0,1,59x19,61,21,2]}"
+wide = "main-vertical"
+
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "code"
+layout = "@ide"
+panes = [{ command = "nvim" }, { command = "" }, { command = "" }]
+
+[[sessions.test.windows]]
+name = "logs"
+layout = "@wide"
+panes = [{ command = "" }]
+"#,
+        )
+        .unwrap();
+        config.resolve_layouts().unwrap();
+
+        let session = &config.sessions["test"];
+        assert_eq!(session.windows[0].layout.as_deref(), Some(config.layouts["ide"].as_str()));
+        assert_eq!(session.windows[1].layout.as_deref(), Some("main-vertical"));
+        session.validate().unwrap();
+
+        // A custom layout must have one cell per pane
+        let mut window = session.windows[0].clone();
+        window.panes.pop();
+        assert!(window.validate().is_err());
+
+        config.sessions.get_mut("test").unwrap().windows[1].layout = Some("@nope".to_string());
+        let err = config.resolve_layouts().unwrap_err().to_string();
+        assert!(err.contains("'@nope', which is not defined"));
+    }
+
+    #[test]
+    fn test_pane_sizing() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "win"
+
+[[sessions.test.windows.panes]]
+command = "nvim"
+
+[[sessions.test.windows.panes]]
+command = ""
+size = "30%"
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert_eq!(session.windows[0].panes[1].size.as_deref(), Some("30%"));
+    }
+
+    #[test]
+    fn test_per_window_root() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+root = "~/projects"
+
+[[sessions.test.windows]]
+name = "win1"
+
+[[sessions.test.windows.panes]]
+command = ""
+
+[[sessions.test.windows]]
+name = "win2"
+root = "~/other"
+
+[[sessions.test.windows.panes]]
+command = ""
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert_eq!(session.windows[1].root.as_deref(), Some("~/other"));
+    }
+
+    #[test]
+    fn test_relative_roots() {
+        assert_eq!(expand_nested_root("backend/api", "/src/app"), "/src/app/backend/api");
+        assert_eq!(expand_nested_root("./web/../docs", "/src/app"), "/src/app/docs");
+        assert_eq!(expand_nested_root("/opt/x", "/src/app"), "/opt/x");
+
+        assert!(validate_relative_root("backend/../api", "pane").is_ok());
+        assert!(validate_relative_root("../sibling", "pane").is_err());
+        assert!(validate_relative_root("a/../../b", "pane").is_err());
+        assert!(validate_relative_root("~/anywhere", "pane").is_ok());
+    }
+
+    #[test]
+    fn test_per_pane_root() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "win"
+
+[[sessions.test.windows.panes]]
+command = "npm start"
+root = "~/service-a"
+
+[[sessions.test.windows.panes]]
+command = "npm start"
+root = "~/service-b"
+"#,
+        )
+        .unwrap();
+
+        let session = config.sessions.get("test").unwrap();
+        assert_eq!(
+            session.windows[0].panes[0].root.as_deref(),
+            Some("~/service-a")
+        );
+        assert_eq!(
+            session.windows[0].panes[1].root.as_deref(),
+            Some("~/service-b")
+        );
+    }
+
+    #[test]
+    fn test_on_conflict() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.sessions["dev"].on_conflict, OnConflict::Attach);
+
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+on_conflict = "rename"
 
 [[sessions.test.windows]]
-name = "first"
-[[sessions.test.windows.panes]]
-command = ""
+name = "win"
 
-[[sessions.test.windows]]
-name = "second"
 [[sessions.test.windows.panes]]
 command = ""
 "#,
         )
         .unwrap();
-
-        let session = config.sessions.get("test").unwrap();
-        assert_eq!(session.resolve_startup_window(), 1);
+        assert_eq!(config.sessions["test"].on_conflict, OnConflict::Rename);
     }
 
     #[test]
-    fn test_startup_window_by_name() {
+    fn test_tmux_retry_config() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.tmux.retries, 3);
+        assert_eq!(config.tmux.retry_backoff_ms, 50);
+
         let config: Config = toml::from_str(
             r#"
+[tmux]
+retries = 0
+
 [sessions.test]
 name = "test"
-root = "~"
-startup_window = "second"
 
 [[sessions.test.windows]]
-name = "first"
-[[sessions.test.windows.panes]]
-command = ""
+name = "win"
 
-[[sessions.test.windows]]
-name = "second"
 [[sessions.test.windows.panes]]
 command = ""
 "#,
         )
         .unwrap();
-
-        let session = config.sessions.get("test").unwrap();
-        assert_eq!(session.resolve_startup_window(), 1);
+        assert_eq!(config.tmux.retries, 0);
+        assert_eq!(config.tmux.retry_backoff_ms, 50);
     }
 
     #[test]
-    fn test_window_layout_validation() {
+    fn test_log_targets() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.log.targets, vec![LogTarget::File]);
+
         let config: Config = toml::from_str(
             r#"
+[log]
+targets = ["file", "journald"]
+
 [sessions.test]
 name = "test"
 
 [[sessions.test.windows]]
 name = "win"
-layout = "main-vertical"
 
 [[sessions.test.windows.panes]]
 command = ""
 "#,
         )
         .unwrap();
-
-        let session = config.sessions.get("test").unwrap();
-        assert!(session.validate().is_ok());
+        assert_eq!(
+            config.log.targets,
+            vec![LogTarget::File, LogTarget::Journald]
+        );
     }
 
     #[test]
-    fn test_invalid_layout() {
+    fn test_encrypted_env_values() {
         let config: Config = toml::from_str(
             r#"
 [sessions.test]
@@ -545,20 +2761,52 @@ name = "test"
 
 [[sessions.test.windows]]
 name = "win"
-layout = "invalid-layout"
 
 [[sessions.test.windows.panes]]
 command = ""
+env = { PLAIN = "x", API_KEY = { sops = "secrets.yaml#api_key" }, TOKEN = { age = "token.age" }, VAULT_TOKEN = { prompt = true, hidden = true } }
 "#,
         )
         .unwrap();
 
         let session = config.sessions.get("test").unwrap();
-        assert!(session.validate().is_err());
+        let env = &session.windows[0].panes[0].env;
+        assert_eq!(env["PLAIN"], EnvValue::Plain("x".to_string()));
+        assert_eq!(
+            env["API_KEY"],
+            EnvValue::Sops {
+                sops: "secrets.yaml#api_key".to_string()
+            }
+        );
+        assert_eq!(
+            env["TOKEN"],
+            EnvValue::Age {
+                age: "token.age".to_string(),
+                identity: None
+            }
+        );
+        assert_eq!(
+            env["VAULT_TOKEN"],
+            EnvValue::Prompt {
+                prompt: true,
+                hidden: true
+            }
+        );
+        assert!(session.validate().is_ok());
+
+        let missing_key = EnvValue::Sops {
+            sops: "secrets.yaml".to_string(),
+        };
+        assert!(missing_key.validate("API_KEY").is_err());
+
+        let keyring = EnvValue::Plain("keyring:github/me".to_string());
+        assert!(keyring.validate("TOKEN").is_ok());
+        let keyring = EnvValue::Plain("keyring:github".to_string());
+        assert!(keyring.validate("TOKEN").is_err());
     }
 
     #[test]
-    fn test_pane_sizing() {
+    fn test_pane_send_options() {
         let config: Config = toml::from_str(
             r#"
 [sessions.test]
@@ -568,103 +2816,602 @@ name = "test"
 name = "win"
 
 [[sessions.test.windows.panes]]
-command = "nvim"
+command = "make"
 
 [[sessions.test.windows.panes]]
-command = ""
-size = "30%"
+command = "print('Enter')"
+send_enter = false
+literal = true
 "#,
         )
         .unwrap();
 
-        let session = config.sessions.get("test").unwrap();
-        assert_eq!(session.windows[0].panes[1].size.as_deref(), Some("30%"));
+        let panes = &config.sessions["test"].windows[0].panes;
+        assert!(panes[0].send_enter);
+        assert!(!panes[0].literal);
+        assert!(!panes[1].send_enter);
+        assert!(panes[1].literal);
     }
 
     #[test]
-    fn test_per_window_root() {
+    fn test_split_direction() {
         let config: Config = toml::from_str(
             r#"
 [sessions.test]
 name = "test"
-root = "~/projects"
 
 [[sessions.test.windows]]
-name = "win1"
+name = "win"
 
 [[sessions.test.windows.panes]]
 command = ""
 
-[[sessions.test.windows]]
-name = "win2"
-root = "~/other"
-
 [[sessions.test.windows.panes]]
 command = ""
+split = "horizontal"
 "#,
         )
         .unwrap();
 
         let session = config.sessions.get("test").unwrap();
-        assert_eq!(session.windows[1].root.as_deref(), Some("~/other"));
+        assert_eq!(
+            session.windows[0].panes[1].split.as_deref(),
+            Some("horizontal")
+        );
     }
 
     #[test]
-    fn test_per_pane_root() {
+    fn test_pane_options() {
+        let window: Window = toml::from_str(
+            r#"
+name = "server"
+panes = [{ command = "", options = { remain-on-exit = true, history-limit = 5000, "@role" = "api" } }]
+"#,
+        )
+        .unwrap();
+        window.validate().unwrap();
+
+        let options: Vec<(String, String)> = window.panes[0]
+            .options
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_string()))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                ("@role".to_string(), "api".to_string()),
+                ("history-limit".to_string(), "5000".to_string()),
+                ("remain-on-exit".to_string(), "on".to_string()),
+            ]
+        );
+
+        let mut bad = window.clone();
+        bad.panes[0]
+            .options
+            .insert("remain on exit".to_string(), OptionValue::Flag(false));
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_session_and_window_options() {
         let config: Config = toml::from_str(
             r#"
-[sessions.test]
-name = "test"
+[sessions.dev]
+name = "dev"
+options = { status-left = "[#S] ", mouse = true }
 
-[[sessions.test.windows]]
-name = "win"
+[[sessions.dev.windows]]
+name = "logs"
+options = { automatic-rename = false, "@role" = "logs" }
+panes = [{ command = "" }]
+"#,
+        )
+        .unwrap();
+        let session = &config.sessions["dev"];
+        session.validate().unwrap();
+        assert_eq!(session.options["status-left"].to_string(), "[#S] ");
+        assert_eq!(session.options["mouse"].to_string(), "on");
+        assert_eq!(
+            session.windows[0].options["automatic-rename"].to_string(),
+            "off"
+        );
 
-[[sessions.test.windows.panes]]
-command = "npm start"
-root = "~/service-a"
+        let mut bad = session.clone();
+        bad.windows[0]
+            .options
+            .insert("automatic rename".to_string(), OptionValue::Flag(true));
+        let error = bad.validate().unwrap_err();
+        assert!(format!("{:#}", error).contains("on window 'logs'"));
 
-[[sessions.test.windows.panes]]
-command = "npm start"
-root = "~/service-b"
+        let mut bad = session.clone();
+        bad.options.insert("@".to_string(), OptionValue::Number(1));
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_ticket_session() {
+        let config: Config = toml::from_str(
+            r#"
+[ticket]
+template = "dev"
+name = "t-{ticket_lower}"
+root = "~/tickets/{ticket}"
+
+[sessions.dev]
+name = "dev"
+root = "~/src"
+[[sessions.dev.windows]]
+name = "editor"
+panes = [{ command = "nvim" }]
 "#,
         )
         .unwrap();
+        let ticket = config.ticket.as_ref().unwrap();
+        ticket.validate(&config).unwrap();
 
-        let session = config.sessions.get("test").unwrap();
-        assert_eq!(
-            session.windows[0].panes[0].root.as_deref(),
-            Some("~/service-a")
+        let session = ticket.session("JIRA-12", &config).unwrap();
+        assert_eq!(session.name, "t-jira-12");
+        assert_eq!(session.root, "~/tickets/JIRA-12");
+        assert_eq!(session.windows[0].panes[0].command, "nvim");
+        assert_eq!(ticket.branch_name("JIRA-12").unwrap(), "JIRA-12");
+
+        assert!(ticket.session("../etc", &config).is_err());
+        assert!(ticket.session("a b", &config).is_err());
+
+        let mut unknown = ticket.clone();
+        unknown.template = "nope".to_string();
+        assert!(unknown.validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_on_attach_send_validation() {
+        let mut session: Session = toml::from_str(
+            r#"
+name = "dev"
+on_attach_send = { command = "git fetch" }
+
+[[windows]]
+name = "editor"
+panes = [{ command = "" }]
+"#,
+        )
+        .unwrap();
+        session.validate().unwrap();
+        let send = session.on_attach_send.clone().unwrap();
+        assert_eq!(session.attach_send_window(&send), "editor");
+
+        session.on_attach_send = Some(AttachSend {
+            window: Some("logs".to_string()),
+            ..send.clone()
+        });
+        assert!(session.validate().is_err());
+
+        session.on_attach_send = Some(AttachSend {
+            command: " ".to_string(),
+            ..send
+        });
+        assert!(session.validate().is_err());
+    }
+
+    #[test]
+    fn test_expand_window_names() {
+        let mut session: Session = toml::from_str(
+            r#"
+name = "dev"
+root = "/src/app"
+
+[[windows]]
+name = "{root_basename}"
+panes = [{ command = "" }]
+
+[[windows]]
+name = "{session}-{index}"
+root = "api"
+panes = [{ command = "" }]
+"#,
+        )
+        .unwrap();
+        session.expand_window_names().unwrap();
+        let names: Vec<_> = session.windows.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "dev-1"]);
+
+        // Derived sessions are expanded again from the original names
+        session.name = "JIRA-1".to_string();
+        session.root = "/src/jira-1".to_string();
+        session.expand_window_names().unwrap();
+        let names: Vec<_> = session.windows.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["jira-1", "JIRA-1-1"]);
+
+        session.windows[0].name_template = Some("{ticket}".to_string());
+        assert!(session.expand_window_names().is_err());
+    }
+
+    #[test]
+    fn test_without_commands() {
+        let session: Session = toml::from_str(
+            r#"
+name = "dev"
+on_attach_send = { command = "git fetch" }
+
+[[windows]]
+name = "editor"
+close_on_exit = "window"
+panes = [{ command = "nvim", setup = ["nvm use"] }, { command = "htop", exec = true }]
+"#,
+        )
+        .unwrap();
+        let bare = session.without_commands();
+        bare.validate().unwrap();
+        assert!(bare.on_attach_send.is_none());
+        assert!(
+            bare.windows[0]
+                .panes
+                .iter()
+                .all(|p| p.command.is_empty() && p.setup.is_empty() && !p.exec)
         );
-        assert_eq!(
-            session.windows[0].panes[1].root.as_deref(),
-            Some("~/service-b")
+        assert_eq!(bare.windows[0].panes.len(), 2);
+    }
+
+    #[test]
+    fn test_group_with_validation() {
+        let session = |extra: &str| -> Session {
+            toml::from_str(&format!(
+                "name = \"dev-2\"\ngroup_with = \"dev\"\n{}",
+                extra
+            ))
+            .unwrap()
+        };
+
+        // Grouped sessions have no windows, and pick their startup window when created
+        assert!(session("startup_window = 3").validate().is_ok());
+        let own_windows = session("[[windows]]\nname = \"w\"\n[[windows.panes]]\n");
+        assert!(
+            own_windows
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("windows of its own")
+        );
+        assert!(
+            session("on_attach_send = { command = \"git fetch\" }")
+                .validate()
+                .is_err()
         );
+        let itself: Session = toml::from_str("name = \"dev\"\ngroup_with = \"dev\"").unwrap();
+        assert!(itself.validate().is_err());
+
+        let ungrouped: Session = toml::from_str("name = \"dev\"").unwrap();
+        assert!(ungrouped.validate().is_err());
     }
 
     #[test]
-    fn test_split_direction() {
-        let config: Config = toml::from_str(
+    fn test_with_params() {
+        let session: Session = toml::from_str(
             r#"
-[sessions.test]
-name = "test"
+name = "web"
+root = "{dir}"
+params = ["port", "dir"]
+on_create = ["docker compose -p web-{port} up -d"]
+
+[[windows]]
+name = "server"
+panes = [{ command = "npm run dev -- --port {port}", setup = ["echo {port}"] }, { command = "", root = "{dir}/logs" }]
+"#,
+        )
+        .unwrap();
+        session.validate().unwrap();
+
+        let values = HashMap::from([
+            ("port".to_string(), "3000".to_string()),
+            ("dir".to_string(), "~/src/app".to_string()),
+        ]);
+        let filled = session.with_params(&values).unwrap();
+        assert_eq!(filled.param_values["port"], "3000");
+        assert_eq!(filled.root, "~/src/app");
+        assert_eq!(filled.on_create, vec!["docker compose -p web-3000 up -d"]);
+        let panes = &filled.windows[0].panes;
+        assert_eq!(panes[0].command, "npm run dev -- --port 3000");
+        assert_eq!(panes[0].setup, vec!["echo 3000"]);
+        assert_eq!(panes[1].root.as_deref(), Some("~/src/app/logs"));
+
+        let missing = session
+            .with_params(&HashMap::from([("port".to_string(), "3000".to_string())]))
+            .unwrap_err();
+        assert!(missing.to_string().contains("--set dir=<value>"));
+
+        let mut unknown = values.clone();
+        unknown.insert("prot".to_string(), "1".to_string());
+        assert!(
+            session
+                .with_params(&unknown)
+                .unwrap_err()
+                .to_string()
+                .contains("'prot'")
+        );
+    }
 
-[[sessions.test.windows]]
-name = "win"
+    #[test]
+    fn test_pass_env() {
+        let session: Session = toml::from_str(
+            r#"
+name = "dev"
+pass_env = ["SSH_AUTH_SOCK", "AWS_*", "*_TOKEN"]
+windows = [{ name = "shell", panes = [{ command = "" }] }]
+"#,
+        )
+        .unwrap();
+        session.validate().unwrap();
+
+        let vars = [
+            ("SSH_AUTH_SOCK", "/tmp/agent.1"),
+            ("SSH_AGENT_PID", "1"),
+            ("AWS_PROFILE", "dev"),
+            ("AWS_", "empty suffix"),
+            ("GITHUB_TOKEN", "x"),
+            ("TOKEN", "no prefix"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let passed: Vec<_> = session
+            .passed_environment(vars)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(passed, ["AWS_", "AWS_PROFILE", "GITHUB_TOKEN", "SSH_AUTH_SOCK"]);
+
+        let invalid = Session {
+            pass_env: vec!["AWS-*".to_string()],
+            ..session
+        };
+        assert!(invalid.validate().unwrap_err().to_string().contains("pass_env"));
+    }
 
-[[sessions.test.windows.panes]]
+    #[test]
+    fn test_command_order() {
+        let window: Window = toml::from_str(
+            r#"
+name = "app"
+panes = [
+    { command = "npm run dev", after = "db" },
+    { command = "tail -f log" },
+    { command = "docker compose up db", title = "db", after = "migrate" },
+    { command = "make migrate-watch", title = "migrate" },
+]
+"#,
+        )
+        .unwrap();
+        window.validate().unwrap();
+        assert_eq!(window.command_order().unwrap(), [1, 3, 2, 0]);
+
+        let mut cycle = window.clone();
+        cycle.panes[3].after = Some("db".to_string());
+        assert!(cycle.validate().unwrap_err().to_string().contains("wait for each other"));
+
+        let mut unknown = window.clone();
+        unknown.panes[0].after = Some("cache".to_string());
+        let error = unknown.validate().unwrap_err().to_string();
+        assert!(error.contains("unknown pane 'cache'"));
+        assert!(error.contains("titled panes: db, migrate"));
+
+        let mut duplicate = window;
+        duplicate.panes[1].title = Some("db".to_string());
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn test_close_strategy_validation() {
+        let mut session: Session = toml::from_str(
+            r#"
+name = "dev"
+close_strategy = "graceful"
+shutdown_command = "docker compose down"
+close_timeout = 30
+
+[[windows]]
+name = "services"
+panes = [{ command = "docker compose up" }]
+"#,
+        )
+        .unwrap();
+        assert_eq!(session.close_strategy, CloseStrategy::Graceful);
+        session.validate().unwrap();
+
+        // shutdown_command and close_timeout have no effect when killing
+        session.close_strategy = CloseStrategy::Kill;
+        assert!(session.validate().is_err());
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let config: Config = toml::from_str(
+            r#"
+[workspaces.platform]
+sessions = ["api", "web"]
+attach = "web"
+
+[workspaces.broken]
+sessions = ["api", "missing"]
+
+[workspaces.stray]
+sessions = ["api"]
+attach = "web"
+
+[sessions.api]
+name = "api"
+[[sessions.api.windows]]
+name = "w"
+[[sessions.api.windows.panes]]
 command = ""
 
-[[sessions.test.windows.panes]]
+[sessions.web]
+name = "web"
+[[sessions.web.windows]]
+name = "w"
+[[sessions.web.windows.panes]]
 command = ""
-split = "horizontal"
 "#,
         )
         .unwrap();
 
-        let session = config.sessions.get("test").unwrap();
+        let platform = config.get_workspace("platform").unwrap();
+        assert_eq!(platform.attach_session(), "web");
+        assert!(platform.validate("platform", &config).is_ok());
+        assert!(config.workspaces["broken"].validate("broken", &config).is_err());
+        assert!(config.workspaces["stray"].validate("stray", &config).is_err());
+        assert!(config.get_workspace("nope").is_err());
+    }
+
+    #[test]
+    fn test_exec_pane_validation() {
+        let window = |pane: &str| -> Window {
+            toml::from_str(&format!("name = \"w\"\n[[panes]]\n{}", pane)).unwrap()
+        };
+
+        assert!(window("command = \"htop\"\nexec = true").validate().is_ok());
+        assert!(window("exec = true").validate().is_err());
+        assert!(
+            window("command = \"npm start\"\nexec = true\nsetup = [\"nvm use\"]")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            window("command = \"htop\"\nexec = true\nlogin_shell = false")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_close_on_exit() {
+        let window: Window = toml::from_str(
+            "name = \"edit\"\nclose_on_exit = \"session\"\n[[panes]]\ncommand = \"nvim\"",
+        )
+        .unwrap();
+        assert_eq!(window.close_on_exit, Some(CloseOnExit::Session));
+        assert!(window.validate().is_ok());
+
+        let window: Window =
+            toml::from_str("name = \"edit\"\nclose_on_exit = \"window\"\n[[panes]]\n").unwrap();
+        assert!(window.validate().is_err());
+    }
+
+    #[test]
+    fn test_send_enter_with_exit_hooks() {
+        let validate = |window: &str| {
+            toml::from_str::<Window>(&format!("name = \"edit\"\n{}", window))
+                .unwrap()
+                .validate()
+        };
+
+        let err =
+            validate("[[panes]]\ncommand = \"make\"\nsend_enter = false\nnotify_on_exit = true")
+                .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("send_enter = false with notify_on_exit")
+        );
+
+        let err = validate(
+            "close_on_exit = \"window\"\n[[panes]]\ncommand = \"nvim\"\nsend_enter = false",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("send_enter = false with close_on_exit")
+        );
+
+        // close_on_exit only watches the first pane
+        assert!(
+            validate(
+                "close_on_exit = \"window\"\n[[panes]]\ncommand = \"nvim\"\n\
+             [[panes]]\ncommand = \"make\"\nsend_enter = false"
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_include_sessions() {
+        let session = |name: &str| -> Session {
+            toml::from_str(&format!(
+                "name = \"{}\"\n[[windows]]\nname = \"w\"\n[[windows.panes]]\n",
+                name
+            ))
+            .unwrap()
+        };
+        let mut config: Config = toml::from_str("").unwrap();
+        config.sessions.insert("api".to_string(), session("api"));
+        config.include_sessions(
+            "team",
+            vec![
+                ("api".to_string(), session("api")),
+                ("web".to_string(), session("web")),
+            ],
+        );
+
+        assert_eq!(config.session_ids(), ["api", "team:api", "web"]);
+        assert_eq!(config.session_source("api"), "global");
+        assert_eq!(config.session_source("team:api"), "team");
+        assert_eq!(config.sessions["team:api"].name, "team-api");
+
+        assert_eq!(config.session_id_for("global:api"), Some("api"));
+        assert_eq!(config.session_id_for("team:api"), Some("team:api"));
+        assert_eq!(config.session_id_for("team:web"), Some("web"));
+        assert_eq!(config.session_id_for("global:web"), None);
+        assert_eq!(config.get_session("team-api").unwrap().name, "team-api");
+    }
+
+    #[test]
+    fn test_find_project_config() {
+        let dir = std::env::temp_dir().join(format!("tmx-project-{}", std::process::id()));
+        let repo = dir.join("repo");
+        let nested = repo.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+
+        // The search stops at the git root
+        fs::write(dir.join(PROJECT_CONFIG_FILE), "").unwrap();
+        assert_eq!(Config::find_project_config(&nested), None);
+
+        fs::write(repo.join(PROJECT_CONFIG_FILE), "").unwrap();
         assert_eq!(
-            session.windows[0].panes[1].split.as_deref(),
-            Some("horizontal")
+            Config::find_project_config(&nested),
+            Some(repo.join(PROJECT_CONFIG_FILE))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_include_project() {
+        let dir = std::env::temp_dir().join(format!("tmx-include-project-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(PROJECT_CONFIG_FILE);
+        fs::write(
+            &path,
+            "default = \"dev\"\n\
+             [sessions.dev]\nname = \"dev\"\nroot = \"web\"\n\
+             [[sessions.dev.windows]]\nname = \"w\"\n[[sessions.dev.windows.panes]]\n",
+        )
+        .unwrap();
+
+        let mut config: Config = toml::from_str(
+            "[sessions.dev]\nname = \"dev\"\n[[sessions.dev.windows]]\nname = \"w\"\n\
+             [[sessions.dev.windows.panes]]\n",
+        )
+        .unwrap();
+        config.include_project(&path).unwrap();
+
+        assert_eq!(config.session_ids(), ["dev", "global:dev"]);
+        assert_eq!(config.session_source("dev"), PROJECT_SOURCE);
+        assert_eq!(config.session_source("global:dev"), CONFIG_SOURCE);
+        assert_eq!(
+            config.sessions["dev"].root,
+            dir.join("web").to_string_lossy()
         );
+        assert_eq!(config.sessions["global:dev"].name, "global-dev");
+        assert_eq!(config.session_id_for("global:dev"), Some("global:dev"));
+        assert!(matches!(config.default, Some(DefaultSession::One(ref id)) if id == "dev"));
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::backup;
+use crate::messages;
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+/// Format-preserving editor for the config file.
+///
+/// Edits are made with toml_edit so comments and formatting elsewhere in the
+/// file survive. Inline `windows = [...]` arrays are converted to
+/// `[[sessions.<id>.windows]]` tables when a window is added or removed.
+pub struct ConfigEditor {
+    path: PathBuf,
+    doc: DocumentMut,
+}
+
+impl ConfigEditor {
+    /// Open a config file for editing.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not valid TOML.
+    pub fn open(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::parse(path, &content)
+    }
+
+    /// Create an editor from config contents (the path is used when saving)
+    fn parse(path: &Path, content: &str) -> Result<Self> {
+        let doc = content
+            .parse::<DocumentMut>()
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            doc,
+        })
+    }
+
+    /// Write the edited config back to its file, backing up the previous version first
+    pub fn save(&self) -> Result<()> {
+        if let Some(backup) = backup::create(&self.path)? {
+            messages::say(messages::CONFIG_BACKED_UP, &[("path", &backup.display())]);
+        }
+
+        fs::write(&self.path, self.doc.to_string())
+            .with_context(|| format!("Failed to write config file: {}", self.path.display()))
+    }
+
+    /// The TOML document being edited
+    pub fn document(&self) -> &DocumentMut {
+        &self.doc
+    }
+
+    /// The TOML document being edited, for edits not covered by the methods here
+    pub fn document_mut(&mut self) -> &mut DocumentMut {
+        &mut self.doc
+    }
+
+    /// Add a session (a `[sessions.<id>]` table) at the end of the file.
+    ///
+    /// # Errors
+    /// Returns an error if a session with this ID already exists.
+    pub fn append_session(&mut self, id: &str, session: Table) -> Result<()> {
+        let sessions = self
+            .doc
+            .entry("sessions")
+            .or_insert_with(|| {
+                let mut sessions = Table::new();
+                sessions.set_implicit(true);
+                Item::Table(sessions)
+            })
+            .as_table_like_mut()
+            .context("sessions in the config file is not a table")?;
+
+        if sessions.contains_key(id) {
+            anyhow::bail!(
+                "Session '{}' already exists in the config file\n  \
+                 Hint: Choose another ID with --id",
+                id
+            );
+        }
+        sessions.insert(id, Item::Table(session));
+        Ok(())
+    }
+
+    /// Set a named layout in the `[layouts]` table, creating the table if needed.
+    ///
+    /// # Returns
+    /// Whether a layout with this name was replaced.
+    pub fn set_layout(&mut self, name: &str, layout: &str) -> Result<bool> {
+        let layouts = self
+            .doc
+            .entry("layouts")
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .context("layouts in the config file is not a table")?;
+
+        let replaced = layouts.contains_key(name);
+        layouts.insert(name, toml_edit::value(layout));
+        Ok(replaced)
+    }
+
+    /// Rename the window at `offset` in session `id`
+    pub fn rename_window(&mut self, id: &str, offset: usize, name: &str) -> Result<()> {
+        let window = self.window_mut(id, offset)?;
+        window["name"] = toml_edit::value(name);
+        Ok(())
+    }
+
+    /// Remove the window at `offset` from session `id`
+    pub fn remove_window(&mut self, id: &str, offset: usize) -> Result<()> {
+        let windows = self.windows_mut(id)?;
+        if offset >= windows.len() {
+            anyhow::bail!("Session '{}' has no window {}", id, offset);
+        }
+        windows.remove(offset);
+        Ok(())
+    }
+
+    /// Append a window with `pane_count` empty panes to session `id`
+    pub fn append_window(&mut self, id: &str, name: &str, pane_count: usize) -> Result<()> {
+        let mut panes = ArrayOfTables::new();
+        for _ in 0..pane_count.max(1) {
+            panes.push(empty_pane_table());
+        }
+
+        let mut window = Table::new();
+        window["name"] = toml_edit::value(name);
+        window.insert("panes", Item::ArrayOfTables(panes));
+
+        self.windows_mut(id)?.push(window);
+        Ok(())
+    }
+
+    /// Grow or shrink the panes list of the window at `offset` to `count` panes.
+    ///
+    /// New panes are appended with an empty command; extra panes are removed
+    /// from the end.
+    pub fn set_pane_count(&mut self, id: &str, offset: usize, count: usize) -> Result<()> {
+        let window = self.window_mut(id, offset)?;
+        let panes = window
+            .get_mut("panes")
+            .with_context(|| format!("Window {} of session '{}' has no panes", offset, id))?;
+
+        match panes {
+            Item::ArrayOfTables(panes) => {
+                while panes.len() > count {
+                    panes.remove(panes.len() - 1);
+                }
+                while panes.len() < count {
+                    panes.push(empty_pane_table());
+                }
+            }
+            Item::Value(Value::Array(panes)) => {
+                while panes.len() > count {
+                    panes.remove(panes.len() - 1);
+                }
+                while panes.len() < count {
+                    let mut pane = InlineTable::new();
+                    pane.insert("command", "".into());
+                    panes.push(pane);
+                }
+            }
+            _ => anyhow::bail!("panes of window {} in session '{}' is not an array", offset, id),
+        }
+
+        Ok(())
+    }
+
+    /// Get the windows of a session as an array of tables
+    fn windows_mut(&mut self, id: &str) -> Result<&mut ArrayOfTables> {
+        let session = self
+            .doc
+            .get_mut("sessions")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|sessions| sessions.get_mut(id))
+            .and_then(Item::as_table_like_mut)
+            .with_context(|| format!("Session '{}' not found in config file", id))?;
+
+        let windows = session
+            .get_mut("windows")
+            .with_context(|| format!("Session '{}' has no windows", id))?;
+
+        // Normalize `windows = [{ ... }]` to `[[sessions.<id>.windows]]`
+        if windows.is_value() {
+            let inline = std::mem::take(windows);
+            *windows = inline
+                .into_array_of_tables()
+                .map(Item::ArrayOfTables)
+                .map_err(|_| anyhow::anyhow!("windows of session '{}' is not an array of tables", id))?;
+        }
+
+        windows
+            .as_array_of_tables_mut()
+            .with_context(|| format!("windows of session '{}' is not an array of tables", id))
+    }
+
+    /// Get the window at `offset` in session `id`
+    fn window_mut(&mut self, id: &str, offset: usize) -> Result<&mut Table> {
+        self.windows_mut(id)?
+            .get_mut(offset)
+            .with_context(|| format!("Session '{}' has no window {}", id, offset))
+    }
+}
+
+impl std::fmt::Display for ConfigEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+/// A `[[...panes]]` table with an empty command
+fn empty_pane_table() -> Table {
+    let mut pane = Table::new();
+    pane["command"] = toml_edit::value("");
+    pane
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const CONFIG: &str = r#"# My sessions
+[sessions.dev]
+name = "dev"
+
+[[sessions.dev.windows]]
+name = "editor"   # keep this comment
+
+[[sessions.dev.windows.panes]]
+command = "nvim"
+
+[[sessions.dev.windows]]
+name = "shell"
+panes = [{ command = "" }, { command = "htop" }]
+"#;
+
+    fn edited(edit: impl FnOnce(&mut ConfigEditor)) -> (String, Config) {
+        let mut editor = ConfigEditor::parse(Path::new("tmx.toml"), CONFIG).unwrap();
+        edit(&mut editor);
+        let content = editor.to_string();
+        let config = toml::from_str(&content).unwrap();
+        (content, config)
+    }
+
+    #[test]
+    fn test_rename_window_preserves_comments() {
+        let (content, config) = edited(|e| e.rename_window("dev", 0, "code").unwrap());
+        assert!(content.contains("# My sessions"));
+        assert_eq!(config.sessions["dev"].windows[0].name, "code");
+    }
+
+    #[test]
+    fn test_append_and_remove_window() {
+        let (_, config) = edited(|e| {
+            e.append_window("dev", "logs", 2).unwrap();
+            e.remove_window("dev", 0).unwrap();
+        });
+        let windows = &config.sessions["dev"].windows;
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].name, "shell");
+        assert_eq!(windows[1].name, "logs");
+        assert_eq!(windows[1].panes.len(), 2);
+    }
+
+    #[test]
+    fn test_set_pane_count() {
+        let (_, config) = edited(|e| {
+            e.set_pane_count("dev", 0, 3).unwrap();
+            e.set_pane_count("dev", 1, 1).unwrap();
+        });
+        let windows = &config.sessions["dev"].windows;
+        assert_eq!(windows[0].panes.len(), 3);
+        assert_eq!(windows[0].panes[0].command, "nvim");
+        assert_eq!(windows[1].panes.len(), 1);
+    }
+
+    #[test]
+    fn test_append_session() {
+        let mut session = Table::new();
+        session["name"] = toml_edit::value("scratch");
+        let mut window = Table::new();
+        window["name"] = toml_edit::value("main");
+        let mut panes = ArrayOfTables::new();
+        panes.push(empty_pane_table());
+        window.insert("panes", Item::ArrayOfTables(panes));
+        let mut windows = ArrayOfTables::new();
+        windows.push(window);
+        session.insert("windows", Item::ArrayOfTables(windows));
+
+        let (content, config) = edited(|e| {
+            e.append_session("scratch", session.clone()).unwrap();
+            assert!(e.append_session("dev", session).is_err());
+        });
+        assert!(content.ends_with(
+            "[sessions.scratch]\nname = \"scratch\"\n\n\
+             [[sessions.scratch.windows]]\nname = \"main\"\n\n\
+             [[sessions.scratch.windows.panes]]\ncommand = \"\"\n"
+        ));
+        assert_eq!(config.sessions["scratch"].windows[0].name, "main");
+        assert_eq!(config.sessions["dev"].windows.len(), 2);
+    }
+
+    #[test]
+    fn test_set_layout() {
+        let layout = "b25d,80x24,0,0,0";
+        let (content, config) = edited(|e| {
+            assert!(!e.set_layout("wide", "main-vertical").unwrap());
+            assert!(e.set_layout("wide", layout).unwrap());
+        });
+        assert!(content.starts_with("# My sessions\n"));
+        assert!(content.ends_with(&format!("[layouts]\nwide = \"{}\"\n", layout)));
+        assert_eq!(config.layouts["wide"], layout);
+    }
+
+    #[test]
+    fn test_unknown_session() {
+        let mut editor = ConfigEditor::parse(Path::new("tmx.toml"), CONFIG).unwrap();
+        assert!(editor.rename_window("nope", 0, "x").is_err());
+        assert!(editor.rename_window("dev", 5, "x").is_err());
+    }
+}
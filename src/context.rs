@@ -1,9 +1,10 @@
 use anyhow::Result;
 use once_cell::sync::OnceCell;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::config::Config;
-use crate::tmux;
+use crate::config::{CONFIG_SOURCE, Config, PROJECT_SOURCE, Session};
+use crate::overrides::Override;
+use crate::trust;
 
 /// Shared context for commands containing configuration and cached state.
 ///
@@ -14,12 +15,17 @@ pub struct Context {
     config: OnceCell<Config>,
     /// Path to config file (resolved from CLI arg > env var > default)
     config_path: PathBuf,
+    /// The project's `.tmx.toml` found from the current directory, merged
+    /// into the config once the user trusts it (not looked for with --config)
+    project_config_path: Option<PathBuf>,
+    /// Values replaced in the config for this invocation (from --set)
+    overrides: Vec<Override>,
     /// Whether to print debug/verbose output (from -v flag)
     verbose: bool,
     /// Whether we're running inside a tmux session (checked once at startup)
     pub is_inside_tmux: bool,
-    /// Cached tmux base-index (lazy-loaded)
-    base_index: OnceCell<usize>,
+    /// Socket path of the tmux server we're running inside (from TMUX)
+    pub tmux_socket: Option<PathBuf>,
 }
 
 impl Context {
@@ -27,12 +33,23 @@ impl Context {
     ///
     /// This reads all environment variables at startup:
     /// - TMX_CONFIG_PATH: Custom config path
-    /// - TMUX: Whether we're inside tmux
+    /// - TMUX: Whether we're inside tmux, and the server's socket
+    ///
+    /// Unless a config file is given with --config, it also looks for a
+    /// project config (`.tmx.toml`) in the current directory and its parents.
     ///
     /// # Arguments
     /// * `config_path` - Optional config path from CLI --config flag
     /// * `verbose` - Whether to enable verbose/debug output (from -v flag)
     pub fn new(config_path: Option<String>, verbose: bool) -> Result<Self> {
+        // A config given on the command line is used on its own
+        let project_config_path = match config_path {
+            Some(_) => None,
+            None => std::env::current_dir()
+                .ok()
+                .and_then(|cwd| Config::find_project_config(&cwd)),
+        };
+
         // Resolve config path from: CLI arg > TMX_CONFIG_PATH env > default
         let resolved_path = if let Some(path) = config_path {
             PathBuf::from(shellexpand::tilde(&path).to_string())
@@ -44,37 +61,84 @@ impl Context {
         };
 
         // Check if we're inside tmux (read TMUX env var once)
-        let is_inside_tmux = std::env::var("TMUX").is_ok();
+        let tmux_env = std::env::var("TMUX").ok();
+        let is_inside_tmux = tmux_env.is_some();
+        // TMUX is "<socket path>,<server pid>,<session index>"
+        let tmux_socket = tmux_env
+            .as_deref()
+            .and_then(|v| v.rsplitn(3, ',').last())
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
 
         Ok(Self {
             config: OnceCell::new(),
             config_path: resolved_path,
+            project_config_path,
+            overrides: Vec::new(),
             verbose,
             is_inside_tmux,
-            base_index: OnceCell::new(),
+            tmux_socket,
         })
     }
 
+    /// Apply `--set` overrides to the configuration when it is loaded.
+    ///
+    /// # Arguments
+    /// * `overrides` - Values to replace in the config file, for this invocation only
+    pub fn with_overrides(mut self, overrides: Vec<Override>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// A context that merges the given project config instead of the one
+    /// found at startup, e.g. for a project created by this invocation.
+    ///
+    /// # Arguments
+    /// * `project_config_path` - The project's `.tmx.toml`
+    pub fn for_project(&self, project_config_path: PathBuf) -> Self {
+        Self {
+            config: OnceCell::new(),
+            config_path: self.config_path.clone(),
+            project_config_path: Some(project_config_path),
+            overrides: self.overrides.clone(),
+            verbose: self.verbose,
+            is_inside_tmux: self.is_inside_tmux,
+            tmux_socket: self.tmux_socket.clone(),
+        }
+    }
+
+    /// A context that loads the configuration again, e.g. after this
+    /// invocation added sessions to it.
+    pub fn reloaded(&self) -> Self {
+        Self {
+            config: OnceCell::new(),
+            config_path: self.config_path.clone(),
+            project_config_path: self.project_config_path.clone(),
+            overrides: self.overrides.clone(),
+            verbose: self.verbose,
+            is_inside_tmux: self.is_inside_tmux,
+            tmux_socket: self.tmux_socket.clone(),
+        }
+    }
+
     /// Get the configuration, loading it lazily on first access.
     ///
+    /// The project config is only merged in if the user trusts it (see
+    /// [`trust::check`]), as it can run commands.
+    ///
     /// # Returns
     /// A reference to the loaded configuration.
     ///
     /// # Errors
     /// Returns an error if the config file cannot be read or parsed.
     pub fn config(&self) -> Result<&Config> {
-        self.config
-            .get_or_try_init(|| Config::load_from(&self.config_path))
-    }
-
-    /// Get the tmux base-index, caching it after the first query.
-    ///
-    /// # Returns
-    /// The base-index value (typically 0 or 1).
-    pub fn base_index(&self) -> Result<usize> {
-        self.base_index
-            .get_or_try_init(tmux::get_base_index)
-            .copied()
+        self.config.get_or_try_init(|| {
+            let project = match self.project_config_path.as_deref() {
+                Some(path) if trust::check(path)? => Some(path),
+                _ => None,
+            };
+            Config::load_with_project(&self.config_path, project, &self.overrides)
+        })
     }
 
     /// Check if verbose/debug mode is enabled.
@@ -85,8 +149,47 @@ impl Context {
     }
 
     /// Get the config path (useful for displaying to user).
-    #[allow(dead_code)]
     pub fn config_path(&self) -> &PathBuf {
         &self.config_path
     }
+
+    /// Get the path of the project config found at startup, if any (merged
+    /// into the configuration once trusted).
+    pub fn project_config_path(&self) -> Option<&PathBuf> {
+        self.project_config_path.as_ref()
+    }
+
+    /// Get the file a configured session is defined in, and its ID there.
+    ///
+    /// # Returns
+    /// None for sessions from shared config repositories, which tmx doesn't edit.
+    pub fn session_file(&self, id: &str) -> Option<(&Path, String)> {
+        let source = self.config().ok()?.session_source(id);
+        let path = match source {
+            CONFIG_SOURCE => self.config_path.as_path(),
+            PROJECT_SOURCE => self.project_config_path.as_deref()?,
+            _ => return None,
+        };
+        // Shadowed sessions are known as `<source>:<id>`
+        let key = id
+            .strip_prefix(source)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .unwrap_or(id);
+        Some((path, key.to_string()))
+    }
+
+    /// Get the directory relative paths in a session's config are resolved
+    /// against: the directory of the file that defines it.
+    pub fn session_config_dir(&self, session: &Session) -> PathBuf {
+        let project = self.config().ok().is_some_and(|config| {
+            config
+                .session_id_for(&session.name)
+                .is_some_and(|id| config.session_source(id) == PROJECT_SOURCE)
+        });
+        let file = match &self.project_config_path {
+            Some(path) if project => path,
+            _ => &self.config_path,
+        };
+        file.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
 }
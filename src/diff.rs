@@ -0,0 +1,284 @@
+use crate::config::Session;
+use crate::table::{Cell, Style, Table};
+use crate::tmux::query::LiveSession;
+
+/// A structural difference between a session's config and its running state.
+///
+/// Windows are matched by position: config window `offset` corresponds to
+/// the live window at tmux index `base_index + offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// A configured window with no live window at its index
+    MissingWindow { offset: usize, name: String },
+    /// A live window with no configured window at its index
+    ExtraWindow {
+        index: usize,
+        name: String,
+        pane_count: usize,
+    },
+    /// The live window at a configured window's index has a different name
+    WindowName {
+        offset: usize,
+        index: usize,
+        config_name: String,
+        live_name: String,
+    },
+    /// The live window has a different number of panes than configured
+    PaneCount {
+        offset: usize,
+        index: usize,
+        name: String,
+        config_count: usize,
+        live_count: usize,
+    },
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::MissingWindow { offset, name } => {
+                write!(f, "Window '{}' (#{}) is configured but not running", name, offset)
+            }
+            Difference::ExtraWindow {
+                index,
+                name,
+                pane_count,
+            } => write!(
+                f,
+                "Window '{}' (index {}, {} pane(s)) is running but not configured",
+                name, index, pane_count
+            ),
+            Difference::WindowName {
+                index,
+                config_name,
+                live_name,
+                ..
+            } => write!(
+                f,
+                "Window at index {} is named '{}' in config but '{}' in tmux",
+                index, config_name, live_name
+            ),
+            Difference::PaneCount {
+                name,
+                config_count,
+                live_count,
+                ..
+            } => write!(
+                f,
+                "Window '{}' has {} pane(s) in config but {} running",
+                name, config_count, live_count
+            ),
+        }
+    }
+}
+
+/// Compute the differences between a session's config and its live state.
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `live` - The running session as queried from tmux
+/// * `base_index` - The effective base-index of the running session
+pub fn diff_session(session: &Session, live: &LiveSession, base_index: usize) -> Vec<Difference> {
+    let mut diffs = Vec::new();
+
+    // The windows of a grouped session are configured in its group
+    if session.group_with.is_some() {
+        return diffs;
+    }
+
+    for (offset, window) in session.windows.iter().enumerate() {
+        let index = base_index + offset;
+        let Some(live_window) = live.window(index) else {
+            diffs.push(Difference::MissingWindow {
+                offset,
+                name: window.name.clone(),
+            });
+            continue;
+        };
+
+        if live_window.name != window.name {
+            diffs.push(Difference::WindowName {
+                offset,
+                index,
+                config_name: window.name.clone(),
+                live_name: live_window.name.clone(),
+            });
+        }
+
+        if live_window.panes.len() != window.panes.len() {
+            diffs.push(Difference::PaneCount {
+                offset,
+                index,
+                name: window.name.clone(),
+                config_count: window.panes.len(),
+                live_count: live_window.panes.len(),
+            });
+        }
+    }
+
+    let configured = base_index..base_index + session.windows.len();
+    for live_window in live.windows.iter().filter(|w| !configured.contains(&w.index)) {
+        diffs.push(Difference::ExtraWindow {
+            index: live_window.index,
+            name: live_window.name.clone(),
+            pane_count: live_window.panes.len(),
+        });
+    }
+
+    diffs
+}
+
+/// Summarize differences as a table of windows and their config/tmux state.
+///
+/// # Arguments
+/// * `diffs` - Differences as returned by `diff_session`
+/// * `base_index` - The base-index the differences were computed with
+pub fn differences_table(diffs: &[Difference], base_index: usize) -> Table {
+    let mut table = Table::new(&["INDEX", "WINDOW", "CONFIG", "TMUX"]);
+    for difference in diffs {
+        let (index, name, config, live) = match difference {
+            Difference::MissingWindow { offset, name } => (
+                base_index + offset,
+                name.clone(),
+                Cell::from("present"),
+                Cell::new("missing", Style::Red),
+            ),
+            Difference::ExtraWindow {
+                index,
+                name,
+                pane_count,
+            } => (
+                *index,
+                name.clone(),
+                Cell::new("missing", Style::Red),
+                Cell::from(format!("{} pane(s)", pane_count)),
+            ),
+            Difference::WindowName {
+                index,
+                config_name,
+                live_name,
+                ..
+            } => (
+                *index,
+                config_name.clone(),
+                Cell::from(format!("named '{}'", config_name)),
+                Cell::new(format!("named '{}'", live_name), Style::Yellow),
+            ),
+            Difference::PaneCount {
+                index,
+                name,
+                config_count,
+                live_count,
+                ..
+            } => (
+                *index,
+                name.clone(),
+                Cell::from(format!("{} pane(s)", config_count)),
+                Cell::new(format!("{} pane(s)", live_count), Style::Yellow),
+            ),
+        };
+        table.row(vec![index.to_string().into(), name.into(), config, live]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::tmux::query::{LivePane, LiveWindow};
+
+    fn live_window(index: usize, name: &str, panes: usize) -> LiveWindow {
+        LiveWindow {
+            id: format!("@{}", index),
+            index,
+            name: name.to_string(),
+            layout: String::new(),
+            active: false,
+            panes: (0..panes)
+                .map(|i| LivePane {
+                    id: format!("%{}", i),
+                    index: i,
+                    active: false,
+                    dead: false,
+                    current_command: "zsh".to_string(),
+                    cwd: "/tmp".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_session() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.test]
+name = "test"
+
+[[sessions.test.windows]]
+name = "editor"
+panes = [{ command = "nvim" }, { command = "" }]
+
+[[sessions.test.windows]]
+name = "shell"
+panes = [{ command = "" }]
+
+[[sessions.test.windows]]
+name = "logs"
+panes = [{ command = "" }]
+"#,
+        )
+        .unwrap();
+        let session = &config.sessions["test"];
+
+        let live = LiveSession {
+            id: "$1".to_string(),
+            name: "test".to_string(),
+            windows: vec![
+                live_window(1, "editor", 3),
+                live_window(2, "bash", 1),
+                live_window(5, "scratch", 1),
+            ],
+        };
+
+        let diffs = diff_session(session, &live, 1);
+        assert_eq!(
+            diffs,
+            vec![
+                Difference::PaneCount {
+                    offset: 0,
+                    index: 1,
+                    name: "editor".to_string(),
+                    config_count: 2,
+                    live_count: 3,
+                },
+                Difference::WindowName {
+                    offset: 1,
+                    index: 2,
+                    config_name: "shell".to_string(),
+                    live_name: "bash".to_string(),
+                },
+                Difference::MissingWindow {
+                    offset: 2,
+                    name: "logs".to_string(),
+                },
+                Difference::ExtraWindow {
+                    index: 5,
+                    name: "scratch".to_string(),
+                    pane_count: 1,
+                },
+            ]
+        );
+
+        assert_eq!(
+            differences_table(&diffs, 1).render_with(false),
+            "\
+INDEX  WINDOW   CONFIG         TMUX
+1      editor   2 pane(s)      3 pane(s)
+2      shell    named 'shell'  named 'bash'
+3      logs     present        missing
+5      scratch  missing        1 pane(s)
+"
+        );
+    }
+}
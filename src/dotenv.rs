@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Read and parse a dotenv file.
+///
+/// # Returns
+/// The variables in file order (a later assignment of the same key wins when applied).
+pub fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read env file: {}", path.display()))?;
+    parse(&content).with_context(|| format!("Failed to parse env file: {}", path.display()))
+}
+
+/// Parse dotenv content.
+///
+/// Supports `KEY=value` lines with an optional `export ` prefix, `#` comments,
+/// blank lines, single-quoted values (taken literally), double-quoted values
+/// (with `\n`, `\t`, `\"` and `\\` escapes, may span lines) and unquoted values
+/// (trailing ` # comment` removed). Variables are not interpolated.
+pub fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((line_idx, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+        let (key, raw_value) = line.split_once('=').with_context(|| {
+            format!(
+                "Line {}: expected KEY=value\n  Found: '{}'",
+                line_idx + 1,
+                line
+            )
+        })?;
+
+        let key = key.trim();
+        if !is_valid_key(key) {
+            anyhow::bail!(
+                "Line {}: invalid variable name '{}'\n  \
+                 Hint: Names may contain letters, digits and underscores, and can't start with a digit",
+                line_idx + 1,
+                key
+            );
+        }
+
+        let raw_value = raw_value.trim_start();
+        let value = if let Some(rest) = raw_value.strip_prefix('\'') {
+            let (value, _) = rest.split_once('\'').with_context(|| {
+                format!("Line {}: unterminated single-quoted value", line_idx + 1)
+            })?;
+            value.to_string()
+        } else if let Some(rest) = raw_value.strip_prefix('"') {
+            // Double-quoted values may continue on the following lines
+            let mut quoted = rest.to_string();
+            loop {
+                if let Some(end) = closing_quote(&quoted) {
+                    quoted.truncate(end);
+                    break;
+                }
+                let (_, next) = lines.next().with_context(|| {
+                    format!("Line {}: unterminated double-quoted value", line_idx + 1)
+                })?;
+                quoted.push('\n');
+                quoted.push_str(next);
+            }
+            unescape(&quoted)
+        } else {
+            let value = match raw_value.find(" #") {
+                Some(comment) => &raw_value[..comment],
+                None => raw_value,
+            };
+            value.trim_end().to_string()
+        };
+
+        vars.push((key.to_string(), value));
+    }
+
+    Ok(vars)
+}
+
+/// Check that a variable name is a valid shell identifier
+pub fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Byte offset of the first unescaped `"`
+fn closing_quote(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Expand the escapes supported in double-quoted values
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# Database
+DB_HOST=localhost
+export DB_PORT = 5432
+PLAIN=value # trailing comment
+SINGLE='literal $HOME \n'
+DOUBLE="line1\nline2 \"quoted\""
+MULTI="first
+second"
+EMPTY=
+"#;
+        assert_eq!(
+            parse(content).unwrap(),
+            pairs(&[
+                ("DB_HOST", "localhost"),
+                ("DB_PORT", "5432"),
+                ("PLAIN", "value"),
+                ("SINGLE", "literal $HOME \\n"),
+                ("DOUBLE", "line1\nline2 \"quoted\""),
+                ("MULTI", "first\nsecond"),
+                ("EMPTY", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_errors() {
+        assert!(parse("NO_EQUALS").is_err());
+        assert!(parse("1BAD=x").is_err());
+        assert!(parse("OPEN=\"never closed").is_err());
+        assert!(parse("OPEN='never closed").is_err());
+    }
+}
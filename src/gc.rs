@@ -0,0 +1,127 @@
+//! Cleanup of what tmx keeps on disk, so it doesn't grow without bound.
+//!
+//! A pass drops state records of sessions that no longer exist, removes
+//! config backups older than `[gc] backup_retention_days` and trims the log
+//! file to `[log] max_size_kb`. `tmx gc` runs one; with `[gc] auto` (the
+//! default), tmx also runs one when it starts, at most once a day.
+
+use crate::backup;
+use crate::config::{GcConfig, LogConfig};
+use crate::log;
+use crate::state::{self, State};
+use crate::tmux;
+use anyhow::Result;
+use std::time::{Duration, SystemTime};
+
+/// Minimum time between automatic passes
+const AUTO_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// What a pass cleaned up
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Sessions whose records were dropped from the state file
+    pub sessions: Vec<String>,
+    /// Number of config backups removed
+    pub backups: usize,
+    /// Bytes trimmed from the log file
+    pub log_bytes: u64,
+}
+
+impl Report {
+    /// Whether the pass found nothing to clean up
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.backups == 0 && self.log_bytes == 0
+    }
+}
+
+/// Run a cleanup pass.
+///
+/// Session records are only checked against the tmux server when tmux is
+/// installed; a server that isn't running has no sessions, so all records
+/// are stale then.
+///
+/// # Arguments
+/// * `gc` - Retention settings (`[gc]`)
+/// * `log_config` - Log settings (`[log]`), for the log size cap
+///
+/// # Errors
+/// Returns an error if tmux can't be asked for its sessions or a file can't
+/// be removed or rewritten.
+pub fn run(gc: &GcConfig, log_config: &LogConfig) -> Result<Report> {
+    let mut report = Report::default();
+
+    let running = if tmux::is_installed() {
+        Some(tmux::list_sessions()?)
+    } else {
+        None
+    };
+    report.sessions = State::update(|state| {
+        state.last_gc = state::now();
+        let Some(running) = running else {
+            return Vec::new();
+        };
+        state.prune_sessions(|name| {
+            running
+                .iter()
+                .any(|s| s == name)
+                .then(|| tmux::session_created(name).ok())
+                .flatten()
+        })
+    })?;
+
+    if gc.backup_retention_days > 0 {
+        let retention = Duration::from_secs(gc.backup_retention_days * SECS_PER_DAY);
+        if let Some(cutoff) = SystemTime::now().checked_sub(retention) {
+            report.backups = backup::prune(cutoff)?;
+        }
+    }
+
+    if log_config.max_size_kb > 0 {
+        report.log_bytes = log::enforce_size_cap(log_config.max_size_kb * 1024)?;
+    }
+
+    log::info(&format!(
+        "gc: dropped {} session record(s), removed {} backup(s), trimmed {} log byte(s)",
+        report.sessions.len(),
+        report.backups,
+        report.log_bytes
+    ));
+    Ok(report)
+}
+
+/// Run a cleanup pass on startup if `[gc] auto` is on and the last pass was
+/// a day ago or more. Failures are logged, never shown or returned.
+///
+/// # Arguments
+/// * `gc` - Retention settings (`[gc]`)
+/// * `log_config` - Log settings (`[log]`), for the log size cap
+pub fn run_if_due(gc: &GcConfig, log_config: &LogConfig) {
+    if !gc.auto || !is_due(State::load().last_gc, state::now()) {
+        return;
+    }
+    if let Err(e) = run(gc, log_config) {
+        log::error(&format!("gc: {}", e));
+    }
+}
+
+/// Whether an automatic pass is due, given when the last one ran
+fn is_due(last_gc: u64, now: u64) -> bool {
+    now.saturating_sub(last_gc) >= AUTO_INTERVAL_SECS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due() {
+        let now = 1_800_000_000;
+        assert!(is_due(0, now));
+        assert!(is_due(now - AUTO_INTERVAL_SECS, now));
+        assert!(!is_due(now - 60, now));
+        // A clock set back doesn't make it due
+        assert!(!is_due(now + 60, now));
+    }
+}
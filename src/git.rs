@@ -0,0 +1,76 @@
+//! Running git for shared config repositories and ticket worktrees.
+
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+
+use crate::log;
+
+/// Run git, failing with its stderr if it reports an error
+pub fn run(args: &[&str]) -> Result<()> {
+    log::info(&format!("git {}", args.join(" ")));
+    let output = Command::new("git").args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("git is not installed\n  Hint: Install git and make sure it is on PATH")
+        } else {
+            anyhow::anyhow!("Failed to run git: {}", e)
+        }
+    })?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed\n  {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Whether a local branch exists in a repository
+pub fn branch_exists(repo: &Path, branch: &str) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{}", branch))
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// The branch checked out in a directory's repository, or the abbreviated
+/// commit on a detached HEAD.
+///
+/// # Returns
+/// None if the directory isn't in a git repository (or git isn't installed).
+pub fn current_branch(dir: &Path) -> Option<String> {
+    let query = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|out| !out.is_empty())
+    };
+    query(&["symbolic-ref", "--short", "-q", "HEAD"])
+        .or_else(|| query(&["rev-parse", "--short", "HEAD"]))
+}
+
+/// Check out a branch in a new worktree, creating the branch if needed.
+///
+/// # Arguments
+/// * `repo` - The repository to add the worktree to
+/// * `path` - Where to create the worktree
+/// * `branch` - The branch to check out
+pub fn add_worktree(repo: &Path, path: &Path, branch: &str) -> Result<()> {
+    let repo_arg = repo.to_string_lossy();
+    let path_arg = path.to_string_lossy();
+    if branch_exists(repo, branch) {
+        run(&["-C", &repo_arg, "worktree", "add", &path_arg, branch])
+    } else {
+        run(&["-C", &repo_arg, "worktree", "add", "-b", branch, &path_arg])
+    }
+}
@@ -0,0 +1,221 @@
+//! Diagrams of a session's window and pane structure (Mermaid and Graphviz).
+
+use crate::config::Session;
+use crate::session;
+use crate::tmux::query::LiveSession;
+
+/// The structure of a session, as drawn in a diagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagram {
+    pub session: String,
+    pub windows: Vec<DiagramWindow>,
+}
+
+/// A window and the labels of its panes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramWindow {
+    pub index: usize,
+    pub name: String,
+    pub layout: Option<String>,
+    pub panes: Vec<String>,
+}
+
+impl Diagram {
+    /// Diagram of a configured session: pane commands, splits and sizes
+    pub fn from_config(session: &Session) -> Self {
+        let windows = session
+            .windows
+            .iter()
+            .enumerate()
+            .map(|(index, window)| {
+                let panes = window
+                    .panes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pane)| {
+                        let mut label = format!("{}: {}", i, command_label(&pane.command));
+                        if i > 0 {
+                            let split = if session::determine_split_direction(i, pane) {
+                                "horizontal"
+                            } else {
+                                "vertical"
+                            };
+                            label.push_str(&format!(" ({}", split));
+                            if let Some(ref size) = pane.size {
+                                label.push_str(&format!(", {}", size));
+                            }
+                            label.push(')');
+                        }
+                        label
+                    })
+                    .collect();
+                DiagramWindow {
+                    index,
+                    name: window.name.clone(),
+                    layout: (window.panes.len() > 1)
+                        .then(|| session::determine_layout(window, window.panes.len()).to_string()),
+                    panes,
+                }
+            })
+            .collect();
+
+        Self {
+            session: session.name.clone(),
+            windows,
+        }
+    }
+
+    /// Diagram of a running session: the command running in each pane
+    pub fn from_live(live: &LiveSession) -> Self {
+        let windows = live
+            .windows
+            .iter()
+            .map(|window| DiagramWindow {
+                index: window.index,
+                name: window.name.clone(),
+                layout: None,
+                panes: window
+                    .panes
+                    .iter()
+                    .map(|pane| format!("{}: {}", pane.index, command_label(&pane.current_command)))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            session: live.name.clone(),
+            windows,
+        }
+    }
+
+    /// Render as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        out.push_str(&format!(
+            "    s[\"{}\"]\n",
+            mermaid_escape(&format!("session: {}", self.session))
+        ));
+        for window in &self.windows {
+            let id = format!("w{}", window.index);
+            out.push_str(&format!(
+                "    s --> {}[\"{}\"]\n",
+                id,
+                mermaid_escape(&window_label(window))
+            ));
+            for (i, pane) in window.panes.iter().enumerate() {
+                out.push_str(&format!(
+                    "    {} --> {}p{}[\"{}\"]\n",
+                    id,
+                    id,
+                    i,
+                    mermaid_escape(pane)
+                ));
+            }
+        }
+        out
+    }
+
+    /// Render as a Graphviz digraph
+    pub fn to_dot(&self) -> String {
+        let mut out = format!("digraph {} {{\n", dot_quote(&self.session));
+        out.push_str("    node [shape=box];\n");
+        out.push_str(&format!(
+            "    s [label={}];\n",
+            dot_quote(&format!("session: {}", self.session))
+        ));
+        for window in &self.windows {
+            let id = format!("w{}", window.index);
+            out.push_str(&format!(
+                "    {} [label={}];\n    s -> {};\n",
+                id,
+                dot_quote(&window_label(window)),
+                id
+            ));
+            for (i, pane) in window.panes.iter().enumerate() {
+                out.push_str(&format!(
+                    "    {}p{} [label={}];\n    {} -> {}p{};\n",
+                    id,
+                    i,
+                    dot_quote(pane),
+                    id,
+                    id,
+                    i
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// A pane's command, or `shell` for panes without one
+fn command_label(command: &str) -> &str {
+    if command.trim().is_empty() {
+        "shell"
+    } else {
+        command
+    }
+}
+
+/// `window 1: editor (tiled)`
+fn window_label(window: &DiagramWindow) -> String {
+    match window.layout {
+        Some(ref layout) => format!("window {}: {} ({})", window.index, window.name, layout),
+        None => format!("window {}: {}", window.index, window.name),
+    }
+}
+
+/// Escape text for a quoted Mermaid node label
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Quote text as a Graphviz ID
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagram() -> Diagram {
+        let session: Session = toml::from_str(
+            r#"
+name = "dev"
+
+[[windows]]
+name = "editor"
+panes = [{ command = "nvim" }, { command = "", size = "30%" }]
+
+[[windows]]
+name = "logs"
+panes = [{ command = "tail -f \"app.log\"" }]
+"#,
+        )
+        .unwrap();
+        Diagram::from_config(&session)
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        assert_eq!(
+            diagram().to_mermaid(),
+            "graph TD\n\
+             \x20   s[\"session: dev\"]\n\
+             \x20   s --> w0[\"window 0: editor (even-horizontal)\"]\n\
+             \x20   w0 --> w0p0[\"0: nvim\"]\n\
+             \x20   w0 --> w0p1[\"1: shell (horizontal, 30%)\"]\n\
+             \x20   s --> w1[\"window 1: logs\"]\n\
+             \x20   w1 --> w1p0[\"0: tail -f #quot;app.log#quot;\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = diagram().to_dot();
+        assert!(dot.starts_with("digraph \"dev\" {\n"));
+        assert!(dot.contains("    w1p0 [label=\"0: tail -f \\\"app.log\\\"\"];\n    w1 -> w1p0;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Pane health checks (`healthcheck = "curl -sf localhost:8080/health"`).
+//!
+//! A check is a shell command run in the pane's root directory; it passes
+//! when it exits with status 0 before the timeout. Checks run in parallel so
+//! a dashboard of many panes is as slow as its slowest check.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a check may run before it counts as failed
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running check is polled for its exit status
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A health check to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub command: String,
+    /// Directory the command runs in
+    pub dir: PathBuf,
+}
+
+/// The result of a health check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Health {
+    Pass,
+    /// Why the check failed, e.g. `exit 7` or `timed out`
+    Fail(String),
+}
+
+impl Health {
+    /// Badge shown next to the pane, e.g. `✓ pass` or `✗ fail (exit 7)`
+    pub fn badge(&self) -> String {
+        match self {
+            Health::Pass => "✓ pass".to_string(),
+            Health::Fail(reason) => format!("✗ fail ({})", reason),
+        }
+    }
+}
+
+/// Run health checks in parallel.
+///
+/// # Arguments
+/// * `checks` - The checks to run
+/// * `timeout` - How long each check may take
+///
+/// # Returns
+/// One result per check, in the same order.
+pub fn run_checks(checks: &[Check], timeout: Duration) -> Vec<Health> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = checks
+            .iter()
+            .map(|check| scope.spawn(move || run_check(check, timeout)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Health::Fail("check panicked".to_string()))
+            })
+            .collect()
+    })
+}
+
+/// Run one check, killing it once the timeout passes
+fn run_check(check: &Check, timeout: Duration) -> Health {
+    let spawned = Command::new("sh")
+        .arg("-c")
+        .arg(&check.command)
+        .current_dir(&check.dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => return Health::Fail(format!("can't run: {}", e)),
+    };
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Health::Pass,
+            Ok(Some(status)) => {
+                return Health::Fail(match status.code() {
+                    Some(code) => format!("exit {}", code),
+                    None => "killed".to_string(),
+                });
+            }
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Health::Fail("timed out".to_string());
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Health::Fail(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_checks() {
+        let check = |command: &str| Check {
+            command: command.to_string(),
+            dir: std::env::temp_dir(),
+        };
+        let results = run_checks(
+            &[check("true"), check("exit 7"), check("sleep 5")],
+            Duration::from_millis(200),
+        );
+        assert_eq!(
+            results,
+            [
+                Health::Pass,
+                Health::Fail("exit 7".to_string()),
+                Health::Fail("timed out".to_string()),
+            ]
+        );
+        assert_eq!(results[1].badge(), "✗ fail (exit 7)");
+    }
+}
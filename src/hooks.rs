@@ -0,0 +1,191 @@
+//! Shell commands run at points in a session's lifecycle.
+//!
+//! `on_create` runs before a session is created, `pre_window` before each of
+//! its windows, `post_create` once it is built, and `pre_close` before
+//! `tmx close` stops it. Each command runs with `sh -c`, one after the other,
+//! with `TMX_SESSION` set to the session name and `TMX_SESSION_JSON` to the
+//! resolved session; the first one that fails stops the rest of its hook.
+
+use crate::config::Session;
+use crate::log;
+use crate::messages;
+use crate::resolved::{ResolvedSession, SESSION_JSON_ENV};
+use anyhow::Result;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A point in a session's lifecycle that runs commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    OnCreate,
+    PreWindow,
+    PostCreate,
+    PreClose,
+}
+
+impl Hook {
+    /// The config field the hook's commands are set with
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Hook::OnCreate => "on_create",
+            Hook::PreWindow => "pre_window",
+            Hook::PostCreate => "post_create",
+            Hook::PreClose => "pre_close",
+        }
+    }
+}
+
+/// The session hook commands run for, as passed in their environment
+pub struct HookSession<'a> {
+    /// Session name (`TMX_SESSION`)
+    pub name: &'a str,
+    /// Resolved session JSON (`TMX_SESSION_JSON`)
+    pub json: Option<String>,
+}
+
+impl<'a> HookSession<'a> {
+    /// Describe a configured session to its hook commands.
+    ///
+    /// # Arguments
+    /// * `name` - The session name
+    /// * `id` - The config key of the session, if known
+    /// * `session` - The session configuration
+    pub fn new(name: &'a str, id: Option<&str>, session: &Session) -> Self {
+        let json = match ResolvedSession::new(id, session).to_json() {
+            Ok(json) => Some(json),
+            Err(e) => {
+                log::error(&format!("session '{}': {}", name, e));
+                None
+            }
+        };
+        Self { name, json }
+    }
+}
+
+/// Run a hook's commands in a directory, stopping at the first failure.
+///
+/// # Arguments
+/// * `hook` - The hook being run
+/// * `commands` - Its shell commands
+/// * `session` - The session, passed to the commands in their environment
+/// * `dir` - Working directory of the commands
+///
+/// # Errors
+/// Returns an error naming the command if one can't be started or exits
+/// with a non-zero status.
+pub fn run(hook: Hook, commands: &[String], session: &HookSession, dir: &Path) -> Result<()> {
+    for command in commands {
+        messages::say(
+            messages::HOOK_RUNNING,
+            &[("hook", &hook.as_str()), ("command", command)],
+        );
+        log::info(&format!(
+            "{} hook of session '{}' in {}: {}",
+            hook.as_str(),
+            session.name,
+            dir.display(),
+            command
+        ));
+        let status = shell_command(command, session, dir)
+            .status()
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "{} command '{}' couldn't run: {}",
+                    hook.as_str(),
+                    command,
+                    e
+                )
+            })?;
+        if !status.success() {
+            anyhow::bail!(
+                "{} command '{}' failed ({})",
+                hook.as_str(),
+                command,
+                match status.code() {
+                    Some(code) => format!("exit {}", code),
+                    None => "killed".to_string(),
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Build the process running a session's shell command with `sh -c`, as
+/// hooks (and a graceful close's `shutdown_command`) run.
+///
+/// `TMX_SESSION` is set to the session name and `TMX_SESSION_JSON` to the
+/// resolved session. Output goes to the terminal, or to stderr when stdout
+/// is reserved for a session target.
+///
+/// # Arguments
+/// * `command` - The shell command
+/// * `session` - The session it runs for
+/// * `dir` - Working directory of the command
+pub fn shell_command(command: &str, session: &HookSession, dir: &Path) -> Command {
+    let mut process = Command::new("sh");
+    process
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .env("TMX_SESSION", session.name)
+        .stdout(if messages::on_stderr() {
+            Stdio::from(std::io::stderr())
+        } else {
+            Stdio::inherit()
+        });
+    if let Some(ref json) = session.json {
+        process.env(SESSION_JSON_ENV, json);
+    }
+    process
+}
+
+/// Run a hook whose failure doesn't stop what it runs for, reporting the
+/// failure as a warning.
+///
+/// # Arguments
+/// * `hook` - The hook being run
+/// * `commands` - Its shell commands
+/// * `session` - The session, passed to the commands in their environment
+/// * `dir` - Working directory of the commands
+pub fn run_or_warn(hook: Hook, commands: &[String], session: &HookSession, dir: &Path) {
+    if let Err(e) = run(hook, commands, session, dir) {
+        log::error(&format!("session '{}': {}", session.name, e));
+        messages::say(messages::HOOK_FAILED, &[("error", &e)]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("tmx-hooks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let session: Session =
+            toml::from_str("name = \"dev\"\nroot = \"/\"\nwindows = []").unwrap();
+        let session = HookSession::new("dev", Some("work"), &session);
+        let commands = [
+            "test \"$TMX_SESSION\" = dev".to_string(),
+            "printf %s \"$TMX_SESSION_JSON\" > session.json".to_string(),
+        ];
+        run(Hook::OnCreate, &commands, &session, &dir).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("session.json")).unwrap()).unwrap();
+        assert_eq!(json["id"], "work");
+        assert_eq!(json["name"], "dev");
+
+        // The first failure stops the hook
+        let commands = ["exit 3".to_string(), "touch after".to_string()];
+        let error = run(Hook::PreClose, &commands, &session, &dir).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "pre_close command 'exit 3' failed (exit 3)"
+        );
+        assert!(!dir.join("after").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,107 @@
+//! Custom tmux layout strings, as printed by `tmux list-windows -F '#{window_layout}'`.
+//!
+//! A layout string is a checksum followed by a tree of cells, e.g.
+//! `b25d,208x50,0,0{104x50,0,0,1,103x50,105,0,2}`: each cell is
+//! `WIDTHxHEIGHT,X,Y` followed by a pane ID, or by child cells in `{}`
+//! (side by side) or `[]` (stacked).
+
+/// Check a custom layout string and count its panes.
+///
+/// # Returns
+/// The number of panes, or None if the string isn't a valid layout or its
+/// checksum doesn't match.
+pub fn pane_count(layout: &str) -> Option<usize> {
+    let (checksum, body) = layout.split_once(',')?;
+    if checksum.len() != 4 || u16::from_str_radix(checksum, 16).ok()? != self::checksum(body) {
+        return None;
+    }
+
+    let mut parser = Parser {
+        bytes: body.as_bytes(),
+        pos: 0,
+    };
+    let panes = parser.cell()?;
+    (parser.pos == body.len()).then_some(panes)
+}
+
+/// The checksum tmux prefixes layout strings with
+pub fn checksum(body: &str) -> u16 {
+    body.bytes().fold(0u16, |sum, byte| {
+        let rotated = (sum >> 1) | ((sum & 1) << 15);
+        rotated.wrapping_add(u16::from(byte))
+    })
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Parse a cell and return the number of panes in it
+    fn cell(&mut self) -> Option<usize> {
+        self.number()?;
+        self.expect(b'x')?;
+        self.number()?;
+        self.expect(b',')?;
+        self.number()?;
+        self.expect(b',')?;
+        self.number()?;
+
+        match self.bytes.get(self.pos) {
+            Some(b',') => {
+                self.pos += 1;
+                self.number()?;
+                Some(1)
+            }
+            Some(&open @ (b'{' | b'[')) => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                let mut panes = self.cell()?;
+                while self.bytes.get(self.pos) == Some(&b',') {
+                    self.pos += 1;
+                    panes += self.cell()?;
+                }
+                self.expect(close)?;
+                Some(panes)
+            }
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<()> {
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        (self.pos > start).then_some(())
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.bytes.get(self.pos) == Some(&byte)).then(|| self.pos += 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_count() {
+        let body = "208x50,0,0{104x50,0,0,1,103x50,105,0[103x25,105,0,2,103x24,105,26,3]}";
+        let layout = format!("{:04x},{}", checksum(body), body);
+        assert_eq!(pane_count(&layout), Some(3));
+        // As printed by tmux 3.3
+        assert_eq!(
+            pane_count("95e4,120x40,0,0{60x40,0,0,0,59x40,61,0[59x20,61,0,1,59x19,61,21,2]}"),
+            Some(3)
+        );
+        assert_eq!(pane_count(&format!("{:04x},80x24,0,0,5", checksum("80x24,0,0,5"))), Some(1));
+
+        // Wrong checksum, truncated tree, no checksum
+        assert_eq!(pane_count(&format!("0000,{}", body)), None);
+        let truncated = &body[..body.len() - 1];
+        assert_eq!(pane_count(&format!("{:04x},{}", checksum(truncated), truncated)), None);
+        assert_eq!(pane_count("tiled"), None);
+    }
+}
@@ -0,0 +1,44 @@
+//! tmx: a tmux session manager with declarative TOML configuration.
+//!
+//! The binary in `main.rs` is a thin CLI over these modules; they are exposed
+//! as a library so the integration tests in `tests/` can drive real tmux.
+
+pub mod backup;
+pub mod checksum;
+pub mod cli;
+pub mod close;
+pub mod commands;
+pub mod config;
+pub mod config_edit;
+pub mod context;
+pub mod diff;
+pub mod dotenv;
+pub mod gc;
+pub mod git;
+pub mod graph;
+pub mod health;
+pub mod hooks;
+pub mod layout;
+pub mod lint;
+pub mod lock;
+pub mod log;
+pub mod messages;
+pub mod migrate;
+pub mod notify;
+pub mod overrides;
+pub mod placeholders;
+pub mod prompt;
+pub mod remote;
+pub mod resolved;
+pub mod roots;
+pub mod secrets;
+pub mod session;
+pub mod shell;
+pub mod snapshot;
+pub mod shells;
+pub mod state;
+pub mod table;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+pub mod tmux;
+pub mod trust;
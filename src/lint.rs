@@ -0,0 +1,310 @@
+//! Config lints: settings that are valid but probably don't do what was meant.
+
+use crate::config::{self, EnvFile, Session, Window};
+use crate::session::determine_split_direction;
+
+/// Absolute pane widths above this many columns don't fit typical terminals
+const TYPICAL_MAX_COLUMNS: u32 = 250;
+
+/// Absolute pane heights above this many lines don't fit typical terminals
+const TYPICAL_MAX_LINES: u32 = 80;
+
+/// A lint warning with the location it applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// Stable machine-readable identifier, e.g. `first-pane-size`
+    pub code: &'static str,
+    /// Session ID in the config
+    pub session: String,
+    pub window: Option<String>,
+    /// 0-based pane index within the window
+    pub pane: Option<usize>,
+    /// The config field the warning is about
+    pub field: &'static str,
+    pub message: String,
+    /// How to fix it
+    pub hint: String,
+}
+
+impl Lint {
+    /// Where the warning applies, e.g. `Session 'dev', window 'editor', pane 1`
+    pub fn location(&self) -> String {
+        let mut location = format!("Session '{}'", self.session);
+        if let Some(ref window) = self.window {
+            location.push_str(&format!(", window '{}'", window));
+        }
+        if let Some(pane) = self.pane {
+            location.push_str(&format!(", pane {}", pane));
+        }
+        location
+    }
+}
+
+/// Build a lint for a location in a session
+fn new_lint(
+    code: &'static str,
+    id: &str,
+    window: Option<&Window>,
+    pane: Option<usize>,
+    field: &'static str,
+    message: String,
+    hint: String,
+) -> Lint {
+    Lint {
+        code,
+        session: id.to_string(),
+        window: window.map(|w| w.name.clone()),
+        pane,
+        field,
+        message,
+        hint,
+    }
+}
+
+/// Warnings reported by `tmx validate`: valid settings that interact in a
+/// surprising way.
+///
+/// # Arguments
+/// * `id` - The session ID in the config
+/// * `session` - The session configuration
+pub fn validate_warnings(id: &str, session: &Session) -> Vec<Lint> {
+    session
+        .windows
+        .iter()
+        .filter(|w| w.layout.is_some() && w.panes.iter().any(|p| p.size.is_some()))
+        .map(|window| {
+            new_lint(
+                "layout-with-sizes",
+                id,
+                Some(window),
+                None,
+                "size",
+                "both layout and pane sizes specified - sizes will override layout".to_string(),
+                "Remove layout, or the pane sizes to keep the layout's proportions".to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Paths that can't be fully expanded: roots and env files referring to
+/// undefined environment variables or unknown users.
+///
+/// These are warnings for `tmx validate`, and errors with `--strict`.
+///
+/// # Arguments
+/// * `id` - The session ID in the config
+/// * `session` - The session configuration
+pub fn path_warnings(id: &str, session: &Session) -> Vec<Lint> {
+    fn env_files(env_file: &Option<EnvFile>) -> impl Iterator<Item = &String> {
+        env_file.iter().flat_map(|f| f.paths())
+    }
+
+    let mut paths: Vec<(Option<&Window>, Option<usize>, &'static str, &String)> = Vec::new();
+    paths.push((None, None, "root", &session.root));
+    paths.extend(env_files(&session.env_file).map(|p| (None, None, "env_file", p)));
+    for window in &session.windows {
+        paths.extend(window.root.iter().map(|r| (Some(window), None, "root", r)));
+        paths.extend(env_files(&window.env_file).map(|p| (Some(window), None, "env_file", p)));
+        for (i, pane) in window.panes.iter().enumerate() {
+            paths.extend(pane.root.iter().map(|r| (Some(window), Some(i), "root", r)));
+            paths.extend(env_files(&pane.env_file).map(|p| (Some(window), Some(i), "env_file", p)));
+        }
+    }
+
+    paths
+        .into_iter()
+        .filter_map(|(window, pane, field, path)| {
+            let problems = config::path_problems(path);
+            (!problems.is_empty()).then(|| {
+                new_lint(
+                    "undefined-variable",
+                    id,
+                    window,
+                    pane,
+                    field,
+                    format!(
+                        "{} \"{}\" refers to {}, which is not defined",
+                        field,
+                        path,
+                        problems.join(", ")
+                    ),
+                    "Set the variable (or create the user) before running tmx, or use a literal path"
+                        .to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Lint a session configuration (including the `validate_warnings` and
+/// `path_warnings`).
+///
+/// # Arguments
+/// * `id` - The session ID in the config
+/// * `session` - The session configuration (assumed to pass validation)
+pub fn lint_session(id: &str, session: &Session) -> Vec<Lint> {
+    let mut lints = validate_warnings(id, session);
+    lints.extend(path_warnings(id, session));
+    let lint = |code, window, pane, field, message, hint| {
+        new_lint(code, id, window, pane, field, message, hint)
+    };
+
+    if let Some(startup_pane) = session.startup_pane
+        && let Some(window) = session.windows.get(session.resolve_startup_window())
+        && startup_pane >= window.panes.len()
+    {
+        lints.push(lint(
+            "startup-pane-out-of-range",
+            Some(window),
+            None,
+            "startup_pane",
+            format!(
+                "startup_pane = {} but the startup window has {} pane(s)",
+                startup_pane,
+                window.panes.len()
+            ),
+            format!(
+                "Use an index from 0 to {}",
+                window.panes.len().saturating_sub(1)
+            ),
+        ));
+    }
+
+    for window in &session.windows {
+        if let Some(ref layout) = window.layout
+            && window.panes.iter().any(|p| p.split.is_some())
+        {
+            lints.push(lint(
+                "layout-overrides-split",
+                Some(window),
+                None,
+                "layout",
+                format!(
+                    "layout '{}' rearranges the panes, so their split directions have no effect",
+                    layout
+                ),
+                "Remove layout to keep the splits, or remove split from the panes".to_string(),
+            ));
+        }
+
+        for (i, pane) in window.panes.iter().enumerate() {
+            if !pane.env.is_empty() && pane.command.trim().is_empty() {
+                lints.push(lint(
+                    "env-without-command",
+                    Some(window),
+                    Some(i),
+                    "env",
+                    "env is set but the pane runs no command".to_string(),
+                    "The variables only reach the pane's shell; set command, or move env to a \
+                     pane that runs the program needing it"
+                        .to_string(),
+                ));
+            }
+
+            let Some(ref size) = pane.size else { continue };
+            if i == 0 {
+                lints.push(lint(
+                    "first-pane-size",
+                    Some(window),
+                    Some(i),
+                    "size",
+                    format!(
+                        "size = \"{}\" on the first pane is ignored: it gets the space left by the other panes",
+                        size
+                    ),
+                    "Set size on the other panes instead".to_string(),
+                ));
+            } else if let Ok(cells) = size.parse::<u32>() {
+                let (limit, unit) = if determine_split_direction(i, pane) {
+                    (TYPICAL_MAX_COLUMNS, "columns")
+                } else {
+                    (TYPICAL_MAX_LINES, "lines")
+                };
+                if cells > limit {
+                    lints.push(lint(
+                        "size-too-large",
+                        Some(window),
+                        Some(i),
+                        "size",
+                        format!(
+                            "size = \"{}\" is more {} than typical terminals have (> {})",
+                            size, unit, limit
+                        ),
+                        "Use a percentage, e.g. size = \"30%\"".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_lint_session() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.dev]
+name = "dev"
+startup_pane = 2
+
+[[sessions.dev.windows]]
+name = "editor"
+layout = "tiled"
+panes = [
+    { command = "nvim", size = "40%" },
+    { command = "", split = "vertical", env = { A = "1" } },
+]
+
+[[sessions.dev.windows]]
+name = "logs"
+panes = [{ command = "" }, { command = "tail -f log", size = "500" }, { command = "", size = "20" }]
+"#,
+        )
+        .unwrap();
+
+        let lints = lint_session("dev", &config.sessions["dev"]);
+        let codes: Vec<_> = lints.iter().map(|l| (l.code, l.pane)).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("layout-with-sizes", None),
+                ("startup-pane-out-of-range", None),
+                ("layout-overrides-split", None),
+                ("first-pane-size", Some(0)),
+                ("env-without-command", Some(1)),
+                ("size-too-large", Some(1)),
+            ]
+        );
+        assert_eq!(
+            lints[3].location(),
+            "Session 'dev', window 'editor', pane 0"
+        );
+    }
+
+    #[test]
+    fn test_path_warnings() {
+        let config: Config = toml::from_str(
+            r#"
+[sessions.api]
+name = "api"
+root = "$TMX_LINT_UNDEFINED/api"
+
+[[sessions.api.windows]]
+name = "server"
+panes = [{ command = "", root = "~/api", env_file = "${TMX_LINT_UNDEFINED}.env" }]
+"#,
+        )
+        .unwrap();
+
+        let lints = path_warnings("api", &config.sessions["api"]);
+        let fields: Vec<_> = lints.iter().map(|l| (l.field, l.pane)).collect();
+        assert_eq!(fields, vec![("root", None), ("env_file", Some(0))]);
+        assert!(lints[0].message.contains("$TMX_LINT_UNDEFINED"));
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+
+use crate::log;
+use crate::messages;
+use crate::tmux;
+
+/// Exclusive per-session lock held while tmx builds or modifies a session.
+///
+/// Backed by an advisory file lock in ~/.cache/tmx/locks/, so it is released
+/// automatically when the guard is dropped or the process exits.
+pub struct SessionLock {
+    _file: File,
+}
+
+impl SessionLock {
+    /// Get the lock directory (~/.cache/tmx/locks)
+    fn lock_dir() -> Result<PathBuf> {
+        let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home_dir.join(".cache").join("tmx").join("locks"))
+    }
+
+    /// Acquire the lock for a session, waiting for any other tmx process holding it.
+    ///
+    /// # Arguments
+    /// * `session` - The session name (sanitized for tmux before use)
+    pub fn acquire(session: &str) -> Result<Self> {
+        let dir = Self::lock_dir()?;
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create lock directory: {}", dir.display()))?;
+
+        let name = tmux::sanitize_session_name(session).replace('/', "_");
+        let path = dir.join(format!("{}.lock", name));
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file: {}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                log::info(&format!("waiting for lock on session '{}'", name));
+                messages::say(messages::SESSION_LOCK_WAITING, &[("session", &name)]);
+                file.lock()
+                    .with_context(|| format!("Failed to lock {}", path.display()))?;
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+            }
+        }
+
+        Ok(Self { _file: file })
+    }
+}
@@ -1,14 +1,37 @@
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::time::SystemTime;
 
-use once_cell::sync::Lazy;
+use anyhow::{Context, Result};
+use once_cell::sync::{Lazy, OnceCell};
 
-/// Global log file handle
-static LOG_FILE: Lazy<Mutex<Option<std::fs::File>>> = Lazy::new(|| Mutex::new(None));
+use crate::config::LogTarget;
+
+/// Global log file handle (a file, or stderr when `--log-file -` is used)
+static LOG_FILE: Lazy<Mutex<Option<Box<dyn Write + Send>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Active log targets (file only until the config has been loaded)
+static TARGETS: Lazy<Mutex<Vec<LogTarget>>> = Lazy::new(|| Mutex::new(vec![LogTarget::File]));
+
+/// Syslog sockets to try, in order (Linux, then macOS)
+const SYSLOG_SOCKETS: &[&str] = &["/dev/log", "/var/run/syslog"];
+
+/// systemd-journald native protocol socket
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Identifier attached to syslog and journal records
+const IDENTIFIER: &str = "tmx";
+
+/// Path of the log file in use (None when logging to stderr or disabled)
+static LOG_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Tag marking command trace records, used by `tmx logs --commands`
+pub const COMMAND_TRACE_TAG: &str = "[CMD]";
 
 /// Global debug mode flag
 static DEBUG_MODE: AtomicBool = AtomicBool::new(false);
@@ -18,7 +41,7 @@ fn log_dir() -> Option<PathBuf> {
     dirs::home_dir().map(|p| p.join(".cache").join("tmx"))
 }
 
-/// Get the log file path (~/.cache/tmx/tmx.log)
+/// Get the default log file path (~/.cache/tmx/tmx.log)
 fn log_path() -> Option<PathBuf> {
     log_dir().map(|p| p.join("tmx.log"))
 }
@@ -26,48 +49,199 @@ fn log_path() -> Option<PathBuf> {
 /// Initialize the logger, creating the log directory if needed.
 /// Should be called once at startup.
 ///
+/// The log file is resolved from: `--log-file` > TMX_LOG_FILE env > ~/.cache/tmx/tmx.log.
+/// A path of `-` sends log records to stderr instead of a file.
+///
 /// # Arguments
 /// * `verbose` - If true, enables debug level logging
-pub fn init(verbose: bool) {
+/// * `log_file` - Optional log file path from the CLI --log-file flag
+pub fn init(verbose: bool, log_file: Option<&str>) {
     // Set debug mode
     DEBUG_MODE.store(verbose, Ordering::SeqCst);
 
-    let Some(dir) = log_dir() else {
+    let override_path = log_file
+        .map(str::to_string)
+        .or_else(|| std::env::var("TMX_LOG_FILE").ok());
+
+    if override_path.as_deref() == Some("-") {
+        set_sink(Box::new(std::io::stderr()), verbose);
         return;
+    }
+
+    let path = match override_path {
+        Some(path) => PathBuf::from(shellexpand::tilde(&path).to_string()),
+        None => match log_path() {
+            Some(path) => path,
+            None => return,
+        },
     };
 
     // Create log directory if it doesn't exist
-    if let Err(e) = fs::create_dir_all(&dir) {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty())
+        && let Err(e) = fs::create_dir_all(dir)
+    {
         eprintln!("Warning: Could not create log directory: {}", e);
         return;
     }
 
-    let Some(path) = log_path() else {
-        return;
-    };
-
     // Open log file in append mode
     match OpenOptions::new().create(true).append(true).open(&path) {
         Ok(file) => {
-            let mut guard = LOG_FILE.lock().unwrap();
-            *guard = Some(file);
-            drop(guard);
-            let mode = if verbose { "debug" } else { "info" };
-            log(&format!("--- tmx session started (log level: {}) ---", mode));
+            let _ = LOG_PATH.set(path);
+            set_sink(Box::new(file), verbose);
         }
         Err(e) => {
-            eprintln!("Warning: Could not open log file: {}", e);
+            eprintln!("Warning: Could not open log file {}: {}", path.display(), e);
         }
     }
 }
 
+/// Install the log file sink and write the startup marker
+fn set_sink(sink: Box<dyn Write + Send>, verbose: bool) {
+    let mut guard = LOG_FILE.lock().unwrap();
+    *guard = Some(sink);
+    drop(guard);
+    let mode = if verbose { "debug" } else { "info" };
+    log(&format!("--- tmx session started (log level: {}) ---", mode));
+}
+
+/// Get the path of the log file in use, if logging to a file
+pub fn path() -> Option<&'static PathBuf> {
+    LOG_PATH.get()
+}
+
+/// Keep the log file under a size by dropping its oldest records.
+///
+/// A file over `max_bytes` is cut down to its newest half, starting at a
+/// record boundary, so it isn't trimmed again on every run.
+///
+/// # Returns
+/// The number of bytes removed (0 when not logging to a file).
+pub fn enforce_size_cap(max_bytes: u64) -> Result<u64> {
+    let Some(path) = path() else {
+        return Ok(0);
+    };
+    // Hold the sink so records aren't written while the file is rewritten
+    let _guard = LOG_FILE.lock();
+    let content =
+        fs::read(path).with_context(|| format!("Failed to read log file: {}", path.display()))?;
+    let Some(kept) = newest_records(&content, max_bytes) else {
+        return Ok(0);
+    };
+    // The sink appends, so it continues at the end of the trimmed file
+    fs::write(path, kept)
+        .with_context(|| format!("Failed to trim log file: {}", path.display()))?;
+    Ok((content.len() - kept.len()) as u64)
+}
+
+/// The newest records of a log over `max_bytes`, about half of it
+fn newest_records(content: &[u8], max_bytes: u64) -> Option<&[u8]> {
+    let len = content.len() as u64;
+    if len <= max_bytes {
+        return None;
+    }
+    let cut = (len - max_bytes / 2) as usize;
+    // A record starts after the newline ending the one before
+    let start = content[cut - 1..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(content.len(), |i| cut + i);
+    Some(&content[start..])
+}
+
+/// Set the targets log records are sent to.
+///
+/// Called once the configuration is available; records logged before that
+/// only go to the log file. An empty list disables logging entirely.
+pub fn set_targets(targets: &[LogTarget]) {
+    if let Ok(mut guard) = TARGETS.lock() {
+        *guard = targets.to_vec();
+    }
+}
+
 /// Check if debug mode is enabled
 pub fn is_debug() -> bool {
     DEBUG_MODE.load(Ordering::SeqCst)
 }
 
-/// Log a message to the log file with timestamp
+/// Log a message to all configured targets
 pub fn log(message: &str) {
+    write_record(SEVERITY_INFO, message);
+}
+
+/// Syslog severity levels (RFC 5424) used for syslog and journal records
+const SEVERITY_ERROR: u8 = 3;
+const SEVERITY_INFO: u8 = 6;
+const SEVERITY_DEBUG: u8 = 7;
+
+/// Dispatch a record to each active target
+fn write_record(severity: u8, message: &str) {
+    let targets = match TARGETS.lock() {
+        Ok(guard) => guard.clone(),
+        Err(_) => return,
+    };
+
+    for target in targets {
+        match target {
+            LogTarget::File => write_file(message),
+            LogTarget::Syslog => write_syslog(severity, message),
+            LogTarget::Journald => write_journald(severity, message),
+        }
+    }
+}
+
+/// Send a record to the local syslog daemon (RFC 3164 format, user facility)
+#[cfg(unix)]
+fn write_syslog(severity: u8, message: &str) {
+    const FACILITY_USER: u8 = 1;
+
+    let Ok(socket) = UnixDatagram::unbound() else {
+        return;
+    };
+
+    let record = format!(
+        "<{}>{}[{}]: {}",
+        FACILITY_USER * 8 + severity,
+        IDENTIFIER,
+        std::process::id(),
+        message
+    );
+
+    for path in SYSLOG_SOCKETS {
+        if socket.send_to(record.as_bytes(), path).is_ok() {
+            return;
+        }
+    }
+}
+
+/// Send a record to systemd-journald using its native protocol
+#[cfg(unix)]
+fn write_journald(severity: u8, message: &str) {
+    let Ok(socket) = UnixDatagram::unbound() else {
+        return;
+    };
+
+    // Multi-line messages would need the binary field encoding, so keep it to one line
+    let record = format!(
+        "MESSAGE={}\nPRIORITY={}\nSYSLOG_IDENTIFIER={}\nSYSLOG_PID={}\n",
+        message.replace('\n', " "),
+        severity,
+        IDENTIFIER,
+        std::process::id()
+    );
+
+    let _ = socket.send_to(record.as_bytes(), JOURNALD_SOCKET);
+}
+
+/// Syslog and journald are only available on Unix
+#[cfg(not(unix))]
+fn write_syslog(_severity: u8, _message: &str) {}
+
+#[cfg(not(unix))]
+fn write_journald(_severity: u8, _message: &str) {}
+
+/// Append a message to the log file with timestamp
+fn write_file(message: &str) {
     let Ok(mut guard) = LOG_FILE.lock() else {
         return;
     };
@@ -80,16 +254,7 @@ pub fn log(message: &str) {
     let timestamp = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .map(|d| {
-            let secs = d.as_secs();
-            // Simple UTC timestamp formatting
-            let days = secs / 86400;
-            let time_secs = secs % 86400;
-            let hours = time_secs / 3600;
-            let minutes = (time_secs % 3600) / 60;
-            let seconds = time_secs % 60;
-
-            // Calculate date from days since epoch (1970-01-01)
-            let (year, month, day) = days_to_ymd(days);
+            let (year, month, day, hours, minutes, seconds) = utc_datetime(d.as_secs());
             format!(
                 "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
                 year, month, day, hours, minutes, seconds
@@ -102,6 +267,20 @@ pub fn log(message: &str) {
     let _ = file.flush();
 }
 
+/// Split seconds since the Unix epoch into UTC (year, month, day, hours, minutes, seconds)
+pub fn utc_datetime(secs: u64) -> (u64, u64, u64, u64, u64, u64) {
+    // Simple UTC timestamp formatting
+    let days = secs / 86400;
+    let time_secs = secs % 86400;
+    let hours = time_secs / 3600;
+    let minutes = (time_secs % 3600) / 60;
+    let seconds = time_secs % 60;
+
+    // Calculate date from days since epoch (1970-01-01)
+    let (year, month, day) = days_to_ymd(days);
+    (year, month, day, hours, minutes, seconds)
+}
+
 /// Convert days since Unix epoch to year, month, day
 fn days_to_ymd(days: u64) -> (u64, u64, u64) {
     // Simplified calculation - good enough for logging
@@ -143,17 +322,99 @@ fn is_leap_year(year: i64) -> bool {
 /// Log a debug message (only logged when -v flag is used)
 pub fn debug(message: &str) {
     if is_debug() {
-        log(&format!("[DEBUG] {}", message));
+        write_record(SEVERITY_DEBUG, &format!("[DEBUG] {}", message));
+    }
+}
+
+/// Hide the values of environment variables passed to tmux (`-e KEY=value`),
+/// which may be secrets, in arguments written to the log
+pub fn redact_args(args: &[&str]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut env_value = false;
+    for arg in args {
+        redacted.push(match arg.split_once('=') {
+            Some((key, _)) if env_value => format!("{}=<redacted>", key),
+            _ => arg.to_string(),
+        });
+        env_value = *arg == "-e";
+    }
+    redacted
+}
+
+/// Record an executed external command (only logged when -v flag is used)
+///
+/// # Arguments
+/// * `args` - Arguments passed to tmux
+/// * `duration` - How long the command took
+/// * `status` - Exit code, or None if it was killed by a signal or failed to start
+/// * `stderr` - Captured stderr (empty if not captured)
+pub fn trace_command(args: &[&str], duration: std::time::Duration, status: Option<i32>, stderr: &str) {
+    if !is_debug() {
+        return;
     }
+
+    let status = status.map_or_else(|| "none".to_string(), |c| c.to_string());
+    write_record(
+        SEVERITY_DEBUG,
+        &format!(
+            "[DEBUG] {} tmux args={:?} status={} duration_ms={} stderr={:?}",
+            COMMAND_TRACE_TAG,
+            redact_args(args),
+            status,
+            duration.as_millis(),
+            stderr.trim()
+        ),
+    );
 }
 
 /// Log an info message
 pub fn info(message: &str) {
-    log(&format!("[INFO] {}", message));
+    write_record(SEVERITY_INFO, &format!("[INFO] {}", message));
 }
 
 /// Log an error message
 pub fn error(message: &str) {
-    log(&format!("[ERROR] {}", message));
+    write_record(SEVERITY_ERROR, &format!("[ERROR] {}", message));
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_args() {
+        let args = [
+            "split-window",
+            "-e",
+            "API_KEY=s3cret",
+            "-c",
+            "/src",
+            "-e",
+            "DEBUG",
+            "echo A=b",
+        ];
+        assert_eq!(
+            redact_args(&args),
+            [
+                "split-window",
+                "-e",
+                "API_KEY=<redacted>",
+                "-c",
+                "/src",
+                "-e",
+                "DEBUG",
+                "echo A=b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_newest_records() {
+        let log = b"[1] first\n[2] second\n[3] third\n";
+        assert_eq!(newest_records(log, 100), None);
+        // Cut to half of the cap, at the start of a record
+        assert_eq!(newest_records(log, 20), Some(&b"[3] third\n"[..]));
+        assert_eq!(newest_records(log, 24), Some(&b"[3] third\n"[..]));
+        assert_eq!(newest_records(log, 0), Some(&b""[..]));
+    }
+}
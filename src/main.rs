@@ -1,24 +1,20 @@
-mod cli;
-mod commands;
-mod config;
-mod context;
-mod log;
-mod session;
-mod shells;
-mod tmux;
-
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
-use context::Context;
+use tmx::cli::{
+    AttachMode, Cli, Commands, ConfigCommands, ImportCommands, LayoutCommands, ProjectCommands, ServerCommands,
+    WorkspaceCommands,
+};
+use tmx::context::Context;
+use tmx::overrides::Override;
+use tmx::{commands, gc, log, messages, remote, tmux};
 
 fn main() {
     // Parse CLI first to get verbose flag
     let cli = Cli::parse();
 
-    // Initialize logging to ~/.cache/tmx/tmx.log
+    // Initialize logging to ~/.cache/tmx/tmx.log (or --log-file / TMX_LOG_FILE)
     // Pass verbose flag to enable debug level logging
-    log::init(cli.verbose);
+    log::init(cli.verbose, cli.log_file.as_deref());
 
     if let Err(e) = run(cli) {
         log::error(&format!("{}", e));
@@ -30,24 +26,239 @@ fn main() {
 fn run(cli: Cli) -> Result<()> {
 
     // Create context once with all CLI arguments and env vars
-    let ctx = Context::new(cli.config, cli.verbose)?;
+    let overrides = cli
+        .overrides
+        .iter()
+        .map(|arg| Override::parse(arg))
+        .collect::<Result<Vec<_>>>()?;
+    let ctx = Context::new(cli.config, cli.verbose)?.with_overrides(overrides);
+
+    // Apply global settings from config (defaults if the config can't be loaded).
+    // `tmx project trust` skips this, so it doesn't ask about the file it trusts
+    let trusting = matches!(
+        cli.command,
+        Some(Commands::Project {
+            action: ProjectCommands::Trust { .. }
+        })
+    );
+    if !trusting && let Ok(config) = ctx.config() {
+        log::set_targets(&config.log.targets);
+        tmux::set_retry_policy(config.tmux.retries, config.tmux.retry_backoff_ms);
+        tmux::set_wsl(config.tmux.wsl);
+        tmux::set_socket_name(config.tmux.socket_name.as_deref());
+        tmux::set_control_mode(config.tmux.control_mode);
+        // `tmx gc` runs its own pass
+        if !matches!(cli.command, Some(Commands::Gc)) {
+            gc::run_if_due(&config.gc, &config.log);
+        }
+    }
+
+    if cli.print_target && cli.command.is_some() {
+        anyhow::bail!(
+            "--print-target before a command only applies to cycling with 'tmx'\n  \
+             Hint: Use 'tmx open <session> --print-target'"
+        );
+    }
+    if cli.attach_mode.is_some() && cli.command.is_some() {
+        anyhow::bail!(
+            "--attach-mode before a command only applies to cycling with 'tmx'\n  \
+             Hint: Put it after the command, e.g. 'tmx open <session> --attach-mode switch'"
+        );
+    }
 
     match cli.command {
-        Some(Commands::Open { session }) => commands::start::run(&session, &ctx),
-        Some(Commands::Close { session }) => commands::stop::run(&session, &ctx),
-        Some(Commands::Refresh { session }) => commands::refresh::run(&session, &ctx),
-        Some(Commands::List) => commands::list::run(&ctx),
+        Some(Commands::Open {
+            session,
+            sha256,
+            force,
+            no_commands,
+            attach_mode,
+            new_terminal,
+            print_target,
+        }) => {
+            let attach_mode = if print_target {
+                attach_mode_from_args(Some(AttachMode::Print), false)
+            } else {
+                attach_mode_from_args(attach_mode, new_terminal)
+            };
+            if remote::is_url(&session) {
+                commands::import::open(
+                    &session,
+                    sha256.as_deref(),
+                    force,
+                    no_commands,
+                    attach_mode,
+                    &ctx,
+                )
+            } else if sha256.is_some() {
+                anyhow::bail!(
+                    "--sha256 only applies to opening a URL\n  Hint: Use 'tmx open https://<config URL> --sha256 <checksum>'"
+                )
+            } else {
+                commands::start::run(&session, force, no_commands, attach_mode, &ctx)
+            }
+        }
+        Some(Commands::Attach {
+            session,
+            attach_mode,
+            new_terminal,
+        }) => commands::attach::run(
+            session.as_deref(),
+            attach_mode_from_args(attach_mode, new_terminal),
+            &ctx,
+        ),
+        Some(Commands::Picker { tmux_popup }) => commands::picker::run(tmux_popup, &ctx),
+        Some(Commands::Close { session, force }) => {
+            commands::stop::run(session.as_deref(), force, &ctx)
+        }
+        Some(Commands::Refresh { session, window }) => {
+            commands::refresh::run(session.as_deref(), window.as_deref(), &ctx)
+        }
+        Some(Commands::Reload { session }) => commands::reload::run(&session, &ctx),
+        Some(Commands::Sync { session }) => commands::sync::run(&session, &ctx),
+        Some(Commands::Run {
+            target,
+            timeout,
+            command,
+        }) => commands::run::run(&target, &command, timeout, &ctx),
+        Some(Commands::Copy {
+            target,
+            lines,
+            no_clipboard,
+        }) => commands::buffer::copy(&target, lines, !no_clipboard, &ctx),
+        Some(Commands::Paste { target }) => commands::buffer::paste(&target, &ctx),
+        Some(Commands::Bench { session, iterations }) => {
+            commands::bench::run(&session, iterations, &ctx)
+        }
+        Some(Commands::SyncConfig { url }) => commands::sync_config::run(url.as_deref()),
+        Some(Commands::Import {
+            action: ImportCommands::Url { url, sha256, yes },
+        }) => commands::import::url(&url, sha256.as_deref(), yes, &ctx),
+        Some(Commands::Ticket {
+            ticket,
+            attach_mode,
+            new_terminal,
+        }) => commands::ticket::run(
+            &ticket,
+            attach_mode_from_args(attach_mode, new_terminal),
+            &ctx,
+        ),
+        Some(Commands::Layout { action }) => match action {
+            LayoutCommands::Save { target, name } => commands::layout::save(&target, &name, &ctx),
+            LayoutCommands::Apply { target, name } => commands::layout::apply(&target, &name, &ctx),
+        },
+        Some(Commands::Project { action }) => match action {
+            ProjectCommands::New {
+                name,
+                template,
+                attach_mode,
+                new_terminal,
+            } => commands::project::new(
+                &name,
+                template,
+                attach_mode_from_args(attach_mode, new_terminal),
+                &ctx,
+            ),
+            ProjectCommands::Trust { path } => commands::project::trust(path.as_deref(), &ctx),
+        },
+        Some(Commands::Workspace { action }) => match action {
+            WorkspaceCommands::Open {
+                name,
+                attach_mode,
+                new_terminal,
+            } => commands::workspace::open(
+                &name,
+                attach_mode_from_args(attach_mode, new_terminal),
+                &ctx,
+            ),
+            WorkspaceCommands::Close { name } => commands::workspace::close(&name, &ctx),
+        },
+        Some(Commands::Server { action }) => match action {
+            ServerCommands::Start => commands::server::start(&ctx),
+            ServerCommands::Kill { force } => commands::server::kill(force, &ctx),
+            ServerCommands::Info => commands::server::info(&ctx),
+        },
+        Some(Commands::Graph {
+            session,
+            format,
+            live,
+        }) => commands::graph::run(&session, format, live, &ctx),
+        Some(Commands::Status {
+            session: Some(session),
+            summary: true,
+            ..
+        }) => commands::status::summary(&session, &ctx),
+        Some(Commands::Status {
+            session, health, ..
+        }) => commands::status::run(session.as_deref(), health, &ctx),
+        Some(Commands::Snapshot {
+            session,
+            id,
+            append,
+        }) => commands::snapshot::run(&session, id.as_deref(), append, &ctx),
+        Some(Commands::PromptSegment { format }) => commands::prompt_segment::run(format, &ctx),
+        Some(Commands::List { watch }) => commands::list::run(watch, &ctx),
+        Some(Commands::Logs { commands: only_commands, lines }) => {
+            commands::logs::run(only_commands, lines)
+        }
+        Some(Commands::Gc) => commands::gc::run(&ctx),
+        Some(Commands::History {
+            session,
+            lines,
+            format,
+        }) => commands::history::run(session.as_deref(), lines, format),
+        Some(Commands::Config {
+            action: ConfigCommands::Restore { backup, list },
+        }) => commands::config::restore(backup.as_deref(), list, &ctx),
+        Some(Commands::Config {
+            action: ConfigCommands::Messages,
+        }) => commands::config::messages(),
         Some(Commands::Init) => commands::init::run(),
-        Some(Commands::Validate) => commands::validate::run(&ctx),
-        Some(Commands::Completions { shell }) => {
+        Some(Commands::Validate { format, strict }) => {
+            commands::validate::run(format, strict, &ctx)
+        }
+        Some(Commands::Lint) => commands::lint::run(&ctx),
+        Some(Commands::Migrate { dry_run }) => commands::migrate::run(dry_run, &ctx),
+        Some(Commands::Version) => commands::version::run(ctx.is_verbose(), &ctx),
+        Some(Commands::Completions { shell, install }) => {
             let shell = shell.parse()?;
-            commands::completions::run_completions(shell)
+            commands::completions::run_completions(shell, install)
         }
-        Some(Commands::ListConfigured) => commands::list::list_configured(&ctx),
+        Some(Commands::ListConfigured { sources }) => {
+            commands::list::list_configured(sources, &ctx)
+        }
+        Some(Commands::ListWorkspaces) => commands::list::list_workspaces(&ctx),
         Some(Commands::ListRunning) => commands::list::list_running(),
+        Some(Commands::ListWindows { session }) => commands::list::list_windows(&session, &ctx),
+        Some(Commands::NotifyExit {
+            session,
+            window,
+            pane,
+            status,
+        }) => commands::notify::exit(&session, &window, &pane, &status),
+        Some(Commands::SessionDied { session }) => commands::notify::session_died(&session, &ctx),
+        Some(Commands::External(args)) => commands::plugin::run(&args, &ctx),
         None => {
             // Default command: cycle through sessions
-            commands::default::run(&ctx)
+            let attach_mode = if cli.print_target {
+                Some(AttachMode::Print)
+            } else {
+                cli.attach_mode
+            };
+            commands::default::run(attach_mode_from_args(attach_mode, false), &ctx)
         }
     }
 }
+
+/// Combine the attach flags, moving messages to stderr in print mode so
+/// stdout only carries the session name
+fn attach_mode_from_args(
+    attach_mode: Option<AttachMode>,
+    new_terminal: bool,
+) -> Option<AttachMode> {
+    let attach_mode = AttachMode::from_args(attach_mode, new_terminal);
+    if attach_mode == Some(AttachMode::Print) {
+        messages::use_stderr();
+    }
+    attach_mode
+}
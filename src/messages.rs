@@ -0,0 +1,571 @@
+//! User-facing messages with stable identifiers and optional translations.
+//!
+//! Each message has an ID that never changes (e.g. `session.created`) and an
+//! English template with `{name}` placeholders. Output goes through `say`,
+//! which prints the message in the user's language and logs it in English
+//! with its ID, so log parsers don't depend on the wording or the locale.
+//!
+//! The catalog covers status and progress messages. Tables, listings,
+//! machine-readable output, prompts and error messages are printed as they are.
+//!
+//! Translations are flat TOML files mapping IDs to templates, read from
+//! `~/.config/tmx/messages/<lang>.toml`. The language comes from `LC_ALL`,
+//! `LC_MESSAGES` or `LANG` (`de_DE.UTF-8` tries `de_DE.toml`, then
+//! `de.toml`). Missing IDs, and translations using placeholders the English
+//! message doesn't have, fall back to English. `tmx config messages` prints
+//! the English catalog as a starting point.
+
+use crate::config::Config;
+use crate::log;
+use crate::placeholders;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A user-facing message
+#[derive(Debug, Clone, Copy)]
+pub struct Message {
+    /// Stable identifier, used in logs and translation files
+    pub id: &'static str,
+    /// English template with `{name}` placeholders
+    pub text: &'static str,
+}
+
+pub const SESSION_CREATING: Message = Message {
+    id: "session.creating",
+    text: "Creating session '{session}' with {windows} window(s)...",
+};
+pub const SESSION_CREATING_DEFAULT: Message = Message {
+    id: "session.creating_default",
+    text: "Creating session '{session}' using default layout...",
+};
+pub const SESSION_CREATING_GROUPED: Message = Message {
+    id: "session.creating_grouped",
+    text: "Creating session '{session}' grouped with '{group}'...",
+};
+pub const SESSION_WAITING_FOR_SETUP: Message = Message {
+    id: "session.waiting_for_setup",
+    text: "  Waiting for setup in window '{window}'...",
+};
+pub const SESSION_SETUP_TIMEOUT: Message = Message {
+    id: "session.setup_timeout",
+    text: "Warning: Setup of pane {pane} in window '{window}' didn't finish within {seconds}s, \
+           its command will run once setup completes",
+};
+pub const SESSION_CREATED: Message = Message {
+    id: "session.created",
+    text: "✓ Session '{session}' created",
+};
+pub const SESSION_WINDOW_COUNT: Message = Message {
+    id: "session.window_count",
+    text: "  Windows: {windows}",
+};
+pub const SESSION_WINDOW_PANES: Message = Message {
+    id: "session.window_panes",
+    text: "    - {window}: {panes} pane(s)",
+};
+pub const SESSION_GROUPED: Message = Message {
+    id: "session.grouped",
+    text: "  Shares {windows} window(s) with '{group}'",
+};
+pub const LAYOUT_TOO_SMALL: Message = Message {
+    id: "layout.too_small",
+    text: "Warning: Window '{window}' is {width}x{height}, too small for pane {pane} with size {size}; \
+           applying its layout without pane sizes",
+};
+pub const SESSION_EXIT_HOOKS_MISSED: Message = Message {
+    id: "session.exit_hooks_missed",
+    text: "Warning: Pane {pane} in window {window} exited before its exit hooks were installed",
+};
+pub const SESSION_ENV_FILE_MISSING: Message = Message {
+    id: "session.env_file_missing",
+    text: "Warning: Env file {path} not found, skipping",
+};
+pub const SESSION_NAME_SANITIZED: Message = Message {
+    id: "session.name_sanitized",
+    text: "Note: Session name '{name}' contains special characters and will be created as '{session}'",
+};
+pub const SESSION_STARTING_DEFAULT: Message = Message {
+    id: "session.starting_default",
+    text: "No sessions running. Starting '{session}'...",
+};
+pub const SESSION_LOCK_WAITING: Message = Message {
+    id: "session.lock_waiting",
+    text: "Waiting for another tmx process working on '{session}'...",
+};
+pub const SESSION_ATTACHING: Message = Message {
+    id: "session.attaching",
+    text: "Attaching to existing session '{session}'...",
+};
+pub const SESSION_RECREATING: Message = Message {
+    id: "session.recreating",
+    text: "Recreating session '{session}' from config...",
+};
+pub const SESSION_RENAMED: Message = Message {
+    id: "session.renamed",
+    text: "Session '{session}' is already running, creating '{name}' instead...",
+};
+pub const SESSION_KILLING: Message = Message {
+    id: "session.killing",
+    text: "Killing existing session '{session}'...",
+};
+pub const SESSION_OPENED_IN_TERMINAL: Message = Message {
+    id: "session.opened_in_terminal",
+    text: "✓ Opened session '{session}' in a new terminal window",
+};
+pub const SESSION_RELOADING: Message = Message {
+    id: "session.reloading",
+    text: "Reloading session '{session}'...",
+};
+pub const SESSION_RELOADED: Message = Message {
+    id: "session.reloaded",
+    text: "✓ Session '{session}' reloaded",
+};
+pub const SESSION_REFRESHING: Message = Message {
+    id: "session.refreshing",
+    text: "Refreshing layout for session '{session}'...",
+};
+pub const SESSION_REFRESHING_WINDOW: Message = Message {
+    id: "session.refreshing_window",
+    text: "Refreshing layout for window '{window}' of session '{session}'...",
+};
+pub const REFRESH_WINDOW_MISSING: Message = Message {
+    id: "refresh.window_missing",
+    text: "  Window '{window}': not running (renamed or closed?), skipping",
+};
+pub const REFRESH_WINDOW_PANES: Message = Message {
+    id: "refresh.window_panes",
+    text: "  Window '{window}': current={current} panes, config={expected} panes",
+};
+pub const REFRESH_ADDING_PANES: Message = Message {
+    id: "refresh.adding_panes",
+    text: "    Adding {count} pane(s)...",
+};
+pub const REFRESH_KEEPING_PANES: Message = Message {
+    id: "refresh.keeping_panes",
+    text: "    Keeping {count} extra pane(s) (not removing)",
+};
+pub const REFRESH_APPLYING_LAYOUT: Message = Message {
+    id: "refresh.applying_layout",
+    text: "    Applying layout and sizes...",
+};
+pub const SESSION_REFRESHED: Message = Message {
+    id: "session.refreshed",
+    text: "✓ Session '{session}' layout refreshed",
+};
+pub const SYNC_MATCHES: Message = Message {
+    id: "sync.matches",
+    text: "✓ Session '{session}' matches its configuration",
+};
+pub const SYNC_DIFFERS: Message = Message {
+    id: "sync.differs",
+    text: "Session '{session}' differs from its configuration in {count} place(s):",
+};
+pub const SYNC_SKIPPED: Message = Message {
+    id: "sync.skipped",
+    text: "  Skipped",
+};
+pub const SYNC_WROTE: Message = Message {
+    id: "sync.wrote",
+    text: "✓ Wrote {count} change(s) to {path}",
+};
+pub const SESSION_SYNCED: Message = Message {
+    id: "session.synced",
+    text: "✓ Session '{session}' synced",
+};
+pub const SESSION_STOPPING_PROCESSES: Message = Message {
+    id: "session.stopping_processes",
+    text: "Stopping processes in session '{session}'...",
+};
+pub const SESSION_SHUTDOWN_RUNNING: Message = Message {
+    id: "session.shutdown_running",
+    text: "  Running '{command}'...",
+};
+pub const SESSION_SHUTDOWN_FAILED: Message = Message {
+    id: "session.shutdown_failed",
+    text: "Warning: '{command}' failed ({status})",
+};
+pub const SESSION_SHUTDOWN_TIMEOUT: Message = Message {
+    id: "session.shutdown_timeout",
+    text: "Warning: '{command}' didn't finish within {seconds}s and was stopped",
+};
+pub const SESSION_KILLING_BUSY: Message = Message {
+    id: "session.killing_busy",
+    text: "Warning: Still running after {seconds}s, killing: {processes}",
+};
+pub const SESSION_STOPPED: Message = Message {
+    id: "session.stopped",
+    text: "✓ Session '{session}' stopped",
+};
+pub const SESSION_SWITCHED: Message = Message {
+    id: "session.switched",
+    text: "Switched to session '{session}'",
+};
+pub const SESSION_CLIENT_DETACHES: Message = Message {
+    id: "session.client_detaches",
+    text: "The client will detach with the session",
+};
+pub const SESSION_CLIENT_EXITS: Message = Message {
+    id: "session.client_exits",
+    text: "Warning: No other session is running, so the client will exit with the session",
+};
+pub const WORKSPACE_RUNNING: Message = Message {
+    id: "workspace.running",
+    text: "✓ Workspace '{workspace}' is running ({sessions} sessions)",
+};
+pub const WORKSPACE_NOT_RUNNING: Message = Message {
+    id: "workspace.not_running",
+    text: "No sessions of workspace '{workspace}' are running",
+};
+pub const TICKET_WORKTREE: Message = Message {
+    id: "ticket.worktree",
+    text: "Creating worktree {root} on branch '{branch}'...",
+};
+pub const TICKET_CREATING: Message = Message {
+    id: "ticket.creating",
+    text: "Creating session '{session}' from template '{template}'...",
+};
+pub const PROJECT_CREATED: Message = Message {
+    id: "project.created",
+    text: "✓ Created project '{project}' in {dir}",
+};
+pub const HOOK_RUNNING: Message = Message {
+    id: "hook.running",
+    text: "  Running {hook}: {command}",
+};
+pub const HOOK_FAILED: Message = Message {
+    id: "hook.failed",
+    text: "Warning: {error}",
+};
+pub const IMPORT_FETCHED: Message = Message {
+    id: "import.fetched",
+    text: "Fetched {url} ({bytes} bytes, sha256 {sha256})",
+};
+pub const IMPORT_SESSION: Message = Message {
+    id: "import.session",
+    text: "  Session '{session}' with {windows} window(s)",
+};
+pub const IMPORT_COMMAND: Message = Message {
+    id: "import.command",
+    text: "    {kind}: {command}",
+};
+pub const IMPORT_DONE: Message = Message {
+    id: "import.done",
+    text: "✓ Imported {count} session(s) from {url} into {path}",
+};
+pub const IMPORT_UNCHANGED: Message = Message {
+    id: "import.unchanged",
+    text: "✓ {url} is unchanged since it was imported into {path}",
+};
+pub const IMPORT_OPEN_HINT: Message = Message {
+    id: "import.open_hint",
+    text: "  Open it with 'tmx open {session}'",
+};
+pub const SESSION_CYCLE_ATTACHING: Message = Message {
+    id: "session.cycle_attaching",
+    text: "Attaching to session '{session}'...",
+};
+pub const STATUS_NO_SESSIONS: Message = Message {
+    id: "status.no_sessions",
+    text: "No sessions running",
+};
+pub const HISTORY_EMPTY: Message = Message {
+    id: "history.empty",
+    text: "No operations recorded yet",
+};
+pub const HISTORY_EMPTY_SESSION: Message = Message {
+    id: "history.empty_session",
+    text: "No operations recorded for session '{session}'",
+};
+pub const CONFIG_BACKED_UP: Message = Message {
+    id: "config.backed_up",
+    text: "Backed up config to {path}",
+};
+pub const REMOTE_CLONING: Message = Message {
+    id: "remote.cloning",
+    text: "Cloning {url} into {dir}...",
+};
+pub const REMOTE_UPDATING: Message = Message {
+    id: "remote.updating",
+    text: "Updating {dir}...",
+};
+pub const PROJECT_UNTRUSTED: Message = Message {
+    id: "project.untrusted",
+    text: "{path} is a project config tmx hasn't used before",
+};
+pub const PROJECT_CHANGED: Message = Message {
+    id: "project.changed",
+    text: "{path} has changed since it was trusted",
+};
+pub const PROJECT_SKIPPED: Message = Message {
+    id: "project.skipped",
+    text: "Warning: Not using untrusted project config {path} (review it, then run 'tmx project trust')",
+};
+pub const PROJECT_TRUSTED: Message = Message {
+    id: "project.trusted",
+    text: "✓ Trusted {path}",
+};
+pub const CANCELLED: Message = Message {
+    id: "prompt.cancelled",
+    text: "Cancelled",
+};
+
+/// All messages, in the order `tmx config messages` lists them
+pub const CATALOG: &[Message] = &[
+    SESSION_CREATING,
+    SESSION_CREATING_DEFAULT,
+    SESSION_CREATING_GROUPED,
+    SESSION_WAITING_FOR_SETUP,
+    SESSION_SETUP_TIMEOUT,
+    SESSION_CREATED,
+    SESSION_WINDOW_COUNT,
+    SESSION_WINDOW_PANES,
+    SESSION_GROUPED,
+    LAYOUT_TOO_SMALL,
+    SESSION_EXIT_HOOKS_MISSED,
+    SESSION_ENV_FILE_MISSING,
+    SESSION_NAME_SANITIZED,
+    SESSION_STARTING_DEFAULT,
+    SESSION_LOCK_WAITING,
+    SESSION_ATTACHING,
+    SESSION_RECREATING,
+    SESSION_RENAMED,
+    SESSION_KILLING,
+    SESSION_OPENED_IN_TERMINAL,
+    SESSION_RELOADING,
+    SESSION_RELOADED,
+    SESSION_REFRESHING,
+    SESSION_REFRESHING_WINDOW,
+    REFRESH_WINDOW_MISSING,
+    REFRESH_WINDOW_PANES,
+    REFRESH_ADDING_PANES,
+    REFRESH_KEEPING_PANES,
+    REFRESH_APPLYING_LAYOUT,
+    SESSION_REFRESHED,
+    SYNC_MATCHES,
+    SYNC_DIFFERS,
+    SYNC_SKIPPED,
+    SYNC_WROTE,
+    SESSION_SYNCED,
+    SESSION_STOPPING_PROCESSES,
+    SESSION_SHUTDOWN_RUNNING,
+    SESSION_SHUTDOWN_FAILED,
+    SESSION_SHUTDOWN_TIMEOUT,
+    SESSION_KILLING_BUSY,
+    SESSION_STOPPED,
+    SESSION_SWITCHED,
+    SESSION_CLIENT_DETACHES,
+    SESSION_CLIENT_EXITS,
+    WORKSPACE_RUNNING,
+    WORKSPACE_NOT_RUNNING,
+    TICKET_WORKTREE,
+    TICKET_CREATING,
+    PROJECT_CREATED,
+    HOOK_RUNNING,
+    HOOK_FAILED,
+    IMPORT_FETCHED,
+    IMPORT_SESSION,
+    IMPORT_COMMAND,
+    IMPORT_DONE,
+    IMPORT_UNCHANGED,
+    IMPORT_OPEN_HINT,
+    SESSION_CYCLE_ATTACHING,
+    STATUS_NO_SESSIONS,
+    HISTORY_EMPTY,
+    HISTORY_EMPTY_SESSION,
+    CONFIG_BACKED_UP,
+    REMOTE_CLONING,
+    REMOTE_UPDATING,
+    PROJECT_UNTRUSTED,
+    PROJECT_CHANGED,
+    PROJECT_SKIPPED,
+    PROJECT_TRUSTED,
+    CANCELLED,
+];
+
+/// Values for a message's placeholders
+pub type Args<'a> = &'a [(&'a str, &'a dyn Display)];
+
+/// Whether messages are printed on stderr (`--attach-mode print`)
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Translations for the user's language, by message ID
+static TRANSLATIONS: Lazy<HashMap<String, String>> = Lazy::new(load_translations);
+
+/// Print a message in the user's language and log it with its ID.
+///
+/// # Arguments
+/// * `message` - The message to print
+/// * `args` - Values for its placeholders, e.g. `&[("session", &name)]`
+pub fn say(message: Message, args: Args) {
+    let english = render(message.text, args).unwrap_or_else(|| message.text.to_string());
+    log::info(&format!("[{}] {}", message.id, english.trim_start()));
+    let text = text_in(message, args, &TRANSLATIONS);
+    if on_stderr() {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// Print a message on stderr, whatever the output mode, e.g. a warning
+/// that mustn't end up in output read by scripts.
+///
+/// # Arguments
+/// * `message` - The message to print
+/// * `args` - Values for its placeholders
+pub fn warn(message: Message, args: Args) {
+    let english = render(message.text, args).unwrap_or_else(|| message.text.to_string());
+    log::info(&format!("[{}] {}", message.id, english.trim_start()));
+    eprintln!("{}", text_in(message, args, &TRANSLATIONS));
+}
+
+/// Print messages on stderr from now on, so stdout only carries output
+/// meant for scripts (`--attach-mode print`)
+pub fn use_stderr() {
+    TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Whether messages are printed on stderr
+pub fn on_stderr() -> bool {
+    TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// A message using a translation when there is a usable one
+fn text_in(message: Message, args: Args, translations: &HashMap<String, String>) -> String {
+    translations
+        .get(message.id)
+        .and_then(|template| render(template, args))
+        .or_else(|| render(message.text, args))
+        .unwrap_or_else(|| message.text.to_string())
+}
+
+/// Fill in a template's placeholders.
+///
+/// # Returns
+/// None if the template uses a placeholder without a value, or is malformed.
+fn render(template: &str, args: Args) -> Option<String> {
+    placeholders::expand(template, |name| {
+        args.iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+            .ok_or_else(|| anyhow::anyhow!("no value for '{}'", name))
+    })
+    .ok()
+}
+
+/// The English catalog as a translation file
+pub fn catalog_toml() -> String {
+    let mut doc = toml_edit::DocumentMut::new();
+    for message in CATALOG {
+        doc[message.id] = toml_edit::value(message.text);
+    }
+    doc.to_string()
+}
+
+/// Language codes to look for, most specific first (e.g. `de_DE`, `de`).
+///
+/// # Arguments
+/// * `locale` - A locale such as `de_DE.UTF-8` or `sr_RS@latin`
+fn languages(locale: &str) -> Vec<String> {
+    let lang = locale.split(['.', '@']).next().unwrap_or_default();
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+    let mut languages = vec![lang.to_string()];
+    if let Some((base, _)) = lang.split_once(['_', '-']) {
+        languages.push(base.to_string());
+    }
+    languages
+}
+
+/// The locale for messages: the first non-empty of LC_ALL, LC_MESSAGES and LANG
+fn locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Directory of translation files (~/.config/tmx/messages)
+pub fn translations_dir() -> Option<PathBuf> {
+    Config::config_dir().ok().map(|dir| dir.join("messages"))
+}
+
+/// Read the translation file for the user's language, if there is one
+fn load_translations() -> HashMap<String, String> {
+    let (Some(locale), Some(dir)) = (locale(), translations_dir()) else {
+        return HashMap::new();
+    };
+    for lang in languages(&locale) {
+        let path = dir.join(format!("{}.toml", lang));
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        return match toml::from_str(&content) {
+            Ok(translations) => {
+                log::debug(&format!("messages: using {}", path.display()));
+                translations
+            }
+            Err(e) => {
+                log::error(&format!("messages: invalid {}: {}", path.display(), e));
+                HashMap::new()
+            }
+        };
+    }
+    HashMap::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_ids_are_unique() {
+        let ids: HashSet<&str> = CATALOG.iter().map(|message| message.id).collect();
+        assert_eq!(ids.len(), CATALOG.len());
+
+        let parsed: HashMap<String, String> = toml::from_str(&catalog_toml()).unwrap();
+        assert_eq!(parsed["session.created"], SESSION_CREATED.text);
+    }
+
+    #[test]
+    fn test_text_in() {
+        let args: Args = &[("session", &"dev"), ("windows", &3)];
+        let none = HashMap::new();
+        assert_eq!(
+            text_in(SESSION_CREATING, args, &none),
+            "Creating session 'dev' with 3 window(s)..."
+        );
+
+        let mut de = HashMap::new();
+        de.insert(
+            "session.creating".to_string(),
+            "Erstelle Sitzung '{session}' mit {windows} Fenster(n)...".to_string(),
+        );
+        assert_eq!(
+            text_in(SESSION_CREATING, args, &de),
+            "Erstelle Sitzung 'dev' mit 3 Fenster(n)..."
+        );
+
+        // A placeholder the message doesn't have falls back to English
+        de.insert("session.creating".to_string(), "Sitzung {name}".to_string());
+        assert_eq!(
+            text_in(SESSION_CREATING, args, &de),
+            "Creating session 'dev' with 3 window(s)..."
+        );
+    }
+
+    #[test]
+    fn test_languages() {
+        assert_eq!(languages("de_DE.UTF-8"), vec!["de_DE", "de"]);
+        assert_eq!(languages("sr_RS@latin"), vec!["sr_RS", "sr"]);
+        assert_eq!(languages("fr"), vec!["fr"]);
+        assert!(languages("C.UTF-8").is_empty());
+        assert!(languages("POSIX").is_empty());
+    }
+}
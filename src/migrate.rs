@@ -0,0 +1,103 @@
+//! Upgrades of older config files to the current format version.
+//!
+//! Each format change adds a step that rewrites a document from its version
+//! to the next, so a file of any older version is migrated step by step.
+//! Steps edit the document with toml_edit, keeping comments and formatting.
+
+use crate::config::CONFIG_VERSION;
+use anyhow::{Context, Result};
+use toml_edit::DocumentMut;
+
+/// A migration from one format version to the next
+struct Step {
+    /// Version the step upgrades from (to `from + 1`)
+    from: u32,
+    /// Rewrite the document, returning a description of each change
+    apply: fn(&mut DocumentMut) -> Result<Vec<String>>,
+}
+
+/// Migration steps, in version order
+const STEPS: &[Step] = &[Step {
+    from: 1,
+    apply: v1_to_v2,
+}];
+
+/// Get the format version of a config document (1 without a `version` key)
+pub fn document_version(doc: &DocumentMut) -> Result<u32> {
+    match doc.get("version") {
+        None => Ok(1),
+        Some(item) => item
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .context("version must be a positive integer"),
+    }
+}
+
+/// Migrate a config document to the current format version.
+///
+/// # Returns
+/// A description of each change made (empty if the document is current).
+///
+/// # Errors
+/// Returns an error if the document is newer than this tmx supports or a
+/// step fails.
+pub fn migrate(doc: &mut DocumentMut) -> Result<Vec<String>> {
+    let mut version = document_version(doc)?;
+    if version > CONFIG_VERSION {
+        anyhow::bail!(
+            "Config format version {} is newer than this tmx supports ({})\n  Hint: Upgrade tmx",
+            version,
+            CONFIG_VERSION
+        );
+    }
+
+    let mut changes = Vec::new();
+    while version < CONFIG_VERSION {
+        let step = STEPS
+            .iter()
+            .find(|s| s.from == version)
+            .with_context(|| format!("No migration from config format version {}", version))?;
+        changes.extend((step.apply)(doc)?);
+        version += 1;
+        doc["version"] = toml_edit::value(i64::from(version));
+        changes.push(format!("Set version = {}", version));
+    }
+    Ok(changes)
+}
+
+/// Version 2 introduced the `version` key itself; the rest of the format is unchanged
+fn v1_to_v2(_doc: &mut DocumentMut) -> Result<Vec<String>> {
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_migrate_v1() {
+        let mut doc: DocumentMut = "# My sessions\n[sessions.dev]\nname = \"dev\"\nwindows = [{ name = \"a\", panes = [{ command = \"\" }] }]\n"
+            .parse()
+            .unwrap();
+        assert_eq!(document_version(&doc).unwrap(), 1);
+
+        let changes = migrate(&mut doc).unwrap();
+        assert_eq!(changes, vec!["Set version = 2"]);
+        assert_eq!(document_version(&doc).unwrap(), CONFIG_VERSION);
+
+        let content = doc.to_string();
+        assert!(content.contains("# My sessions"));
+        let config: Config = toml::from_str(&content).unwrap();
+        assert_eq!(config.format_version(), CONFIG_VERSION);
+
+        // Already current
+        assert!(migrate(&mut doc).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_newer_version() {
+        let mut doc: DocumentMut = "version = 99\n".parse().unwrap();
+        assert!(migrate(&mut doc).is_err());
+    }
+}
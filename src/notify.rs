@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::log;
+
+/// Show a desktop notification.
+///
+/// Uses `osascript` on macOS and `notify-send` elsewhere.
+///
+/// # Arguments
+/// * `title` - Notification title
+/// * `body` - Notification text
+pub fn send(title: &str, body: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        // Pass the text as arguments so it never has to be quoted for AppleScript
+        let mut command = Command::new("osascript");
+        command
+            .args(["-e", "on run argv"])
+            .args(["-e", "display notification (item 2 of argv) with title (item 1 of argv)"])
+            .args(["-e", "end run"])
+            .args([title, body]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "tmx", title, body]);
+        command
+    };
+
+    log::info(&format!("notify: {}: {}", title, body));
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {:?}", command.get_program()))?;
+    if !status.success() {
+        anyhow::bail!("{:?} failed with status: {}", command.get_program(), status);
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! Config overrides given on the command line with `--set PATH=VALUE`.
+//!
+//! The path is a dotted path into the config file, with array indices as
+//! numbers and quotes around keys that contain dots, e.g.
+//! `sessions.dev.windows.0.panes.0.command` or `sessions."my.app".root`.
+//! Overrides are applied to the parsed file before it is loaded, so they
+//! only last for one invocation.
+
+use crate::config::TOP_LEVEL_KEYS;
+use anyhow::Result;
+use toml::{Table, Value};
+
+/// One `--set PATH=VALUE` override
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    path: Vec<String>,
+    value: Value,
+}
+
+impl Override {
+    /// Parse a `PATH=VALUE` override.
+    ///
+    /// The value is read as TOML if it is valid TOML (`42`, `true`,
+    /// `["a", "b"]`, `"quoted"`), and as a plain string otherwise, so
+    /// `command=nvim +42 src/main.rs` needs no extra quoting.
+    pub fn parse(arg: &str) -> Result<Self> {
+        let Some((path, value)) = arg.split_once('=') else {
+            anyhow::bail!(
+                "Invalid override '{}'\n  \
+                 Hint: Use --set PATH=VALUE, e.g. --set sessions.dev.root=~/src/app",
+                arg
+            );
+        };
+        let path = parse_path(path.trim())
+            .ok_or_else(|| anyhow::anyhow!("Invalid override path '{}'", path.trim()))?;
+        Ok(Self {
+            path,
+            value: parse_value(value),
+        })
+    }
+
+    /// The dotted path, as given
+    pub fn path(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// The session parameter this override gives a value for: a single key
+    /// that isn't a top-level config key, e.g. `--set port=3000`.
+    ///
+    /// # Returns
+    /// The parameter name and its value as text, or None for config paths.
+    pub fn param(&self) -> Option<(String, String)> {
+        let [name] = self.path.as_slice() else {
+            return None;
+        };
+        if TOP_LEVEL_KEYS.contains(&name.as_str()) {
+            return None;
+        }
+        let value = match &self.value {
+            Value::String(value) => value.clone(),
+            other => other.to_string(),
+        };
+        Some((name.clone(), value))
+    }
+
+    /// Set the value in a parsed config file, creating missing tables.
+    ///
+    /// # Errors
+    /// Returns an error if the path runs through a value that isn't a table
+    /// or array, or indexes past the end of an array.
+    pub fn apply(&self, table: &mut Table) -> Result<()> {
+        let mut root = Value::Table(std::mem::take(table));
+        let result = self.set(&mut root, 0);
+        if let Value::Table(root) = root {
+            *table = root;
+        }
+        result
+    }
+
+    /// Set the value below `value`, which is at `self.path[..depth]`
+    fn set(&self, value: &mut Value, depth: usize) -> Result<()> {
+        let key = &self.path[depth];
+        let is_last = depth + 1 == self.path.len();
+        let child = match value {
+            Value::Table(table) => {
+                if is_last {
+                    table.insert(key.clone(), self.value.clone());
+                    return Ok(());
+                }
+                table
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Table(Table::new()))
+            }
+            Value::Array(array) => {
+                let index = key.parse::<usize>().map_err(|_| {
+                    anyhow::anyhow!(
+                        "Override '{}': '{}' is an array\n  Hint: Follow it with an index, e.g. {}.0",
+                        self.path(),
+                        self.path[..depth].join("."),
+                        self.path[..depth].join(".")
+                    )
+                })?;
+                let len = array.len();
+                let element = array.get_mut(index).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Override '{}': index {} is out of range (the array has {} element(s))",
+                        self.path(),
+                        index,
+                        len
+                    )
+                })?;
+                if is_last {
+                    *element = self.value.clone();
+                    return Ok(());
+                }
+                element
+            }
+            other => anyhow::bail!(
+                "Override '{}': '{}' is a {}, not a table",
+                self.path(),
+                self.path[..depth].join("."),
+                other.type_str()
+            ),
+        };
+        self.set(child, depth + 1)
+    }
+}
+
+/// Split a dotted path, honoring `"quoted.keys"`
+fn parse_path(path: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    loop {
+        let (segment, remaining) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => match rest.find('.') {
+                Some(end) => (&rest[..end], &rest[end..]),
+                None => (rest, ""),
+            },
+        };
+        if segment.is_empty() {
+            return None;
+        }
+        segments.push(segment.to_string());
+        match remaining.strip_prefix('.') {
+            Some(next) => rest = next,
+            None if remaining.is_empty() => return Some(segments),
+            None => return None,
+        }
+    }
+}
+
+/// Read a value as TOML, falling back to a plain string
+fn parse_value(value: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Table {
+        toml::from_str(
+            r#"
+[sessions.dev]
+name = "dev"
+windows = [{ name = "editor", panes = [{ command = "nvim" }, { command = "" }] }]
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut table = config();
+        for arg in [
+            "sessions.dev.windows.0.panes.0.command=nvim +42 src/main.rs",
+            "sessions.dev.startup_pane=1",
+            "sessions.dev.tags=[\"tmp\"]",
+            "sessions.\"my.app\".root=~/src/app",
+        ] {
+            Override::parse(arg).unwrap().apply(&mut table).unwrap();
+        }
+
+        let dev = &table["sessions"]["dev"];
+        assert_eq!(
+            dev["windows"][0]["panes"][0]["command"].as_str(),
+            Some("nvim +42 src/main.rs")
+        );
+        assert_eq!(dev["startup_pane"].as_integer(), Some(1));
+        assert_eq!(dev["tags"][0].as_str(), Some("tmp"));
+        assert_eq!(table["sessions"]["my.app"]["root"].as_str(), Some("~/src/app"));
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(Override::parse("sessions.dev.root").is_err());
+        assert!(Override::parse("sessions..root=x").is_err());
+
+        let mut table = config();
+        let out_of_range = Override::parse("sessions.dev.windows.3.name=x").unwrap();
+        let error = out_of_range.apply(&mut table).unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+        let through_string = Override::parse("sessions.dev.name.first=x").unwrap();
+        assert!(through_string.apply(&mut table).is_err());
+    }
+
+    #[test]
+    fn test_param() {
+        let param = |arg: &str| Override::parse(arg).unwrap().param();
+        assert_eq!(
+            param("port=3000"),
+            Some(("port".to_string(), "3000".to_string()))
+        );
+        assert_eq!(
+            param("dir=~/src/app"),
+            Some(("dir".to_string(), "~/src/app".to_string()))
+        );
+        assert_eq!(param("terminal_command=alacritty -e"), None);
+        assert_eq!(param("sessions.dev.root=~"), None);
+    }
+}
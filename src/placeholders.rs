@@ -0,0 +1,231 @@
+use anyhow::Result;
+use std::fs;
+use std::process::Command;
+
+/// Placeholders accepted in session names
+pub const NAME_PLACEHOLDERS: &[&str] = &["hostname"];
+
+/// Placeholders accepted in `terminal_command`
+pub const TERMINAL_PLACEHOLDERS: &[&str] = &["session", "hostname"];
+
+/// Placeholders accepted in the `[ticket]` name, root and branch
+pub const TICKET_PLACEHOLDERS: &[&str] = &["ticket", "ticket_lower", "hostname"];
+
+/// Placeholders accepted in window names
+pub const WINDOW_PLACEHOLDERS: &[&str] = &["session", "root_basename", "index", "hostname"];
+
+/// Placeholders accepted in `on_died`
+pub const ON_DIED_PLACEHOLDERS: &[&str] = &["name", "hostname"];
+
+/// Expand `{placeholder}`s in a session name.
+///
+/// `{hostname}` is replaced by the machine's short hostname (up to the first
+/// `.`), so a config shared across machines gives each one distinct session
+/// names, e.g. `name = "dev-{hostname}"`.
+///
+/// # Errors
+/// Returns an error for unknown or unterminated placeholders, or if the
+/// hostname can't be determined.
+pub fn expand_session_name(name: &str) -> Result<String> {
+    expand(name, |placeholder| match placeholder {
+        "hostname" => hostname_value(),
+        _ => Err(unknown_placeholder(placeholder, NAME_PLACEHOLDERS)),
+    })
+}
+
+/// Expand `{placeholder}`s in a `terminal_command` word.
+///
+/// `{session}` is replaced by the session being opened (e.g. for a window
+/// title or class) and `{hostname}` by the short hostname.
+///
+/// # Errors
+/// Returns an error for unknown or unterminated placeholders.
+pub fn expand_terminal_command(word: &str, session: &str) -> Result<String> {
+    expand(word, |placeholder| match placeholder {
+        "session" => Ok(session.to_string()),
+        "hostname" => hostname_value(),
+        _ => Err(unknown_placeholder(placeholder, TERMINAL_PLACEHOLDERS)),
+    })
+}
+
+/// Expand `{placeholder}`s in a `[ticket]` template.
+///
+/// `{ticket}` is replaced by the ticket ID as given, `{ticket_lower}` by the
+/// lowercased ID and `{hostname}` by the short hostname.
+///
+/// # Errors
+/// Returns an error for unknown or unterminated placeholders.
+pub fn expand_ticket(template: &str, ticket: &str) -> Result<String> {
+    expand(template, |placeholder| match placeholder {
+        "ticket" => Ok(ticket.to_string()),
+        "ticket_lower" => Ok(ticket.to_lowercase()),
+        "hostname" => hostname_value(),
+        _ => Err(unknown_placeholder(placeholder, TICKET_PLACEHOLDERS)),
+    })
+}
+
+/// Expand `{placeholder}`s in a window name.
+///
+/// `{session}` is replaced by the session name, `{root_basename}` by the last
+/// component of the window's root, `{index}` by the window's position in the
+/// session (from 0) and `{hostname}` by the short hostname.
+///
+/// # Errors
+/// Returns an error for unknown or unterminated placeholders.
+pub fn expand_window_name(name: &str, session: &str, root: &str, index: usize) -> Result<String> {
+    expand(name, |placeholder| match placeholder {
+        "session" => Ok(session.to_string()),
+        "root_basename" => {
+            let root = root.trim_end_matches('/');
+            Ok(root.rsplit('/').next().filter(|b| !b.is_empty()).unwrap_or("/").to_string())
+        }
+        "index" => Ok(index.to_string()),
+        "hostname" => hostname_value(),
+        _ => Err(unknown_placeholder(placeholder, WINDOW_PLACEHOLDERS)),
+    })
+}
+
+/// Expand `{placeholder}`s in an `on_died` command.
+///
+/// `{name}` is replaced by the name of the session that closed and
+/// `{hostname}` by the short hostname.
+///
+/// # Errors
+/// Returns an error for unknown or unterminated placeholders.
+pub fn expand_on_died(command: &str, name: &str) -> Result<String> {
+    expand(command, |placeholder| match placeholder {
+        "name" => Ok(name.to_string()),
+        "hostname" => hostname_value(),
+        _ => Err(unknown_placeholder(placeholder, ON_DIED_PLACEHOLDERS)),
+    })
+}
+
+/// The short hostname, or an error explaining how to provide it
+fn hostname_value() -> Result<String> {
+    hostname().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Could not determine the hostname for '{{hostname}}'\n  \
+             Hint: Set the HOSTNAME environment variable"
+        )
+    })
+}
+
+/// Error for a placeholder that isn't supported where it is used
+fn unknown_placeholder(placeholder: &str, supported: &[&str]) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown placeholder '{{{}}}'\n  Hint: Supported placeholders: {}",
+        placeholder,
+        supported
+            .iter()
+            .map(|p| format!("{{{}}}", p))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Replace each `{name}` in a template with the value returned by `lookup`.
+///
+/// `${VAR}` environment variable references are left for path expansion.
+pub(crate) fn expand(template: &str, mut lookup: impl FnMut(&str) -> Result<String>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| {
+            anyhow::anyhow!("Unterminated placeholder in '{}'\n  Hint: Close it with '}}'", template)
+        })?;
+        if out.ends_with('$') {
+            out.push_str(&rest[start..start + end + 2]);
+        } else {
+            out.push_str(&lookup(&after[..end])?);
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The machine's short hostname (without the domain)
+fn hostname() -> Option<String> {
+    let full = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| {
+            let output = Command::new("hostname").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+        })?;
+
+    let short = full.trim().split('.').next().unwrap_or_default();
+    (!short.is_empty()).then(|| short.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let lookup = |p: &str| match p {
+            "hostname" => Ok("box".to_string()),
+            _ => anyhow::bail!("unknown"),
+        };
+        assert_eq!(expand("dev-{hostname}", lookup).unwrap(), "dev-box");
+        assert_eq!(expand("{hostname}/{hostname}", lookup).unwrap(), "box/box");
+        assert_eq!(expand("plain", lookup).unwrap(), "plain");
+        assert!(expand("dev-{user}", lookup).is_err());
+        assert!(expand("dev-{hostname", lookup).is_err());
+    }
+
+    #[test]
+    fn test_expand_session_name() {
+        let name = expand_session_name("dev-{hostname}").unwrap();
+        assert!(name.starts_with("dev-"));
+        assert!(name.len() > "dev-".len());
+        assert!(!name.contains('{'));
+        assert!(expand_session_name("dev-{nope}").is_err());
+        assert!(expand_session_name("dev-{session}").is_err());
+    }
+
+    #[test]
+    fn test_expand_terminal_command() {
+        assert_eq!(
+            expand_terminal_command("--title=tmx:{session}", "api").unwrap(),
+            "--title=tmx:api"
+        );
+        assert!(expand_terminal_command("{nope}", "api").is_err());
+    }
+
+    #[test]
+    fn test_expand_ticket() {
+        assert_eq!(
+            expand_ticket("${SRC}/tickets/{ticket_lower}", "JIRA-12").unwrap(),
+            "${SRC}/tickets/jira-12"
+        );
+        assert_eq!(expand_ticket("{ticket}", "JIRA-12").unwrap(), "JIRA-12");
+        assert!(expand_ticket("{session}", "JIRA-12").is_err());
+    }
+
+    #[test]
+    fn test_expand_window_name() {
+        assert_eq!(
+            expand_window_name("{session}:{root_basename}-{index}", "dev", "/src/app/", 2).unwrap(),
+            "dev:app-2"
+        );
+        assert_eq!(expand_window_name("{root_basename}", "dev", "/", 0).unwrap(), "/");
+        assert!(expand_window_name("{ticket}", "dev", "/src", 0).is_err());
+    }
+
+    #[test]
+    fn test_expand_on_died() {
+        assert_eq!(
+            expand_on_died("notify-send 'session {name} died'", "dev").unwrap(),
+            "notify-send 'session dev died'"
+        );
+        assert!(expand_on_died("echo {session}", "dev").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Ask the user to pick one of several single-letter choices.
+///
+/// The question is printed with the choices, e.g. `[l] update tmux  [s] skip`,
+/// and re-asked until a valid letter is entered.
+///
+/// # Returns
+/// The chosen letter, or None if stdin was closed.
+pub fn choose(question: &str, choices: &[(char, &str)]) -> Result<Option<char>> {
+    let menu: Vec<String> = choices
+        .iter()
+        .map(|(key, label)| format!("[{}] {}", key, label))
+        .collect();
+
+    let stdin = io::stdin();
+    loop {
+        print!("{}\n  {}: ", question, menu.join("  "));
+        io::stdout().flush().context("Failed to flush stdout")?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).context("Failed to read input")? == 0 {
+            println!();
+            return Ok(None);
+        }
+
+        let answer = line.trim().to_lowercase();
+        if let Some((key, _)) = choices
+            .iter()
+            .find(|(key, _)| answer.len() == 1 && answer.starts_with(*key))
+        {
+            return Ok(Some(*key));
+        }
+    }
+}
+
+/// Ask the user to pick one item from a numbered list.
+///
+/// # Returns
+/// The index of the chosen item, or None if nothing was chosen (empty input or stdin closed).
+pub fn pick(question: &str, items: &[String]) -> Result<Option<usize>> {
+    println!("{}", question);
+    for (i, item) in items.iter().enumerate() {
+        println!("  {}) {}", i + 1, item);
+    }
+    read_number(items.len())
+}
+
+/// Ask the user to pick one item from a numbered list that fits the terminal.
+///
+/// Items are laid out in as many columns as it takes to show them all
+/// below the question, and cut short to fit the width.
+///
+/// # Arguments
+/// * `size` - The terminal's (rows, columns)
+///
+/// # Returns
+/// The index of the chosen item, or None if nothing was chosen (empty input or stdin closed).
+pub fn pick_sized(question: &str, items: &[String], size: (usize, usize)) -> Result<Option<usize>> {
+    let (rows, cols) = size;
+    println!("{}", truncate(question, cols));
+    // The question and the input line take a row each
+    for line in columns(items, rows.saturating_sub(2), cols) {
+        println!("{}", line);
+    }
+    read_number(items.len())
+}
+
+/// The size of the terminal on stdin as (rows, columns)
+pub fn terminal_size() -> Option<(usize, usize)> {
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let size = String::from_utf8_lossy(&output.stdout);
+    let (rows, cols) = size.trim().split_once(' ')?;
+    Some((rows.parse().ok()?, cols.parse().ok()?))
+}
+
+/// Lay out numbered items top to bottom in as many columns as it takes to
+/// fit `height` rows, each column cut to its share of `width`
+fn columns(items: &[String], height: usize, width: usize) -> Vec<String> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let digits = items.len().to_string().len();
+    let cells: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| format!("{:>digits$}) {}", i + 1, item))
+        .collect();
+
+    let column_count = items.len().div_ceil(height.max(1));
+    let row_count = items.len().div_ceil(column_count);
+    let column_width = (width / column_count).max(1);
+    (0..row_count)
+        .map(|row| {
+            let line: Vec<String> = (0..column_count)
+                .filter_map(|column| cells.get(column * row_count + row))
+                .map(|cell| {
+                    let cell = truncate(cell, column_width.saturating_sub(1).max(1));
+                    format!("{:<column_width$}", cell)
+                })
+                .collect();
+            line.concat().trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Cut text to at most `width` characters, marking the cut with '…'
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Read a number between 1 and `count` from stdin
+fn read_number(count: usize) -> Result<Option<usize>> {
+    let stdin = io::stdin();
+    loop {
+        print!("Number (Enter to cancel): ");
+        io::stdout().flush().context("Failed to flush stdout")?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).context("Failed to read input")? == 0 {
+            println!();
+            return Ok(None);
+        }
+
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        if let Ok(n) = answer.parse::<usize>()
+            && (1..=count).contains(&n)
+        {
+            return Ok(Some(n - 1));
+        }
+    }
+}
+
+/// Ask the user to type a value.
+///
+/// With `hidden`, terminal echo is turned off while the value is typed.
+///
+/// # Errors
+/// Returns an error if stdin is not a terminal or was closed before a value was entered.
+pub fn ask(question: &str, hidden: bool) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Cannot ask for '{}': stdin is not a terminal\n  \
+             Hint: Run tmx from an interactive shell",
+            question
+        );
+    }
+
+    read_value(
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+        question,
+        hidden.then_some(EchoOff::new),
+    )
+}
+
+/// Print a question and read the typed value.
+///
+/// # Arguments
+/// * `input` - Where the value is typed
+/// * `output` - Where the question is printed
+/// * `question` - The question, printed before the value
+/// * `hide` - Hides the typed value until the guard it returns is dropped (None: shown)
+fn read_value<G>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    hide: Option<impl FnOnce() -> G>,
+) -> Result<String> {
+    write!(output, "{}: ", question).context("Failed to write prompt")?;
+    output.flush().context("Failed to flush stdout")?;
+
+    let hidden = hide.map(|hide| hide());
+    let mut line = String::new();
+    let read = input.read_line(&mut line);
+    if hidden.is_some() {
+        drop(hidden);
+        // The user's Enter wasn't echoed
+        writeln!(output).context("Failed to write prompt")?;
+    }
+
+    if read.context("Failed to read input")? == 0 {
+        anyhow::bail!("No value entered for '{}'", question);
+    }
+    Ok(line.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// Turns terminal echo off until dropped
+struct EchoOff;
+
+impl EchoOff {
+    fn new() -> Self {
+        stty("-echo");
+        EchoOff
+    }
+}
+
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        stty("echo");
+    }
+}
+
+/// Change a setting of the terminal on stdin
+fn stty(setting: &str) {
+    let _ = Command::new("stty")
+        .arg(setting)
+        .stdin(Stdio::inherit())
+        .status();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("session-{}", i)).collect()
+    }
+
+    #[test]
+    fn test_columns() {
+        assert_eq!(
+            columns(&items(2), 10, 40),
+            vec!["1) session-1", "2) session-2"]
+        );
+
+        // Too many for the height: fill columns top to bottom
+        let lines = columns(&items(5), 3, 40);
+        assert_eq!(
+            lines,
+            vec![
+                "1) session-1        4) session-4",
+                "2) session-2        5) session-5",
+                "3) session-3",
+            ]
+        );
+
+        // Too narrow: cut each cell short
+        assert_eq!(columns(&items(1), 5, 8), vec!["1) ses…"]);
+        assert!(columns(&[], 5, 80).is_empty());
+    }
+
+    #[test]
+    fn test_read_value() {
+        let mut output = Vec::new();
+        let value = read_value(
+            &mut "secret\r\n".as_bytes(),
+            &mut output,
+            "Value for TOKEN",
+            None::<fn()>,
+        )
+        .unwrap();
+        assert_eq!(value, "secret");
+        assert_eq!(String::from_utf8(output).unwrap(), "Value for TOKEN: ");
+    }
+
+    #[test]
+    fn test_read_hidden_value() {
+        let hidden = std::cell::Cell::new(false);
+        let mut output = Vec::new();
+        let value = read_value(
+            &mut "secret\n".as_bytes(),
+            &mut output,
+            "Value for TOKEN",
+            Some(|| hidden.set(true)),
+        )
+        .unwrap();
+        assert_eq!(value, "secret");
+        assert!(hidden.get());
+        // The newline the hidden Enter didn't echo
+        assert_eq!(String::from_utf8(output).unwrap(), "Value for TOKEN: \n");
+
+        let err = read_value(
+            &mut "".as_bytes(),
+            &mut Vec::new(),
+            "Value for TOKEN",
+            None::<fn()>,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "No value entered for 'Value for TOKEN'");
+    }
+}
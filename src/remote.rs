@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::{Config, Session};
+use crate::git;
+use crate::messages;
+
+/// A config fragment from a shared repository (only sessions are shared)
+#[derive(Debug, Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    sessions: HashMap<String, Session>,
+}
+
+/// Source of the sessions in `.toml` files directly in the remote config
+/// directory, such as configs imported from URLs
+pub const REMOTE_SOURCE: &str = "remote";
+
+/// Sessions by ID, in the order they were loaded
+pub type Sessions = Vec<(String, Session)>;
+
+/// Get the directory holding cloned config repositories (~/.config/tmx/remote)
+pub fn remote_dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("remote"))
+}
+
+/// Derive the checkout directory name from a git URL
+/// (`git@github.com:team/tmx-layouts.git` -> `tmx-layouts`)
+pub fn repo_dir_name(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(url);
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    if name.is_empty() {
+        "config".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Clone a config repository, or pull it if it was cloned before.
+///
+/// # Returns
+/// The checkout directory.
+pub fn clone_or_pull(url: &str) -> Result<PathBuf> {
+    let dir = remote_dir()?.join(repo_dir_name(url));
+
+    if dir.join(".git").exists() {
+        pull(&dir)?;
+    } else {
+        fs::create_dir_all(remote_dir()?)
+            .context("Failed to create remote config directory")?;
+        messages::say(messages::REMOTE_CLONING, &[("url", &url), ("dir", &dir.display())]);
+        git::run(&["clone", "--depth", "1", url, &dir.to_string_lossy()])?;
+    }
+
+    Ok(dir)
+}
+
+/// Pull updates for an existing checkout
+pub fn pull(dir: &Path) -> Result<()> {
+    messages::say(messages::REMOTE_UPDATING, &[("dir", &dir.display())]);
+    git::run(&["-C", &dir.to_string_lossy(), "pull", "--ff-only"])
+}
+
+/// List the checkouts in the remote config directory
+pub fn checkouts() -> Result<Vec<PathBuf>> {
+    let dir = remote_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut checkouts: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(".git").exists())
+        .collect();
+    checkouts.sort();
+    Ok(checkouts)
+}
+
+/// Largest config accepted from a URL
+const MAX_FETCH_SIZE: &str = "1M";
+
+/// Whether `tmx open` was given a URL rather than a session
+pub fn is_url(arg: &str) -> bool {
+    arg.contains("://")
+}
+
+/// Fetch a shared config over HTTPS with curl, following redirects to
+/// HTTPS URLs only.
+///
+/// # Errors
+/// Returns an error for other schemes, if curl isn't installed, or if the
+/// download fails (including HTTP error statuses).
+pub fn fetch(url: &str) -> Result<Vec<u8>> {
+    if !url.starts_with("https://") {
+        anyhow::bail!(
+            "Only https:// URLs can be imported, not '{}'\n  \
+             Hint: Serve the config over HTTPS, or download it and copy it into {}",
+            url,
+            remote_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    crate::log::info(&format!("fetching {}", url));
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--proto", "=https", "--max-filesize", MAX_FETCH_SIZE])
+        .arg(url)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow::anyhow!(
+                    "curl is not installed\n  Hint: Install curl to import configs from URLs"
+                )
+            } else {
+                anyhow::anyhow!("Failed to run curl: {}", e)
+            }
+        })?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Could not fetch {}\n  {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Get the file a config fetched from a URL is cached in, directly in the
+/// remote config directory so its sessions form the `remote` source
+/// (`https://example.com/layouts/rust-dev.toml` -> `remote/rust-dev.toml`).
+pub fn import_path(url: &str) -> Result<PathBuf> {
+    Ok(remote_dir()?.join(import_file_name(url)))
+}
+
+/// Derive a cache file name from the last segment of a URL's path
+fn import_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let name = match path.split_once('/') {
+        Some((_, path)) => path.trim_end_matches('/').rsplit('/').next().unwrap_or(""),
+        None => "",
+    };
+    let name = name.strip_suffix(".toml").unwrap_or(name);
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches(['-', '.']);
+    format!("{}.toml", if name.is_empty() { "imported" } else { name })
+}
+
+/// Load the sessions defined in all `.toml` files under a directory
+/// (recursively, skipping `.git`), in path order.
+pub fn load_sessions(dir: &Path) -> Result<Sessions> {
+    let mut files = Vec::new();
+    collect_toml_files(dir, &mut files)?;
+    files.sort();
+
+    let mut sessions = Vec::new();
+    for file in files {
+        sessions.extend(load_file(&file)?);
+    }
+    Ok(sessions)
+}
+
+/// Load the sessions under the remote config directory grouped by source.
+///
+/// Each checkout is a source named after its directory; `.toml` files
+/// directly in the remote directory form the `remote` source.
+///
+/// # Returns
+/// (source, sessions) pairs, ordered by source name.
+pub fn load_sources(dir: &Path) -> Result<Vec<(String, Sessions)>> {
+    let mut files = Vec::new();
+    collect_toml_files(dir, &mut files)?;
+    files.sort();
+
+    let mut sources: BTreeMap<String, Sessions> = BTreeMap::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        let source = match relative.parent().and_then(|p| p.iter().next()) {
+            Some(checkout) => checkout.to_string_lossy().to_string(),
+            None => REMOTE_SOURCE.to_string(),
+        };
+        sources.entry(source).or_default().extend(load_file(&file)?);
+    }
+    Ok(sources.into_iter().collect())
+}
+
+/// Load the sessions of one shared config file, ordered by ID
+fn load_file(file: &Path) -> Result<Sessions> {
+    let content = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read shared config: {}", file.display()))?;
+    let fragment: Fragment = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse shared config: {}", file.display()))?;
+
+    let mut sessions: Vec<_> = fragment.sessions.into_iter().collect();
+    sessions.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sessions)
+}
+
+/// Recursively collect `.toml` files, skipping hidden directories such as `.git`
+fn collect_toml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_toml_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "toml") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_dir_name() {
+        assert_eq!(repo_dir_name("git@github.com:team/tmx-layouts.git"), "tmx-layouts");
+        assert_eq!(repo_dir_name("https://example.com/team/layouts/"), "layouts");
+        assert_eq!(repo_dir_name("git@host:layouts"), "layouts");
+    }
+
+    #[test]
+    fn test_import_file_name() {
+        assert_eq!(
+            import_file_name("https://example.com/layouts/rust-dev.toml"),
+            "rust-dev.toml"
+        );
+        assert_eq!(
+            import_file_name("https://example.com/raw/api?token=1#x"),
+            "api.toml"
+        );
+        assert_eq!(
+            import_file_name("https://example.com/a/my%20layout.toml"),
+            "my-20layout.toml"
+        );
+        assert_eq!(import_file_name("https://example.com/"), "imported.toml");
+        assert_eq!(import_file_name("https://example.com/.."), "imported.toml");
+    }
+
+    #[test]
+    fn test_load_sessions() {
+        let dir = std::env::temp_dir().join(format!("tmx-remote-{}", std::process::id()));
+        fs::create_dir_all(dir.join("team").join(".git")).unwrap();
+        fs::write(
+            dir.join("team").join("api.toml"),
+            "[sessions.api]\nname = \"api\"\n[[sessions.api.windows]]\nname = \"w\"\n[[sessions.api.windows.panes]]\ncommand = \"\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("team").join(".git").join("ignored.toml"), "not toml [").unwrap();
+
+        let sessions = load_sessions(&dir).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].0, "api");
+
+        fs::copy(dir.join("team").join("api.toml"), dir.join("local.toml")).unwrap();
+        let sources = load_sources(&dir).unwrap();
+        let names: Vec<_> = sources
+            .iter()
+            .map(|(source, sessions)| (source.as_str(), sessions.len()))
+            .collect();
+        assert_eq!(names, [("remote", 1), ("team", 1)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::{CloseOnExit, EnvValue, OptionValue, Session};
+use crate::session;
+use crate::tmux;
+
+/// Version of the resolved session JSON contract.
+///
+/// Bump this when fields are removed or change meaning; adding fields is
+/// backwards compatible and does not require a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Environment variable carrying the resolved session JSON for hooks and plugins
+pub const SESSION_JSON_ENV: &str = "TMX_SESSION_JSON";
+
+/// A session with all defaults applied, as handed to hooks and plugins.
+///
+/// Paths are expanded, layouts and split directions are resolved to the
+/// values tmx actually uses, and indices are 0-based offsets (add the tmux
+/// base-index to get real window indices).
+#[derive(Debug, Serialize)]
+pub struct ResolvedSession {
+    pub schema: u32,
+    /// Config key of the session (None for sessions built from the default template)
+    pub id: Option<String>,
+    pub name: String,
+    /// Session name as created in tmux (special characters replaced)
+    pub tmux_name: String,
+    pub root: String,
+    pub startup_window: usize,
+    pub startup_pane: usize,
+    /// Session environment as configured; encrypted values are passed as references
+    pub env: HashMap<String, EnvValue>,
+    /// tmux options set on the session
+    pub options: BTreeMap<String, OptionValue>,
+    pub windows: Vec<ResolvedWindow>,
+}
+
+/// A window with defaults applied
+#[derive(Debug, Serialize)]
+pub struct ResolvedWindow {
+    pub index: usize,
+    pub name: String,
+    pub root: String,
+    /// Layout applied when the window has more than one pane
+    pub layout: Option<String>,
+    /// What closes when the first pane's command exits
+    pub close_on_exit: Option<CloseOnExit>,
+    /// Environment of the window's panes as configured (below each pane's `env`)
+    pub env: HashMap<String, EnvValue>,
+    /// tmux options set on the window
+    pub options: BTreeMap<String, OptionValue>,
+    pub panes: Vec<ResolvedPane>,
+}
+
+/// A pane with defaults applied
+#[derive(Debug, Serialize)]
+pub struct ResolvedPane {
+    pub index: usize,
+    pub command: String,
+    /// Environment as configured; encrypted values are passed as references, never decrypted
+    pub env: HashMap<String, EnvValue>,
+    /// Env files applied before `env`, lowest precedence first
+    pub env_files: Vec<String>,
+    pub root: String,
+    /// Commands run and waited for before `command`
+    pub setup: Vec<String>,
+    /// Whether Enter is pressed after the command
+    pub send_enter: bool,
+    /// Whether the command is typed literally
+    pub literal: bool,
+    /// Whether the command runs as the pane's process instead of in a shell
+    pub exec: bool,
+    /// Split used to create the pane (None for the first pane in a window)
+    pub split: Option<String>,
+    pub size: Option<String>,
+    /// tmux options set on the pane
+    pub options: BTreeMap<String, OptionValue>,
+    /// Pane style, e.g. `bg=colour52`
+    pub style: Option<String>,
+    pub title: Option<String>,
+    /// Title of the pane whose setup and command are sent before this pane's
+    pub after: Option<String>,
+    /// Command run by `tmx status --health`
+    pub healthcheck: Option<String>,
+}
+
+impl ResolvedSession {
+    /// Resolve a session configuration into its stable JSON representation.
+    ///
+    /// # Arguments
+    /// * `id` - The config key of the session, if it came from config
+    /// * `session` - The session configuration
+    pub fn new(id: Option<&str>, session: &Session) -> Self {
+        let session_root = session.root_expanded();
+
+        let windows = session
+            .windows
+            .iter()
+            .enumerate()
+            .map(|(window_idx, window)| {
+                let window_root = window.root_expanded(&session_root);
+                let pane_count = window.panes.len();
+                let layout = (pane_count > 1)
+                    .then(|| session::determine_layout(window, pane_count).to_string());
+
+                let panes = window
+                    .panes
+                    .iter()
+                    .enumerate()
+                    .map(|(pane_idx, pane)| {
+                        let split = (pane_idx > 0).then(|| {
+                            if session::determine_split_direction(pane_idx, pane) {
+                                "horizontal".to_string()
+                            } else {
+                                "vertical".to_string()
+                            }
+                        });
+
+                        ResolvedPane {
+                            index: pane_idx,
+                            command: pane.command.clone(),
+                            env: pane.env.clone(),
+                            env_files: session::env_file_paths(session, window, pane)
+                                .iter()
+                                .map(|p| p.to_string_lossy().to_string())
+                                .collect(),
+                            setup: pane.setup.clone(),
+                            send_enter: pane.send_enter,
+                            literal: pane.literal,
+                            exec: pane.exec,
+                            root: pane.root_expanded(&window_root),
+                            split,
+                            size: pane.size.clone(),
+                            options: pane.options.clone(),
+                            style: pane.style.clone(),
+                            title: pane.title.clone(),
+                            after: pane.after.clone(),
+                            healthcheck: pane.healthcheck.clone(),
+                        }
+                    })
+                    .collect();
+
+                ResolvedWindow {
+                    index: window_idx,
+                    name: window.name.clone(),
+                    root: window_root,
+                    layout,
+                    close_on_exit: window.close_on_exit,
+                    env: window.env.clone(),
+                    options: window.options.clone(),
+                    panes,
+                }
+            })
+            .collect();
+
+        Self {
+            schema: SCHEMA_VERSION,
+            id: id.map(str::to_string),
+            name: session.name.clone(),
+            tmux_name: tmux::sanitize_session_name(&session.name),
+            root: session_root,
+            startup_window: session.resolve_startup_window(),
+            startup_pane: session.get_startup_pane(),
+            env: session.env.clone(),
+            options: session.options.clone(),
+            windows,
+        }
+    }
+
+    /// Serialize to a single-line JSON string
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).context("Failed to serialize resolved session")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, DEFAULT_CONFIG};
+
+    #[test]
+    fn test_resolved_session_json() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        let session = config.sessions.get("work").unwrap();
+        let resolved = ResolvedSession::new(Some("work"), session);
+
+        assert_eq!(resolved.schema, SCHEMA_VERSION);
+        assert_eq!(resolved.startup_window, 0);
+        assert_eq!(resolved.windows[1].layout.as_deref(), Some("tiled"));
+        assert_eq!(resolved.windows[1].panes[0].split, None);
+        assert_eq!(
+            resolved.windows[1].panes[1].split.as_deref(),
+            Some("horizontal")
+        );
+        assert!(!resolved.root.contains('~'));
+
+        let json: serde_json::Value = serde_json::from_str(&resolved.to_json().unwrap()).unwrap();
+        assert_eq!(json["schema"], SCHEMA_VERSION);
+        assert_eq!(json["id"], "work");
+        assert_eq!(json["windows"][0]["name"], "code");
+    }
+}
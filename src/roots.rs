@@ -0,0 +1,209 @@
+//! Working directories of a session's windows and panes, resolved to checked paths.
+//!
+//! Roots are expanded, checked to exist and canonicalized before anything is
+//! created, so a missing directory is reported (or handled according to the
+//! session's `missing_root` policy) instead of tmux failing opaquely.
+
+use crate::config::{self, MissingRoot, Session};
+use crate::log;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The resolved roots of a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRoots {
+    pub root: PathBuf,
+    /// One entry per configured window, in config order
+    pub windows: Vec<WindowRoots>,
+}
+
+/// The resolved roots of a window and its panes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowRoots {
+    pub root: PathBuf,
+    /// One entry per configured pane, in config order
+    pub panes: Vec<PathBuf>,
+}
+
+impl SessionRoots {
+    /// Resolve the roots of a session, its windows and panes.
+    ///
+    /// Relative window and pane roots are resolved against their parent's
+    /// resolved root.
+    ///
+    /// # Arguments
+    /// * `session` - The session configuration
+    ///
+    /// # Returns
+    /// An error if a root doesn't exist and `missing_root` is `error`, or if
+    /// it can't be created with `missing_root = "create"`.
+    pub fn resolve(session: &Session) -> Result<Self> {
+        let policy = session.missing_root;
+        let root = resolve_dir(
+            &session.root_expanded(),
+            policy,
+            &format!("session '{}'", session.name),
+        )?;
+
+        let windows = session
+            .windows
+            .iter()
+            .map(|window| {
+                let window_root = resolve_dir(
+                    &window.root_expanded(&root.to_string_lossy()),
+                    policy,
+                    &format!("window '{}'", window.name),
+                )?;
+                let panes = window
+                    .panes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pane)| match pane.root {
+                        // Panes without their own root share the window's
+                        None => Ok(window_root.clone()),
+                        Some(_) => resolve_dir(
+                            &pane.root_expanded(&window_root.to_string_lossy()),
+                            policy,
+                            &format!("pane {} of window '{}'", i, window.name),
+                        ),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(WindowRoots {
+                    root: window_root,
+                    panes,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { root, windows })
+    }
+}
+
+/// Check that an expanded root is a directory, applying the missing-root policy.
+///
+/// # Arguments
+/// * `path` - The expanded root
+/// * `policy` - What to do if the directory doesn't exist
+/// * `owner` - What the root belongs to, for messages (e.g. `window 'editor'`)
+///
+/// # Returns
+/// The canonical path of the directory to use.
+pub fn resolve_dir(path: &str, policy: MissingRoot, owner: &str) -> Result<PathBuf> {
+    let dir = Path::new(path);
+    if dir.is_dir() {
+        return canonical(dir);
+    }
+    if dir.exists() {
+        anyhow::bail!(
+            "Root of {} is not a directory: {}\n  Hint: Point root at a directory",
+            owner,
+            path
+        );
+    }
+
+    match policy {
+        MissingRoot::Error => {
+            let problems = config::path_problems(path);
+            let hint = if problems.is_empty() {
+                "Create it, or set missing_root = \"create\" or \"home\" on the session".to_string()
+            } else {
+                format!("{} is not defined", problems.join(", "))
+            };
+            anyhow::bail!(
+                "Root of {} does not exist: {}\n  Hint: {}",
+                owner,
+                path,
+                hint
+            );
+        }
+        MissingRoot::Create => {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create root of {}: {}", owner, path))?;
+            log::info(&format!("created missing root of {}: {}", owner, path));
+            println!("  Created missing root of {}: {}", owner, path);
+            canonical(dir)
+        }
+        MissingRoot::Home => {
+            let home = dirs::home_dir().context("Could not determine the home directory")?;
+            log::info(&format!(
+                "root of {} does not exist, using {}: {}",
+                owner,
+                home.display(),
+                path
+            ));
+            println!(
+                "Warning: Root of {} does not exist, using {}: {}",
+                owner,
+                home.display(),
+                path
+            );
+            canonical(&home)
+        }
+    }
+}
+
+fn canonical(dir: &Path) -> Result<PathBuf> {
+    dir.canonicalize()
+        .with_context(|| format!("Failed to resolve {}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_dir_policies() {
+        let base = std::env::temp_dir().join(format!("tmx-roots-{}", std::process::id()));
+        let missing = base.join("a/b");
+        let missing = missing.to_string_lossy();
+
+        let err = resolve_dir(&missing, MissingRoot::Error, "window 'w'").unwrap_err();
+        assert!(err.to_string().starts_with("Root of window 'w' does not exist"));
+
+        let created = resolve_dir(&missing, MissingRoot::Create, "window 'w'").unwrap();
+        assert!(created.is_dir());
+        assert!(created.is_absolute());
+
+        // Existing directories are canonicalized
+        let dotted = format!("{}/a/../a/b", base.display());
+        assert_eq!(
+            resolve_dir(&dotted, MissingRoot::Error, "window 'w'").unwrap(),
+            created
+        );
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_resolve_session_roots() {
+        let base = std::env::temp_dir().join(format!("tmx-session-roots-{}", std::process::id()));
+        fs::create_dir_all(base.join("api")).unwrap();
+        let session: Session = toml::from_str(&format!(
+            r#"
+name = "app"
+root = "{}"
+missing_root = "create"
+
+[[windows]]
+name = "api"
+root = "api"
+panes = [{{ command = "" }}, {{ command = "", root = "logs" }}]
+"#,
+            base.display()
+        ))
+        .unwrap();
+
+        let roots = SessionRoots::resolve(&session).unwrap();
+        let base = base.canonicalize().unwrap();
+        assert_eq!(roots.root, base);
+        assert_eq!(roots.windows[0].root, base.join("api"));
+        assert_eq!(
+            roots.windows[0].panes,
+            vec![base.join("api"), base.join("api/logs")]
+        );
+        assert!(base.join("api/logs").is_dir());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use crate::config::{EnvValue, KEYRING_SCHEME};
+use crate::log;
+use crate::prompt;
+
+/// Values entered at prompts, so a variable used by several panes is asked for once
+static PROMPTED: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Header of an ASCII-armored age ciphertext
+const AGE_ARMOR_HEADER: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Resolve a pane's environment to plaintext values, decrypting secrets.
+///
+/// Relative paths of encrypted files are resolved against `base_dir`
+/// (the directory of the config file).
+///
+/// # Returns
+/// The variables sorted by name, so exports are sent in a stable order.
+pub fn resolve_env(env: &HashMap<String, EnvValue>, base_dir: &Path) -> Result<Vec<(String, String)>> {
+    let mut resolved = env
+        .iter()
+        .map(|(key, value)| {
+            let plain = resolve(key, value, base_dir).map_err(|e| {
+                anyhow::anyhow!("Failed to resolve env variable '{}':\n{}", key, e)
+            })?;
+            Ok((key.clone(), plain))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    resolved.sort();
+    Ok(resolved)
+}
+
+/// Resolve a single environment value to plaintext
+pub fn resolve(key: &str, value: &EnvValue, base_dir: &Path) -> Result<String> {
+    match value {
+        EnvValue::Plain(value) => match value.strip_prefix(KEYRING_SCHEME) {
+            Some(entry) => keyring_lookup(entry),
+            None => Ok(value.clone()),
+        },
+        EnvValue::Sops { sops } => decrypt_sops(sops, base_dir),
+        EnvValue::Age { age, identity } => decrypt_age(age, identity.as_deref(), base_dir),
+        EnvValue::Prompt { hidden, .. } => ask_value(key, *hidden),
+    }
+}
+
+/// Ask for a variable's value, reusing an earlier answer for the same variable
+fn ask_value(key: &str, hidden: bool) -> Result<String> {
+    remembered(key, || {
+        log::info(&format!("prompting for env variable '{}'", key));
+        prompt::ask(&format!("Value for {}", key), hidden)
+    })
+}
+
+/// The earlier answer for a variable, or the one `ask` gets, kept for next time
+fn remembered(key: &str, ask: impl FnOnce() -> Result<String>) -> Result<String> {
+    if let Some(value) = PROMPTED.lock().ok().and_then(|p| p.get(key).cloned()) {
+        return Ok(value);
+    }
+
+    let value = ask()?;
+    if let Ok(mut prompted) = PROMPTED.lock() {
+        prompted.insert(key.to_string(), value.clone());
+    }
+    Ok(value)
+}
+
+/// Resolve a possibly relative path against the config directory
+fn resolve_path(path: &str, base_dir: &Path) -> PathBuf {
+    let expanded = PathBuf::from(shellexpand::tilde(path).to_string());
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        base_dir.join(expanded)
+    }
+}
+
+/// Convert a dotted key (`db.password`) into a sops `--extract` expression (`["db"]["password"]`)
+fn sops_extract_path(key: &str) -> String {
+    key.split('.').map(|part| format!("[\"{}\"]", part)).collect()
+}
+
+/// Decrypt one key of a sops-encrypted file (`"secrets.yaml#api_key"`)
+fn decrypt_sops(spec: &str, base_dir: &Path) -> Result<String> {
+    let (file, key) = spec.split_once('#').with_context(|| {
+        format!(
+            "Invalid sops reference '{}'\n  Expected: '<file>#<key>', e.g. \"secrets.yaml#api_key\"",
+            spec
+        )
+    })?;
+    let path = resolve_path(file, base_dir);
+
+    log::info(&format!("decrypting '{}' from {} with sops", key, path.display()));
+    let output = Command::new("sops")
+        .args(["--decrypt", "--extract", &sops_extract_path(key)])
+        .arg(&path)
+        .output()
+        .map_err(|e| missing_tool_error("sops", e))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "sops could not decrypt '{}' from {}\n  {}",
+            key,
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(trim_newline(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Decrypt an age ciphertext, given inline (ASCII-armored) or as a file path
+fn decrypt_age(data: &str, identity: Option<&str>, base_dir: &Path) -> Result<String> {
+    let identity = match identity {
+        Some(identity) => resolve_path(identity, base_dir),
+        None => default_age_identity()?,
+    };
+
+    let mut command = Command::new("age");
+    command.arg("--decrypt").arg("--identity").arg(&identity);
+
+    let inline = data.trim_start().starts_with(AGE_ARMOR_HEADER);
+    if !inline {
+        command.arg(resolve_path(data, base_dir));
+    }
+
+    log::info(&format!("decrypting age value with identity {}", identity.display()));
+    let mut child = command
+        .stdin(if inline { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| missing_tool_error("age", e))?;
+
+    if inline && let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data.trim().as_bytes())
+            .context("Failed to pass ciphertext to age")?;
+    }
+
+    let output = child.wait_with_output().context("Failed to run age")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "age could not decrypt the value\n  {}\n  Hint: Check the identity file {}",
+            String::from_utf8_lossy(&output.stderr).trim(),
+            identity.display()
+        );
+    }
+
+    Ok(trim_newline(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Look up a `<service>/<account>` entry in the platform secret store.
+///
+/// Uses the Secret Service via `secret-tool` on Linux (attributes `service` and
+/// `username`, as used by the Python and Rust keyring libraries) and the login
+/// keychain via `security` on macOS.
+fn keyring_lookup(entry: &str) -> Result<String> {
+    let (service, account) = entry.split_once('/').with_context(|| {
+        format!(
+            "Invalid keyring reference '{}{}'\n  Expected: 'keyring:<service>/<account>'",
+            KEYRING_SCHEME, entry
+        )
+    })?;
+
+    let (tool, args) = if cfg!(target_os = "macos") {
+        ("security", vec!["find-generic-password", "-s", service, "-a", account, "-w"])
+    } else {
+        ("secret-tool", vec!["lookup", "service", service, "username", account])
+    };
+
+    log::info(&format!("looking up '{}' in the keyring with {}", entry, tool));
+    let output = Command::new(tool)
+        .args(&args)
+        .output()
+        .map_err(|e| missing_tool_error(tool, e))?;
+
+    // secret-tool exits successfully with no output when the entry doesn't exist
+    if !output.status.success() || output.stdout.is_empty() {
+        anyhow::bail!(
+            "No keyring entry for service '{}' and account '{}'\n  {}",
+            service,
+            account,
+            keyring_store_hint(service, account)
+        );
+    }
+
+    Ok(trim_newline(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Hint showing how to add a keyring entry on this platform
+fn keyring_store_hint(service: &str, account: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!(
+            "Hint: Add it with 'security add-generic-password -s {} -a {} -w'",
+            service, account
+        )
+    } else {
+        format!(
+            "Hint: Add it with 'secret-tool store --label={} service {} username {}'",
+            service, service, account
+        )
+    }
+}
+
+/// Default age identity: $TMX_AGE_IDENTITY, or the key file sops uses
+fn default_age_identity() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("TMX_AGE_IDENTITY") {
+        return Ok(PathBuf::from(shellexpand::tilde(&path).to_string()));
+    }
+    let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home_dir.join(".config").join("sops").join("age").join("keys.txt"))
+}
+
+/// Error for a decryption tool that could not be started
+fn missing_tool_error(tool: &str, error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        anyhow::anyhow!(
+            "{} is not installed\n  Hint: Install {} to use this kind of env value",
+            tool,
+            tool
+        )
+    } else {
+        anyhow::anyhow!("Failed to run {}: {}", tool, error)
+    }
+}
+
+/// Strip a single trailing newline, which tools add when printing a value
+fn trim_newline(mut value: String) -> String {
+    if value.ends_with('\n') {
+        value.pop();
+        if value.ends_with('\r') {
+            value.pop();
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sops_extract_path() {
+        assert_eq!(sops_extract_path("api_key"), "[\"api_key\"]");
+        assert_eq!(sops_extract_path("db.password"), "[\"db\"][\"password\"]");
+    }
+
+    #[test]
+    fn test_remembered_prompt() {
+        let key = "TMX_TEST_REMEMBERED";
+        assert!(remembered(key, || anyhow::bail!("No value entered")).is_err());
+        assert_eq!(
+            remembered(key, || Ok("first".to_string())).unwrap(),
+            "first"
+        );
+    }
+
+    #[test]
+    fn test_resolve_plain_env() {
+        let env = HashMap::from([
+            ("B".to_string(), EnvValue::Plain("2".to_string())),
+            ("A".to_string(), EnvValue::Plain("1".to_string())),
+        ]);
+        let resolved = resolve_env(&env, Path::new("/")).unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string())
+            ]
+        );
+    }
+}
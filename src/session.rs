@@ -1,9 +1,54 @@
-use crate::config::Session;
+use crate::config::{
+    self, AttachSend, CloseOnExit, EnvFileMissing, Pane, Session, StartupWindow, Window,
+};
 use crate::context::Context;
-use crate::tmux;
-use anyhow::Result;
+use crate::dotenv;
+use crate::hooks::{self, Hook, HookSession};
+use crate::log;
+use crate::messages;
+use crate::roots::{SessionRoots, WindowRoots};
+use crate::secrets;
+use crate::shell;
+use crate::state;
+use crate::tmux::{self, query};
+use anyhow::{Context as _, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time spent in each phase of building a session (see `tmx bench`)
+#[derive(Debug)]
+pub struct PhaseTimings {
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl PhaseTimings {
+    fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Add the time since the previous lap to a phase
+    fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    /// Phases in the order they were first entered, with their total duration
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+}
 
 /// Create a new tmux session from a configuration.
 ///
@@ -18,97 +63,956 @@ use std::time::Duration;
 /// Returns an error if validation fails, tmux commands fail, or if
 /// any part of the session creation process encounters an issue.
 pub fn create_session(session: &Session, ctx: &Context) -> Result<()> {
+    build_session(session, ctx, false).map(|_| ())
+}
+
+/// Create a throwaway session and measure its phases.
+///
+/// Unlike `create_session`, no progress is printed and the session isn't
+/// recorded as created by tmx.
+pub fn create_session_measured(session: &Session, ctx: &Context) -> Result<PhaseTimings> {
+    build_session(session, ctx, true)
+}
+
+/// Build a session from its configuration, timing each phase
+fn build_session(session: &Session, ctx: &Context, throwaway: bool) -> Result<PhaseTimings> {
+    let mut timings = PhaseTimings::new();
+
     // Validate session
     session.validate()?;
 
-    // Get tmux base-index from context (cached)
-    let base_index = ctx.base_index()?;
+    if let Some(ref group) = session.group_with {
+        return build_grouped_session(session, group, ctx, throwaway, timings);
+    }
+
     let verbose = ctx.is_verbose();
 
     let session_name = &session.name;
-    let session_root = session.root_expanded();
 
-    println!(
-        "Creating session '{}' with {} window(s)...",
-        session_name,
-        session.windows.len()
-    );
+    // Check the roots up front so a missing directory doesn't leave a half-built session
+    let roots = SessionRoots::resolve(session)?;
+
+    // Load env files and decrypt env values up front so a failure doesn't leave a half-built session
+    check_env_files(session)?;
+    let config_dir = ctx.session_config_dir(session);
+    let pane_envs = session
+        .windows
+        .iter()
+        .map(|window| {
+            window
+                .panes
+                .iter()
+                .map(|pane| pane_environment(session, window, pane, &config_dir))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    timings.lap("prepare");
+
+    if !throwaway {
+        messages::say(
+            messages::SESSION_CREATING,
+            &[
+                ("session", &session_name),
+                ("windows", &session.windows.len()),
+            ],
+        );
+    }
+
+    let hook_session = hook_session(session, ctx);
+    if !throwaway {
+        run_on_create(session, &hook_session, &roots.root)?;
+        run_pre_window(
+            session,
+            &hook_session,
+            &session.windows[0],
+            &roots.windows[0].root,
+        );
+    }
 
     // Create the session with the first window
     let first_window_name = &session.windows[0].name;
-    let first_window_root = session.windows[0].root_expanded(&session_root);
-    tmux::new_session(session_name, first_window_name, Some(&first_window_root))?;
+    let first_pane = &session.windows[0].panes[0];
+    let session_env = session_environment(session, &config_dir)?;
+    let mut first_window_index = tmux::new_session(
+        session_name,
+        first_window_name,
+        Some(&roots.windows[0].root),
+        &session_env,
+        pane_process(session, first_pane, &pane_envs[0][0]).as_ref(),
+    )?;
+
+    // new-session -e also sets the first pane's variables in the session
+    // environment, which would leak them into every later pane
+    for (key, _) in &pane_envs[0][0] {
+        match session_env.iter().find(|(k, _)| k == key) {
+            Some((_, value)) => tmux::set_session_environment(session_name, key, value)?,
+            None => tmux::unset_session_environment(session_name, key)?,
+        }
+    }
+
+    // A session-local base-index (e.g. set by a session-created hook) only applies
+    // after the first window exists, so move that window to keep indices contiguous
+    let base_index = tmux::session_base_index(session_name)?;
+    if first_window_index != base_index {
+        log::info(&format!(
+            "moving first window from index {} to session base-index {}",
+            first_window_index, base_index
+        ));
+        tmux::move_window(session_name, first_window_index, base_index)?;
+        first_window_index = base_index;
+    }
+    timings.lap("new-session");
+
+    // The rest of the session is built through one control-mode client
+    let control = tmux::control_client(session_name);
+
+    // Indices tmux actually assigned, which depend on the session's effective base-index
+    let mut window_indices = Vec::with_capacity(session.windows.len());
+    // Each window with its index and the order its panes' setup and commands are sent in
+    let mut started_windows = Vec::with_capacity(session.windows.len());
 
     // Process each window
     for (window_offset, window) in session.windows.iter().enumerate() {
-        let window_index = base_index + window_offset;
-        let window_root = window.root_expanded(&session_root);
+        let window_roots = &roots.windows[window_offset];
 
         // Create window (first window already exists)
-        if window_offset > 0 {
-            tmux::new_window(session_name, &window.name, Some(&window_root))?;
+        let window_index = if window_offset == 0 {
+            first_window_index
+        } else {
+            if !throwaway {
+                run_pre_window(session, &hook_session, window, &window_roots.root);
+            }
+            tmux::new_window(
+                session_name,
+                &window.name,
+                Some(&window_roots.root),
+                pane_process(session, &window.panes[0], &pane_envs[window_offset][0]).as_ref(),
+            )?
+        };
+        window_indices.push(window_index);
+        set_up_window(
+            session,
+            window,
+            window_index,
+            window_roots,
+            &pane_envs[window_offset],
+            verbose,
+            &mut timings,
+        )?;
+
+        // Panes with `after` get their setup and command after the pane they name
+        started_windows.push((window, window_index, window.command_order()?));
+    }
+
+    // Setup runs before any main command, so the commands start in a prepared environment
+    run_setups(session, &started_windows, throwaway)?;
+    timings.lap("setup");
+
+    for (window, window_index, order) in &started_windows {
+        send_commands(session_name, *window_index, window, order)?;
+    }
+    timings.lap("commands");
+
+    if let Some(ref send) = session.on_attach_send {
+        install_attach_send(session, send)?;
+    }
+    if session.on_died.is_some() && !throwaway {
+        install_died_hook()?;
+    }
+
+    // Select the startup window and pane
+    let startup_window_idx = window_indices[session.resolve_startup_window()];
+    let startup_pane = session.get_startup_pane();
+
+    tmux::select_window(session_name, startup_window_idx)?;
+    tmux::select_pane(session_name, startup_window_idx, startup_pane)?;
+
+    // Detach the control client first, so `destroy_unattached` doesn't kill the session
+    drop(control);
+    apply_session_options(session)?;
+    timings.lap("select");
+
+    if throwaway {
+        return Ok(timings);
+    }
+
+    record_created(session, ctx);
+    messages::say(messages::SESSION_CREATED, &[("session", &session_name)]);
+    messages::say(
+        messages::SESSION_WINDOW_COUNT,
+        &[("windows", &session.windows.len())],
+    );
+
+    // Show summary
+    for window in &session.windows {
+        messages::say(
+            messages::SESSION_WINDOW_PANES,
+            &[("window", &window.name), ("panes", &window.panes.len())],
+        );
+    }
+
+    hooks::run_or_warn(
+        Hook::PostCreate,
+        &session.post_create,
+        &hook_session,
+        &roots.root,
+    );
+
+    Ok(timings)
+}
+
+/// Fill in a window whose first pane exists: its options, the rest of its
+/// panes, layout and pane options.
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `window` - The window configuration
+/// * `window_index` - Index of the window in tmux
+/// * `window_roots` - Checked roots of the window and its panes
+/// * `pane_envs` - Environment of each of its panes
+/// * `verbose` - Whether to print what is done
+/// * `timings` - Where the time spent is recorded
+fn set_up_window(
+    session: &Session,
+    window: &Window,
+    window_index: usize,
+    window_roots: &WindowRoots,
+    pane_envs: &[Vec<(String, String)>],
+    verbose: bool,
+    timings: &mut PhaseTimings,
+) -> Result<()> {
+    let session_name = &session.name;
+    apply_window_options(session, window_index, window)?;
+
+    if window.panes[0].exec {
+        install_exec_hooks(
+            session_name,
+            window_index,
+            0,
+            window.panes[0].notify_on_exit,
+            window.close_on_exit,
+        );
+    }
+
+    // Create panes for this window
+    let pane_count = window.panes.len();
+
+    if pane_count > 1 {
+        // Create additional panes (first pane already exists)
+        // Don't apply sizes during creation since apply_window_layout will handle it
+        create_window_panes(
+            session_name,
+            window_index,
+            window,
+            window_roots,
+            1,     // Start at index 1 (first pane already exists)
+            false, // Don't apply sizes here - let apply_window_layout handle it
+            Some(pane_envs),
+            session,
+            verbose,
+        )?;
+        timings.lap("windows");
+
+        // Always apply layout and sizes
+        apply_window_layout(session_name, window_index, window, verbose)?;
+        timings.lap("layout");
+
+        // Wait for panes to initialize before sending commands
+        // This prevents issues where vim/neovim gets incorrect dimensions
+        thread::sleep(Duration::from_millis(500));
+        timings.lap("pane-init");
+    } else {
+        timings.lap("windows");
+    }
+
+    apply_pane_options(session_name, window_index, window)
+}
+
+/// Add a configured window to a running session, built as when the session
+/// is created: `pre_window` hooks, pane environment, options and layout,
+/// then the panes' setup and commands.
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `window_offset` - Offset of the window in the session's `windows`
+/// * `window_index` - Index to create the window at in tmux
+/// * `ctx` - Shared context containing configuration and state
+pub fn add_window(
+    session: &Session,
+    window_offset: usize,
+    window_index: usize,
+    ctx: &Context,
+) -> Result<()> {
+    let window = &session.windows[window_offset];
+    let roots = SessionRoots::resolve(session)?;
+    let window_roots = &roots.windows[window_offset];
+    let config_dir = ctx.session_config_dir(session);
+    let pane_envs = window
+        .panes
+        .iter()
+        .map(|pane| pane_environment(session, window, pane, &config_dir))
+        .collect::<Result<Vec<_>>>()?;
+
+    run_pre_window(
+        session,
+        &hook_session(session, ctx),
+        window,
+        &window_roots.root,
+    );
+    tmux::new_window_at(
+        &session.name,
+        window_index,
+        &window.name,
+        Some(&window_roots.root),
+        pane_process(session, &window.panes[0], &pane_envs[0]).as_ref(),
+    )?;
+    set_up_window(
+        session,
+        window,
+        window_index,
+        window_roots,
+        &pane_envs,
+        ctx.is_verbose(),
+        &mut PhaseTimings::new(),
+    )?;
+
+    let order = window.command_order()?;
+    run_setups(session, &[(window, window_index, order.clone())], false)?;
+    send_commands(&session.name, window_index, window, &order)
+}
+
+/// Send the panes' setup steps, with at most `setup_concurrency` of them
+/// running at once across the session: the others are queued and sent in
+/// batches, each once the previous batch signalled it finished (or timed out).
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `windows` - Each window with its index and the order of its panes
+/// * `throwaway` - Whether the session is only measured (no progress output)
+fn run_setups(
+    session: &Session,
+    windows: &[(&Window, usize, Vec<usize>)],
+    throwaway: bool,
+) -> Result<()> {
+    let setups: Vec<(&Window, usize, usize)> = windows
+        .iter()
+        .flat_map(|(window, window_index, order)| {
+            order
+                .iter()
+                .filter(|&&pane_idx| !window.panes[pane_idx].setup.is_empty())
+                .map(move |&pane_idx| (*window, *window_index, pane_idx))
+        })
+        .collect();
+    if setups.is_empty() {
+        return Ok(());
+    }
+
+    if !throwaway {
+        let mut announced: Vec<&str> = Vec::new();
+        for (window, _, _) in &setups {
+            if !announced.contains(&window.name.as_str()) {
+                announced.push(&window.name);
+                messages::say(
+                    messages::SESSION_WAITING_FOR_SETUP,
+                    &[("window", &window.name)],
+                );
+            }
         }
+    }
 
-        // Create panes for this window
-        let pane_count = window.panes.len();
+    let nonce = setup_nonce();
+    let limit = session.setup_concurrency.unwrap_or(usize::MAX);
+    for (batch_idx, batch) in setups.chunks(limit).enumerate() {
+        if batch_idx > 0 {
+            log::info(&format!(
+                "starting queued setup batch {} of session '{}'",
+                batch_idx + 1,
+                session.name
+            ));
+        }
+        let mut pending = Vec::with_capacity(batch.len());
+        for &(window, window_index, pane_idx) in batch {
+            let pane = &window.panes[pane_idx];
+            pending.push((
+                window,
+                start_setup(&session.name, window_index, pane_idx, pane, &nonce)?,
+            ));
+        }
+        for (window, (pane_idx, channel, timeout)) in pending {
+            if !tmux::wait_for(&channel, Duration::from_secs(timeout))? {
+                log::error(&format!(
+                    "setup of pane {} in window '{}' timed out after {}s",
+                    pane_idx, window.name, timeout
+                ));
+                messages::say(
+                    messages::SESSION_SETUP_TIMEOUT,
+                    &[
+                        ("pane", &pane_idx),
+                        ("window", &window.name),
+                        ("seconds", &timeout),
+                    ],
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send the main commands of a window's panes, in the given order (exec
+/// panes are already running theirs)
+fn send_commands(
+    session_name: &str,
+    window_index: usize,
+    window: &Window,
+    order: &[usize],
+) -> Result<()> {
+    for &pane_idx in order {
+        let pane = &window.panes[pane_idx];
+        if pane.command.is_empty() || pane.exec {
+            continue;
+        }
 
-        if pane_count > 1 {
-            // Create additional panes (first pane already exists)
-            // Don't apply sizes during creation since apply_window_layout will handle it
-            create_window_panes(
+        let close_on_exit = window.close_on_exit.filter(|_| pane_idx == 0);
+        if pane.notify_on_exit || close_on_exit.is_some() {
+            install_exit_hooks(
                 session_name,
                 window_index,
-                window,
-                &window_root,
-                1, // Start at index 1 (first pane already exists)
-                false, // Don't apply sizes here - let apply_window_layout handle it
-                verbose,
+                pane_idx,
+                pane.notify_on_exit,
+                close_on_exit,
+            )?;
+            // The pane only dies (firing the hooks) when its shell exits;
+            // validation rules out send_enter = false here
+            let command = format!("{}; exit", pane.command);
+            tmux::send_text(
+                session_name,
+                window_index,
+                pane_idx,
+                &command,
+                pane.literal,
+                true,
+            )?;
+        } else {
+            tmux::send_text(
+                session_name,
+                window_index,
+                pane_idx,
+                &pane.command,
+                pane.literal,
+                pane.send_enter,
             )?;
+        }
+    }
+    Ok(())
+}
 
-            // Always apply layout and sizes
-            apply_window_layout(session_name, window_index, window, verbose)?;
+/// Build a session that shares the windows of a running session (`group_with`).
+///
+/// Only the session itself is created: its windows are the group's, and its
+/// `startup_window` is picked among them.
+fn build_grouped_session(
+    session: &Session,
+    group: &str,
+    ctx: &Context,
+    throwaway: bool,
+    mut timings: PhaseTimings,
+) -> Result<PhaseTimings> {
+    let session_name = &session.name;
+    let group = group_session_name(group, ctx);
+    let roots = SessionRoots::resolve(session)?;
+    let hook_session = hook_session(session, ctx);
 
-            // Wait for panes to initialize before sending commands
-            // This prevents issues where vim/neovim gets incorrect dimensions
-            thread::sleep(Duration::from_millis(500));
+    let Some(live) = query::live_session(&group)? else {
+        anyhow::bail!(
+            "Session '{}' to group '{}' with is not running\n  Hint: Open it first with 'tmx open {}'",
+            group,
+            session_name,
+            group
+        );
+    };
+    let startup_window = match &session.startup_window {
+        None => None,
+        Some(startup) => {
+            let window = match startup {
+                StartupWindow::Index(offset) => live.windows.get(*offset),
+                StartupWindow::Name(name) => live.windows.iter().find(|w| &w.name == name),
+            };
+            let Some(window) = window else {
+                let names: Vec<_> = live.windows.iter().map(|w| w.name.as_str()).collect();
+                anyhow::bail!(
+                    "Session '{}' has no startup_window {} among the windows of '{}'\n  Hint: Its windows are: {}",
+                    session_name,
+                    match startup {
+                        StartupWindow::Index(offset) => offset.to_string(),
+                        StartupWindow::Name(name) => format!("'{}'", name),
+                    },
+                    group,
+                    names.join(", ")
+                );
+            };
+            Some(window.index)
         }
+    };
+    timings.lap("prepare");
 
-        // Send commands to all panes in this window
-        for (pane_idx, pane) in window.panes.iter().enumerate() {
-            // Note: Working directory is already set via -c flag when creating the pane
-            // so we don't need to cd here
+    if !throwaway {
+        messages::say(
+            messages::SESSION_CREATING_GROUPED,
+            &[("session", &session_name), ("group", &group)],
+        );
+        run_on_create(session, &hook_session, &roots.root)?;
+    }
 
-            // Send environment variables
-            for (key, value) in &pane.env {
-                let export_cmd = format!("export {}={}", key, shell_escape(value));
-                tmux::send_keys(session_name, window_index, pane_idx, &export_cmd)?;
-            }
+    tmux::new_grouped_session(
+        session_name,
+        &group,
+        Some(&roots.root),
+        &session_environment(session, &ctx.session_config_dir(session))?,
+    )?;
+    timings.lap("new-session");
 
-            // Send the command
-            if !pane.command.is_empty() {
-                tmux::send_keys(session_name, window_index, pane_idx, &pane.command)?;
-            }
-        }
+    if let Some(index) = startup_window {
+        tmux::select_window(session_name, index)?;
+    }
+    if let Some(ref send) = session.on_attach_send {
+        install_attach_send(session, send)?;
+    }
+    if session.on_died.is_some() && !throwaway {
+        install_died_hook()?;
     }
+    apply_session_options(session)?;
+    timings.lap("select");
 
-    // Select the startup window and pane
-    let startup_window_idx = base_index + session.resolve_startup_window();
-    let startup_pane = session.get_startup_pane();
+    if throwaway {
+        return Ok(timings);
+    }
 
-    tmux::select_window(session_name, startup_window_idx)?;
-    tmux::select_pane(session_name, startup_window_idx, startup_pane)?;
+    record_created(session, ctx);
+    messages::say(messages::SESSION_CREATED, &[("session", &session_name)]);
+    messages::say(
+        messages::SESSION_GROUPED,
+        &[("windows", &live.windows.len()), ("group", &group)],
+    );
 
-    println!("✓ Session '{}' created", session_name);
-    println!("  Windows: {}", session.windows.len());
+    hooks::run_or_warn(
+        Hook::PostCreate,
+        &session.post_create,
+        &hook_session,
+        &roots.root,
+    );
 
-    // Show summary
-    for window in &session.windows {
-        println!("    - {}: {} pane(s)", window.name, window.panes.len());
+    Ok(timings)
+}
+
+/// The running session name `group_with` refers to: a configured session's
+/// (sanitized) name, or the value as given
+fn group_session_name(group: &str, ctx: &Context) -> String {
+    let configured = ctx.config().ok().and_then(|c| c.get_session(group));
+    tmux::sanitize_session_name(configured.map_or(group, |s| s.name.as_str()))
+}
+
+/// Remember that tmx created a session (used for name-collision detection)
+fn record_created(session: &Session, ctx: &Context) {
+    let id = ctx
+        .config()
+        .ok()
+        .and_then(|c| c.session_id_for(&session.name));
+    if let Err(e) = state::record_session(&session.name, id, &session.param_values) {
+        log::error(&format!(
+            "failed to record session '{}': {}",
+            session.name, e
+        ));
+    }
+}
+
+/// The session as its hook commands see it
+fn hook_session<'a>(session: &'a Session, ctx: &Context) -> HookSession<'a> {
+    let config = ctx.config().ok();
+    let id = config.and_then(|c| c.session_id_for(&session.name));
+    HookSession::new(&session.name, id, session)
+}
+
+/// Run the session's `on_create` commands; a failure stops before anything is created
+fn run_on_create(session: &Session, hook_session: &HookSession, root: &Path) -> Result<()> {
+    hooks::run(Hook::OnCreate, &session.on_create, hook_session, root).map_err(|e| {
+        anyhow::anyhow!(
+            "{}\n  Hint: Session '{}' wasn't created; fix the command, or open it with --no-commands to skip hooks",
+            e,
+            session.name
+        )
+    })
+}
+
+/// Run the session's `pre_window` commands, then the window's, in the window's root
+fn run_pre_window(session: &Session, hook_session: &HookSession, window: &Window, root: &Path) {
+    let commands: Vec<String> = session
+        .pre_window
+        .iter()
+        .chain(&window.pre_window)
+        .cloned()
+        .collect();
+    hooks::run_or_warn(Hook::PreWindow, &commands, hook_session, root);
+}
+
+/// Keep a pane open after its process exits and show a desktop notification when it does.
+///
+/// Installs a pane-level `pane-died` hook that runs `tmx __notify-exit` with
+/// the pane's session, window, index and exit status.
+fn install_exit_notification(session_name: &str, window_index: usize, pane_index: usize) -> Result<()> {
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "tmx".to_string());
+    let notify = format!(
+        "{} __notify-exit #{{q:session_name}} #{{q:window_name}} #{{pane_index}} #{{pane_dead_status}}",
+        shell::shell_escape(&exe)
+    );
+
+    tmux::set_pane_option(session_name, window_index, pane_index, "remain-on-exit", "on")?;
+    tmux::set_pane_hook(
+        session_name,
+        window_index,
+        pane_index,
+        "pane-died",
+        &format!("run-shell -b {}", tmux::quote_argument(&notify)),
+    )
+}
+
+/// Type a command into a window whenever a client attaches (`on_attach_send`).
+///
+/// The `client-attached` hook targets the window by name, so it still finds
+/// it after windows are moved.
+fn install_attach_send(session: &Session, send: &AttachSend) -> Result<()> {
+    let target = format!(
+        "={}:={}",
+        tmux::sanitize_session_name(&session.name),
+        session.attach_send_window(send)
+    );
+    let command = format!(
+        "send-keys -t {} {} Enter",
+        tmux::quote_argument(&target),
+        tmux::quote_argument(&send.command)
+    );
+    tmux::set_session_hook(&session.name, "client-attached", &command)
+}
+
+/// Run `tmx __session-died` whenever a session closes (`on_died`).
+///
+/// The hook is global, as a session's own hooks are gone once it is closed;
+/// it is installed once per server and ignores sessions without `on_died`.
+fn install_died_hook() -> Result<()> {
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "tmx".to_string());
+    let died = format!(
+        "{} __session-died #{{q:hook_session_name}}",
+        shell::shell_escape(&exe)
+    );
+    tmux::ensure_global_hook(
+        "session-closed",
+        "__session-died",
+        &format!("run-shell -b {}", tmux::quote_argument(&died)),
+    )?;
+    Ok(())
+}
+
+/// Install the exit hooks of a pane: a desktop notification and/or closing
+/// its window or session (`close_on_exit`)
+fn install_exit_hooks(
+    session_name: &str,
+    window_index: usize,
+    pane_index: usize,
+    notify: bool,
+    close_on_exit: Option<CloseOnExit>,
+) -> Result<()> {
+    if notify {
+        install_exit_notification(session_name, window_index, pane_index)?;
+    }
+
+    if let Some(scope) = close_on_exit {
+        // Hook commands run with the dead pane as their target
+        let close = match scope {
+            CloseOnExit::Window => "kill-window",
+            CloseOnExit::Session => "kill-session",
+        };
+        tmux::set_pane_option(session_name, window_index, pane_index, "remain-on-exit", "on")?;
+        tmux::set_pane_hook(session_name, window_index, pane_index, "pane-died", close)?;
+    }
+
+    Ok(())
+}
+
+/// Install the exit hooks of an exec pane right after starting it.
+///
+/// A command that exits before this runs takes its pane with it, so a failure
+/// is only reported as a warning.
+fn install_exec_hooks(
+    session_name: &str,
+    window_index: usize,
+    pane_index: usize,
+    notify: bool,
+    close_on_exit: Option<CloseOnExit>,
+) {
+    if !notify && close_on_exit.is_none() {
+        return;
+    }
+
+    if let Err(e) = install_exit_hooks(session_name, window_index, pane_index, notify, close_on_exit) {
+        log::error(&format!(
+            "failed to install exit hooks for pane {}.{}: {}",
+            window_index, pane_index, e
+        ));
+        messages::say(
+            messages::SESSION_EXIT_HOOKS_MISSED,
+            &[("pane", &pane_index), ("window", &window_index)],
+        );
+    }
+}
+
+/// Build the session environment, inherited by the shells of all panes.
+///
+/// It holds the session's `env` and the `pass_env` variables from tmx's own
+/// environment (`env` wins when both set a variable). With `isolate_history`,
+/// HISTFILE points at the session's own history file, so shells start with
+/// and save to per-session history.
+fn session_environment(session: &Session, config_dir: &Path) -> Result<Vec<(String, String)>> {
+    let mut env = secrets::resolve_env(&session.env, config_dir)?;
+    let passed: Vec<_> = session
+        .passed_environment(std::env::vars())
+        .into_iter()
+        .filter(|(key, _)| !session.env.contains_key(key))
+        .collect();
+    env.extend(passed);
+    if !session.isolate_history {
+        return Ok(env);
+    }
+
+    let history_file = session.history_file()?;
+    if let Some(dir) = history_file.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create history directory {}", dir.display()))?;
+    }
+    env.push((
+        "HISTFILE".to_string(),
+        history_file.to_string_lossy().to_string(),
+    ));
+    Ok(env)
+}
+
+/// Copy the `pass_env` variables from tmx's environment into a running
+/// session, so panes created from now on get fresh values (e.g. a new
+/// SSH_AUTH_SOCK after reconnecting).
+///
+/// # Arguments
+/// * `session` - The session's configuration
+pub fn update_passed_environment(session: &Session) -> Result<()> {
+    for (key, value) in session.passed_environment(std::env::vars()) {
+        tmux::set_session_environment(&session.name, &key, &value)?;
+    }
+    Ok(())
+}
+
+/// Shell command starting the shell tmux would start (which it sets `SHELL`
+/// to) as a non-login shell
+const NON_LOGIN_SHELL: &str = "exec \"$SHELL\"";
+
+/// Shell command starting that shell as a login shell, for panes that want
+/// one in a session with a `default_command`
+const LOGIN_SHELL: &str = "exec \"$SHELL\" -l";
+
+/// Get how to start a pane's process: with its environment, and with the
+/// command of panes with `exec = true` or the shell command for panes whose
+/// shell tmux doesn't start as configured by itself.
+///
+/// The environment is passed to the process rather than typed into its
+/// shell, so values (including secrets) never reach shell history or the
+/// pane's scrollback.
+///
+/// # Returns
+/// None if the pane is started as tmux would by itself.
+fn pane_process<'a>(
+    session: &'a Session,
+    pane: &'a Pane,
+    env: &'a [(String, String)],
+) -> Option<tmux::PaneProcess<'a>> {
+    let command = if pane.exec {
+        Some(pane.command.as_str())
+    } else {
+        shell_command(session, pane)
+    };
+    (command.is_some() || !env.is_empty()).then_some(tmux::PaneProcess { command, env })
+}
+
+/// Get the shell command a pane starts with instead of tmux's default (a
+/// login shell): the session's `default_command`, or the shell as a
+/// non-login shell with `login_shell = false`. With a `default_command`,
+/// `login_shell = true` starts a login shell instead.
+///
+/// # Returns
+/// None if tmux's default is what the pane wants.
+fn shell_command<'a>(session: &'a Session, pane: &Pane) -> Option<&'a str> {
+    let command = match (
+        pane.login_shell.or(session.login_shell),
+        &session.default_command,
+    ) {
+        (Some(true), Some(_)) => LOGIN_SHELL,
+        (Some(false), None) => NON_LOGIN_SHELL,
+        (_, Some(command)) => command.as_str(),
+        (_, None) => return None,
+    };
+    Some(command)
+}
+
+/// Value unique to one build, so its setup channels can't be signalled by a
+/// leftover setup of an earlier build of the same session
+fn setup_nonce() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    format!("{}-{}", std::process::id(), nanos)
+}
+
+/// Name of the tmux wait-for channel signalled when a pane's setup is done
+fn setup_channel(
+    session_name: &str,
+    window_index: usize,
+    pane_index: usize,
+    nonce: &str,
+) -> String {
+    let session: String = tmux::sanitize_session_name(session_name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!(
+        "tmx-setup-{}-{}-{}-{}",
+        session, window_index, pane_index, nonce
+    )
+}
+
+/// Type a pane's setup steps, followed by signalling a wait-for channel once
+/// they're done.
+///
+/// # Returns
+/// The pane index, the channel to wait for and the pane's setup timeout.
+fn start_setup(
+    session_name: &str,
+    window_index: usize,
+    pane_idx: usize,
+    pane: &Pane,
+    nonce: &str,
+) -> Result<(usize, String, u64)> {
+    let channel = setup_channel(session_name, window_index, pane_idx, nonce);
+    let mut steps = pane.setup.clone();
+    steps.push(format!("tmux wait-for -S {}", channel));
+    tmux::send_keys(session_name, window_index, pane_idx, &steps.join("; "))?;
+    Ok((pane_idx, channel, pane.setup_timeout))
+}
+
+/// Get the env files applied to a pane, lowest precedence first.
+///
+/// Session, window and pane files are resolved against the session, window
+/// and pane root respectively.
+pub fn env_file_paths(session: &Session, window: &Window, pane: &Pane) -> Vec<PathBuf> {
+    let session_root = session.root_expanded();
+    let window_root = window.root_expanded(&session_root);
+    let pane_root = pane.root_expanded(&window_root);
+
+    let levels = [
+        (&session.env_file, session_root),
+        (&window.env_file, window_root),
+        (&pane.env_file, pane_root),
+    ];
+
+    levels
+        .iter()
+        .filter_map(|(env_file, root)| env_file.as_ref().map(|f| (f, root)))
+        .flat_map(|(env_file, root)| {
+            env_file.paths().iter().map(move |path| {
+                let path = PathBuf::from(config::expand_path(path));
+                if path.is_absolute() {
+                    path
+                } else {
+                    Path::new(root).join(path)
+                }
+            })
+        })
+        .collect()
+}
+
+/// Check that the session's env files exist, according to its `env_file_missing` policy
+fn check_env_files(session: &Session) -> Result<()> {
+    let paths: BTreeSet<PathBuf> = session
+        .windows
+        .iter()
+        .flat_map(|window| {
+            window
+                .panes
+                .iter()
+                .flat_map(move |pane| env_file_paths(session, window, pane))
+        })
+        .collect();
+
+    for path in paths.iter().filter(|p| !p.exists()) {
+        match session.env_file_missing {
+            EnvFileMissing::Error => anyhow::bail!(
+                "Env file not found: {}\n  \
+                 Hint: Create the file, or set env_file_missing = \"warn\" or \"ignore\" in session '{}'",
+                path.display(),
+                session.name
+            ),
+            EnvFileMissing::Warn => {
+                log::info(&format!("env file {} not found, skipping", path.display()));
+                messages::say(
+                    messages::SESSION_ENV_FILE_MISSING,
+                    &[("path", &path.display())],
+                );
+            }
+            EnvFileMissing::Ignore => {
+                log::info(&format!("env file {} not found, ignoring", path.display()));
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Build a pane's environment: env files (session, window, then pane), then the
+/// window's `env` map, then the pane's, with later values overriding earlier ones.
+///
+/// The session's `env` isn't included, as panes inherit it from the session
+/// environment. Missing env files are skipped; `check_env_files` has already
+/// applied the policy.
+fn pane_environment(
+    session: &Session,
+    window: &Window,
+    pane: &Pane,
+    config_dir: &Path,
+) -> Result<Vec<(String, String)>> {
+    let mut layers = Vec::new();
+    for path in env_file_paths(session, window, pane) {
+        if path.exists() {
+            layers.push(dotenv::load(&path)?);
+        }
+    }
+    layers.push(secrets::resolve_env(&window.env, config_dir)?);
+    layers.push(secrets::resolve_env(&pane.env, config_dir)?);
+
+    Ok(merge_environment(layers))
+}
+
+/// Merge environment layers given lowest precedence first: a variable in a
+/// later layer overrides the same variable in earlier ones.
+///
+/// # Returns
+/// The variables sorted by name, so exports are sent in a stable order.
+fn merge_environment(layers: Vec<Vec<(String, String)>>) -> Vec<(String, String)> {
+    let mut vars = BTreeMap::new();
+    for layer in layers {
+        vars.extend(layer);
+    }
+    vars.into_iter().collect()
+}
+
 /// Create panes for a window
 ///
 /// This function creates additional panes for a window (beyond the first pane which already exists).
@@ -118,27 +1022,33 @@ pub fn create_session(session: &Session, ctx: &Context) -> Result<()> {
 /// * `session_name` - The tmux session name
 /// * `window_index` - The window index
 /// * `window` - The window configuration
-/// * `window_root` - The window's root directory
+/// * `roots` - The resolved roots of the window's panes
 /// * `start_idx` - Starting pane index (1 for new windows, current_count for refresh)
 /// * `apply_sizes` - Whether to apply custom pane sizes from config
+/// * `pane_envs` - Resolved environment of each pane; when given, panes are
+///   started with it, and panes with `exec = true` with their command
+///   instead of a shell
+/// * `session` - The session configuration, for the shell panes start with
 /// * `verbose` - Whether to print debug info
 ///
 /// # Returns
 /// Returns Ok(()) on success, or an error if pane creation fails
+#[allow(clippy::too_many_arguments)]
 pub fn create_window_panes(
     session_name: &str,
     window_index: usize,
     window: &crate::config::Window,
-    window_root: &str,
+    roots: &WindowRoots,
     start_idx: usize,
     apply_sizes: bool,
+    pane_envs: Option<&[Vec<(String, String)>]>,
+    session: &Session,
     verbose: bool,
 ) -> Result<()> {
     let pane_count = window.panes.len();
 
     for pane_idx in start_idx..pane_count {
         let pane = &window.panes[pane_idx];
-        let pane_root = pane.root_expanded(window_root);
         let horizontal = determine_split_direction(pane_idx, pane);
 
         // Apply size if requested and pane has custom size
@@ -148,16 +1058,148 @@ pub fn create_window_panes(
             None
         };
 
+        let process = match pane_envs {
+            Some(envs) => pane_process(session, pane, &envs[pane_idx]),
+            None => shell_command(session, pane).map(|command| tmux::PaneProcess {
+                command: Some(command),
+                env: &[],
+            }),
+        };
         tmux::split_window_with_size(
             session_name,
             window_index,
             horizontal,
             size,
-            Some(&pane_root),
+            Some(&roots.panes[pane_idx]),
+            process.as_ref(),
             verbose,
         )?;
+
+        if pane.exec && process.is_some() && pane.notify_on_exit {
+            install_exec_hooks(session_name, window_index, pane_idx, true, None);
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the session's options (`default_command`, `destroy_unattached`,
+/// `detach_on_destroy`), then its configured tmux `options`
+///
+/// tmux before 3.4 has no `previous` and `next` for `detach-on-destroy`, so
+/// they fall back to `off` there; `tmx close` still switches in cycling order.
+fn apply_session_options(session: &Session) -> Result<()> {
+    // tmux destroys a session without clients as soon as `destroy-unattached`
+    // is turned on, so that waits until the first client attaches
+    if session.destroy_unattached == Some(true) {
+        let target = format!("={}:", tmux::sanitize_session_name(&session.name));
+        let command = format!(
+            "set-option -t {} destroy-unattached on",
+            tmux::quote_argument(&target)
+        );
+        tmux::set_session_hook(&session.name, "client-attached", &command)?;
+    }
+
+    for (option, value) in session.session_options() {
+        let result = tmux::set_session_option(&session.name, option, value);
+        if let Err(e) = result {
+            if option != "detach-on-destroy" || !matches!(value, "previous" | "next") {
+                return Err(e);
+            }
+            log::info(&format!("detach-on-destroy {} unsupported ({}), using off", value, e));
+            tmux::set_session_option(&session.name, option, "off")?;
+        }
     }
 
+    for (option, value) in &session.options {
+        tmux::set_session_option(&session.name, option, &value.to_string()).with_context(|| {
+            format!(
+                "Failed to set option '{}' on session '{}'\n  \
+                 Hint: Window options such as automatic-rename belong in a window's options",
+                option, session.name
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Set the session's window options (`window_size`, `aggressive_resize`) and
+/// the window's configured tmux `options` on a window
+///
+/// # Arguments
+/// * `session` - The session configuration
+/// * `window_index` - The window index
+/// * `window` - The window configuration
+pub fn apply_window_options(
+    session: &Session,
+    window_index: usize,
+    window: &crate::config::Window,
+) -> Result<()> {
+    for (option, value) in session.window_options() {
+        tmux::set_window_option(&session.name, window_index, option, value)?;
+    }
+    for (option, value) in &window.options {
+        tmux::set_window_option(&session.name, window_index, option, &value.to_string())
+            .with_context(|| {
+                format!(
+                    "Failed to set option '{}' on window '{}'\n  \
+                     Hint: Session options such as status-left belong in the session's options",
+                    option, window.name
+                )
+            })?;
+    }
+    Ok(())
+}
+
+/// Set each pane's configured tmux `options` (`set-option -p`), `style` and
+/// `title`; windows with titled panes show them in the pane borders.
+///
+/// # Arguments
+/// * `session_name` - The tmux session name
+/// * `window_index` - The window index
+/// * `window` - The window configuration
+pub fn apply_pane_options(
+    session_name: &str,
+    window_index: usize,
+    window: &crate::config::Window,
+) -> Result<()> {
+    for (pane_idx, pane) in window.panes.iter().enumerate() {
+        for (option, value) in &pane.options {
+            tmux::set_pane_option(session_name, window_index, pane_idx, option, &value.to_string())
+                .with_context(|| {
+                    format!(
+                        "Failed to set option '{}' on pane {} in window '{}'\n  \
+                         Hint: Pane options need tmux 3.0 or newer",
+                        option, pane_idx, window.name
+                    )
+                })?;
+        }
+        if let Some(ref style) = pane.style {
+            // What `select-pane -P` sets, without making the pane active
+            tmux::set_pane_option(session_name, window_index, pane_idx, "window-style", style)
+                .with_context(|| {
+                    format!(
+                        "Failed to set style '{}' on pane {} in window '{}'",
+                        style, pane_idx, window.name
+                    )
+                })?;
+        }
+        if let Some(ref title) = pane.title {
+            tmux::set_pane_title(session_name, window_index, pane_idx, title).with_context(
+                || {
+                    format!(
+                        "Failed to set title '{}' on pane {} in window '{}'\n  \
+                     Hint: Pane titles need tmux 2.6 or newer",
+                        title, pane_idx, window.name
+                    )
+                },
+            )?;
+        }
+    }
+
+    if window.panes.iter().any(|pane| pane.title.is_some()) {
+        tmux::set_window_option(session_name, window_index, "pane-border-status", "top")?;
+    }
     Ok(())
 }
 
@@ -191,35 +1233,36 @@ pub fn apply_window_layout(
         // Get window dimensions for calculating percentage-based sizes
         let (window_width, window_height) = tmux::get_window_dimensions(session_name, window_index)?;
 
-        // Then apply custom pane sizes (which override the layout)
-        for (pane_idx, pane) in window.panes.iter().enumerate() {
-            if let Some(ref size_spec) = pane.size {
-                // Determine split direction to know which dimension to resize
-                let is_horizontal = determine_split_direction(pane_idx, pane);
-
-                // Calculate absolute size from percentage or use as-is
-                let absolute_size = if size_spec.ends_with('%') {
-                    let percentage = size_spec.trim_end_matches('%')
-                        .parse::<f64>()
-                        .map_err(|_| anyhow::anyhow!("Invalid percentage: {}", size_spec))?;
-
-                    // Calculate based on the dimension we're resizing
-                    let dimension = if is_horizontal { window_width } else { window_height };
-                    ((dimension as f64) * (percentage / 100.0)) as usize
-                } else {
-                    // Absolute size
-                    size_spec.parse::<usize>()
-                        .map_err(|_| anyhow::anyhow!("Invalid size: {}", size_spec))?
-                };
-
-                tmux::resize_pane(
-                    session_name,
-                    window_index,
-                    pane_idx,
-                    absolute_size,
-                    is_horizontal,
-                    verbose,
-                )?;
+        // Then apply custom pane sizes (which override the layout), unless
+        // one doesn't fit, which leaves the layout as it is
+        match fit_pane_sizes(window, window_width, window_height)? {
+            Fit::Sizes(sizes) => {
+                for size in sizes {
+                    tmux::resize_pane(
+                        session_name,
+                        window_index,
+                        size.pane,
+                        size.cells,
+                        size.horizontal,
+                        verbose,
+                    )?;
+                }
+            }
+            Fit::TooSmall { pane, size } => {
+                log::info(&format!(
+                    "window '{}' is {}x{}, too small for pane {} with size {}; not applying sizes",
+                    window.name, window_width, window_height, pane, size
+                ));
+                messages::say(
+                    messages::LAYOUT_TOO_SMALL,
+                    &[
+                        ("window", &window.name),
+                        ("width", &window_width),
+                        ("height", &window_height),
+                        ("pane", &pane),
+                        ("size", &size),
+                    ],
+                );
             }
         }
     }
@@ -227,6 +1270,68 @@ pub fn apply_window_layout(
     Ok(())
 }
 
+/// Smallest width or height a sized pane, and the panes beside it, are left with
+const MIN_PANE_SIZE: usize = 2;
+
+/// A configured pane size in cells
+#[derive(Debug, PartialEq)]
+struct PaneSize {
+    pane: usize,
+    cells: usize,
+    /// Whether `cells` is a width (side-by-side split) rather than a height
+    horizontal: bool,
+}
+
+/// Whether a window's configured pane sizes fit its dimensions
+#[derive(Debug, PartialEq)]
+enum Fit {
+    /// The sizes of the panes that have one
+    Sizes(Vec<PaneSize>),
+    /// The first pane whose size doesn't fit, with its size as configured
+    TooSmall { pane: usize, size: String },
+}
+
+/// Resolve a window's configured pane sizes against its dimensions.
+///
+/// A size fits if the pane gets at least `MIN_PANE_SIZE` cells and leaves
+/// that much (plus the border) for the other panes along the same dimension.
+fn fit_pane_sizes(window: &Window, width: usize, height: usize) -> Result<Fit> {
+    let mut sizes = Vec::new();
+    for (pane_idx, pane) in window.panes.iter().enumerate() {
+        let Some(ref size_spec) = pane.size else {
+            continue;
+        };
+        // Determine split direction to know which dimension to resize
+        let horizontal = determine_split_direction(pane_idx, pane);
+        let dimension = if horizontal { width } else { height };
+
+        // Calculate absolute size from percentage or use as-is
+        let cells = if let Some(percentage) = size_spec.strip_suffix('%') {
+            let percentage = percentage
+                .parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid percentage: {}", size_spec))?;
+            ((dimension as f64) * (percentage / 100.0)) as usize
+        } else {
+            size_spec
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("Invalid size: {}", size_spec))?
+        };
+
+        if cells < MIN_PANE_SIZE || cells + 1 + MIN_PANE_SIZE > dimension {
+            return Ok(Fit::TooSmall {
+                pane: pane_idx,
+                size: size_spec.clone(),
+            });
+        }
+        sizes.push(PaneSize {
+            pane: pane_idx,
+            cells,
+            horizontal,
+        });
+    }
+    Ok(Fit::Sizes(sizes))
+}
+
 /// Determine split direction based on pane config or default pattern
 ///
 /// Returns `true` for horizontal split (side-by-side), `false` for vertical split (top-bottom).
@@ -256,30 +1361,169 @@ pub fn determine_layout(window: &crate::config::Window, pane_count: usize) -> &s
     }
 }
 
-/// Simple shell escaping for environment variable values
-fn shell_escape(s: &str) -> String {
-    const SPECIAL_CHARS: &str = "'\"`$\\";
-    let needs_escaping = s
-        .chars()
-        .any(|c| c.is_whitespace() || SPECIAL_CHARS.contains(c));
-
-    if needs_escaping {
-        format!("'{}'", s.replace('\'', "'\\''"))
-    } else {
-        s.to_string()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_shell_escape() {
-        assert_eq!(shell_escape("simple"), "simple");
-        assert_eq!(shell_escape("with space"), "'with space'");
-        assert_eq!(shell_escape("with'quote"), "'with'\\''quote'");
-        assert_eq!(shell_escape("$VAR"), "'$VAR'");
+    fn test_setup_channel() {
+        assert_eq!(setup_channel("dev", 1, 0, "42-7"), "tmx-setup-dev-1-0-42-7");
+        // Channel names are typed into the pane's shell, so they must be shell-safe
+        assert_eq!(
+            setup_channel("my proj's.app", 2, 1, "42-7"),
+            "tmx-setup-my_proj_s_app-2-1-42-7"
+        );
+    }
+
+    #[test]
+    fn test_merge_environment() {
+        let vars = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let merged = merge_environment(vec![
+            vars(&[("PORT", "3000"), ("RUST_LOG", "info")]),
+            vars(&[("RUST_LOG", "debug"), ("DATABASE_URL", "postgres://db")]),
+            vars(&[]),
+        ]);
+        assert_eq!(
+            merged,
+            vars(&[
+                ("DATABASE_URL", "postgres://db"),
+                ("PORT", "3000"),
+                ("RUST_LOG", "debug"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_shell_process() {
+        let session = |extra: &str| -> Session {
+            toml::from_str(&format!(
+                "name = \"api\"\n{}\n[[windows]]\nname = \"w\"\n\
+                 panes = [{{ command = \"\" }}, {{ command = \"\", login_shell = true }}, \
+                 {{ command = \"\", login_shell = false }}, {{ command = \"htop\", exec = true }}]",
+                extra
+            ))
+            .unwrap()
+        };
+        let commands = |session: &Session| -> Vec<Option<String>> {
+            session.windows[0]
+                .panes
+                .iter()
+                .map(|pane| {
+                    pane_process(session, pane, &[])
+                        .and_then(|process| process.command.map(str::to_string))
+                })
+                .collect()
+        };
+        let some = |command: &str| Some(command.to_string());
+
+        // tmux starts login shells by itself
+        assert_eq!(
+            commands(&session("")),
+            [None, None, some(NON_LOGIN_SHELL), some("htop")]
+        );
+        assert_eq!(
+            commands(&session("login_shell = false")),
+            [
+                some(NON_LOGIN_SHELL),
+                None,
+                some(NON_LOGIN_SHELL),
+                some("htop")
+            ]
+        );
+        assert_eq!(
+            commands(&session("default_command = \"exec zsh\"")),
+            [
+                some("exec zsh"),
+                some(LOGIN_SHELL),
+                some("exec zsh"),
+                some("htop")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pane_environment() {
+        let session: Session = toml::from_str(
+            r#"
+name = "api"
+env = { DATABASE_URL = "postgres://localhost/dev" }
+
+[[windows]]
+name = "server"
+env = { RUST_LOG = "info", PORT = "3000" }
+panes = [{ command = "cargo run" }, { command = "cargo watch", env = { RUST_LOG = "debug" } }]
+"#,
+        )
+        .unwrap();
+        let window = &session.windows[0];
+        let env = |pane: usize| {
+            pane_environment(&session, window, &window.panes[pane], Path::new("/")).unwrap()
+        };
+
+        // Panes inherit the session's env from the session environment
+        assert_eq!(
+            env(0),
+            [
+                ("PORT".to_string(), "3000".to_string()),
+                ("RUST_LOG".to_string(), "info".to_string()),
+            ]
+        );
+        assert_eq!(
+            env(1),
+            [
+                ("PORT".to_string(), "3000".to_string()),
+                ("RUST_LOG".to_string(), "debug".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fit_pane_sizes() {
+        let window: Window = toml::from_str(
+            r#"
+name = "dev"
+panes = [{ command = "" }, { command = "", size = "30%" }, { command = "", size = "10" }]
+"#,
+        )
+        .unwrap();
+
+        // Pane 1 splits side by side (width), pane 2 stacked (height)
+        assert_eq!(
+            fit_pane_sizes(&window, 200, 50).unwrap(),
+            Fit::Sizes(vec![
+                PaneSize {
+                    pane: 1,
+                    cells: 60,
+                    horizontal: true
+                },
+                PaneSize {
+                    pane: 2,
+                    cells: 10,
+                    horizontal: false
+                },
+            ])
+        );
+        // 10 rows leave nothing for the pane above
+        assert_eq!(
+            fit_pane_sizes(&window, 200, 12).unwrap(),
+            Fit::TooSmall {
+                pane: 2,
+                size: "10".to_string()
+            }
+        );
+        // 30% of 5 columns is a single column
+        assert_eq!(
+            fit_pane_sizes(&window, 5, 50).unwrap(),
+            Fit::TooSmall {
+                pane: 1,
+                size: "30%".to_string()
+            }
+        );
     }
 
     #[test]
@@ -287,9 +1531,22 @@ mod tests {
         let pane = crate::config::Pane {
             command: String::new(),
             env: std::collections::HashMap::new(),
+            env_file: None,
+            login_shell: None,
+            send_enter: true,
+            literal: false,
+            notify_on_exit: false,
+            exec: false,
+            setup: Vec::new(),
+            setup_timeout: 300,
             root: None,
             split: Some("horizontal".to_string()),
             size: None,
+            options: Default::default(),
+            style: None,
+            title: None,
+            after: None,
+            healthcheck: None,
         };
         assert!(determine_split_direction(0, &pane));
 
@@ -305,9 +1562,22 @@ mod tests {
         let pane = crate::config::Pane {
             command: String::new(),
             env: std::collections::HashMap::new(),
+            env_file: None,
+            login_shell: None,
+            send_enter: true,
+            literal: false,
+            notify_on_exit: false,
+            exec: false,
+            setup: Vec::new(),
+            setup_timeout: 300,
             root: None,
             split: None,
             size: None,
+            options: Default::default(),
+            style: None,
+            title: None,
+            after: None,
+            healthcheck: None,
         };
         // Odd indices = horizontal
         assert!(determine_split_direction(1, &pane));
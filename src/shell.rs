@@ -0,0 +1,87 @@
+/// Shell families with different syntax for reading a command's exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// sh, bash, zsh, ksh, dash, ...: `export KEY=value`
+    Posix,
+    /// fish: `set -gx KEY value`
+    Fish,
+    /// Nushell: `$env.KEY = "value"`
+    Nu,
+    /// csh and tcsh: `setenv KEY value`
+    Csh,
+}
+
+impl ShellKind {
+    /// Determine the shell family from a shell name or path.
+    ///
+    /// Accepts what tmux reports as `#{pane_current_command}`, including the
+    /// `-` prefix of login shells.
+    ///
+    /// # Returns
+    /// The shell family, or None if the name isn't a known shell.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().trim_start_matches('-');
+        let name = name.rsplit('/').next().unwrap_or(name);
+        match name {
+            "sh" | "bash" | "zsh" | "ksh" | "mksh" | "dash" | "ash" => Some(ShellKind::Posix),
+            "fish" => Some(ShellKind::Fish),
+            "nu" => Some(ShellKind::Nu),
+            "csh" | "tcsh" => Some(ShellKind::Csh),
+            _ => None,
+        }
+    }
+
+    /// Build the command that writes the previous command's exit status to a
+    /// file (the path must already be quoted for the shell)
+    pub fn status_command(self, path: &str) -> String {
+        match self {
+            ShellKind::Posix => format!("echo $? > {}", path),
+            ShellKind::Fish | ShellKind::Csh => format!("echo $status > {}", path),
+            ShellKind::Nu => format!("$env.LAST_EXIT_CODE | save -f {}", path),
+        }
+    }
+}
+
+/// Simple shell escaping for environment variable values
+pub fn shell_escape(s: &str) -> String {
+    const SPECIAL_CHARS: &str = "'\"`$\\";
+    let needs_escaping = s
+        .chars()
+        .any(|c| c.is_whitespace() || SPECIAL_CHARS.contains(c));
+
+    if needs_escaping {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(shell_escape("simple"), "simple");
+        assert_eq!(shell_escape("with space"), "'with space'");
+        assert_eq!(shell_escape("with'quote"), "'with'\\''quote'");
+        assert_eq!(shell_escape("$VAR"), "'$VAR'");
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(ShellKind::from_name("bash"), Some(ShellKind::Posix));
+        assert_eq!(ShellKind::from_name("-zsh"), Some(ShellKind::Posix));
+        assert_eq!(ShellKind::from_name("/usr/bin/fish"), Some(ShellKind::Fish));
+        assert_eq!(ShellKind::from_name("nvim"), None);
+        assert_eq!(ShellKind::from_name("nu"), Some(ShellKind::Nu));
+        assert_eq!(ShellKind::from_name("tcsh"), Some(ShellKind::Csh));
+    }
+
+    #[test]
+    fn test_status_command() {
+        assert_eq!(ShellKind::Posix.status_command("/tmp/s"), "echo $? > /tmp/s");
+        assert_eq!(ShellKind::Fish.status_command("/tmp/s"), "echo $status > /tmp/s");
+        assert_eq!(ShellKind::Nu.status_command("/tmp/s"), "$env.LAST_EXIT_CODE | save -f /tmp/s");
+    }
+}
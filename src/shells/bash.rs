@@ -21,6 +21,16 @@ _tmx_completions() {{
         words=("${{COMP_WORDS[@]}}")
     fi
 
+    # Global options that take a file
+    if [[ "$prev" == "--log-file" ]]; then
+        COMPREPLY=($(compgen -f -- "$cur"))
+        return 0
+    fi
+    if [[ $cword -eq 2 && "$prev" == "--attach-mode" ]]; then
+        COMPREPLY=($(compgen -W "attach switch print new-terminal" -- "$cur"))
+        return 0
+    fi
+
     # Get the subcommand (first argument after tmx)
     local cmd=""
     if [[ $cword -gt 1 ]]; then
@@ -30,13 +40,27 @@ _tmx_completions() {{
     case "$cmd" in
         "")
             # No subcommand yet - suggest subcommands
-            local commands="open close refresh list init validate completions help o c r ls"
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--log-file --print-target --attach-mode" -- "$cur"))
+                return 0
+            fi
+            local commands="open attach picker close refresh reload sync sync-config import ticket workspace layout project bench server run copy paste graph status snapshot prompt-segment list logs history gc config init validate lint migrate version completions help o a c r ws ls"
             COMPREPLY=($(compgen -W "$commands" -- "$cur"))
             return 0
             ;;
         open|o)
             # Suggest running sessions first, then configured-not-running
-            if [[ $cword -eq 2 ]]; then
+            if [[ "$prev" == "--attach-mode" ]]; then
+                COMPREPLY=($(compgen -W "attach switch print new-terminal" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--force --no-commands --attach-mode --new-terminal --terminal --print-target --sha256" -- "$cur"))
+            elif [[ "${{COMP_LINE:0:COMP_POINT}}" =~ [^\ ]*:[^\ ]*$ ]]; then
+                # "<source>:<id>", as typed, since ':' usually splits words in bash
+                local target="${{BASH_REMATCH[0]}}"
+                local configured=$(tmx __list-configured 2>/dev/null)
+                COMPREPLY=($(compgen -W "$configured" -- "$target"))
+                [[ "$cur" != "$target" ]] && COMPREPLY=("${{COMPREPLY[@]#*:}}")
+            elif [[ $cword -eq 2 ]]; then
                 local running=$(tmx __list-running 2>/dev/null)
                 local configured=$(tmx __list-configured 2>/dev/null)
                 # Combine: running first, then configured that aren't running
@@ -50,15 +74,51 @@ _tmx_completions() {{
             fi
             return 0
             ;;
+        picker)
+            COMPREPLY=($(compgen -W "--tmux-popup" -- "$cur"))
+            return 0
+            ;;
+        attach|a)
+            # Suggest running sessions
+            if [[ "$prev" == "--attach-mode" ]]; then
+                COMPREPLY=($(compgen -W "attach switch print new-terminal" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--attach-mode --new-terminal --terminal" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        ticket)
+            if [[ "$prev" == "--attach-mode" ]]; then
+                COMPREPLY=($(compgen -W "attach switch print new-terminal" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--attach-mode --new-terminal --terminal" -- "$cur"))
+            fi
+            return 0
+            ;;
         close|c)
             # Suggest running sessions
-            if [[ $cword -eq 2 ]]; then
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--force" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
                 local sessions=$(tmx __list-running 2>/dev/null)
                 COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
             fi
             return 0
             ;;
         refresh|r)
+            # Suggest running sessions
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--window" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        reload|sync)
             # Suggest running sessions
             if [[ $cword -eq 2 ]]; then
                 local sessions=$(tmx __list-running 2>/dev/null)
@@ -71,10 +131,166 @@ _tmx_completions() {{
             if [[ $cword -eq 2 ]]; then
                 local shells="fish bash zsh"
                 COMPREPLY=($(compgen -W "$shells" -- "$cur"))
+            elif [[ $cword -eq 3 ]]; then
+                COMPREPLY=($(compgen -W "--install" -- "$cur"))
+            fi
+            return 0
+            ;;
+        config)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "restore messages" -- "$cur"))
+            elif [[ "${{words[2]}}" == "restore" ]]; then
+                COMPREPLY=($(compgen -W "--list" -- "$cur"))
+            fi
+            return 0
+            ;;
+        bench)
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--iterations" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-configured 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        graph)
+            if [[ "$prev" == "--format" ]]; then
+                COMPREPLY=($(compgen -W "mermaid dot" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--format --live" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-configured 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        status)
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--health --summary" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local running=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$running" -- "$cur"))
+            fi
+            return 0
+            ;;
+        snapshot)
+            if [[ "$prev" == "--id" ]]; then
+                return 0
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--id --append" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local running=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$running" -- "$cur"))
             fi
             return 0
             ;;
-        list|ls|init|validate|help)
+        prompt-segment)
+            if [[ "$prev" == "--format" ]]; then
+                COMPREPLY=($(compgen -W "text json" -- "$cur"))
+            else
+                COMPREPLY=($(compgen -W "--format" -- "$cur"))
+            fi
+            return 0
+            ;;
+        workspace|ws)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "open close" -- "$cur"))
+            elif [[ $cword -eq 3 ]]; then
+                local workspaces=$(tmx __list-workspaces 2>/dev/null)
+                COMPREPLY=($(compgen -W "$workspaces" -- "$cur"))
+            fi
+            return 0
+            ;;
+        import)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "url" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--sha256 --yes" -- "$cur"))
+            fi
+            return 0
+            ;;
+        layout)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "save apply" -- "$cur"))
+            elif [[ $cword -eq 3 ]]; then
+                local sessions=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        project)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "new trust" -- "$cur"))
+            elif [[ "${{words[2]}}" == "trust" ]]; then
+                COMPREPLY=($(compgen -f -- "$cur"))
+            elif [[ "$prev" == "--template" ]]; then
+                COMPREPLY=($(compgen -W "rust node python" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--template --attach-mode --new-terminal" -- "$cur"))
+            fi
+            return 0
+            ;;
+        run|copy|paste)
+            # The target word as typed, since ':' usually splits words in bash
+            local target="${{COMP_LINE:0:COMP_POINT}}"
+            target="${{target##* }}"
+            if [[ "$target" == *:* ]]; then
+                # Complete window names after "<session>:"
+                local session="${{target%%:*}}"
+                local windows=$(tmx __list-windows "$session" 2>/dev/null)
+                local prefix=""
+                [[ "$cur" == "$session:"* ]] && prefix="$session:"
+                COMPREPLY=($(compgen -P "$prefix" -W "$windows" -- "${{target#*:}}"))
+            elif [[ "$cur" == -* ]]; then
+                local options="--timeout"
+                [[ "$cmd" == "copy" ]] && options="--lines --no-clipboard"
+                [[ "$cmd" == "paste" ]] && options=""
+                COMPREPLY=($(compgen -W "$options" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-running 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        server)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "start kill info" -- "$cur"))
+            elif [[ "${{words[2]}}" == "kill" ]]; then
+                COMPREPLY=($(compgen -W "--force" -- "$cur"))
+            fi
+            return 0
+            ;;
+        logs)
+            COMPREPLY=($(compgen -W "--commands --lines" -- "$cur"))
+            return 0
+            ;;
+        history)
+            if [[ "$prev" == "--format" ]]; then
+                COMPREPLY=($(compgen -W "text json" -- "$cur"))
+            elif [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "--lines --format" -- "$cur"))
+            elif [[ $cword -eq 2 ]]; then
+                local sessions=$(tmx __list-configured 2>/dev/null)
+                COMPREPLY=($(compgen -W "$sessions" -- "$cur"))
+            fi
+            return 0
+            ;;
+        validate)
+            COMPREPLY=($(compgen -W "--format --strict" -- "$cur"))
+            if [[ "$prev" == "--format" ]]; then
+                COMPREPLY=($(compgen -W "text json" -- "$cur"))
+            fi
+            return 0
+            ;;
+        migrate)
+            COMPREPLY=($(compgen -W "--dry-run" -- "$cur"))
+            return 0
+            ;;
+        list|ls)
+            COMPREPLY=($(compgen -W "--watch" -- "$cur"))
+            return 0
+            ;;
+        init|lint|version|help)
             # No additional completions needed
             return 0
             ;;
@@ -9,18 +9,46 @@ complete -c tmx -f
 
 # Subcommands
 complete -c tmx -n "__fish_use_subcommand" -a "open" -d "Open/attach to session"
+complete -c tmx -n "__fish_use_subcommand" -a "attach" -d "Attach to running session"
+complete -c tmx -n "__fish_use_subcommand" -a "picker" -d "Pick a session and switch to it"
 complete -c tmx -n "__fish_use_subcommand" -a "close" -d "Close session"
 complete -c tmx -n "__fish_use_subcommand" -a "refresh" -d "Refresh session layout"
+complete -c tmx -n "__fish_use_subcommand" -a "reload" -d "Recreate session from config"
+complete -c tmx -n "__fish_use_subcommand" -a "sync" -d "Reconcile session with config"
+complete -c tmx -n "__fish_use_subcommand" -a "sync-config" -d "Clone or update shared config repository"
+complete -c tmx -n "__fish_use_subcommand" -a "import" -d "Import a session config from a URL"
+complete -c tmx -n "__fish_use_subcommand" -a "ticket" -d "Open the session for a ticket"
+complete -c tmx -n "__fish_use_subcommand" -a "workspace" -d "Open or close a workspace"
+complete -c tmx -n "__fish_use_subcommand" -a "layout" -d "Save and apply window layouts"
+complete -c tmx -n "__fish_use_subcommand" -a "project" -d "Create a project with a session config"
+complete -c tmx -n "__fish_use_subcommand" -a "bench" -d "Measure session creation"
+complete -c tmx -n "__fish_use_subcommand" -a "server" -d "Start, stop or inspect the tmux server"
+complete -c tmx -n "__fish_use_subcommand" -a "run" -d "Run a command in a pane and exit with its status"
+complete -c tmx -n "__fish_use_subcommand" -a "copy" -d "Copy the end of a pane to the paste buffer and clipboard"
+complete -c tmx -n "__fish_use_subcommand" -a "paste" -d "Paste the tmux paste buffer into a pane"
+complete -c tmx -n "__fish_use_subcommand" -a "graph" -d "Print a diagram of a session"
+complete -c tmx -n "__fish_use_subcommand" -a "status" -d "Show what runs in each pane of running sessions"
+complete -c tmx -n "__fish_use_subcommand" -a "snapshot" -d "Print a running session as config"
+complete -c tmx -n "__fish_use_subcommand" -a "prompt-segment" -d "Print session status for a shell prompt"
 complete -c tmx -n "__fish_use_subcommand" -a "list" -d "List configured and running sessions"
+complete -c tmx -n "__fish_use_subcommand" -a "logs" -d "Show the tmx log"
+complete -c tmx -n "__fish_use_subcommand" -a "history" -d "Show recent opens, closes and refreshes"
+complete -c tmx -n "__fish_use_subcommand" -a "gc" -d "Drop stale session state, old config backups and old log records"
+complete -c tmx -n "__fish_use_subcommand" -a "config" -d "Manage the config file"
 complete -c tmx -n "__fish_use_subcommand" -a "init" -d "Initialize config file"
 complete -c tmx -n "__fish_use_subcommand" -a "validate" -d "Validate config syntax"
+complete -c tmx -n "__fish_use_subcommand" -a "lint" -d "Warn about likely config mistakes"
+complete -c tmx -n "__fish_use_subcommand" -a "migrate" -d "Upgrade the config file format"
+complete -c tmx -n "__fish_use_subcommand" -a "version" -d "Show version information"
 complete -c tmx -n "__fish_use_subcommand" -a "completions" -d "Generate shell completions"
 complete -c tmx -n "__fish_use_subcommand" -a "help" -d "Show help message"
 
 # Command aliases
 complete -c tmx -n "__fish_use_subcommand" -a "o" -d "Alias for open"
+complete -c tmx -n "__fish_use_subcommand" -a "a" -d "Alias for attach"
 complete -c tmx -n "__fish_use_subcommand" -a "c" -d "Alias for close"
 complete -c tmx -n "__fish_use_subcommand" -a "r" -d "Alias for refresh"
+complete -c tmx -n "__fish_use_subcommand" -a "ws" -d "Alias for workspace"
 complete -c tmx -n "__fish_use_subcommand" -a "ls" -d "Alias for list"
 
 # Helper functions
@@ -37,6 +65,21 @@ function __tmx_running_sessions
     tmx __list-running 2>/dev/null
 end
 
+function __tmx_run_targets
+    # After "<session>:", complete the session's windows
+    set -l token (commandline -ct)
+    if string match -q -- "*:*" $token
+        set -l session (string split -m 1 : -- $token)[1]
+        for w in (tmx __list-windows $session 2>/dev/null)
+            echo -e "$session:$w\tWindow"
+        end
+    else
+        for s in (tmx __list-running 2>/dev/null)
+            echo -e "$s\tRunning"
+        end
+    end
+end
+
 function __tmx_open_sessions
     # Get running sessions first
     set -l running (tmx __list-running 2>/dev/null)
@@ -44,11 +87,11 @@ function __tmx_open_sessions
     for s in $running
         echo -e "$s\tRunning"
     end
-    # Get configured sessions that are NOT running
-    set -l configured (tmx __list-configured 2>/dev/null)
-    for s in $configured
-        if not contains $s $running
-            echo -e "$s\tConfigured"
+    # Get configured sessions that are NOT running, with where they come from
+    for line in (tmx __list-configured --sources 2>/dev/null)
+        set -l s (string split -m 1 \t -- $line)
+        if not contains $s[1] $running
+            echo -e "$s[1]\tConfigured ($s[2])"
         end
     end
 end
@@ -56,22 +99,146 @@ end
 # Dynamic completions for open (configured + running sessions with descriptions)
 complete -c tmx -n "__tmx_using_command open" -a "(__tmx_open_sessions)"
 complete -c tmx -n "__tmx_using_command o" -a "(__tmx_open_sessions)"
+complete -c tmx -n "__tmx_using_command open" -s f -l force -d "Recreate session from config"
+complete -c tmx -n "__tmx_using_command o" -s f -l force -d "Recreate session from config"
+complete -c tmx -n "__tmx_using_command open" -l no-commands -d "Don't run configured commands"
+complete -c tmx -n "__tmx_using_command o" -l no-commands -d "Don't run configured commands"
+complete -c tmx -n "__tmx_using_command open" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session"
+complete -c tmx -n "__tmx_using_command open" -l new-terminal -l terminal -d "Open in a new terminal window"
+complete -c tmx -n "__tmx_using_command o" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session"
+complete -c tmx -n "__tmx_using_command o" -l new-terminal -l terminal -d "Open in a new terminal window"
+complete -c tmx -n "__tmx_using_command open" -l print-target -d "Only print the session name"
+complete -c tmx -n "__tmx_using_command o" -l print-target -d "Only print the session name"
+complete -c tmx -n "__tmx_using_command open" -l sha256 -x -d "Expected checksum of a config URL"
+complete -c tmx -n "__tmx_using_command o" -l sha256 -x -d "Expected checksum of a config URL"
+
+# Import subcommand
+complete -c tmx -n "__tmx_using_command import; and not __fish_seen_subcommand_from url" -a "url" -d "Fetch a config over HTTPS and import its sessions"
+complete -c tmx -n "__tmx_using_command import" -l sha256 -x -d "Expected checksum of the config"
+complete -c tmx -n "__tmx_using_command import" -s y -l yes -d "Import without confirmation"
+
+# Options for ticket
+complete -c tmx -n "__tmx_using_command ticket" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session"
+complete -c tmx -n "__tmx_using_command ticket" -l new-terminal -l terminal -d "Open in a new terminal window"
+
+# Dynamic completions for attach (running sessions)
+complete -c tmx -n "__tmx_using_command attach" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command a" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command attach" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session"
+complete -c tmx -n "__tmx_using_command attach" -l new-terminal -l terminal -d "Open in a new terminal window"
+complete -c tmx -n "__tmx_using_command a" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session"
+complete -c tmx -n "__tmx_using_command a" -l new-terminal -l terminal -d "Open in a new terminal window"
 
 # Dynamic completions for close (running sessions)
 complete -c tmx -n "__tmx_using_command close" -a "(__tmx_running_sessions)" -d "Running"
 complete -c tmx -n "__tmx_using_command c" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command close" -s f -l force -d "Don't confirm closing the current session"
+complete -c tmx -n "__tmx_using_command c" -s f -l force -d "Don't confirm closing the current session"
 
 # Dynamic completions for refresh (running sessions)
 complete -c tmx -n "__tmx_using_command refresh" -a "(__tmx_running_sessions)" -d "Running"
 complete -c tmx -n "__tmx_using_command r" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command refresh" -s w -l window -x -d "Only refresh this window"
+complete -c tmx -n "__tmx_using_command r" -s w -l window -x -d "Only refresh this window"
+
+# Dynamic completions for reload (running sessions)
+complete -c tmx -n "__tmx_using_command reload" -a "(__tmx_running_sessions)" -d "Running"
+
+# Dynamic completions for sync (running sessions)
+complete -c tmx -n "__tmx_using_command sync" -a "(__tmx_running_sessions)" -d "Running"
+
+# Dynamic completions for bench (configured sessions)
+complete -c tmx -n "__tmx_using_command bench" -a "(__tmx_configured_sessions)" -d "Configured"
+complete -c tmx -n "__tmx_using_command bench" -s n -l iterations -x -d "Number of timed runs"
+
+# Dynamic completions for graph (configured sessions)
+complete -c tmx -n "__tmx_using_command graph" -a "(__tmx_configured_sessions)" -d "Configured"
+complete -c tmx -n "__tmx_using_command graph" -l format -x -a "mermaid dot" -d "Diagram format"
+complete -c tmx -n "__tmx_using_command graph" -l live -d "Draw the running session"
+
+# Workspace subcommands and names
+complete -c tmx -n "__tmx_using_command workspace; and not __fish_seen_subcommand_from open close" -a "open close"
+complete -c tmx -n "__tmx_using_command workspace; and __fish_seen_subcommand_from open close" -a "(tmx __list-workspaces 2>/dev/null)" -d "Workspace"
+complete -c tmx -n "__tmx_using_command ws; and not __fish_seen_subcommand_from open close" -a "open close"
+complete -c tmx -n "__tmx_using_command ws; and __fish_seen_subcommand_from open close" -a "(tmx __list-workspaces 2>/dev/null)" -d "Workspace"
+
+# Options for picker
+complete -c tmx -n "__tmx_using_command picker" -l tmux-popup -d "Run in a tmux popup"
+
+# Layout subcommands and running sessions
+complete -c tmx -n "__tmx_using_command layout; and not __fish_seen_subcommand_from save apply" -a "save apply"
+complete -c tmx -n "__tmx_using_command layout; and __fish_seen_subcommand_from save apply" -a "(tmx __list-running 2>/dev/null)" -d "Running"
+
+# Project subcommand and templates
+complete -c tmx -n "__tmx_using_command project; and not __fish_seen_subcommand_from new trust" -a "new" -d "Create a project and open its session"
+complete -c tmx -n "__tmx_using_command project; and not __fish_seen_subcommand_from new trust" -a "trust" -d "Allow tmx to use a project config"
+complete -c tmx -n "__tmx_using_command project" -l template -x -a "rust node python" -d "Stack to set up"
+
+# Dynamic completions for run (running sessions, then windows after the colon)
+complete -c tmx -n "__tmx_using_command run" -a "(__tmx_run_targets)"
+complete -c tmx -n "__tmx_using_command run" -l timeout -x -d "Stop waiting after this many seconds"
+
+# Options for copy and paste (same targets as run)
+complete -c tmx -n "__tmx_using_command copy" -a "(__tmx_run_targets)"
+complete -c tmx -n "__tmx_using_command copy" -s n -l lines -x -d "Number of lines to copy"
+complete -c tmx -n "__tmx_using_command copy" -l no-clipboard -d "Only fill the tmux paste buffer"
+complete -c tmx -n "__tmx_using_command paste" -a "(__tmx_run_targets)"
+
+# Server subcommands
+complete -c tmx -n "__tmx_using_command server; and not __fish_seen_subcommand_from start kill info" -a "start kill info"
+complete -c tmx -n "__tmx_using_command server; and __fish_seen_subcommand_from kill" -s f -l force -d "Kill without confirmation"
+
+# Options for status
+complete -c tmx -n "__tmx_using_command status" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command status" -l health -d "Run the panes' health checks"
+complete -c tmx -n "__tmx_using_command status" -l summary -d "Print one line for the session"
+
+# Options for snapshot
+complete -c tmx -n "__tmx_using_command snapshot" -a "(__tmx_running_sessions)" -d "Running"
+complete -c tmx -n "__tmx_using_command snapshot" -l id -x -d "Config key for the session"
+complete -c tmx -n "__tmx_using_command snapshot" -l append -d "Add the session to the config file"
+
+# Options for logs
+complete -c tmx -n "__tmx_using_command logs" -l commands -d "Only show tmux command trace"
+complete -c tmx -n "__tmx_using_command logs" -s n -l lines -x -d "Number of lines to show"
+
+# Options for history
+complete -c tmx -n "__tmx_using_command history" -a "(__tmx_configured_sessions)" -d "Configured"
+complete -c tmx -n "__tmx_using_command history" -s n -l lines -x -d "Number of operations to show"
+complete -c tmx -n "__tmx_using_command history" -l format -x -a "text json" -d "Output format"
+
+# Options for list
+complete -c tmx -n "__tmx_using_command list" -s w -l watch -d "Redraw every few seconds"
+complete -c tmx -n "__tmx_using_command ls" -s w -l watch -d "Redraw every few seconds"
+
+# Options for prompt-segment
+complete -c tmx -n "__tmx_using_command prompt-segment" -l format -x -a "text json" -d "Output format"
+
+# Options for validate
+complete -c tmx -n "__tmx_using_command validate" -l format -x -a "text json" -d "Output format"
+complete -c tmx -n "__tmx_using_command validate" -l strict -d "Fail on undefined variables in paths"
+
+# Options for migrate
+complete -c tmx -n "__tmx_using_command migrate" -l dry-run -d "Show changes without writing"
+
+# Config subcommands
+complete -c tmx -n "__tmx_using_command config" -a "restore" -d "Restore config from a backup"
+complete -c tmx -n "__tmx_using_command config" -a "messages" -d "Print the message catalog for translation"
+complete -c tmx -n "__tmx_using_command config" -l list -d "List available backups"
 
 # Shell options for completions command
 complete -c tmx -n "__tmx_using_command completions" -a "fish" -d "Fish shell"
 complete -c tmx -n "__tmx_using_command completions" -a "bash" -d "Bash shell"
 complete -c tmx -n "__tmx_using_command completions" -a "zsh" -d "Zsh shell"
+complete -c tmx -n "__tmx_using_command completions" -l install -d "Install to the shell completions directory"
 
 # Help options
 complete -c tmx -s h -l help -d "Show help message"
+
+# Global options
+complete -c tmx -n "__fish_use_subcommand" -l log-file -r -F -d "Write logs to this file (- for stderr)"
+complete -c tmx -n "__fish_use_subcommand" -l print-target -d "Print the session cycling would go to"
+complete -c tmx -n "__fish_use_subcommand" -l attach-mode -x -a "attach switch print new-terminal" -d "How to hand over to the session cycling goes to"
 "#,
         version
     )
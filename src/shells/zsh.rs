@@ -10,6 +10,8 @@ pub fn generate_completions() -> String {
 _tmx_configured_sessions() {{
     local -a sessions
     sessions=(${{(f)"$(tmx __list-configured 2>/dev/null)"}})
+    # Escape the ':' of <source>:<id> for _describe
+    sessions=(${{sessions//:/\\:}})
     if (( ${{#sessions}} > 0 )); then
         _describe 'configured session' sessions
     fi
@@ -24,11 +26,26 @@ _tmx_running_sessions() {{
     fi
 }}
 
+# Helper function to complete <session>:<window> targets
+_tmx_targets() {{
+    if [[ $PREFIX == *:* ]]; then
+        local session=${{PREFIX%%:*}}
+        local -a windows
+        windows=(${{(f)"$(tmx __list-windows $session 2>/dev/null)"}})
+        compset -P '*:'
+        if (( ${{#windows}} > 0 )); then
+            _describe 'window' windows
+        fi
+    else
+        _tmx_running_sessions
+    fi
+}}
+
 # Helper function to get all sessions with status descriptions
 _tmx_open_sessions() {{
     local -a running configured running_desc configured_desc
     running=(${{(f)"$(tmx __list-running 2>/dev/null)"}})
-    configured=(${{(f)"$(tmx __list-configured 2>/dev/null)"}})
+    configured=(${{(f)"$(tmx __list-configured --sources 2>/dev/null)"}})
 
     # Add running sessions with description
     running_desc=()
@@ -36,11 +53,13 @@ _tmx_open_sessions() {{
         running_desc+=("$s:Running")
     done
 
-    # Add configured sessions that are not running
+    # Add configured sessions that are not running, with where they come from
     configured_desc=()
-    for s in $configured; do
+    local line s
+    for line in $configured; do
+        s=${{line%%$'\t'*}}
         if (( ! ${{running[(Ie)$s]}} )); then
-            configured_desc+=("$s:Configured")
+            configured_desc+=("${{s//:/\\:}}:Configured (${{line#*$'\t'}})")
         fi
     done
 
@@ -71,21 +90,159 @@ _tmx() {{
         '(-h --help)'{{-h,--help}}'[Show help message]' \
         '(-c --config)'{{-c,--config}}'[Path to config file]:config file:_files' \
         '(-v --verbose)'{{-v,--verbose}}'[Enable verbose/debug output]' \
+        '--log-file[Write logs to this file (- for stderr)]:log file:_files' \
+        '*--set[Override a config value for this invocation]:PATH=VALUE:' \
+        '--print-target[Print the session cycling would go to]' \
+        '--attach-mode[How to hand over to the session cycling goes to]:mode:(attach switch print new-terminal)' \
         '1: :_tmx_commands' \
         '*::arg:->args'
 
     case $line[1] in
         open|o)
-            _tmx_open_sessions
+            _arguments \
+                '(-f --force)'{{-f,--force}}'[Recreate session from config]' \
+                '--no-commands[Do not run configured commands]' \
+                '--attach-mode[How to hand over to the session]:mode:(attach switch print new-terminal)' \
+                '(--new-terminal --terminal)'{{--new-terminal,--terminal}}'[Open in a new terminal window]' \
+                '--print-target[Only print the session name, progress goes to stderr]' \
+                '--sha256[Expected checksum of a config URL]:checksum:' \
+                '1: :_tmx_open_sessions'
+            ;;
+        attach|a)
+            _arguments \
+                '--attach-mode[How to hand over to the session]:mode:(attach switch print new-terminal)' \
+                '(--new-terminal --terminal)'{{--new-terminal,--terminal}}'[Open in a new terminal window]' \
+                '1: :_tmx_running_sessions'
+            ;;
+        ticket)
+            _arguments \
+                '--attach-mode[How to hand over to the session]:mode:(attach switch print new-terminal)' \
+                '(--new-terminal --terminal)'{{--new-terminal,--terminal}}'[Open in a new terminal window]' \
+                '1:ticket ID:'
             ;;
         close|c)
-            _tmx_running_sessions
+            _arguments \
+                '(-f --force)'{{-f,--force}}'[Do not confirm closing the current session]' \
+                '1:: :_tmx_running_sessions'
             ;;
         refresh|r)
+            _arguments \
+                '(-w --window)'{{-w,--window}}'[Only refresh this window]:window:' \
+                '1:: :_tmx_running_sessions'
+            ;;
+        reload|sync)
             _tmx_running_sessions
             ;;
+        bench)
+            _arguments \
+                '(-n --iterations)'{{-n,--iterations}}'[Number of timed runs]:iterations:' \
+                '1: :_tmx_configured_sessions'
+            ;;
+        graph)
+            _arguments \
+                '--format[Diagram format]:format:(mermaid dot)' \
+                '--live[Draw the running session]' \
+                '1: :_tmx_configured_sessions'
+            ;;
+        status)
+            _arguments \
+                '(--summary)--health[Run the panes health checks]' \
+                '(--health)--summary[Print one line for the session, e.g. for status-right]' \
+                '1: :_tmx_running_sessions'
+            ;;
+        snapshot)
+            _arguments \
+                '--id[Config key for the session]:id:' \
+                '--append[Add the session to the config file]' \
+                '1: :_tmx_running_sessions'
+            ;;
+        prompt-segment)
+            _arguments \
+                '--format[Output format]:format:(text json)'
+            ;;
+        workspace|ws)
+            _arguments \
+                '1:action:(open close)' \
+                '2:workspace:($(tmx __list-workspaces 2>/dev/null))'
+            ;;
+        picker)
+            _arguments \
+                '--tmux-popup[Run in a tmux popup and switch the client that opened it]'
+            ;;
+        import)
+            _arguments \
+                '--sha256[Expected checksum of the config]:checksum:' \
+                '(-y --yes)'{{-y,--yes}}'[Import without confirmation]' \
+                '1:source:(url)' \
+                '2:https URL:_urls'
+            ;;
+        layout)
+            _arguments \
+                '1:action:(save apply)' \
+                '2: :_tmx_running_sessions' \
+                '3:layout name:'
+            ;;
+        project)
+            _arguments \
+                '--template[Stack to set up]:template:(rust node python)' \
+                '--attach-mode[How to hand over to the session]:mode:(attach switch print new-terminal)' \
+                '--new-terminal[Open in a new terminal window]' \
+                '1:action:(new trust)' \
+                '2:name or path:_files'
+            ;;
+        run)
+            _arguments \
+                '--timeout[Stop waiting after this many seconds]:seconds:' \
+                '1: :_tmx_targets'
+            ;;
+        copy)
+            _arguments \
+                '(-n --lines)'{{-n,--lines}}'[Number of lines to copy]:lines:' \
+                '--no-clipboard[Only fill the tmux paste buffer]' \
+                '1: :_tmx_targets'
+            ;;
+        paste)
+            _arguments \
+                '1: :_tmx_targets'
+            ;;
+        server)
+            _arguments \
+                '1:action:(start kill info)' \
+                '(-f --force)'{{-f,--force}}'[Kill without confirmation]'
+            ;;
+        logs)
+            _arguments \
+                '--commands[Only show tmux command trace]' \
+                '(-n --lines)'{{-n,--lines}}'[Number of lines to show]:lines:'
+            ;;
+        history)
+            _arguments \
+                '(-n --lines)'{{-n,--lines}}'[Number of operations to show]:lines:' \
+                '--format[Output format]:format:(text json)' \
+                '1: :_tmx_configured_sessions'
+            ;;
+        config)
+            _arguments \
+                '--list[List available backups]' \
+                '1:action:(restore messages)'
+            ;;
+        migrate)
+            _arguments \
+                '--dry-run[Show changes without writing]'
+            ;;
+        list|ls)
+            _arguments \
+                '(-w --watch)'{{-w,--watch}}'[Redraw every SECONDS (default: 2)]::seconds:'
+            ;;
+        validate)
+            _arguments \
+                '--format[Output format]:format:(text json)' \
+                '--strict[Fail on undefined variables in paths]'
+            ;;
         completions)
-            _tmx_shells
+            _arguments \
+                '--install[Install to the shell completions directory]' \
+                '1: :_tmx_shells'
             ;;
     esac
 }}
@@ -96,14 +253,42 @@ _tmx_commands() {{
     commands=(
         'open:Open or attach to a session'
         'o:Alias for open'
+        'attach:Attach to a running session (never creates one)'
+        'picker:Pick a running or configured session and switch to it'
+        'a:Alias for attach'
         'close:Close a running session'
         'c:Alias for close'
         'refresh:Refresh the layout of a running session'
         'r:Alias for refresh'
+        'reload:Recreate a session from config, keeping focus'
+        'sync:Reconcile a running session with its config'
+        'sync-config:Clone or update a shared git repository of session configs'
+        'import:Import a session config from a URL'
+        'ticket:Open the session for a ticket, created from a template'
+        'workspace:Open or close a workspace of sessions'
+        'ws:Alias for workspace'
+        'layout:Save window layouts and apply them to other windows'
+        'project:Create a project with a session config for its stack'
+        'bench:Measure session creation on a separate tmux server'
+        'server:Start, stop or inspect the tmux server'
+        'run:Run a command in a pane and exit with its status'
+        'copy:Copy the end of a pane to the paste buffer and clipboard'
+        'paste:Paste the tmux paste buffer into a pane'
+        'graph:Print a diagram of a session (Mermaid or Graphviz)'
+        'status:Show what runs in each pane of running sessions'
+        'snapshot:Print a running session as config'
+        'prompt-segment:Print the current session and drift status for a shell prompt'
         'list:List configured and running sessions'
         'ls:Alias for list'
+        'logs:Show the tmx log'
+        'history:Show recent opens, closes and refreshes'
+        'gc:Drop stale session state, old config backups and old log records'
+        'config:Manage the configuration file'
         'init:Initialize configuration file'
         'validate:Validate configuration syntax'
+        'lint:Warn about likely config mistakes'
+        'migrate:Upgrade the config file format'
+        'version:Show version information'
         'completions:Generate shell completions'
         'help:Show help message'
     )
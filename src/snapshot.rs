@@ -0,0 +1,157 @@
+//! Config for a running session, built from what tmux reports (`tmx snapshot`).
+//!
+//! Windows keep their names and, with several panes, their exact layout as
+//! a custom layout string. Panes get the program running in them as their
+//! command (shells become empty commands) and their working directory as a
+//! root when it differs from the session root.
+
+use crate::config::contract_home;
+use crate::shell::ShellKind;
+use crate::tmux::query::{LivePane, LiveSession};
+use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table};
+
+/// Build the `[sessions.<id>]` block for a running session.
+///
+/// # Arguments
+/// * `id` - Config key of the session
+/// * `live` - The running session
+/// * `root` - The session's working directory (`#{session_path}`)
+pub fn session_document(id: &str, live: &LiveSession, root: &str) -> DocumentMut {
+    let mut session = Table::new();
+    session["name"] = toml_edit::value(&live.name);
+    session["root"] = toml_edit::value(contract_home(root));
+
+    let mut windows = ArrayOfTables::new();
+    for live_window in &live.windows {
+        let mut window = Table::new();
+        window["name"] = toml_edit::value(&live_window.name);
+        if live_window.panes.len() > 1 {
+            window["layout"] = toml_edit::value(&live_window.layout);
+        }
+
+        let mut panes = ArrayOfTables::new();
+        for live_pane in &live_window.panes {
+            panes.push(pane_table(live_pane, root));
+        }
+        window.insert("panes", Item::ArrayOfTables(panes));
+        windows.push(window);
+    }
+    session.insert("windows", Item::ArrayOfTables(windows));
+
+    let mut sessions = Table::new();
+    sessions.set_implicit(true);
+    sessions.insert(id, Item::Table(session));
+
+    let mut doc = DocumentMut::new();
+    doc.insert("sessions", Item::Table(sessions));
+    doc
+}
+
+/// A pane's command and, if it differs from the session root, its root
+fn pane_table(pane: &LivePane, session_root: &str) -> Table {
+    let mut table = Table::new();
+    let command = if ShellKind::from_name(&pane.current_command).is_some() {
+        ""
+    } else {
+        pane.current_command.as_str()
+    };
+    table["command"] = toml_edit::value(command);
+
+    if pane.cwd != session_root {
+        // Directories below the session root are written relative to it
+        let root = Path::new(&pane.cwd)
+            .strip_prefix(session_root)
+            .ok()
+            .map(|relative| relative.to_string_lossy().to_string())
+            .filter(|relative| !relative.is_empty())
+            .unwrap_or_else(|| contract_home(&pane.cwd));
+        table["root"] = toml_edit::value(root);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::tmux::query::LiveWindow;
+
+    fn pane(index: usize, command: &str, cwd: &str) -> LivePane {
+        LivePane {
+            id: format!("%{}", index),
+            index,
+            active: false,
+            dead: false,
+            current_command: command.to_string(),
+            cwd: cwd.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_session_document() {
+        let layout = "95e4,120x40,0,0{60x40,0,0,0,59x40,61,0[59x20,61,0,1,59x19,61,21,2]}";
+        let live = LiveSession {
+            id: "$1".to_string(),
+            name: "scratch".to_string(),
+            windows: vec![
+                LiveWindow {
+                    id: "@1".to_string(),
+                    index: 1,
+                    name: "editor".to_string(),
+                    layout: "b25d,80x24,0,0,0".to_string(),
+                    active: true,
+                    panes: vec![pane(0, "nvim", "/srv/app")],
+                },
+                LiveWindow {
+                    id: "@2".to_string(),
+                    index: 2,
+                    name: "servers".to_string(),
+                    layout: layout.to_string(),
+                    active: false,
+                    panes: vec![
+                        pane(0, "zsh", "/srv/app"),
+                        pane(1, "node", "/srv/app/web"),
+                        pane(2, "-bash", "/var/log"),
+                    ],
+                },
+            ],
+        };
+
+        let toml = session_document("scratch", &live, "/srv/app").to_string();
+        assert_eq!(
+            toml,
+            format!(
+                r#"[sessions.scratch]
+name = "scratch"
+root = "/srv/app"
+
+[[sessions.scratch.windows]]
+name = "editor"
+
+[[sessions.scratch.windows.panes]]
+command = "nvim"
+
+[[sessions.scratch.windows]]
+name = "servers"
+layout = "{}"
+
+[[sessions.scratch.windows.panes]]
+command = ""
+
+[[sessions.scratch.windows.panes]]
+command = "node"
+root = "web"
+
+[[sessions.scratch.windows.panes]]
+command = ""
+root = "/var/log"
+"#,
+                layout
+            )
+        );
+
+        let config: Config = toml::from_str(&toml).unwrap();
+        config.sessions["scratch"].validate().unwrap();
+    }
+}
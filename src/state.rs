@@ -0,0 +1,355 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::log;
+use crate::tmux;
+
+/// Number of operations kept in the history (older ones are dropped)
+const HISTORY_LIMIT: usize = 500;
+
+/// Persistent record of sessions created by tmx (~/.cache/tmx/state.toml).
+///
+/// Each entry stores the tmux `session_created` timestamp so that a session
+/// that was later killed and recreated by hand under the same name is not
+/// mistaken for one tmx created. Changes go through [`State::update`], which
+/// holds a lock so concurrent tmx processes don't lose each other's writes.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct State {
+    #[serde(default)]
+    pub sessions: BTreeMap<String, SessionRecord>,
+    /// Sessions opened, closed and refreshed, oldest first (see `tmx history`)
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    /// When stale state and old files were last cleaned up (seconds since
+    /// the Unix epoch, see `tmx gc`)
+    #[serde(default)]
+    pub last_gc: u64,
+    /// SHA-256 of each project config the user approved, by path (see
+    /// `tmx project trust`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted_projects: BTreeMap<String, String>,
+}
+
+/// A session created by tmx
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SessionRecord {
+    /// Config key the session was created from (None for default-template sessions)
+    #[serde(default)]
+    pub id: Option<String>,
+    /// tmux `session_created` timestamp (seconds since the Unix epoch)
+    pub created: u64,
+    /// Values of the session's `params` it was opened with, reused when it
+    /// is reloaded, refreshed or synced
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+/// An operation on a session, as shown by `tmx history`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// When the operation finished (seconds since the Unix epoch)
+    pub time: u64,
+    pub operation: Operation,
+    pub session: String,
+    pub outcome: Outcome,
+    /// Why the operation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Operations recorded in the history
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Open,
+    Close,
+    Refresh,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Open => "open",
+            Operation::Close => "close",
+            Operation::Refresh => "refresh",
+        })
+    }
+}
+
+/// Whether a recorded operation succeeded
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok,
+    Failed,
+}
+
+impl State {
+    /// Get the state file path (~/.cache/tmx/state.toml)
+    pub fn path() -> Result<PathBuf> {
+        let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home_dir.join(".cache").join("tmx").join("state.toml"))
+    }
+
+    /// Load the state file, returning an empty state if it doesn't exist or is unreadable
+    pub fn load() -> Self {
+        match Self::path() {
+            Ok(path) => Self::load_from(&path),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Load a state file, returning an empty state if it doesn't exist or is unreadable
+    fn load_from(path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        toml::from_str(&content).unwrap_or_else(|e| {
+            log::error(&format!("ignoring corrupt state file {}: {}", path.display(), e));
+            Self::default()
+        })
+    }
+
+    /// Load the state file, change it and save it, holding the state lock
+    /// throughout so changes made by other tmx processes at the same time
+    /// aren't lost.
+    ///
+    /// # Arguments
+    /// * `change` - Applied to the loaded state before it is saved
+    ///
+    /// # Returns
+    /// What `change` returned.
+    pub fn update<T>(change: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        Self::update_at(&Self::path()?, change)
+    }
+
+    /// Load, change and save the state file at a path while holding its lock
+    fn update_at<T>(path: &Path, change: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create state directory: {}", dir.display()))?;
+        }
+        let _lock = lock(path)?;
+
+        let mut state = Self::load_from(path);
+        let result = change(&mut state);
+        state.save_to(path)?;
+        Ok(result)
+    }
+
+    /// Write the state file, replacing it in one step so readers never see
+    /// a partly written file
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self).context("Failed to serialize state")?;
+        let temp = path.with_extension(format!("toml.{}.tmp", std::process::id()));
+        fs::write(&temp, content)
+            .with_context(|| format!("Failed to write state file: {}", temp.display()))?;
+        fs::rename(&temp, path)
+            .with_context(|| format!("Failed to replace state file: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Drop the records of sessions that no longer exist: not running, or
+    /// recreated under the same name since tmx created them.
+    ///
+    /// # Arguments
+    /// * `created` - The `session_created` timestamp of a running session
+    ///   by name, or None if it isn't running
+    ///
+    /// # Returns
+    /// The names of the dropped sessions.
+    pub fn prune_sessions(&mut self, created: impl Fn(&str) -> Option<u64>) -> Vec<String> {
+        let stale: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|(name, record)| created(name) != Some(record.created))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &stale {
+            self.sessions.remove(name);
+        }
+        stale
+    }
+}
+
+/// Take the exclusive lock guarding a state file, waiting for any other tmx
+/// process holding it (released when the returned file is dropped)
+fn lock(path: &Path) -> Result<File> {
+    let lock_path = path.with_extension("lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    Ok(file)
+}
+
+/// Record that tmx created a running session.
+///
+/// # Arguments
+/// * `name` - The session name
+/// * `id` - The config key the session was created from, if any
+/// * `params` - Values of the session's `params`
+pub fn record_session(
+    name: &str,
+    id: Option<&str>,
+    params: &BTreeMap<String, String>,
+) -> Result<()> {
+    let created = tmux::session_created(name)?;
+    State::update(|state| {
+        state.sessions.insert(
+            tmux::sanitize_session_name(name),
+            SessionRecord {
+                id: id.map(str::to_string),
+                created,
+                params: params.clone(),
+            },
+        );
+    })
+}
+
+/// The `params` values a session tmx created was opened with, if it's recorded
+pub fn recorded_params(name: &str) -> Option<BTreeMap<String, String>> {
+    State::load()
+        .sessions
+        .remove(&tmux::sanitize_session_name(name))
+        .map(|record| record.params)
+}
+
+/// Add an operation and its outcome to the history.
+///
+/// Failing to save the history is logged rather than returned, so it never
+/// fails the operation itself.
+///
+/// # Arguments
+/// * `operation` - What was done
+/// * `session` - The session it was done to
+/// * `result` - The operation's result
+pub fn record_operation<T>(operation: Operation, session: &str, result: &Result<T>) {
+    let entry = HistoryEntry {
+        time: now(),
+        operation,
+        session: tmux::sanitize_session_name(session),
+        outcome: if result.is_ok() {
+            Outcome::Ok
+        } else {
+            Outcome::Failed
+        },
+        // Only the first line; hints are for the terminal, not the history
+        error: result
+            .as_ref()
+            .err()
+            .and_then(|e| e.to_string().lines().next().map(str::to_string)),
+    };
+
+    if let Err(e) = State::update(|state| push_history(state, entry)) {
+        log::error(&format!(
+            "failed to record {} of '{}' in the history: {}",
+            operation, session, e
+        ));
+    }
+}
+
+/// Add an entry to the history, dropping the oldest ones beyond the limit
+fn push_history(state: &mut State, entry: HistoryEntry) {
+    state.history.push(entry);
+    let excess = state.history.len().saturating_sub(HISTORY_LIMIT);
+    state.history.drain(..excess);
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Remove a session from the state file
+pub fn forget_session(name: &str) -> Result<()> {
+    State::update(|state| {
+        state.sessions.remove(&tmux::sanitize_session_name(name));
+    })
+}
+
+/// Check whether a running session was created by tmx.
+///
+/// Returns `false` if the session isn't recorded, or if the running session
+/// has a different creation time than the recorded one.
+pub fn is_managed(name: &str) -> bool {
+    let state = State::load();
+    let Some(record) = state.sessions.get(&tmux::sanitize_session_name(name)) else {
+        return false;
+    };
+
+    tmux::session_created(name)
+        .map(|created| created == record.created)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_sessions() {
+        let record = |created| SessionRecord {
+            id: None,
+            created,
+            params: BTreeMap::new(),
+        };
+        let mut state = State::default();
+        state.sessions.insert("api".to_string(), record(100));
+        state.sessions.insert("web".to_string(), record(200));
+        state.sessions.insert("gone".to_string(), record(300));
+
+        // "web" was killed and recreated by hand
+        let running = |name: &str| match name {
+            "api" => Some(100),
+            "web" => Some(250),
+            _ => None,
+        };
+        assert_eq!(state.prune_sessions(running), ["gone", "web"]);
+        assert_eq!(state.sessions.keys().collect::<Vec<_>>(), ["api"]);
+    }
+
+    #[test]
+    fn test_update_concurrently() {
+        let dir = std::env::temp_dir().join(format!("tmx-state-{}", std::process::id()));
+        let path = dir.join("state.toml");
+
+        // Every process's entries survive, none overwritten by another's save
+        let threads: Vec<_> = (0..8)
+            .map(|thread| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        let entry = HistoryEntry {
+                            time: now(),
+                            operation: Operation::Open,
+                            session: format!("s{}", thread),
+                            outcome: Outcome::Ok,
+                            error: None,
+                        };
+                        State::update_at(&path, |state| push_history(state, entry)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(State::load_from(&path).history.len(), 80);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,185 @@
+//! Aligned, optionally colored table output for terminal listings.
+
+use std::io::IsTerminal;
+
+/// Environment variable that disables colored output (https://no-color.org)
+const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Gap between columns
+const COLUMN_GAP: &str = "  ";
+
+/// How a cell is highlighted when colors are enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    #[default]
+    Plain,
+    Bold,
+    Dim,
+    Green,
+    Yellow,
+    Red,
+}
+
+impl Style {
+    /// ANSI SGR code for the style
+    fn code(self) -> Option<&'static str> {
+        match self {
+            Style::Plain => None,
+            Style::Bold => Some("1"),
+            Style::Dim => Some("2"),
+            Style::Green => Some("32"),
+            Style::Yellow => Some("33"),
+            Style::Red => Some("31"),
+        }
+    }
+}
+
+/// A table cell: its text and how to highlight it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cell {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Self::new(text, Style::Plain)
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::new(text, Style::Plain)
+    }
+}
+
+/// A table with a header row, rendered with left-aligned columns
+#[derive(Debug, Clone)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Create a table with the given column headers
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row (missing cells are left empty, extra cells are ignored)
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render the table for stdout, colored if stdout is a terminal and
+    /// NO_COLOR isn't set
+    pub fn render(&self) -> String {
+        self.render_with(color_enabled())
+    }
+
+    /// Render the table, one line per row with the header first.
+    ///
+    /// Columns are as wide as their widest cell; trailing whitespace is trimmed.
+    ///
+    /// # Arguments
+    /// * `color` - Highlight cells with ANSI escape codes
+    pub fn render_with(&self, color: bool) -> String {
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|col| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| cell.text.chars().count())
+                    .chain(std::iter::once(self.headers[col].chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let header: Vec<Cell> = self
+            .headers
+            .iter()
+            .map(|h| Cell::new(h.as_str(), Style::Bold))
+            .collect();
+
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&self.rows) {
+            let mut line = String::new();
+            for (col, width) in widths.iter().enumerate() {
+                let cell = row.get(col).cloned().unwrap_or_default();
+                let padding = width - cell.text.chars().count().min(*width);
+                if col > 0 {
+                    line.push_str(COLUMN_GAP);
+                }
+                line.push_str(&paint(&cell, color));
+                line.push_str(&" ".repeat(padding));
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Whether stdout should be colored
+pub fn color_enabled() -> bool {
+    std::env::var_os(NO_COLOR_ENV).is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+}
+
+/// Wrap a cell's text in the escape codes for its style
+fn paint(cell: &Cell, color: bool) -> String {
+    match cell.style.code() {
+        Some(code) if color && !cell.text.is_empty() => {
+            format!("\x1b[{}m{}\x1b[0m", code, cell.text)
+        }
+        _ => cell.text.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_aligns_columns() {
+        let mut table = Table::new(&["NAME", "STATE", "ROOT"]);
+        table.row(vec![
+            "api".into(),
+            Cell::new("running", Style::Green),
+            "~/src/api".into(),
+        ]);
+        table.row(vec!["frontend".into(), "stopped".into()]);
+
+        assert_eq!(
+            table.render_with(false),
+            "NAME      STATE    ROOT\n\
+             api       running  ~/src/api\n\
+             frontend  stopped\n"
+        );
+    }
+
+    #[test]
+    fn test_render_with_color() {
+        let mut table = Table::new(&["A", "B"]);
+        table.row(vec![Cell::new("x", Style::Green), "y".into()]);
+
+        let rendered = table.render_with(true);
+        assert_eq!(rendered.lines().nth(1).unwrap(), "\x1b[32mx\x1b[0m  y");
+    }
+}
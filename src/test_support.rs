@@ -0,0 +1,165 @@
+//! Helpers for integration tests that run real tmux.
+//!
+//! Tests get a private tmux server (`tmux -L tmx-test-<pid>`) started with an
+//! empty tmux config and a bash without startup files as the pane shell, so
+//! they never touch the user's sessions and don't depend on their
+//! `~/.tmux.conf` or shell setup:
+//!
+//! ```ignore
+//! let Some(server) = TestServer::start() else { return }; // skipped on request
+//! let fixture = server.fixture("basic", include_str!("fixtures/basic.toml"))?;
+//! fixture.create("basic")?;
+//! let live = tmux::query::live_session("basic")?.unwrap();
+//! ```
+
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::commands::start;
+use crate::context::Context;
+use crate::session;
+use crate::tmux;
+
+/// Environment variable that skips the tests needing tmux instead of failing
+/// them when tmux can't be started
+pub const SKIP_TMUX_TESTS_ENV: &str = "TMX_SKIP_TMUX_TESTS";
+
+/// Serializes tests within a process, since the tmux socket is process-wide
+static SERVER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Session that keeps the test server alive between tests' sessions
+const KEEPALIVE_SESSION: &str = "tmx-test-keepalive";
+
+/// Shell that runs pane commands
+const TEST_SHELL: &str = "/bin/bash";
+
+/// Shell started in panes without a command: bash without startup files, which
+/// could slow tests down or change what they see
+const TEST_SHELL_COMMAND: &str = "exec /bin/bash --norc --noprofile";
+
+/// A private tmux server for the duration of a test, killed when dropped
+pub struct TestServer {
+    socket: String,
+    dir: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestServer {
+    /// Start a private tmux server and point all tmx tmux commands at it.
+    ///
+    /// # Returns
+    /// The server, or None if it couldn't be started and `TMX_SKIP_TMUX_TESTS`
+    /// is set (the test should be skipped).
+    ///
+    /// # Panics
+    /// If tmux can't be started and skipping wasn't asked for, so a missing
+    /// tmux doesn't pass the tests silently.
+    pub fn start() -> Option<Self> {
+        // A failed test poisons the lock, which doesn't affect the next test
+        let guard = SERVER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let socket = format!("tmx-test-{}", std::process::id());
+        // The previous test's server may still be shutting down on the same
+        // socket, so retry briefly
+        let started = wait_until(Duration::from_secs(2), || {
+            Command::new("tmux")
+                .args(["-L", &socket, "-f", "/dev/null"])
+                .args(["new-session", "-d", "-s", KEEPALIVE_SESSION])
+                .args([";", "set-option", "-g", "default-shell", TEST_SHELL])
+                .args([";", "set-option", "-g", "default-command", TEST_SHELL_COMMAND])
+                .status()
+                .is_ok_and(|s| s.success())
+        });
+        if !started {
+            if std::env::var_os(SKIP_TMUX_TESTS_ENV).is_some() {
+                eprintln!(
+                    "skipping: could not start tmux ({} is set)",
+                    SKIP_TMUX_TESTS_ENV
+                );
+                return None;
+            }
+            panic!(
+                "Could not start tmux (is it installed?)\n  Hint: Set {}=1 to skip the tests that need it",
+                SKIP_TMUX_TESTS_ENV
+            );
+        }
+
+        tmux::set_socket_name(Some(&socket));
+        let dir = std::env::temp_dir().join(&socket);
+        Some(Self {
+            socket,
+            dir,
+            _guard: guard,
+        })
+    }
+
+    /// Socket name of the server (`tmux -L <socket>`)
+    pub fn socket(&self) -> &str {
+        &self.socket
+    }
+
+    /// Write a fixture config to a temporary file and load it.
+    ///
+    /// # Arguments
+    /// * `name` - File name for the fixture (without `.toml`)
+    /// * `toml` - The config file contents
+    pub fn fixture(&self, name: &str, toml: &str) -> Result<Fixture> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(format!("{}.toml", name));
+        fs::write(&path, toml).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        let ctx = Context::new(Some(path.to_string_lossy().to_string()), false)?;
+        ctx.config()?;
+        Ok(Fixture { ctx })
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = tmux::kill_server();
+        tmux::set_socket_name(None);
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A loaded fixture config
+pub struct Fixture {
+    ctx: Context,
+}
+
+impl Fixture {
+    /// Context to pass to commands
+    pub fn ctx(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Create a session from the fixture (quietly, without recording it as
+    /// created by tmx, so the user's state file is left alone)
+    pub fn create(&self, session_id: &str) -> Result<()> {
+        let (session, _) = start::resolve_session(session_id, self.ctx.config()?)?;
+        session::create_session_measured(&session, &self.ctx).map(|_| ())
+    }
+}
+
+/// Poll a condition until it holds or the timeout expires.
+///
+/// # Returns
+/// Whether the condition held before the timeout.
+pub fn wait_until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let started = Instant::now();
+    loop {
+        if condition() {
+            return true;
+        }
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
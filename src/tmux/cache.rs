@@ -0,0 +1,82 @@
+//! Answers to tmux queries, reused for the rest of one tmx invocation.
+//!
+//! A single command often asks tmux the same thing several times: whether a
+//! session exists, which sessions are running, a session's base-index or
+//! windows. Queries marked as cacheable are answered from here after the
+//! first time. Any command that isn't a known read-only command may change
+//! what tmux would answer, so running one clears the cache; answers never
+//! outlive a change tmx made itself. Loops that wait for tmux to change on
+//! its own (e.g. `tmx list --watch`) clear the cache before each look.
+
+use std::collections::BTreeMap;
+use std::process::Output;
+use std::sync::Mutex;
+
+/// Cached outputs by argument list
+static CACHE: Mutex<BTreeMap<Vec<String>, Output>> = Mutex::new(BTreeMap::new());
+
+/// Commands that only read the server's state
+const READ_ONLY: &[&str] = &[
+    "capture-pane",
+    "display-message",
+    "has-session",
+    "list-buffers",
+    "list-clients",
+    "list-panes",
+    "list-sessions",
+    "list-windows",
+    "show-buffer",
+    "show-environment",
+    "show-hooks",
+    "show-options",
+    "show-window-options",
+];
+
+/// Get the cached output of a query
+pub(super) fn lookup(args: &[&str]) -> Option<Output> {
+    CACHE.lock().ok()?.get(&key(args)).cloned()
+}
+
+/// Remember the output of a query
+pub(super) fn store(args: &[&str], output: &Output) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.insert(key(args), output.clone());
+    }
+}
+
+/// Forget all answers before running a command that may change them
+pub(super) fn invalidate_for(args: &[&str]) {
+    if !is_read_only(args) {
+        clear();
+    }
+}
+
+/// Whether a command only reads the server's state
+fn is_read_only(args: &[&str]) -> bool {
+    args.first().is_some_and(|command| READ_ONLY.contains(command))
+}
+
+/// Forget all answers
+pub(super) fn clear() {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.clear();
+    }
+}
+
+fn key(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(is_read_only(&["list-sessions", "-F", "#{session_name}"]));
+        assert!(is_read_only(&["display-message", "-p", "#{base-index}"]));
+        assert!(!is_read_only(&["new-session", "-d", "-s", "dev"]));
+        assert!(!is_read_only(&["set-option", "-g", "base-index", "1"]));
+        assert!(!is_read_only(&[]));
+    }
+}
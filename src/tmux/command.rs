@@ -0,0 +1,239 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io;
+use std::process::Output;
+use std::str::FromStr;
+
+use super::{
+    execute_tmux_interactive, execute_tmux_with, run_tmux, run_tmux_cached, sanitize_session_name,
+};
+
+/// Target of a tmux command (the `-t` argument).
+///
+/// Session names are sanitized when the target is rendered, so callers pass
+/// names exactly as they appear in config.
+#[derive(Debug, Clone, Copy)]
+pub enum Target<'a> {
+    /// A session (`session`)
+    Session(&'a str),
+    /// The current window of a session (`session:`)
+    SessionWindow(&'a str),
+    /// A window by index (`session:window`)
+    Window(&'a str, usize),
+    /// A pane by window and pane index (`session:window.pane`)
+    Pane(&'a str, usize, usize),
+    /// A pane by its unique tmux ID (`%7`)
+    PaneId(&'a str),
+}
+
+impl fmt::Display for Target<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Target::Session(session) => write!(f, "{}", sanitize_session_name(session)),
+            Target::SessionWindow(session) => write!(f, "{}:", sanitize_session_name(session)),
+            Target::Window(session, window) => {
+                write!(f, "{}:{}", sanitize_session_name(session), window)
+            }
+            Target::Pane(session, window, pane) => {
+                write!(f, "{}:{}.{}", sanitize_session_name(session), window, pane)
+            }
+            Target::PaneId(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+/// A tmux command built from typed parts.
+///
+/// The command renders to an argument list for execution, or to a readable
+/// command line (via `Display`) for dry runs and verbose output.
+///
+/// ```ignore
+/// TmuxCommand::new("split-window")
+///     .target(Target::Window("dev", 1))
+///     .flag("-h")
+///     .option_if_some("-c", root)
+///     .run()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxCommand {
+    args: Vec<String>,
+    /// Whether the answer may be reused within this invocation
+    cached: bool,
+}
+
+impl TmuxCommand {
+    /// Start a command (e.g. `split-window`)
+    pub fn new(command: &str) -> Self {
+        Self {
+            args: vec![command.to_string()],
+            cached: false,
+        }
+    }
+
+    /// Reuse the answer of an identical query from earlier in this invocation,
+    /// unless a command that may change it ran since (for read-only commands)
+    pub fn cached(mut self) -> Self {
+        self.cached = true;
+        self
+    }
+
+    /// Set the target (`-t <target>`)
+    pub fn target(self, target: Target) -> Self {
+        self.option("-t", target.to_string())
+    }
+
+    /// Add a flag without a value (e.g. `-d`)
+    pub fn flag(mut self, flag: &str) -> Self {
+        self.args.push(flag.to_string());
+        self
+    }
+
+    /// Add a flag without a value only if enabled
+    pub fn flag_if(self, flag: &str, enabled: bool) -> Self {
+        if enabled { self.flag(flag) } else { self }
+    }
+
+    /// Add a flag with a value (e.g. `-n editor`)
+    pub fn option(mut self, flag: &str, value: impl Into<String>) -> Self {
+        self.args.push(flag.to_string());
+        self.args.push(value.into());
+        self
+    }
+
+    /// Add a flag with a value only if the value is present
+    pub fn option_if_some(self, flag: &str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.option(flag, value),
+            None => self,
+        }
+    }
+
+    /// Add a positional argument
+    pub fn arg(mut self, value: impl Into<String>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    /// The rendered argument list (without the leading `tmux`)
+    pub fn args(&self) -> Vec<&str> {
+        self.args.iter().map(String::as_str).collect()
+    }
+
+    /// How to run the command: through the query cache or not
+    fn runner(&self) -> fn(&[&str]) -> io::Result<Output> {
+        if self.cached { run_tmux_cached } else { run_tmux }
+    }
+
+    /// Execute the command, failing if tmux reports an error
+    pub fn run(&self) -> Result<()> {
+        execute_tmux_with(&self.args(), self.runner())?;
+        Ok(())
+    }
+
+    /// Execute the command and return its stdout without the trailing newline
+    pub fn output(&self) -> Result<String> {
+        let output = execute_tmux_with(&self.args(), self.runner())?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.trim_end_matches('\n').to_string())
+    }
+
+    /// Execute the command and parse its trimmed stdout
+    pub fn query<T>(&self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let stdout = self.output()?;
+        stdout
+            .trim()
+            .parse()
+            .with_context(|| format!("Failed to parse output of '{}': {}", self, stdout.trim()))
+    }
+
+    /// Execute the command once and return stdout, or None if tmux reported an error.
+    ///
+    /// For queries where failure is an expected answer (e.g. no server running).
+    pub fn output_if_success(&self) -> Result<Option<String>> {
+        let output = (self.runner())(&self.args()).context("Failed to execute tmux command")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(Some(stdout.trim_end_matches('\n').to_string()))
+    }
+
+    /// Execute the command once and report whether it succeeded
+    pub fn succeeds(&self) -> Result<bool> {
+        Ok(self.output_if_success()?.is_some())
+    }
+
+    /// Execute the command attached to the current terminal (for attach)
+    pub fn run_interactive(&self) -> Result<()> {
+        execute_tmux_interactive(&self.args())
+    }
+}
+
+/// Quote a string as a single argument inside a tmux command string (e.g. a hook).
+///
+/// Formats (`#{...}`) are left alone so tmux expands them when the command runs.
+pub fn quote_argument(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$")
+    )
+}
+
+/// Render tmux arguments as a readable, shell-quoted command line
+pub fn render_command(args: &[&str]) -> String {
+    let mut line = String::from("tmux");
+    for arg in args {
+        if arg.is_empty() || arg.chars().any(|c| c.is_whitespace() || c == '\'' || c == '"') {
+            line.push_str(&format!(" '{}'", arg.replace('\'', "'\\''")));
+        } else {
+            line.push(' ');
+            line.push_str(arg);
+        }
+    }
+    line
+}
+
+impl fmt::Display for TmuxCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", render_command(&self.args()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_rendering() {
+        assert_eq!(Target::Session("my.app").to_string(), "my_app");
+        assert_eq!(Target::SessionWindow("dev").to_string(), "dev:");
+        assert_eq!(Target::Window("dev", 1).to_string(), "dev:1");
+        assert_eq!(Target::Pane("a:b", 2, 3).to_string(), "a_b:2.3");
+        assert_eq!(Target::PaneId("%7").to_string(), "%7");
+    }
+
+    #[test]
+    fn test_quote_argument() {
+        assert_eq!(quote_argument("echo #{pane_index}"), "\"echo #{pane_index}\"");
+        assert_eq!(quote_argument("say \"hi\" $HOME"), "\"say \\\"hi\\\" \\$HOME\"");
+    }
+
+    #[test]
+    fn test_command_rendering() {
+        let cmd = TmuxCommand::new("split-window")
+            .target(Target::Window("dev", 1))
+            .flag("-h")
+            .option_if_some("-l", None)
+            .option_if_some("-c", Some("/tmp/my dir"));
+
+        assert_eq!(
+            cmd.args(),
+            vec!["split-window", "-t", "dev:1", "-h", "-c", "/tmp/my dir"]
+        );
+        assert_eq!(cmd.to_string(), "tmux split-window -t dev:1 -h -c '/tmp/my dir'");
+    }
+}
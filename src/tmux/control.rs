@@ -0,0 +1,380 @@
+//! tmux control mode (`tmux -C`): one client connection that runs many commands.
+//!
+//! Building a large session takes hundreds of tmux commands, and starting a
+//! tmux process for each one dominates the time it takes. While a control
+//! client is connected, `run_tmux` writes commands to it and reads their
+//! output from the `%begin`/`%end` blocks tmux answers with; commands it
+//! can't carry (and everything after the connection is lost) fall back to a
+//! tmux process each.
+//!
+//! The client attaches to the session being built with `ignore-size`, so it
+//! doesn't change window sizes, and `no-output`, so pane output isn't
+//! streamed to it (tmux 3.2+; older versions refuse the flags and tmx uses
+//! processes throughout).
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, ExitStatus, Output, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{Target, TmuxCommand, cache, tmux_command};
+use crate::log;
+
+/// Whether session creation may use a control client (`tmux.control_mode`)
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The connected control client, if any
+static CLIENT: Mutex<Option<ControlClient>> = Mutex::new(None);
+
+/// Commands that must not run through the control client: they would move,
+/// detach or block the client itself
+const PROCESS_ONLY: &[&str] = &[
+    "attach-session",
+    "switch-client",
+    "detach-client",
+    "wait-for",
+    "kill-server",
+];
+
+/// Enable or disable control mode
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Keeps a control client connected while it is alive.
+///
+/// Dropping the guard detaches the client.
+pub struct ControlGuard {
+    connected: bool,
+}
+
+impl Drop for ControlGuard {
+    fn drop(&mut self) {
+        if self.connected
+            && let Ok(mut client) = CLIENT.lock()
+            && let Some(client) = client.take()
+        {
+            client.close();
+            cache::clear();
+        }
+    }
+}
+
+/// Route tmux commands through a control client attached to `session` until
+/// the returned guard is dropped.
+///
+/// Nothing is connected if control mode is disabled, another client is
+/// already connected, the session would be destroyed when the client
+/// detaches (`destroy-unattached`), or tmux refuses the connection; commands
+/// then run as separate processes.
+pub fn connect(session: &str) -> ControlGuard {
+    let not_connected = ControlGuard { connected: false };
+    if !ENABLED.load(Ordering::SeqCst) || CLIENT.lock().map_or(true, |slot| slot.is_some()) {
+        return not_connected;
+    }
+
+    let destroy_unattached = TmuxCommand::new("display-message")
+        .target(Target::Session(session))
+        .flag("-p")
+        .arg("#{destroy-unattached}")
+        .output_if_success();
+    // Flag options read as 0 or 1 in formats; tmux 3.4 made it a choice ("off")
+    if !matches!(destroy_unattached, Ok(Some(ref value)) if value == "0" || value == "off") {
+        log::info("control mode: not used, the session may be destroyed on detach");
+        return not_connected;
+    }
+
+    match ControlClient::attach(session) {
+        Ok(client) => {
+            log::info(&format!("control mode: attached to session '{}'", session));
+            let Ok(mut slot) = CLIENT.lock() else {
+                client.close();
+                return not_connected;
+            };
+            *slot = Some(client);
+            // The client counts as attached to the session
+            cache::clear();
+            ControlGuard { connected: true }
+        }
+        Err(e) => {
+            log::info(&format!(
+                "control mode: not available ({}), using processes",
+                e
+            ));
+            not_connected
+        }
+    }
+}
+
+/// Run a command through the connected control client.
+///
+/// # Returns
+/// The command's output, or None if there is no client, the command can't
+/// be sent through one, or the connection was lost before tmux started
+/// running it (the caller then runs it as a process).
+pub(super) fn execute(args: &[&str]) -> Option<Output> {
+    if args
+        .first()
+        .is_none_or(|command| PROCESS_ONLY.contains(command))
+    {
+        return None;
+    }
+    // A command line ends at a newline, so such arguments can't be sent
+    if args.iter().any(|arg| arg.contains('\n')) {
+        return None;
+    }
+
+    let mut slot = CLIENT.lock().ok()?;
+    let client = slot.as_mut()?;
+    match client.execute(args) {
+        Ok(output) => Some(output),
+        Err(lost) => {
+            log::info(&format!(
+                "control mode: connection lost ({}), using processes",
+                lost.reason
+            ));
+            if let Some(client) = slot.take() {
+                client.close();
+            }
+            // A command tmux already started isn't run a second time
+            lost.started.then(|| {
+                Block {
+                    success: false,
+                    lines: vec![format!("control client {}", lost.reason)],
+                }
+                .into_output()
+            })
+        }
+    }
+}
+
+/// The control client went away before a command's output was complete
+#[derive(Debug)]
+struct Lost {
+    reason: String,
+    /// Whether tmux had started running the command
+    started: bool,
+}
+
+/// A running `tmux -C attach-session` client
+struct ControlClient {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ControlClient {
+    /// Start a control client attached to a session and wait until tmux accepts it
+    fn attach(session: &str) -> Result<Self, String> {
+        let target = Target::Session(session).to_string();
+        let mut child = tmux_command()
+            .args([
+                "-C",
+                "attach-session",
+                "-f",
+                "ignore-size,no-output",
+                "-t",
+                &target,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err("no pipes to the client".to_string());
+        };
+        let mut client = Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        };
+
+        // tmux answers the attach itself with the first block
+        match client.read_block(None) {
+            Ok(Block { success: true, .. }) => Ok(client),
+            Ok(Block { lines, .. }) => {
+                client.close();
+                Err(lines.join(" "))
+            }
+            Err(lost) => {
+                client.close();
+                Err(lost.reason)
+            }
+        }
+    }
+
+    /// Send one command and read its result
+    fn execute(&mut self, args: &[&str]) -> Result<Output, Lost> {
+        writeln!(self.stdin, "{}", command_line(args))
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| Lost {
+                reason: e.to_string(),
+                started: false,
+            })?;
+        self.read_block(Some("1")).map(Block::into_output)
+    }
+
+    /// Read lines until the next output block ends, skipping notifications.
+    ///
+    /// # Arguments
+    /// * `flags` - Only accept blocks with these flags (`1` marks commands
+    ///   sent by this client); None accepts any block
+    fn read_block(&mut self, flags: Option<&str>) -> Result<Block, Lost> {
+        let lost = |reason: &str| Lost {
+            reason: reason.to_string(),
+            started: false,
+        };
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.stdout.read_line(&mut line) {
+                Ok(0) => return Err(lost("exited")),
+                Ok(_) => {}
+                Err(e) => return Err(lost(&e.to_string())),
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.starts_with("%exit") {
+                return Err(lost("was detached"));
+            }
+            let Some(guard) = line.strip_prefix("%begin ") else {
+                continue;
+            };
+            if flags.is_some_and(|flags| guard.split(' ').nth(2) != Some(flags)) {
+                continue;
+            }
+            return self.read_block_body(guard);
+        }
+    }
+
+    /// Read the lines of a block up to its `%end` or `%error` guard line
+    fn read_block_body(&mut self, guard: &str) -> Result<Block, Lost> {
+        let end = format!("%end {}", guard);
+        let error = format!("%error {}", guard);
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if !matches!(self.stdout.read_line(&mut line), Ok(read) if read > 0) {
+                return Err(Lost {
+                    reason: "exited during the command".to_string(),
+                    started: true,
+                });
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line == end || line == error {
+                return Ok(Block {
+                    success: line == end,
+                    lines,
+                });
+            }
+            lines.push(line.to_string());
+        }
+    }
+
+    /// Detach the client and wait for it to exit
+    fn close(mut self) {
+        // tmux detaches a control client whose input ends
+        drop(self.stdin);
+        let _ = self.child.wait();
+    }
+}
+
+/// The output of one command
+struct Block {
+    success: bool,
+    lines: Vec<String>,
+}
+
+impl Block {
+    /// The output as if the command had run as a process: output on stdout,
+    /// or the error on stderr with exit status 1
+    fn into_output(self) -> Output {
+        let mut text = self.lines.join("\n");
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        let (stdout, stderr) = if self.success {
+            (text, String::new())
+        } else {
+            (String::new(), text)
+        };
+        Output {
+            status: exit_status(if self.success { 0 } else { 1 }),
+            stdout: stdout.into_bytes(),
+            stderr: stderr.into_bytes(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+/// Render arguments as a line for tmux's command parser.
+///
+/// Arguments are single-quoted, so `;`, `#` and `$` reach the command as
+/// typed; single quotes are closed, double-quoted and reopened.
+fn command_line(args: &[&str]) -> String {
+    let mut line = String::new();
+    for (position, arg) in args.iter().enumerate() {
+        if position > 0 {
+            line.push(' ');
+        }
+        if position == 0
+            && !arg.is_empty()
+            && arg.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            line.push_str(arg);
+        } else {
+            line.push('\'');
+            line.push_str(&arg.replace('\'', "'\"'\"'"));
+            line.push('\'');
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(
+            command_line(&["send-keys", "-t", "dev:1.0", "-l", "echo 'hi'; ls #x $HOME"]),
+            r#"send-keys '-t' 'dev:1.0' '-l' 'echo '"'"'hi'"'"'; ls #x $HOME'"#
+        );
+        assert_eq!(
+            command_line(&["set-option", "-g", "@empty", ""]),
+            "set-option '-g' '@empty' ''"
+        );
+    }
+
+    #[test]
+    fn test_block_output() {
+        let ok = Block {
+            success: true,
+            lines: vec!["0 100".to_string(), "1 99".to_string()],
+        }
+        .into_output();
+        assert!(ok.status.success());
+        assert_eq!(ok.stdout, b"0 100\n1 99\n");
+
+        let failed = Block {
+            success: false,
+            lines: vec!["can't find pane: 7".to_string()],
+        }
+        .into_output();
+        assert_eq!(failed.status.code(), Some(1));
+        assert_eq!(failed.stderr, b"can't find pane: 7\n");
+    }
+}
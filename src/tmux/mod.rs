@@ -0,0 +1,1167 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::log;
+
+mod cache;
+mod command;
+mod control;
+pub mod query;
+mod wsl;
+
+pub use command::{quote_argument, render_command, Target, TmuxCommand};
+pub use control::ControlGuard;
+
+/// Server socket name (`-L`) used instead of the default server
+static SOCKET_NAME: RwLock<Option<String>> = RwLock::new(None);
+
+/// Commands executed since recording started (None when not recording)
+#[cfg(feature = "test-support")]
+static RECORDING: std::sync::Mutex<Option<Vec<String>>> = std::sync::Mutex::new(None);
+
+/// Number of times a command is retried after a transient failure
+static RETRIES: AtomicU32 = AtomicU32::new(3);
+
+/// Delay before the first retry in milliseconds (doubled on each retry)
+static RETRY_BACKOFF_MS: AtomicU64 = AtomicU64::new(50);
+
+/// stderr fragments of tmux errors that are worth retrying, e.g. while the
+/// server is still starting up or was restarted underneath us. Not having a
+/// server at all isn't one of them (see `NO_SERVER_ERRORS`).
+const TRANSIENT_ERRORS: &[&str] = &[
+    "lost server",
+    "server exited unexpectedly",
+    "Resource temporarily unavailable",
+];
+
+/// tmux errors meaning that no server is listening on the socket
+const NO_SERVER_ERRORS: &[&str] = &["no server running", "error connecting to"];
+
+/// Configure the retry policy for transient tmux failures.
+///
+/// # Arguments
+/// * `retries` - Number of retries after the first attempt (0 disables retrying)
+/// * `backoff_ms` - Delay before the first retry, doubled for each subsequent retry
+pub fn set_retry_policy(retries: u32, backoff_ms: u64) {
+    RETRIES.store(retries, Ordering::SeqCst);
+    RETRY_BACKOFF_MS.store(backoff_ms, Ordering::SeqCst);
+}
+
+/// Run tmux inside WSL (through `wsl.exe`) when enabled and no native tmux exists.
+///
+/// Start directories are then translated from Windows paths to WSL paths.
+pub fn set_wsl(enabled: bool) {
+    wsl::configure(enabled);
+}
+
+/// Let session creation send its commands through one control-mode client
+/// (`tmux -C`) instead of a tmux process per command
+pub fn set_control_mode(enabled: bool) {
+    control::set_enabled(enabled);
+}
+
+/// Send the following tmux commands through a control-mode client attached to
+/// `session`, until the returned guard is dropped.
+///
+/// Falls back to a tmux process per command when control mode is disabled
+/// or unavailable (tmux before 3.2).
+pub fn control_client(session: &str) -> ControlGuard {
+    control::connect(session)
+}
+
+/// Use a separate tmux server (`tmux -L <name>`) for all following commands,
+/// e.g. to benchmark or test without touching the user's sessions
+pub fn set_socket_name(name: Option<&str>) {
+    if let Ok(mut socket) = SOCKET_NAME.write() {
+        *socket = name.map(str::to_string);
+    }
+    cache::clear();
+}
+
+/// Forget the answers of earlier queries, before looking at state that may
+/// have changed without tmx doing anything (e.g. in a polling loop)
+pub fn clear_query_cache() {
+    cache::clear();
+}
+
+/// Build the command that runs tmux, on the selected server socket
+fn tmux_command() -> std::process::Command {
+    let mut command = wsl::tmux_command();
+    if let Ok(socket) = SOCKET_NAME.read()
+        && let Some(ref name) = *socket
+    {
+        command.args(["-L", name]);
+    }
+    command
+}
+
+/// Start recording every executed tmux command (see `take_recording`)
+#[cfg(feature = "test-support")]
+pub fn start_recording() {
+    if let Ok(mut recording) = RECORDING.lock() {
+        *recording = Some(Vec::new());
+    }
+}
+
+/// Stop recording and return the executed commands, rendered as command lines
+#[cfg(feature = "test-support")]
+pub fn take_recording() -> Vec<String> {
+    RECORDING
+        .lock()
+        .ok()
+        .and_then(|mut recording| recording.take())
+        .unwrap_or_default()
+}
+
+/// Add a command to the recording, if one is active
+#[cfg(feature = "test-support")]
+fn record(args: &[&str]) {
+    if let Ok(mut recording) = RECORDING.lock()
+        && let Some(commands) = recording.as_mut()
+    {
+        commands.push(render_command(args));
+    }
+}
+
+/// Commands are only recorded for tests
+#[cfg(not(feature = "test-support"))]
+fn record(_args: &[&str]) {}
+
+/// Translate a start directory (`-c`) for the tmux that is being run
+fn start_directory(root: Option<&Path>) -> Option<String> {
+    root.map(|root| wsl::translate_path(&root.to_string_lossy()))
+}
+
+/// Check whether a tmux error message indicates a transient failure
+fn is_transient_error(stderr: &str) -> bool {
+    TRANSIENT_ERRORS.iter().any(|e| stderr.contains(e))
+}
+
+/// Sanitize a session name to be compatible with tmux.
+///
+/// Tmux replaces certain special characters (like dots and colons) with underscores
+/// because they're used as separators in target notation (session:window.pane).
+/// This function replicates that behavior to ensure consistency.
+///
+/// # Arguments
+/// * `name` - The original session name
+///
+/// # Returns
+/// The sanitized session name with special characters replaced by underscores
+pub fn sanitize_session_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            // Tmux uses these as separators, so they must be replaced
+            '.' | ':' => '_',
+            // Also replace other potentially problematic characters
+            ' ' | '\t' | '\n' => '_',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Check if tmux is currently installed and available in PATH.
+///
+/// # Returns
+/// `true` if tmux is installed, `false` otherwise.
+pub fn is_installed() -> bool {
+    run_tmux(&["-V"])
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Get the version reported by tmux (e.g. `tmux 3.3a`), or None if it can't be run
+pub fn version() -> Option<String> {
+    run_tmux(&["-V"])
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Get the effective base-index of a session.
+///
+/// Reads the option through the session, so session-local overrides (e.g. set
+/// by a `session-created` hook) are taken into account, not just the global value.
+///
+/// # Arguments
+/// * `session` - The session name
+///
+/// # Returns
+/// The index of the session's first window slot (typically 0 or 1).
+pub fn session_base_index(session: &str) -> Result<usize> {
+    TmuxCommand::new("display-message")
+        .target(Target::Session(session))
+        .flag("-p")
+        .arg("#{base-index}")
+        .cached()
+        .query()
+        .context("Failed to get tmux base-index")
+}
+
+/// Check if a tmux session with the given name exists.
+///
+/// # Arguments
+/// * `name` - The session name to check
+///
+/// # Returns
+/// `Ok(true)` if the session exists, `Ok(false)` if it doesn't, or an error.
+pub fn has_session(name: &str) -> Result<bool> {
+    TmuxCommand::new("has-session")
+        .target(Target::Session(name))
+        .cached()
+        .succeeds()
+        .context("Failed to check session existence")
+}
+
+/// List all currently running tmux sessions.
+///
+/// # Returns
+/// A vector of session names, or an empty vector if no sessions are running
+/// (including when no tmux server is running; see `server_sessions`).
+pub fn list_sessions() -> Result<Vec<String>> {
+    Ok(server_sessions()?.unwrap_or_default())
+}
+
+/// List the sessions of the tmux server, distinguishing a server without
+/// sessions from no server at all.
+///
+/// # Returns
+/// The session names, or None if no tmux server is running.
+///
+/// # Errors
+/// Returns an error if tmux fails for any other reason.
+pub fn server_sessions() -> Result<Option<Vec<String>>> {
+    let stdout = query_server(&["list-sessions", "-F", "#{session_name}"])
+        .context("Failed to list tmux sessions")?;
+    Ok(stdout.map(|s| s.lines().map(|l| l.to_string()).collect()))
+}
+
+/// Find the most recently attached session other than `except`.
+///
+/// # Returns
+/// The session name, or None if no other session is running.
+pub fn last_attached_session(except: &str) -> Result<Option<String>> {
+    let stdout = query_server(&[
+        "list-sessions",
+        "-F",
+        "#{session_last_attached}|:|#{session_name}",
+    ])
+    .context("Failed to list tmux sessions")?
+    .unwrap_or_default();
+    let except = sanitize_session_name(except);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_once("|:|"))
+        .filter(|(_, name)| *name != except)
+        .max_by_key(|(last, _)| last.parse::<u64>().unwrap_or_default())
+        .map(|(_, name)| name.to_string()))
+}
+
+/// Run a query against the tmux server (answers are cached, see `cache`).
+///
+/// # Returns
+/// The command's stdout, or None if no tmux server is running.
+fn query_server(args: &[&str]) -> Result<Option<String>> {
+    let output = run_tmux_cached(args).context("Failed to execute tmux command")?;
+    if output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Ok(Some(stdout.trim_end_matches('\n').to_string()));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if is_no_server_error(&stderr) {
+        return Ok(None);
+    }
+    log::error(&format!("tmux {} -> FAILED: {}", args.join(" "), stderr.trim()));
+    anyhow::bail!("tmux command failed: {}", stderr.trim())
+}
+
+/// Check whether a tmux error means that no server is listening on the socket
+fn is_no_server_error(stderr: &str) -> bool {
+    NO_SERVER_ERRORS.iter().any(|e| stderr.contains(e))
+}
+
+/// The pane tmx is running in, as seen by tmux
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentPane {
+    pub session: String,
+    pub window_index: usize,
+    pub pane_index: usize,
+    /// Unique pane ID (e.g. `%3`)
+    pub id: String,
+}
+
+/// Display-message target for the pane tmx is running in.
+///
+/// `$TMUX_PANE` names the pane itself; without it, tmux falls back to the
+/// most recently active client, which may be looking at another session.
+fn current_pane_command() -> TmuxCommand {
+    let command = TmuxCommand::new("display-message").flag("-p");
+    match std::env::var("TMUX_PANE") {
+        Ok(id) if !id.is_empty() => command.target(Target::PaneId(&id)),
+        _ => command,
+    }
+}
+
+/// Get the current tmux session name (only works when inside tmux).
+///
+/// # Returns
+/// The current session name, or an error if not inside tmux or command fails.
+pub fn get_current_session() -> Result<String> {
+    let session = current_pane_command().arg("#{session_name}").output()?;
+    Ok(session.trim().to_string())
+}
+
+/// Get the session, window and pane tmx is running in (only works when inside tmux).
+///
+/// # Returns
+/// The current pane, or an error if not inside tmux or command fails.
+pub fn get_current_pane() -> Result<CurrentPane> {
+    let output = current_pane_command()
+        .arg("#{session_name}|:|#{window_index}|:|#{pane_index}|:|#{pane_id}")
+        .output()?;
+    let fields: Vec<&str> = output.trim_end().split("|:|").collect();
+    let [session, window_index, pane_index, id] = fields[..] else {
+        anyhow::bail!("Unexpected output of display-message: {}", output);
+    };
+    Ok(CurrentPane {
+        session: session.to_string(),
+        window_index: window_index
+            .parse()
+            .with_context(|| format!("Invalid window index: {}", window_index))?,
+        pane_index: pane_index
+            .parse()
+            .with_context(|| format!("Invalid pane index: {}", pane_index))?,
+        id: id.to_string(),
+    })
+}
+
+/// Get the creation time of a session.
+///
+/// # Arguments
+/// * `name` - The session name
+///
+/// # Returns
+/// The tmux `session_created` timestamp in seconds since the Unix epoch.
+pub fn session_created(name: &str) -> Result<u64> {
+    TmuxCommand::new("display-message")
+        .target(Target::SessionWindow(name))
+        .flag("-p")
+        .arg("#{session_created}")
+        .query()
+}
+
+/// Get the active window name and pane offset of a session.
+///
+/// # Arguments
+/// * `session` - The session name
+///
+/// # Returns
+/// A tuple of (window name, pane offset), the offset being the pane's
+/// position in the window (its index without the window's `pane-base-index`).
+pub fn get_active_pane(session: &str) -> Result<(String, usize)> {
+    let stdout = TmuxCommand::new("display-message")
+        .target(Target::SessionWindow(session))
+        .flag("-p")
+        .arg("#{pane_index} #{pane-base-index} #{window_name}")
+        .output()?;
+
+    // Indices first: window names may contain spaces
+    let mut parts = stdout.splitn(3, ' ');
+    let (Some(pane), Some(base), Some(window)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("Failed to parse active pane: {}", stdout);
+    };
+
+    let pane_index = pane.parse::<usize>().context("Failed to parse pane index")?;
+    let base = base.parse::<usize>().context("Failed to parse pane-base-index")?;
+    Ok((window.to_string(), pane_index.saturating_sub(base)))
+}
+
+/// Get the index of a window's first pane (its `pane-base-index`)
+pub fn pane_base_index(session: &str, window_index: usize) -> Result<usize> {
+    TmuxCommand::new("display-message")
+        .target(Target::Window(session, window_index))
+        .flag("-p")
+        .arg("#{pane-base-index}")
+        .query()
+        .context("Failed to get tmux pane-base-index")
+}
+
+/// Get window dimensions (width and height in cells/lines)
+///
+/// # Arguments
+/// * `session` - The session name
+/// * `window_index` - The window index
+///
+/// # Returns
+/// A tuple of (width, height) in cells/lines
+pub fn get_window_dimensions(session: &str, window_index: usize) -> Result<(usize, usize)> {
+    let stdout = TmuxCommand::new("display-message")
+        .target(Target::Window(session, window_index))
+        .flag("-p")
+        .arg("#{window_width} #{window_height}")
+        .output()?;
+
+    let parts: Vec<&str> = stdout.split_whitespace().collect();
+
+    if parts.len() != 2 {
+        anyhow::bail!("Failed to parse window dimensions: {}", stdout);
+    }
+
+    let width = parts[0].parse::<usize>()
+        .context("Failed to parse window width")?;
+    let height = parts[1].parse::<usize>()
+        .context("Failed to parse window height")?;
+
+    Ok((width, height))
+}
+
+/// How a pane's process is started: its environment, and a command run
+/// instead of the default shell
+pub struct PaneProcess<'a> {
+    /// Shell command to run (None: the default shell)
+    pub command: Option<&'a str>,
+    /// Environment variables set for the process (`-e KEY=value`)
+    pub env: &'a [(String, String)],
+}
+
+/// Append the environment and the shell-command argument of a pane process
+fn with_process(cmd: TmuxCommand, process: Option<&PaneProcess>) -> TmuxCommand {
+    let Some(process) = process else {
+        return cmd;
+    };
+
+    let cmd = process
+        .env
+        .iter()
+        .fold(cmd, |cmd, (key, value)| cmd.option("-e", format!("{}={}", key, value)));
+    match process.command {
+        Some(command) => cmd.arg(command),
+        None => cmd,
+    }
+}
+
+/// Create a new tmux session
+///
+/// # Arguments
+/// * `env` - Variables for the session environment, inherited by every pane
+///   (including the first, whose shell starts with them)
+/// * `process` - Environment of the first pane, and a command to run in it
+///   instead of the default shell
+///
+/// # Returns
+/// The index tmux gave the session's first window.
+pub fn new_session(
+    name: &str,
+    window_name: &str,
+    root: Option<&Path>,
+    env: &[(String, String)],
+    process: Option<&PaneProcess>,
+) -> Result<usize> {
+    let cmd = TmuxCommand::new("new-session")
+        .flag("-d")
+        .flag("-P")
+        .option("-F", "#{window_index}")
+        .option("-s", sanitize_session_name(name))
+        .option("-n", window_name)
+        .option_if_some("-c", start_directory(root).as_deref());
+    let cmd = env
+        .iter()
+        .fold(cmd, |cmd, (key, value)| cmd.option("-e", format!("{}={}", key, value)));
+    with_process(cmd, process).query()
+}
+
+/// Create a session that shares the windows of a running session (`new-session -t`).
+///
+/// The sessions form a session group: windows created or closed in one
+/// appear in the other, but each has its own current window.
+///
+/// # Arguments
+/// * `group` - The running session whose group the new session joins
+/// * `root` - Working directory of windows created from the new session
+/// * `env` - Variables for the session environment
+pub fn new_grouped_session(
+    name: &str,
+    group: &str,
+    root: Option<&Path>,
+    env: &[(String, String)],
+) -> Result<()> {
+    let cmd = TmuxCommand::new("new-session")
+        .flag("-d")
+        .option("-s", sanitize_session_name(name))
+        .target(Target::Session(group))
+        .option_if_some("-c", start_directory(root).as_deref());
+    env.iter()
+        .fold(cmd, |cmd, (key, value)| {
+            cmd.option("-e", format!("{}={}", key, value))
+        })
+        .run()
+}
+
+/// Set a variable in a session's environment (`set-environment`)
+pub fn set_session_environment(session: &str, key: &str, value: &str) -> Result<()> {
+    TmuxCommand::new("set-environment")
+        .target(Target::Session(session))
+        .arg(key)
+        .arg(value)
+        .run()
+}
+
+/// Remove a variable from a session's environment (`set-environment -u`)
+pub fn unset_session_environment(session: &str, key: &str) -> Result<()> {
+    TmuxCommand::new("set-environment")
+        .flag("-u")
+        .target(Target::Session(session))
+        .arg(key)
+        .run()
+}
+
+/// Create a new window in a session
+///
+/// # Arguments
+/// * `process` - Environment of the first pane, and a command to run in it
+///   instead of the default shell
+///
+/// # Returns
+/// The index tmux gave the new window.
+pub fn new_window(
+    session: &str,
+    window_name: &str,
+    root: Option<&Path>,
+    process: Option<&PaneProcess>,
+) -> Result<usize> {
+    let cmd = TmuxCommand::new("new-window")
+        .target(Target::SessionWindow(session))
+        .flag("-P")
+        .option("-F", "#{window_index}")
+        .option("-n", window_name)
+        .option_if_some("-c", start_directory(root).as_deref());
+    with_process(cmd, process).query()
+}
+
+/// Create a new window at a specific index in a session
+pub fn new_window_at(
+    session: &str,
+    window_index: usize,
+    window_name: &str,
+    root: Option<&Path>,
+    process: Option<&PaneProcess>,
+) -> Result<()> {
+    let cmd = TmuxCommand::new("new-window")
+        .target(Target::Window(session, window_index))
+        .option("-n", window_name)
+        .option_if_some("-c", start_directory(root).as_deref());
+    with_process(cmd, process).run()
+}
+
+/// Move a window to another index in the same session
+pub fn move_window(session: &str, from_index: usize, to_index: usize) -> Result<()> {
+    TmuxCommand::new("move-window")
+        .option("-s", Target::Window(session, from_index).to_string())
+        .target(Target::Window(session, to_index))
+        .run()
+}
+
+/// Rename a window
+pub fn rename_window(session: &str, window_index: usize, name: &str) -> Result<()> {
+    TmuxCommand::new("rename-window")
+        .target(Target::Window(session, window_index))
+        .arg(name)
+        .run()
+}
+
+/// Kill a window
+pub fn kill_window(session: &str, window_index: usize) -> Result<()> {
+    TmuxCommand::new("kill-window")
+        .target(Target::Window(session, window_index))
+        .run()
+}
+
+/// Send C-c to a pane (by its tmux pane ID)
+pub fn send_interrupt(pane_id: &str) -> Result<()> {
+    TmuxCommand::new("send-keys")
+        .target(Target::PaneId(pane_id))
+        .arg("C-c")
+        .run()
+}
+
+/// Check whether a pane (by its tmux pane ID) still exists and its process is running
+pub fn pane_alive(pane_id: &str) -> Result<bool> {
+    // display-message falls back to another pane when the target is gone,
+    // so compare the ID it reports
+    let state = TmuxCommand::new("display-message")
+        .target(Target::PaneId(pane_id))
+        .flag("-p")
+        .arg("#{pane_id} #{pane_dead}")
+        .output_if_success()?;
+    Ok(state.is_some_and(|s| s.trim() == format!("{} 0", pane_id)))
+}
+
+/// Kill a pane by its tmux pane ID (e.g. `%7`)
+pub fn kill_pane(pane_id: &str) -> Result<()> {
+    TmuxCommand::new("kill-pane")
+        .target(Target::PaneId(pane_id))
+        .run()
+}
+
+/// Capture the last lines of a pane's history and screen (by its tmux pane ID).
+///
+/// Wrapped lines are joined, so a long line copies as one line.
+pub fn capture_pane(pane_id: &str, lines: usize) -> Result<String> {
+    TmuxCommand::new("capture-pane")
+        .target(Target::PaneId(pane_id))
+        .flag("-p")
+        .flag("-J")
+        .option("-S", format!("-{}", lines))
+        .output()
+}
+
+/// Put text in a new paste buffer, on top of the buffer stack.
+///
+/// With `clipboard`, tmux also sends the text to the clipboard of the
+/// current client's terminal (OSC 52, tmux 3.2+ with `set-clipboard` on).
+pub fn set_buffer(text: &str, clipboard: bool) -> Result<()> {
+    TmuxCommand::new("set-buffer")
+        .flag_if("-w", clipboard)
+        .arg("--")
+        .arg(text)
+        .run()
+}
+
+/// Check whether the server has any paste buffers
+pub fn has_buffers() -> Result<bool> {
+    let buffers = TmuxCommand::new("list-buffers")
+        .option("-F", "#{buffer_name}")
+        .output()?;
+    Ok(!buffers.trim().is_empty())
+}
+
+/// Paste the most recent paste buffer into a pane (by its tmux pane ID).
+///
+/// Bracketed paste is used if the pane's program asked for it, so shells
+/// and editors don't run or indent the text line by line.
+pub fn paste_buffer(pane_id: &str) -> Result<()> {
+    TmuxCommand::new("paste-buffer")
+        .target(Target::PaneId(pane_id))
+        .flag("-p")
+        .run()
+}
+
+/// Split a window with specific size
+pub fn split_window_with_size(
+    session: &str,
+    window_index: usize,
+    horizontal: bool,
+    size: Option<&str>,
+    root: Option<&Path>,
+    process: Option<&PaneProcess>,
+    verbose: bool,
+) -> Result<()> {
+    let split_flag = if horizontal { "-h" } else { "-v" };
+    let mut cmd = TmuxCommand::new("split-window")
+        .target(Target::Window(session, window_index))
+        .flag(split_flag);
+
+    // Add size parameter if specified
+    if let Some(size_spec) = size {
+        cmd = match size_spec.strip_suffix('%') {
+            // Percentage size: use -p flag
+            Some(percentage) => cmd.option("-p", percentage),
+            // Absolute size: use -l flag
+            None => cmd.option("-l", size_spec),
+        };
+    }
+
+    let cmd = with_process(cmd.option_if_some("-c", start_directory(root).as_deref()), process);
+
+    // Debug: print command being executed
+    if verbose {
+        eprintln!("{}", cmd);
+    }
+
+    cmd.run()
+}
+
+/// Apply a layout to a window
+pub fn select_layout(
+    session: &str,
+    window_index: usize,
+    layout: &str,
+    verbose: bool,
+) -> Result<()> {
+    let cmd = TmuxCommand::new("select-layout")
+        .target(Target::Window(session, window_index))
+        .arg(layout);
+
+    // Debug: print layout command
+    if verbose {
+        eprintln!("{}", cmd);
+    }
+
+    cmd.run()
+}
+
+/// Resize a specific pane to an absolute size
+///
+/// # Arguments
+/// * `session` - The session name
+/// * `window_index` - The window index
+/// * `pane_index` - The pane index
+/// * `size` - Absolute size in cells/lines (already calculated from percentage if needed)
+/// * `is_horizontal` - True for horizontal split (resize width), false for vertical (resize height)
+/// * `verbose` - Whether to print debug info
+pub fn resize_pane(
+    session: &str,
+    window_index: usize,
+    pane_index: usize,
+    size: usize,
+    is_horizontal: bool,
+    verbose: bool,
+) -> Result<()> {
+    // For horizontal splits, we resize width (-x)
+    // For vertical splits, we resize height (-y)
+    let dimension_flag = if is_horizontal { "-x" } else { "-y" };
+
+    let cmd = TmuxCommand::new("resize-pane")
+        .target(Target::Pane(session, window_index, pane_index))
+        .option(dimension_flag, size.to_string());
+
+    if verbose {
+        eprintln!("{}", cmd);
+    }
+
+    cmd.run()
+}
+
+/// Send keys (commands) to a specific pane
+pub fn send_keys(session: &str, window_index: usize, pane_index: usize, keys: &str) -> Result<()> {
+    TmuxCommand::new("send-keys")
+        .target(Target::Pane(session, window_index, pane_index))
+        .arg(keys)
+        .arg("C-m")
+        .run()
+}
+
+/// Send text to a pane with control over how it is typed and submitted.
+///
+/// # Arguments
+/// * `literal` - Send the text as literal characters (`-l`), so words like `Enter` aren't key names
+/// * `enter` - Press Enter after the text
+pub fn send_text(
+    session: &str,
+    window_index: usize,
+    pane_index: usize,
+    text: &str,
+    literal: bool,
+    enter: bool,
+) -> Result<()> {
+    let target = Target::Pane(session, window_index, pane_index);
+
+    if !literal && enter {
+        return send_keys(session, window_index, pane_index, text);
+    }
+
+    TmuxCommand::new("send-keys")
+        .target(target)
+        .flag_if("-l", literal)
+        .arg(text)
+        .run()?;
+
+    // With -l, C-m would be typed as text, so Enter is a separate command
+    if enter {
+        TmuxCommand::new("send-keys").target(target).arg("C-m").run()?;
+    }
+
+    Ok(())
+}
+
+/// Set a session-level option (e.g. `detach-on-destroy`)
+pub fn set_session_option(session: &str, option: &str, value: &str) -> Result<()> {
+    TmuxCommand::new("set-option")
+        .target(Target::Session(session))
+        .arg(option)
+        .arg(value)
+        .run()
+}
+
+/// Set a window-level option (e.g. `window-size`)
+pub fn set_window_option(session: &str, window_index: usize, option: &str, value: &str) -> Result<()> {
+    TmuxCommand::new("set-option")
+        .flag("-w")
+        .target(Target::Window(session, window_index))
+        .arg(option)
+        .arg(value)
+        .run()
+}
+
+/// Set a pane-level option (e.g. `remain-on-exit`)
+pub fn set_pane_option(
+    session: &str,
+    window_index: usize,
+    pane_index: usize,
+    option: &str,
+    value: &str,
+) -> Result<()> {
+    TmuxCommand::new("set-option")
+        .flag("-p")
+        .target(Target::Pane(session, window_index, pane_index))
+        .arg(option)
+        .arg(value)
+        .run()
+}
+
+/// Add a tmux command string to a session-level hook (e.g. `client-attached`)
+pub fn set_session_hook(session: &str, hook: &str, command: &str) -> Result<()> {
+    TmuxCommand::new("set-hook")
+        .flag("-a")
+        .target(Target::Session(session))
+        .arg(hook)
+        .arg(command)
+        .run()
+}
+
+/// Add a tmux command string to a global hook (e.g. `session-closed`),
+/// unless a command containing `marker` is already installed.
+///
+/// # Returns
+/// Whether the command was added.
+pub fn ensure_global_hook(hook: &str, marker: &str, command: &str) -> Result<bool> {
+    let installed = TmuxCommand::new("show-hooks").flag("-g").arg(hook).output()?;
+    if installed.contains(marker) {
+        return Ok(false);
+    }
+    TmuxCommand::new("set-hook")
+        .flag("-g")
+        .flag("-a")
+        .arg(hook)
+        .arg(command)
+        .run()?;
+    Ok(true)
+}
+
+/// Add a tmux command string to a pane-level hook (e.g. `pane-died`)
+pub fn set_pane_hook(
+    session: &str,
+    window_index: usize,
+    pane_index: usize,
+    hook: &str,
+    command: &str,
+) -> Result<()> {
+    TmuxCommand::new("set-hook")
+        .flag("-p")
+        .flag("-a")
+        .target(Target::Pane(session, window_index, pane_index))
+        .arg(hook)
+        .arg(command)
+        .run()
+}
+
+/// Wait for a `tmux wait-for -S <channel>` signal.
+///
+/// A signal sent before waiting starts is not lost: tmux remembers it and the
+/// wait returns immediately.
+///
+/// # Returns
+/// `true` if the channel was signalled, `false` if the timeout expired first.
+pub fn wait_for(channel: &str, timeout: Duration) -> Result<bool> {
+    let args = ["wait-for", channel];
+    let started = Instant::now();
+    cache::invalidate_for(&args);
+    record(&args);
+    let mut child = tmux_command()
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to execute tmux command")?;
+
+    loop {
+        if let Some(status) = child.try_wait().context("Failed to wait for tmux")? {
+            log::trace_command(&args, started.elapsed(), status.code(), "");
+            if !status.success() {
+                anyhow::bail!("tmux wait-for {} failed with status: {}", channel, status);
+            }
+            return Ok(true);
+        }
+
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            log::trace_command(&args, started.elapsed(), None, "timed out");
+            return Ok(false);
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Select a window
+pub fn select_window(session: &str, window_index: usize) -> Result<()> {
+    TmuxCommand::new("select-window")
+        .target(Target::Window(session, window_index))
+        .run()
+}
+
+/// Select a pane
+pub fn select_pane(session: &str, window_index: usize, pane_index: usize) -> Result<()> {
+    TmuxCommand::new("select-pane")
+        .target(Target::Pane(session, window_index, pane_index))
+        .run()
+}
+
+/// Set a pane's title (`select-pane -T`, tmux 2.6+), without making it the active pane
+pub fn set_pane_title(
+    session: &str,
+    window_index: usize,
+    pane_index: usize,
+    title: &str,
+) -> Result<()> {
+    TmuxCommand::new("select-pane")
+        .target(Target::Pane(session, window_index, pane_index))
+        .flag("-T")
+        .arg(title)
+        .run()
+}
+
+/// Attach to a session
+pub fn attach_session(name: &str) -> Result<()> {
+    TmuxCommand::new("attach-session")
+        .target(Target::Session(name))
+        .run_interactive()
+}
+
+/// Get the socket path of the tmux server running a session
+pub fn server_socket_path(name: &str) -> Result<String> {
+    let path = TmuxCommand::new("display-message")
+        .target(Target::Session(name))
+        .flag("-p")
+        .arg("#{socket_path}")
+        .output()?;
+    Ok(path.trim().to_string())
+}
+
+/// Build the command line (program and arguments) that attaches a terminal
+/// to a session, addressing its server by socket path so it works from any
+/// environment.
+pub fn attach_argv(name: &str) -> Result<Vec<String>> {
+    let mut argv: Vec<String> = if wsl::is_enabled() {
+        vec!["wsl.exe".into(), "--exec".into(), "tmux".into()]
+    } else {
+        vec!["tmux".into()]
+    };
+    argv.extend([
+        "-S".to_string(),
+        server_socket_path(name)?,
+        "attach-session".to_string(),
+        "-t".to_string(),
+        Target::Session(name).to_string(),
+    ]);
+    Ok(argv)
+}
+
+/// Switch to a session (when already inside tmux)
+pub fn switch_client(name: &str) -> Result<()> {
+    TmuxCommand::new("switch-client")
+        .target(Target::Session(name))
+        .run()
+}
+
+/// List the clients attached to a session (by client name, e.g. `/dev/pts/3`)
+pub fn session_clients(name: &str) -> Result<Vec<String>> {
+    let stdout = TmuxCommand::new("list-clients")
+        .target(Target::Session(name))
+        .option("-F", "#{client_name}")
+        .output()?;
+    Ok(stdout.lines().map(|l| l.to_string()).collect())
+}
+
+/// The client attached to a session that was used most recently, e.g. the
+/// one that opened a popup tmx runs in
+pub fn last_active_client(name: &str) -> Result<Option<String>> {
+    let stdout = TmuxCommand::new("list-clients")
+        .target(Target::Session(name))
+        .option("-F", "#{client_activity} #{client_name}")
+        .output()?;
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .max_by_key(|(activity, _)| activity.parse::<u64>().unwrap_or(0))
+        .map(|(_, client)| client.to_string()))
+}
+
+/// Switch a specific client to a session
+pub fn switch_client_of(client: &str, name: &str) -> Result<()> {
+    TmuxCommand::new("switch-client")
+        .option("-c", client)
+        .target(Target::Session(name))
+        .run()
+}
+
+/// Rename a session
+pub fn rename_session(name: &str, new_name: &str) -> Result<()> {
+    TmuxCommand::new("rename-session")
+        .target(Target::Session(name))
+        .arg(sanitize_session_name(new_name))
+        .run()
+}
+
+/// Kill a session
+pub fn kill_session(name: &str) -> Result<()> {
+    TmuxCommand::new("kill-session")
+        .target(Target::Session(name))
+        .run()
+}
+
+/// Kill the tmux server and all its sessions
+pub fn kill_server() -> Result<()> {
+    TmuxCommand::new("kill-server").run()
+}
+
+/// Start the tmux server and keep it running while it has no sessions
+/// (`exit-empty off`)
+pub fn start_server() -> Result<()> {
+    execute_tmux(&["start-server", ";", "set-option", "-g", "exit-empty", "off"])?;
+    Ok(())
+}
+
+/// Run tmux once, recording the invocation in the command trace.
+///
+/// Non-zero exit statuses are returned as normal output; only failures to
+/// spawn tmux are errors.
+fn run_tmux(args: &[&str]) -> std::io::Result<Output> {
+    let started = Instant::now();
+    cache::invalidate_for(args);
+    record(args);
+    let result = match control::execute(args) {
+        Some(output) => Ok(output),
+        None => tmux_command().args(args).output(),
+    };
+
+    match &result {
+        Ok(output) => log::trace_command(
+            args,
+            started.elapsed(),
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        Err(e) => log::trace_command(args, started.elapsed(), None, &e.to_string()),
+    }
+
+    result
+}
+
+/// Run a query, reusing its answer from earlier in this invocation as long as
+/// no command that may change it ran in between
+fn run_tmux_cached(args: &[&str]) -> std::io::Result<Output> {
+    if let Some(output) = cache::lookup(args) {
+        log::debug(&format!("tmux {} -> (cached)", args.join(" ")));
+        return Ok(output);
+    }
+    let output = run_tmux(args)?;
+    if !is_transient_error(&String::from_utf8_lossy(&output.stderr)) {
+        cache::store(args, &output);
+    }
+    Ok(output)
+}
+
+/// Execute a tmux command, retrying transient failures with exponential backoff
+fn execute_tmux(args: &[&str]) -> Result<Output> {
+    execute_tmux_with(args, run_tmux)
+}
+
+/// Execute a tmux command through `run` (`run_tmux` or `run_tmux_cached`),
+/// retrying transient failures with exponential backoff
+fn execute_tmux_with(
+    args: &[&str],
+    run: fn(&[&str]) -> std::io::Result<Output>,
+) -> Result<Output> {
+    let retries = RETRIES.load(Ordering::SeqCst);
+    let mut backoff = Duration::from_millis(RETRY_BACKOFF_MS.load(Ordering::SeqCst));
+    let mut attempt = 0;
+
+    let output = loop {
+        let output = run(args).context("Failed to execute tmux command")?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() || attempt >= retries || !is_transient_error(&stderr) {
+            break output;
+        }
+
+        attempt += 1;
+        log::info(&format!(
+            "tmux {} -> transient failure ({}), retry {}/{} in {:?}",
+            args.join(" "),
+            stderr.trim(),
+            attempt,
+            retries,
+            backoff
+        ));
+        thread::sleep(backoff);
+        backoff *= 2;
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log::error(&format!(
+            "tmux {} -> FAILED: {}",
+            log::redact_args(args).join(" "),
+            stderr.trim()
+        ));
+        anyhow::bail!("tmux command failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.is_empty() {
+        log::debug(&format!(
+            "tmux {} -> {}",
+            log::redact_args(args).join(" "),
+            stdout.trim()
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Execute a tmux command interactively (for attach)
+fn execute_tmux_interactive(args: &[&str]) -> Result<()> {
+    let started = Instant::now();
+    cache::invalidate_for(args);
+    record(args);
+    let status = tmux_command()
+        .args(args)
+        .status()
+        .context("Failed to execute tmux command")?;
+
+    // stderr goes straight to the terminal, so there is none to record
+    log::trace_command(args, started.elapsed(), status.code(), "");
+
+    if !status.success() {
+        log::error(&format!(
+            "tmux {} -> exit status: {}",
+            log::redact_args(args).join(" "),
+            status
+        ));
+        anyhow::bail!("tmux command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_server_is_not_transient() {
+        let stderr = "error connecting to /tmp/tmux-0/nosuch (No such file or directory)";
+        assert!(is_no_server_error(stderr));
+        assert!(!is_transient_error(stderr));
+        assert!(!is_transient_error("no server running on /tmp/tmux-0/default"));
+        assert!(is_transient_error("lost server"));
+    }
+}
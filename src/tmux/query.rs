@@ -0,0 +1,290 @@
+use anyhow::{Context, Result};
+
+use super::{Target, TmuxCommand};
+
+/// Field separator used in format strings.
+///
+/// Window names may contain spaces, and tmux escapes control characters such
+/// as tabs in its output, so a printable sequence is used instead.
+const SEP: &str = "|:|";
+
+/// Number of fields in PANE_FORMAT
+const PANE_FIELDS: usize = 13;
+
+/// Per-pane format string for `list-panes -s`, one line per pane (path last
+/// so it may contain the separator)
+const PANE_FORMAT: &str = "#{session_id}|:|#{session_name}|:|#{window_id}|:|#{window_index}|:|#{window_name}|:|#{window_layout}|:|#{window_active}|:|#{pane_id}|:|#{pane_index}|:|#{pane_active}|:|#{pane_dead}|:|#{pane_current_command}|:|#{pane_current_path}";
+
+/// A running tmux session as reported by tmux
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveSession {
+    /// tmux session ID (e.g. `$1`)
+    pub id: String,
+    pub name: String,
+    /// Windows ordered by index
+    pub windows: Vec<LiveWindow>,
+}
+
+/// A window in a running session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveWindow {
+    /// tmux window ID (e.g. `@3`)
+    pub id: String,
+    pub index: usize,
+    pub name: String,
+    /// Layout string as reported by `#{window_layout}`
+    pub layout: String,
+    pub active: bool,
+    /// Panes ordered by index
+    pub panes: Vec<LivePane>,
+}
+
+/// A pane in a running window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LivePane {
+    /// tmux pane ID (e.g. `%7`)
+    pub id: String,
+    pub index: usize,
+    pub active: bool,
+    /// Whether the pane's process has exited (only possible with remain-on-exit)
+    pub dead: bool,
+    /// Foreground command running in the pane
+    pub current_command: String,
+    /// Current working directory of the pane
+    pub cwd: String,
+}
+
+impl LiveSession {
+    /// Find a window by its tmux index
+    pub fn window(&self, index: usize) -> Option<&LiveWindow> {
+        self.windows.iter().find(|w| w.index == index)
+    }
+}
+
+/// Format of the server details queried by `server_info`
+const SERVER_FORMAT: &str = "#{pid}|:|#{socket_path}|:|#{version}|:|#{start_time}";
+
+/// A running tmux server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub pid: u32,
+    pub socket_path: String,
+    pub version: String,
+    /// Start time in seconds since the Unix epoch
+    pub started: u64,
+}
+
+/// Query the tmux server tmx talks to.
+///
+/// # Returns
+/// The server's details, or None if no server is running.
+pub fn server_info() -> Result<Option<ServerInfo>> {
+    super::query_server(&["display-message", "-p", SERVER_FORMAT])?
+        .map(|output| parse_server_info(&output))
+        .transpose()
+}
+
+/// Parse the output of the `SERVER_FORMAT` query
+fn parse_server_info(output: &str) -> Result<ServerInfo> {
+    let fields: Vec<&str> = output.trim().split(SEP).collect();
+    let [pid, socket_path, version, started] = fields.as_slice() else {
+        anyhow::bail!("Unexpected tmux server info: '{}'", output.trim());
+    };
+    Ok(ServerInfo {
+        pid: pid.parse().with_context(|| format!("Invalid server pid '{}'", pid))?,
+        socket_path: socket_path.to_string(),
+        version: version.to_string(),
+        started: started
+            .parse()
+            .with_context(|| format!("Invalid server start time '{}'", started))?,
+    })
+}
+
+/// Format of the per-session summaries queried by `session_summaries` (path
+/// last so it may contain the separator)
+const SUMMARY_FORMAT: &str = "#{session_name}|:|#{session_windows}|:|#{session_attached}|:|#{session_path}";
+
+/// Overview of a running session, as shown by `tmx list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub name: String,
+    pub windows: usize,
+    /// Number of clients attached to the session
+    pub attached: usize,
+    /// The session's working directory
+    pub path: String,
+}
+
+/// Summarize all sessions of the tmux server with a single query.
+///
+/// # Returns
+/// The sessions, or None if no tmux server is running.
+pub fn session_summaries() -> Result<Option<Vec<SessionSummary>>> {
+    super::query_server(&["list-sessions", "-F", SUMMARY_FORMAT])
+        .context("Failed to list tmux sessions")?
+        .map(|output| parse_session_summaries(&output))
+        .transpose()
+}
+
+/// Parse the output of the `SUMMARY_FORMAT` query
+fn parse_session_summaries(output: &str) -> Result<Vec<SessionSummary>> {
+    output
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.splitn(4, SEP).collect();
+            let [name, windows, attached, path] = fields.as_slice() else {
+                anyhow::bail!("Unexpected tmux session format: {}", line);
+            };
+            Ok(SessionSummary {
+                name: name.to_string(),
+                windows: parse_index(windows, "window count")?,
+                attached: parse_index(attached, "attached client count")?,
+                path: path.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Query the live state of a running session.
+///
+/// # Arguments
+/// * `session` - The session name
+///
+/// # Returns
+/// The session, or None if it isn't running.
+pub fn live_session(session: &str) -> Result<Option<LiveSession>> {
+    let stdout = TmuxCommand::new("list-panes")
+        .flag("-s")
+        .target(Target::Session(session))
+        .option("-F", PANE_FORMAT)
+        .cached()
+        .output_if_success()
+        .context("Failed to query tmux session")?;
+
+    match stdout {
+        Some(stdout) => parse_session(&stdout).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Build a session from `list-panes -s` output in PANE_FORMAT
+fn parse_session(output: &str) -> Result<LiveSession> {
+    let mut session: Option<LiveSession> = None;
+
+    for line in output.lines().filter(|l| !l.is_empty()) {
+        let fields: Vec<&str> = line.splitn(PANE_FIELDS, SEP).collect();
+        if fields.len() != PANE_FIELDS {
+            anyhow::bail!("Unexpected tmux pane format: {}", line);
+        }
+
+        let session = session.get_or_insert_with(|| LiveSession {
+            id: fields[0].to_string(),
+            name: fields[1].to_string(),
+            windows: Vec::new(),
+        });
+
+        let window_index = parse_index(fields[3], "window index")?;
+        if session.window(window_index).is_none() {
+            session.windows.push(LiveWindow {
+                id: fields[2].to_string(),
+                index: window_index,
+                name: fields[4].to_string(),
+                layout: fields[5].to_string(),
+                active: fields[6] == "1",
+                panes: Vec::new(),
+            });
+        }
+
+        let window = session
+            .windows
+            .iter_mut()
+            .find(|w| w.index == window_index)
+            .expect("window was just inserted");
+
+        window.panes.push(LivePane {
+            id: fields[7].to_string(),
+            index: parse_index(fields[8], "pane index")?,
+            active: fields[9] == "1",
+            dead: fields[10] == "1",
+            current_command: fields[11].to_string(),
+            cwd: fields[12].to_string(),
+        });
+    }
+
+    let mut session = session.context("tmux reported no panes for session")?;
+    session.windows.sort_by_key(|w| w.index);
+    for window in &mut session.windows {
+        window.panes.sort_by_key(|p| p.index);
+    }
+
+    Ok(session)
+}
+
+fn parse_index(value: &str, what: &str) -> Result<usize> {
+    value
+        .parse()
+        .with_context(|| format!("Failed to parse {}: {}", what, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_session() {
+        let output = "\
+$1|:|dev|:|@2|:|2|:|my shell|:|abcd,80x24,0,0,2|:|0|:|%3|:|0|:|1|:|0|:|fish|:|/home/me
+$1|:|dev|:|@1|:|1|:|editor|:|ef01,80x24,0,0|:|1|:|%1|:|1|:|0|:|1|:|nvim|:|/home/me/src
+$1|:|dev|:|@1|:|1|:|editor|:|ef01,80x24,0,0|:|1|:|%0|:|0|:|1|:|0|:|zsh|:|/home/me
+";
+        let session = parse_session(output).unwrap();
+        assert_eq!(session.id, "$1");
+        assert_eq!(session.name, "dev");
+        assert_eq!(session.windows.len(), 2);
+
+        let editor = session.window(1).unwrap();
+        assert_eq!(editor.name, "editor");
+        assert!(editor.active);
+        assert_eq!(editor.panes.len(), 2);
+        assert_eq!(editor.panes[0].id, "%0");
+        assert_eq!(editor.panes[1].current_command, "nvim");
+        assert!(editor.panes[1].dead);
+
+        let shell = session.window(2).unwrap();
+        assert_eq!(shell.name, "my shell");
+        assert_eq!(shell.panes[0].cwd, "/home/me");
+    }
+
+    #[test]
+    fn test_parse_session_summaries() {
+        let output = "api|:|3|:|1|:|/home/me/api\nscratch|:|1|:|0|:|/tmp/a|:|b\n";
+        let sessions = parse_session_summaries(output).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].name, "api");
+        assert_eq!(sessions[0].windows, 3);
+        assert_eq!(sessions[0].attached, 1);
+        assert_eq!(sessions[1].path, "/tmp/a|:|b");
+        assert!(parse_session_summaries("api|:|3").is_err());
+    }
+
+    #[test]
+    fn test_parse_server_info() {
+        let info = parse_server_info("4242|:|/tmp/tmux-1000/default|:|3.4|:|1700000000\n").unwrap();
+        assert_eq!(
+            info,
+            ServerInfo {
+                pid: 4242,
+                socket_path: "/tmp/tmux-1000/default".to_string(),
+                version: "3.4".to_string(),
+                started: 1_700_000_000,
+            }
+        );
+        assert!(parse_server_info("4242|:|/tmp/x").is_err());
+    }
+
+    #[test]
+    fn test_parse_session_rejects_bad_format() {
+        assert!(parse_session("garbage").is_err());
+    }
+}
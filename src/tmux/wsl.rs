@@ -0,0 +1,110 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::log;
+
+/// Whether tmux is run inside WSL through `wsl.exe`
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Variables forwarded into WSL (`WSLENV` entries, `/p` translates paths)
+const FORWARDED_ENV: &[&str] = &["TMUX_TMPDIR/p"];
+
+/// Run tmux through `wsl.exe` when `wsl = true` is configured and no native
+/// tmux is available
+pub fn configure(wsl: bool) {
+    let enabled = wsl && !native_tmux_available();
+    if enabled {
+        log::info("native tmux not found, running tmux through wsl.exe");
+    }
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Check whether tmux is run inside WSL
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Check whether a tmux executable can be started directly
+fn native_tmux_available() -> bool {
+    Command::new("tmux").arg("-V").output().is_ok()
+}
+
+/// Build the command that runs tmux (natively, or inside WSL)
+pub fn tmux_command() -> Command {
+    if !is_enabled() {
+        return Command::new("tmux");
+    }
+
+    let mut wslenv: Vec<String> = std::env::var("WSLENV")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(|v| v.split(':').map(str::to_string).collect())
+        .unwrap_or_default();
+    for entry in FORWARDED_ENV {
+        if !wslenv.iter().any(|e| e == entry) {
+            wslenv.push(entry.to_string());
+        }
+    }
+
+    let mut command = Command::new("wsl.exe");
+    command
+        .args(["--exec", "tmux"])
+        .env("WSLENV", wslenv.join(":"));
+    command
+}
+
+/// Translate a path for tmux: Windows paths become their WSL equivalents
+/// when tmux runs inside WSL, other paths are returned unchanged.
+pub fn translate_path(path: &str) -> String {
+    if is_enabled() {
+        to_wsl_path(path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Convert a Windows path to a WSL path.
+///
+/// `C:\Users\me` becomes `/mnt/c/Users/me`, and paths into a distro's file
+/// system (`\\wsl$\Ubuntu\home\me`, `\\wsl.localhost\Ubuntu\home\me`) become
+/// `/home/me`. Anything else is returned unchanged.
+pub fn to_wsl_path(path: &str) -> String {
+    let normalized = path.replace('\\', "/");
+
+    for prefix in ["//wsl$/", "//wsl.localhost/"] {
+        if let Some(rest) = normalized.strip_prefix(prefix) {
+            // Skip the distro name
+            return match rest.split_once('/') {
+                Some((_, inner)) => format!("/{}", inner),
+                None => "/".to_string(),
+            };
+        }
+    }
+
+    let mut chars = normalized.chars();
+    if let (Some(drive), Some(':')) = (chars.next(), chars.next())
+        && drive.is_ascii_alphabetic()
+    {
+        let rest = chars.as_str().trim_start_matches('/');
+        return format!("/mnt/{}/{}", drive.to_ascii_lowercase(), rest)
+            .trim_end_matches('/')
+            .to_string();
+    }
+
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_wsl_path() {
+        assert_eq!(to_wsl_path(r"C:\Users\me\proj"), "/mnt/c/Users/me/proj");
+        assert_eq!(to_wsl_path("D:/work"), "/mnt/d/work");
+        assert_eq!(to_wsl_path(r"C:\"), "/mnt/c");
+        assert_eq!(to_wsl_path(r"\\wsl$\Ubuntu\home\me"), "/home/me");
+        assert_eq!(to_wsl_path(r"\\wsl.localhost\Ubuntu\home\me\src"), "/home/me/src");
+        assert_eq!(to_wsl_path("/home/me"), "/home/me");
+    }
+}
@@ -0,0 +1,174 @@
+//! Approval of project configs (`.tmx.toml`) before they are used.
+//!
+//! A project config found from the current directory runs its commands when
+//! its sessions are opened, so one that came with a cloned repository isn't
+//! used until the user has seen those commands and agreed, like direnv's
+//! `allow`. The SHA-256 of each approved file is kept in the state file, so
+//! a file that changed since needs approving again.
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::checksum;
+use crate::commands::import;
+use crate::config::Config;
+use crate::log;
+use crate::messages;
+use crate::prompt;
+use crate::state::State;
+
+/// Whether project configs may be used, as decided in this invocation, so
+/// the user is asked only once however often the config is loaded
+static DECISIONS: Lazy<Mutex<HashMap<PathBuf, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether a project config may be used, asking the user if it is new or
+/// has changed since they trusted it.
+///
+/// Without a terminal to ask on, an untrusted file is not used and a warning
+/// is printed on stderr.
+///
+/// # Returns
+/// True if the file is trusted (now or before).
+pub fn check(path: &Path) -> Result<bool> {
+    let mut decisions = DECISIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&trusted) = decisions.get(path) {
+        return Ok(trusted);
+    }
+    let trusted = decide(path)?;
+    decisions.insert(path.to_path_buf(), trusted);
+    Ok(trusted)
+}
+
+/// Check a project config against the state file, asking the user if need be
+fn decide(path: &Path) -> Result<bool> {
+    let content = read(path)?;
+    let sum = checksum::sha256_hex(&content);
+    let recorded = State::load().trusted_projects.remove(&key(path));
+    if recorded.as_deref() == Some(sum.as_str()) {
+        return Ok(true);
+    }
+
+    if !interactive() {
+        log::info(&format!(
+            "trust: not using untrusted project config {}",
+            path.display()
+        ));
+        messages::warn(messages::PROJECT_SKIPPED, &[("path", &path.display())]);
+        return Ok(false);
+    }
+
+    let message = if recorded.is_some() {
+        messages::PROJECT_CHANGED
+    } else {
+        messages::PROJECT_UNTRUSTED
+    };
+    messages::say(message, &[("path", &path.display())]);
+    show(path, &content)?;
+    let question = "Use it? The commands above run when its sessions are opened";
+    if prompt::choose(question, &[('y', "trust"), ('n', "skip")])? != Some('y') {
+        messages::warn(messages::PROJECT_SKIPPED, &[("path", &path.display())]);
+        return Ok(false);
+    }
+    record(path, &sum)?;
+    Ok(true)
+}
+
+/// Trust a project config as it is now, for `tmx project trust`, showing the
+/// commands it runs.
+pub fn trust(path: &Path) -> Result<()> {
+    let content = read(path)?;
+    show(path, &content)?;
+    record(path, &checksum::sha256_hex(&content))?;
+    messages::say(messages::PROJECT_TRUSTED, &[("path", &path.display())]);
+    Ok(())
+}
+
+/// Trust a project config as tmx itself just wrote it: one created by
+/// `tmx project new`, or a trusted one updated by `tmx sync`.
+pub fn trust_written(path: &Path) -> Result<()> {
+    record(path, &checksum::sha256_hex(&read(path)?))
+}
+
+/// Record a project config's checksum in the state file
+fn record(path: &Path, sum: &str) -> Result<()> {
+    log::info(&format!("trust: trusted {} ({})", path.display(), sum));
+    State::update(|state| {
+        state.trusted_projects.insert(key(path), sum.to_string());
+    })
+}
+
+/// Print the sessions a project config defines and the commands they run
+fn show(path: &Path, content: &[u8]) -> Result<()> {
+    let project: Config = toml::from_str(&String::from_utf8_lossy(content))
+        .with_context(|| format!("Failed to parse project config: {}", path.display()))?;
+    for (id, session) in &project.sessions {
+        import::show_commands(id, session);
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("Failed to read project config: {}", path.display()))
+}
+
+/// The state file key of a project config: its canonical path, so it is the
+/// same from every directory of the project
+fn key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| PathBuf::from(path))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Whether the user can be asked: both stdin and stdout are a terminal and
+/// stdout isn't reserved for scripts (`--attach-mode print`)
+fn interactive() -> bool {
+    !cfg!(test)
+        && !messages::on_stderr()
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PROJECT_CONFIG_FILE;
+    use crate::context::Context as TmxContext;
+
+    #[test]
+    fn test_untrusted_project_not_loaded() {
+        let dir = std::env::temp_dir().join(format!("tmx-trust-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let global = dir.join("tmx.toml");
+        fs::write(
+            &global,
+            "[sessions.main]\nname = \"main\"\nroot = \"~\"\n\
+             [[sessions.main.windows]]\nname = \"w\"\n[[sessions.main.windows.panes]]\n",
+        )
+        .unwrap();
+        let project = dir.join(PROJECT_CONFIG_FILE);
+        fs::write(
+            &project,
+            "default = \"evil\"\n\
+             [sessions.evil]\nname = \"evil\"\nroot = \".\"\non_create = [\"touch pwned\"]\n\
+             [[sessions.evil.windows]]\nname = \"w\"\n[[sessions.evil.windows.panes]]\n",
+        )
+        .unwrap();
+
+        let ctx = TmxContext::new(Some(global.to_string_lossy().into_owned()), false)
+            .unwrap()
+            .for_project(project.clone());
+        assert!(!check(&project).unwrap());
+        let config = ctx.config().unwrap();
+        assert!(config.sessions.contains_key("main"));
+        assert!(!config.sessions.contains_key("evil"));
+        assert_eq!(config.default_session_id(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
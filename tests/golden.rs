@@ -0,0 +1,83 @@
+//! Snapshot tests of the exact tmux command sequences tmx produces.
+//!
+//! The commands are recorded while running against a private tmux server and
+//! compared with `insta` snapshots in `tests/snapshots/`. After an intended
+//! change, review and accept the new snapshots with `cargo insta review`.
+
+use std::path::Path;
+use tmx::commands::refresh;
+use tmx::test_support::TestServer;
+use tmx::tmux;
+
+/// Create a fixture session while recording, and compare the commands
+fn assert_create_golden(fixture_name: &str, toml: &str, session_id: &str) {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server.fixture(fixture_name, toml).unwrap();
+
+    tmux::start_recording();
+    let result = fixture.create(session_id);
+    let commands = tmux::take_recording();
+    result.unwrap();
+
+    insta::assert_snapshot!(
+        format!("create-{}", fixture_name),
+        mask_setup_nonces(&(commands.join("\n") + "\n"))
+    );
+}
+
+/// Replace the per-build nonce ending setup channel names, which differs
+/// on every run
+fn mask_setup_nonces(output: &str) -> String {
+    const PREFIX: &str = "tmx-setup-";
+    let mut masked = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(start) = rest.find(PREFIX) {
+        let after = &rest[start..];
+        let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(after.len());
+        let channel = &after[..end];
+        // The nonce is the last two parts: process ID and nanoseconds
+        let name = channel.rsplitn(3, '-').nth(2).unwrap_or(channel);
+        masked.push_str(&rest[..start]);
+        masked.push_str(name);
+        masked.push_str("-<nonce>");
+        rest = &after[end..];
+    }
+    masked.push_str(rest);
+    masked
+}
+
+#[test]
+fn test_create_basic_golden() {
+    assert_create_golden("basic", include_str!("fixtures/basic.toml"), "basic");
+}
+
+#[test]
+fn test_create_features_golden() {
+    assert_create_golden(
+        "features",
+        include_str!("fixtures/features.toml"),
+        "features",
+    );
+}
+
+#[test]
+fn test_refresh_golden() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("refresh", include_str!("fixtures/refresh.toml"))
+        .unwrap();
+    tmux::new_session("grow", "main", Some(Path::new("/")), &[], None).unwrap();
+
+    tmux::start_recording();
+    let result = refresh::run(Some("grow"), None, fixture.ctx());
+    let commands = tmux::take_recording();
+    result.unwrap();
+
+    insta::assert_snapshot!("refresh-grow", commands.join("\n") + "\n");
+}
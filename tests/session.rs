@@ -0,0 +1,131 @@
+//! End-to-end tests of session creation and refresh on a private tmux server.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use tmx::commands::{refresh, run};
+use tmx::test_support::{wait_until, TestServer};
+use tmx::tmux;
+use tmx::tmux::query::live_session;
+
+#[test]
+fn test_create_session_matches_config() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("basic", include_str!("fixtures/basic.toml"))
+        .unwrap();
+    fixture.create("basic").unwrap();
+
+    let live = live_session("basic").unwrap().expect("session is running");
+    let names: Vec<_> = live.windows.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, ["editor", "logs"]);
+    assert_eq!(live.windows[0].panes.len(), 2);
+    assert_eq!(live.windows[1].panes.len(), 3);
+
+    // startup_window is focused
+    assert!(live.windows[1].active);
+
+    // Window and relative pane roots
+    let logs = &live.windows[1];
+    assert_eq!(logs.panes[0].cwd, "/usr");
+    assert_eq!(logs.panes[1].cwd, "/usr/bin");
+}
+
+#[test]
+fn test_create_session_runs_commands() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("basic", include_str!("fixtures/basic.toml"))
+        .unwrap();
+    fixture.create("basic").unwrap();
+
+    let command_of = |window: usize, pane: usize| {
+        live_session("basic")
+            .ok()
+            .flatten()
+            .map(|s| s.windows[window].panes[pane].current_command.clone())
+            .unwrap_or_default()
+    };
+
+    // Typed into the shell
+    assert!(wait_until(Duration::from_secs(5), || command_of(0, 0) == "sleep"));
+    // Started as the pane's process
+    assert_eq!(command_of(1, 2), "sleep");
+}
+
+#[test]
+fn test_refresh_adds_missing_panes() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("refresh", include_str!("fixtures/refresh.toml"))
+        .unwrap();
+
+    tmux::new_session("grow", "main", Some(Path::new("/")), &[], None).unwrap();
+    refresh::run(Some("grow"), None, fixture.ctx()).unwrap();
+
+    let live = live_session("grow").unwrap().expect("session is running");
+    assert_eq!(live.windows[0].panes.len(), 3);
+}
+
+#[test]
+fn test_refresh_only_named_window() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("basic", include_str!("fixtures/basic.toml"))
+        .unwrap();
+    fixture.create("basic").unwrap();
+    for target in ["basic:editor.1", "basic:logs.1"] {
+        let status = Command::new("tmux")
+            .args(["-L", server.socket(), "kill-pane", "-t", target])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    refresh::run(Some("basic"), Some("logs"), fixture.ctx()).unwrap();
+
+    let live = live_session("basic").unwrap().expect("session is running");
+    assert_eq!(live.windows[0].panes.len(), 1);
+    assert_eq!(live.windows[1].panes.len(), 3);
+}
+
+#[test]
+fn test_refresh_not_running() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("basic", include_str!("fixtures/basic.toml"))
+        .unwrap();
+
+    let err = refresh::run(Some("basic"), None, fixture.ctx()).unwrap_err();
+    assert_eq!(err.to_string(), "Session 'basic' is not running");
+}
+
+#[test]
+fn test_run_waits_for_command() {
+    let Some(server) = TestServer::start() else {
+        return;
+    };
+    let fixture = server
+        .fixture("basic", include_str!("fixtures/basic.toml"))
+        .unwrap();
+    fixture.create("basic").unwrap();
+
+    let marker = std::env::temp_dir().join(format!("{}-run-marker", server.socket()));
+    let command = vec![format!("sleep 0.2 && touch {}", marker.display())];
+    run::run("basic:editor.1", &command, Some(10), fixture.ctx()).unwrap();
+
+    // The command has finished by the time run returns
+    assert!(marker.exists());
+    let _ = std::fs::remove_file(marker);
+}